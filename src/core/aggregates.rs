@@ -0,0 +1,320 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// Aggregates enumeration
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::data_types::DataType;
+use crate::data_types::DataType::{NumberType, VaryingType};
+use crate::errors::throw;
+use crate::errors::Errors::TypeMismatch;
+use crate::errors::TypeMismatchErrors::ArgumentsMismatched;
+use crate::expression::Conditions;
+use crate::expression::Expression;
+use crate::expression::Expression::*;
+use crate::inferences::Inferences;
+use crate::machine::Machine;
+use crate::number_kind::NumberKind::{F64Kind, I64Kind};
+use crate::numbers::Numbers::{F64Value, I64Value};
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Null, Number, Undefined};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::ops::Deref;
+
+/// Represents an aggregate function (e.g. count, sum, avg)
+/// which reduces a group of rows into a single value.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Aggregates {
+    Avg,
+    Count,
+    CountDistinct,
+    First,
+    Last,
+    Max,
+    Min,
+    Sum,
+}
+
+impl Aggregates {
+
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Returns true, if the [Expression] contains at least one aggregate function call
+    pub fn contains_any(expr: &Expression) -> bool {
+        let mut found = false;
+        let _ = Self::transform(expr, &mut |e| {
+            found = found || Self::decode(e).is_some();
+            Ok(None)
+        });
+        found
+    }
+
+    /// Decodes an aggregate function call; returning the function and its arguments
+    /// ex: sum(last_sale)
+    pub fn decode(expr: &Expression) -> Option<(Aggregates, Vec<Expression>)> {
+        match expr {
+            FunctionCall { fx, args } => match fx.deref() {
+                Variable(name) => Self::from_name(name).map(|agg| (agg, args.to_owned())),
+                _ => None
+            }
+            _ => None
+        }
+    }
+
+    /// Returns the aggregate function corresponding to the given name
+    pub fn from_name(name: &str) -> Option<Aggregates> {
+        use Aggregates::*;
+        match name {
+            "avg" => Some(Avg),
+            "count" => Some(Count),
+            "count_distinct" => Some(CountDistinct),
+            "first" => Some(First),
+            "last" => Some(Last),
+            "max" => Some(Max),
+            "min" => Some(Min),
+            "sum" => Some(Sum),
+            _ => None
+        }
+    }
+
+    /// Infers the data type of the given (projected) [Expression]
+    pub fn infer(expr: &Expression, columns: &Vec<Column>) -> DataType {
+        match expr {
+            AsValue(_, expr) => Self::infer(expr, columns),
+            Variable(name) => columns.iter()
+                .find(|c| c.get_name() == name)
+                .map(|c| c.get_data_type().to_owned())
+                .unwrap_or(VaryingType(vec![])),
+            other => match Self::decode(other) {
+                Some((agg, args)) => agg.get_return_type(args.first()
+                    .map(|arg| Self::infer(arg, columns))
+                    .unwrap_or(VaryingType(vec![]))),
+                None => Inferences::infer(other)
+            }
+        }
+    }
+
+    /// Replaces all aggregate function calls found within the [Expression]
+    /// with the values computed from the given group of rows.
+    /// ex: count(symbol) > 1 => 3 > 1
+    pub fn resolve(
+        ms: &Machine,
+        expr: &Expression,
+        columns: &Vec<Column>,
+        rows: &Vec<Row>,
+    ) -> std::io::Result<Expression> {
+        Self::transform(expr, &mut |e| match Self::decode(e) {
+            Some((agg, args)) => {
+                let value = agg.evaluate(ms, &args, columns, rows)?;
+                Ok(Some(Literal(value)))
+            }
+            None => Ok(None)
+        })
+    }
+
+    /// Rebuilds the [Expression] tree; replacing each node for which the
+    /// function produces a substitute.
+    fn transform(
+        expr: &Expression,
+        f: &mut dyn FnMut(&Expression) -> std::io::Result<Option<Expression>>,
+    ) -> std::io::Result<Expression> {
+        if let Some(replacement) = f(expr)? {
+            return Ok(replacement);
+        }
+        let result = match expr {
+            ArrayExpression(items) => ArrayExpression(Self::transform_list(items, f)?),
+            AsValue(name, e) => AsValue(name.to_owned(), Self::boxed(e, f)?),
+            Condition(cond) => Condition(Self::transform_cond(cond, f)?),
+            Divide(a, b) => Divide(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            FunctionCall { fx, args } =>
+                FunctionCall { fx: fx.to_owned(), args: Self::transform_list(args, f)? },
+            If { condition, a, b } => If {
+                condition: Self::boxed(condition, f)?,
+                a: Self::boxed(a, f)?,
+                b: match b {
+                    Some(b) => Some(Self::boxed(b, f)?),
+                    None => None
+                },
+            },
+            Minus(a, b) => Minus(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Modulo(a, b) => Modulo(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Multiply(a, b) => Multiply(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Neg(a) => Neg(Self::boxed(a, f)?),
+            Plus(a, b) => Plus(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Pow(a, b) => Pow(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Tuple(items) => Tuple(Self::transform_list(items, f)?),
+            other => other.to_owned()
+        };
+        Ok(result)
+    }
+
+    fn boxed(
+        expr: &Expression,
+        f: &mut dyn FnMut(&Expression) -> std::io::Result<Option<Expression>>,
+    ) -> std::io::Result<Box<Expression>> {
+        Ok(Box::new(Self::transform(expr, f)?))
+    }
+
+    fn transform_cond(
+        cond: &Conditions,
+        f: &mut dyn FnMut(&Expression) -> std::io::Result<Option<Expression>>,
+    ) -> std::io::Result<Conditions> {
+        use crate::expression::Conditions::*;
+        let result = match cond {
+            And(a, b) => And(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Between(a, b, c) => Between(Self::boxed(a, f)?, Self::boxed(b, f)?, Self::boxed(c, f)?),
+            Betwixt(a, b, c) => Betwixt(Self::boxed(a, f)?, Self::boxed(b, f)?, Self::boxed(c, f)?),
+            Contains(a, b) => Contains(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Equal(a, b) => Equal(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            GreaterOrEqual(a, b) => GreaterOrEqual(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            GreaterThan(a, b) => GreaterThan(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            LessOrEqual(a, b) => LessOrEqual(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            LessThan(a, b) => LessThan(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Like(a, b) => Like(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Not(a) => Not(Self::boxed(a, f)?),
+            NotEqual(a, b) => NotEqual(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            Or(a, b) => Or(Self::boxed(a, f)?, Self::boxed(b, f)?),
+            other => other.to_owned()
+        };
+        Ok(result)
+    }
+
+    fn transform_list(
+        items: &Vec<Expression>,
+        f: &mut dyn FnMut(&Expression) -> std::io::Result<Option<Expression>>,
+    ) -> std::io::Result<Vec<Expression>> {
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            results.push(Self::transform(item, f)?);
+        }
+        Ok(results)
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // instance methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Computes the aggregate value for the given group of rows
+    pub fn evaluate(
+        &self,
+        ms: &Machine,
+        args: &Vec<Expression>,
+        columns: &Vec<Column>,
+        rows: &Vec<Row>,
+    ) -> std::io::Result<TypedValue> {
+        use Aggregates::*;
+
+        // count() requires no arguments; everything else requires exactly one
+        let arg = match (self, args.as_slice()) {
+            (Count, []) => return Ok(Number(I64Value(rows.len() as i64))),
+            (_, [arg]) => arg,
+            (_, args) => return throw(TypeMismatch(ArgumentsMismatched(1, args.len())))
+        };
+
+        // evaluate the argument for each row; nulls do not participate
+        let mut values = Vec::with_capacity(rows.len());
+        for row in rows {
+            let (_, value) = ms.with_row(columns, row).evaluate(arg)?;
+            match value {
+                Null | Undefined => {}
+                value => values.push(value)
+            }
+        }
+
+        // reduce the values
+        let result = match self {
+            Avg if values.is_empty() => Null,
+            Avg => {
+                let total = values.iter().fold(0f64, |total, v| total + v.to_f64());
+                Number(F64Value(total / values.len() as f64))
+            }
+            Count => Number(I64Value(values.len() as i64)),
+            CountDistinct => Number(I64Value(values.iter()
+                .collect::<BTreeSet<_>>().len() as i64)),
+            First => values.first().map(|v| v.to_owned()).unwrap_or(Null),
+            Last => values.last().map(|v| v.to_owned()).unwrap_or(Null),
+            Max => Self::pick(values, Ordering::Greater),
+            Min => Self::pick(values, Ordering::Less),
+            Sum => values.into_iter().reduce(|a, b| a + b).unwrap_or(Null),
+        };
+        Ok(result)
+    }
+
+    /// Returns the data type produced by the aggregate function
+    pub fn get_return_type(&self, arg_type: DataType) -> DataType {
+        use Aggregates::*;
+        match self {
+            Avg => NumberType(F64Kind),
+            Count | CountDistinct => NumberType(I64Kind),
+            First | Last | Max | Min | Sum => arg_type,
+        }
+    }
+
+    /// Selects the value that compares with the given [Ordering] against all others
+    fn pick(values: Vec<TypedValue>, preferred: Ordering) -> TypedValue {
+        values.into_iter()
+            .reduce(|a, b| match b.partial_cmp(&a) {
+                Some(ordering) if ordering == preferred => b,
+                _ => a
+            })
+            .unwrap_or(Null)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::numbers::Numbers::F64Value;
+    use crate::testdata::{make_quote, make_quote_columns};
+    use crate::typed_values::TypedValue::StringValue;
+
+    #[test]
+    fn test_contains_any() {
+        let expr = Compiler::build("n: count(symbol) + 1").unwrap();
+        assert!(Aggregates::contains_any(&expr));
+        let expr = Compiler::build("price: last_sale * 2").unwrap();
+        assert!(!Aggregates::contains_any(&expr));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let ms = Machine::empty();
+        let columns = make_quote_columns();
+        let rows = vec![
+            make_quote(0, "ABC", "AMEX", 11.0),
+            make_quote(1, "XYZ", "NYSE", 4.0),
+            make_quote(2, "ABC", "NYSE", 6.0),
+        ];
+        let eval = |agg: Aggregates, arg: &str| {
+            agg.evaluate(&ms, &vec![Variable(arg.into())], &columns, &rows).unwrap()
+        };
+        assert_eq!(eval(Aggregates::Avg, "last_sale"), Number(F64Value(7.0)));
+        assert_eq!(eval(Aggregates::Count, "symbol"), Number(I64Value(3)));
+        assert_eq!(eval(Aggregates::CountDistinct, "symbol"), Number(I64Value(2)));
+        assert_eq!(eval(Aggregates::First, "exchange"), StringValue("AMEX".into()));
+        assert_eq!(eval(Aggregates::Last, "exchange"), StringValue("NYSE".into()));
+        assert_eq!(eval(Aggregates::Max, "last_sale"), Number(F64Value(11.0)));
+        assert_eq!(eval(Aggregates::Min, "last_sale"), Number(F64Value(4.0)));
+        assert_eq!(eval(Aggregates::Sum, "last_sale"), Number(F64Value(21.0)));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let ms = Machine::empty();
+        let columns = make_quote_columns();
+        let rows = vec![
+            make_quote(0, "ABC", "AMEX", 11.0),
+            make_quote(1, "XYZ", "NYSE", 4.0),
+        ];
+        let expr = Compiler::build("count(symbol) > 1").unwrap();
+        let resolved = Aggregates::resolve(&ms, &expr, &columns, &rows).unwrap();
+        assert_eq!(resolved.to_code(), "2 > 1");
+    }
+}
@@ -4,22 +4,41 @@
 ////////////////////////////////////////////////////////////////////
 
 use crate::columns::Column;
+use crate::data_types::DataType::NumberType;
+use crate::errors::Errors::Exact;
 use crate::field;
+use crate::field::FieldMetadata;
 use crate::namespaces::Namespace;
+use crate::number_kind::NumberKind::U64Kind;
 use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{ErrorValue, Null, Undefined};
+use crate::write_ahead_log::WriteAheadLog;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use shared_lib::fail;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
 use std::os::unix::fs::FileExt;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 
-/// BLOB Store
+/// the free cells of the blob stores known to this process => (blob path|cell offset => allocated size);
+/// a cell is free when its header indicates that none of it is used. The map of each store
+/// is rebuilt from its headers when the store is first opened (see [BLOBStore::open_file]).
+static FREE_CELLS: OnceLock<Mutex<HashMap<String, BTreeMap<u64, u64>>>> = OnceLock::new();
+
+/// BLOB Store; a file of contiguous cells => (header|data|growth), where each header
+/// indicates the size of its cell and how much of it is used.
+/// Freed cells are recorded within a free-space map, and reused by subsequent inserts.
 #[derive(Clone)]
 pub struct BLOBStore {
     file: Arc<File>,
     path: String,
+    wal: Option<WriteAheadLog>,
 }
 
 impl BLOBStore {
@@ -28,12 +47,54 @@ impl BLOBStore {
     // static methods
     ////////////////////////////////////////////////////////////////
 
+    /// Compresses the bytes => (raw length|compressed length|deflated bytes)
+    pub fn compress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes)?;
+        let deflated = encoder.finish()?;
+        let mut packed = Vec::with_capacity(COMPRESSED_HEADER_LEN + deflated.len());
+        packed.extend((bytes.len() as u32).to_be_bytes());
+        packed.extend((deflated.len() as u32).to_be_bytes());
+        packed.extend(deflated);
+        Ok(packed)
+    }
+
     fn compute_allocated_size(data_len: usize) -> u64 {
         let data_len_growth = data_len.to_f64().unwrap_or(0.) * 1.25;
         let data_len_growth = data_len_growth.to_u64().unwrap_or(data_len as u64);
         HEADER_LEN as u64 + data_len_growth
     }
 
+    /// Decompresses the bytes previously compressed via [BLOBStore::compress]
+    pub fn decompress(packed: &[u8]) -> std::io::Result<Vec<u8>> {
+        let (raw_len, compressed_len) = Self::decode_compressed_lengths(packed)?;
+        let limit = COMPRESSED_HEADER_LEN + compressed_len;
+        if packed.len() < limit {
+            return fail(format!("Compressed data is truncated ({} < {limit} bytes)", packed.len()));
+        }
+        let mut bytes = Vec::with_capacity(raw_len);
+        DeflateDecoder::new(&packed[COMPRESSED_HEADER_LEN..limit]).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Returns the raw and compressed lengths of bytes previously compressed via [BLOBStore::compress]
+    pub fn decode_compressed_lengths(packed: &[u8]) -> std::io::Result<(usize, usize)> {
+        if packed.len() < COMPRESSED_HEADER_LEN {
+            return fail(format!("Compressed data is truncated ({} bytes)", packed.len()));
+        }
+        let decode = |offset: usize| {
+            let mut scratch = [0u8; 4];
+            scratch.copy_from_slice(&packed[offset..offset + 4]);
+            u32::from_be_bytes(scratch) as usize
+        };
+        Ok((decode(0), decode(4)))
+    }
+
+    fn lock_free_cells() -> std::io::Result<MutexGuard<'static, HashMap<String, BTreeMap<u64, u64>>>> {
+        FREE_CELLS.get_or_init(|| Mutex::new(HashMap::new())).lock()
+            .or_else(|err| fail(err.to_string()))
+    }
+
     /// Opens a blob store by namespace
     pub fn open(ns: &Namespace) -> std::io::Result<Self> {
         fs::create_dir_all(ns.get_root_path())?;
@@ -45,23 +106,134 @@ impl BLOBStore {
         let file = if create_if_not_exists {
             OpenOptions::new().read(true).write(true).create(true).open(path)?
         } else { OpenOptions::new().read(true).write(true).open(path)? };
-        Ok(Self {
+        let store = Self {
             file: Arc::new(file),
             path: path.to_string(),
-        })
+            wal: None,
+        };
+        store.load_free_cells()?;
+        Ok(store)
     }
 
     ////////////////////////////////////////////////////////////////
     // instance methods
     ////////////////////////////////////////////////////////////////
 
-    /// Encodes a binary field
+    /// Journals the writes to this store via the write-ahead log of its table
+    pub fn with_wal(self, wal: &WriteAheadLog) -> Self {
+        Self { wal: Some(wal.for_file(self.path.as_str())), ..self }
+    }
+
+    /// Performs the writes within a single batch, if the store is journaled
+    fn batch<A, F>(&self, f: F) -> std::io::Result<A>
+    where
+        F: FnOnce() -> std::io::Result<A>,
+    {
+        match &self.wal {
+            Some(wal) => wal.batch(f)?,
+            None => f()
+        }
+    }
+
+    /// Rewrites the store such that only the referenced cells remain; relocating each one
+    /// (trimmed to its used size) toward the start of the file, then truncating the file.
+    /// The given function updates the references to each relocated cell (old offset, new offset),
+    /// and, if the store is journaled, is performed within the same batch as the relocation.
+    /// Returns the number of bytes reclaimed.
+    pub fn compact<F>(&self, referenced: &BTreeSet<u64>, mut relocate: F) -> std::io::Result<u64>
+    where
+        F: FnMut(u64, u64) -> std::io::Result<()>,
+    {
+        let len0 = self.file.metadata()?.len();
+        let mut target = 0;
+        for header in self.read_headers()? {
+            if !referenced.contains(&header.offset) { continue; }
+            let (used, end) = (header.used.max(HEADER_LEN as u64), header.offset + header.allocated);
+            // a remainder too small to be a (free) cell is retained by the relocated cell
+            let allocated = match (end - target).saturating_sub(used) {
+                remainder if remainder > 0 && remainder < HEADER_LEN as u64 => end - target,
+                _ => used
+            };
+            if target != header.offset || allocated != header.allocated {
+                self.batch(|| {
+                    self.relocate_cell(&header, target, allocated)?;
+                    match target != header.offset {
+                        true => relocate(header.offset, target),
+                        false => Ok(())
+                    }
+                })?;
+            }
+            target += allocated;
+        }
+        self.set_len(target)?;
+        Self::lock_free_cells()?.remove(&self.path);
+        Ok(len0.saturating_sub(target))
+    }
+
+    /// Decodes a binary field; reading external values from this store and
+    /// decompressing compressed values.
+    pub fn decode_field(
+        &self,
+        column: &Column,
+        buffer: &Vec<u8>,
+        offset: usize,
+    ) -> TypedValue {
+        let fmd = FieldMetadata::decode(buffer[offset]);
+        let result = match (fmd.is_active, fmd.is_compressed, fmd.is_external) {
+            (true, true, _) => self.read_compressed_field(buffer, offset)
+                .and_then(|packed| Self::decompress(&packed))
+                .and_then(|bytes| match bincode::deserialize(&bytes) {
+                    Ok(value) => Ok(value),
+                    Err(err) => fail(err.to_string())
+                }),
+            (true, false, true) => self.read(Self::decode_external_offset(buffer, offset))
+                .map(|(_, value)| value),
+            _ => Ok(column.get_data_type().decode_field_value(buffer, offset))
+        };
+        result.unwrap_or_else(|err| ErrorValue(Exact(err.to_string())))
+    }
+
+    fn decode_external_offset(buffer: &Vec<u8>, offset: usize) -> u64 {
+        NumberType(U64Kind).decode_field_value(buffer, offset).to_u64()
+    }
+
+    /// Returns the raw and stored (e.g. compressed) sizes of a binary field
+    pub fn decode_field_sizes(
+        &self,
+        column: &Column,
+        buffer: &Vec<u8>,
+        offset: usize,
+    ) -> std::io::Result<(usize, usize)> {
+        let fmd = FieldMetadata::decode(buffer[offset]);
+        match (fmd.is_active, fmd.is_compressed) {
+            (false, _) => Ok((0, 0)),
+            (true, true) => Self::decode_compressed_lengths(&self.read_compressed_field(buffer, offset)?),
+            (true, false) => {
+                let size = match bincode::serialized_size(&self.decode_field(column, buffer, offset)) {
+                    Ok(size) => size as usize,
+                    Err(err) => return fail(err.to_string())
+                };
+                Ok((size, size))
+            }
+        }
+    }
+
+    /// Encodes a binary field; where the values of compressed fields are stored deflated
+    /// unless compression does not reduce their size.
     pub fn encode_field(
         &self,
         column: &Column,
         value: &TypedValue,
+        is_compressed: bool,
     ) -> std::io::Result<Vec<u8>> {
         let (data_type, fixed_size) = (column.get_data_type(), column.get_fixed_size());
+        // nulls are encoded as inactive (empty) fields
+        if let Null | Undefined = value { return Ok(vec![0u8; fixed_size]); }
+        if is_compressed {
+            if let Some(encoded) = self.encode_compressed_field(value, fixed_size)? {
+                return Ok(encoded);
+            }
+        }
         let buffer = data_type.encode(value)?;
         let mut encoded = Vec::with_capacity(fixed_size);
         if buffer.len() <= fixed_size {
@@ -76,6 +248,30 @@ impl BLOBStore {
         Ok(encoded)
     }
 
+    fn encode_compressed_field(
+        &self,
+        value: &TypedValue,
+        fixed_size: usize,
+    ) -> std::io::Result<Option<Vec<u8>>> {
+        let bytes = match bincode::serialize(value) {
+            Ok(bytes) => bytes,
+            Err(err) => return fail(err.to_string())
+        };
+        let packed = Self::compress(&bytes)?;
+        if packed.len() >= bytes.len() { return Ok(None); }
+        let mut encoded = Vec::with_capacity(fixed_size);
+        if packed.len() < fixed_size {
+            encoded.push(field::ACTIVE_MASK | field::COMPRESSED_MASK);
+            encoded.extend(packed);
+        } else {
+            let key = self.insert_blob(packed)?;
+            encoded.push(field::ACTIVE_MASK | field::COMPRESSED_MASK | field::EXTERNAL_MASK);
+            encoded.extend(key.offset.to_be_bytes());
+        }
+        encoded.resize(fixed_size, 0u8);
+        Ok(Some(encoded))
+    }
+
     pub fn insert<T>(&self, item: T) -> std::io::Result<BLOBCellMetadata>
     where
         T: serde::ser::Serialize,
@@ -86,19 +282,34 @@ impl BLOBStore {
         }
     }
 
+    /// Releases the cell at the offset for reuse; provided it is in use.
+    pub fn free(&self, offset: u64) -> std::io::Result<()> {
+        let header = self.read_header(offset)?;
+        if header.offset != offset || header.used == 0 { return Ok(()); }
+        self.write_header(&BLOBCellMetadata { used: 0, ..header.clone() })?;
+        Self::lock_free_cells()?.entry(self.path.to_owned()).or_default().insert(offset, header.allocated);
+        Ok(())
+    }
+
     pub fn insert_blob(&self, bytes: Vec<u8>) -> std::io::Result<BLOBCellMetadata> {
+        let used = (HEADER_LEN + bytes.len()) as u64;
+        if let Some(header) = self.take_free_cell(used)? {
+            self.write_header(&header)?;
+            self.write_at(&bytes, header.offset + HEADER_LEN as u64)?;
+            return Ok(header);
+        }
         let limit = self.file.metadata()?.len();
         let offset = limit;
         let header = BLOBCellMetadata {
             offset,
             allocated: Self::compute_allocated_size(bytes.len()),
-            used: (HEADER_LEN + bytes.len()) as u64,
+            used,
         };
         match bincode::serialize(&header) {
             Ok(header_bytes) => {
-                self.file.set_len(limit + header.allocated)?;
-                let _ = self.file.write_at(&header_bytes, header.offset)?;
-                let _ = self.file.write_at(&bytes, header.offset + header_bytes.len() as u64)?;
+                self.set_len(limit + header.allocated)?;
+                self.write_at(&header_bytes, header.offset)?;
+                self.write_at(&bytes, header.offset + header_bytes.len() as u64)?;
                 Ok(header)
             }
             Err(err) => fail(err.to_string())
@@ -122,12 +333,22 @@ impl BLOBStore {
         // first, read the header
         let header = self.read_header(offset)?;
 
-        // next, read the byes indicated within the header
-        let mut buffer: Vec<u8> = vec![0u8; header.used as usize];
+        // next, read the bytes indicated within the header
+        let mut buffer: Vec<u8> = vec![0u8; (header.used as usize).saturating_sub(HEADER_LEN)];
         let _ = self.file.read_at(&mut buffer, offset + HEADER_LEN as u64)?;
         Ok((header, buffer))
     }
 
+    /// Reads the compressed bytes of a field; either inline or from this store
+    fn read_compressed_field(&self, buffer: &Vec<u8>, offset: usize) -> std::io::Result<Vec<u8>> {
+        let fmd = FieldMetadata::decode(buffer[offset]);
+        if fmd.is_external {
+            Ok(self.read_blob(Self::decode_external_offset(buffer, offset))?.1)
+        } else {
+            Ok(buffer[offset + 1..].to_vec())
+        }
+    }
+
     /// Reads the header at the offset from the blob store
     pub fn read_header(&self, offset: u64) -> std::io::Result<BLOBCellMetadata> {
         let mut header_buf: Vec<u8> = vec![0u8; HEADER_LEN];
@@ -138,6 +359,78 @@ impl BLOBStore {
         }
     }
 
+    /// Reads the headers of all cells of the blob store (in order of their offsets)
+    pub fn read_headers(&self) -> std::io::Result<Vec<BLOBCellMetadata>> {
+        let (len, mut offset) = (self.file.metadata()?.len(), 0);
+        let mut headers = Vec::new();
+        while offset < len {
+            let header = self.read_header(offset)?;
+            if header.offset != offset || header.allocated < HEADER_LEN as u64 || header.used > header.allocated {
+                return fail(format!("Corrupt BLOB header at offset {offset} of {}", self.path));
+            }
+            offset += header.allocated;
+            headers.push(header);
+        }
+        Ok(headers)
+    }
+
+    /// Rebuilds the free-space map of this store from the headers of its cells; unless the store
+    /// is already known to this process. The cells of a store whose headers cannot be read
+    /// are not reused (the corruption is reported by [BLOBStore::compact]).
+    fn load_free_cells(&self) -> std::io::Result<()> {
+        if Self::lock_free_cells()?.contains_key(&self.path) { return Ok(()); }
+        let cells = self.read_headers().unwrap_or_default().into_iter()
+            .filter(|header| header.used == 0)
+            .map(|header| (header.offset, header.allocated))
+            .collect::<BTreeMap<_, _>>();
+        Self::lock_free_cells()?.entry(self.path.to_owned()).or_insert(cells);
+        Ok(())
+    }
+
+    /// Moves a cell to the offset, resizing it; the remainder of the original cell
+    /// (if any) becomes a free cell, thus the cells remain contiguous.
+    fn relocate_cell(&self, header: &BLOBCellMetadata, offset: u64, allocated: u64) -> std::io::Result<()> {
+        let (_, bytes) = self.read_blob(header.offset)?;
+        let relocated = BLOBCellMetadata::new(offset, allocated, header.used.max(HEADER_LEN as u64));
+        self.write_header(&relocated)?;
+        self.write_at(&bytes, offset + HEADER_LEN as u64)?;
+        let (end, remainder) = (header.offset + header.allocated, offset + allocated);
+        if end > remainder {
+            self.write_header(&BLOBCellMetadata::new(remainder, end - remainder, 0))?;
+        }
+        Ok(())
+    }
+
+    /// Removes the smallest free cell capable of holding the given number of bytes
+    /// from the free-space map; returning its (updated) header.
+    fn take_free_cell(&self, used: u64) -> std::io::Result<Option<BLOBCellMetadata>> {
+        loop {
+            let (offset, allocated) = {
+                let mut free_cells = Self::lock_free_cells()?;
+                let cells = match free_cells.get_mut(&self.path) {
+                    Some(cells) => cells,
+                    None => return Ok(None)
+                };
+                let found = cells.iter()
+                    .filter(|(_, allocated)| **allocated >= used)
+                    .min_by_key(|(_, allocated)| **allocated)
+                    .map(|(offset, allocated)| (*offset, *allocated));
+                match found {
+                    Some((offset, allocated)) => {
+                        cells.remove(&offset);
+                        (offset, allocated)
+                    }
+                    None => return Ok(None)
+                }
+            };
+            // the cell must still be free (e.g. the store may have since been compacted)
+            let header = self.read_header(offset)?;
+            if header.offset == offset && header.allocated == allocated && header.used == 0 {
+                return Ok(Some(BLOBCellMetadata::new(offset, allocated, used)));
+            }
+        }
+    }
+
     pub fn update<T>(
         &self,
         offset: u64,
@@ -162,24 +455,48 @@ impl BLOBStore {
 
         // create a new header with the new amount used
         let mut new_header = header.clone();
-        new_header.used = bytes.len() as u64;
+        new_header.used = (HEADER_LEN + bytes.len()) as u64;
         assert!(new_header.used <= new_header.allocated);
 
         // serialize the new header
         match bincode::serialize(&new_header) {
             Ok(header_bytes) => {
                 // update the data
-                let _ = self.file.write_at(&header_bytes, header.offset)?;
-                let _ = self.file.write_at(&bytes, header.offset + header_bytes.len() as u64)?;
+                self.write_at(&header_bytes, header.offset)?;
+                self.write_at(&bytes, header.offset + header_bytes.len() as u64)?;
                 Ok(header)
             }
             Err(err) => fail(err.to_string())
         }
     }
+
+    fn set_len(&self, len: u64) -> std::io::Result<()> {
+        match &self.wal {
+            Some(wal) => wal.set_len(&self.file, len),
+            None => self.file.set_len(len)
+        }
+    }
+
+    fn write_header(&self, header: &BLOBCellMetadata) -> std::io::Result<()> {
+        match bincode::serialize(header) {
+            Ok(header_bytes) => self.write_at(&header_bytes, header.offset),
+            Err(err) => fail(err.to_string())
+        }
+    }
+
+    fn write_at(&self, bytes: &[u8], offset: u64) -> std::io::Result<()> {
+        match &self.wal {
+            Some(wal) => wal.write_at(&self.file, bytes, offset),
+            None => self.file.write_all_at(bytes, offset)
+        }
+    }
 }
 
 pub const HEADER_LEN: usize = 24;
 
+/// the length of the header of compressed data => (raw length|compressed length)
+pub const COMPRESSED_HEADER_LEN: usize = 8;
+
 /// BLOB Store: Cell Metadata
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct BLOBCellMetadata {
@@ -210,6 +527,39 @@ mod tests {
     use crate::namespaces::Namespace;
     use crate::typed_values::TypedValue;
     use crate::typed_values::TypedValue::StringValue;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_compact() {
+        let bs = BLOBStore::open(&Namespace::new("blobs", "compact", "data")).unwrap();
+        bs.file.set_len(0).unwrap();
+        let a = bs.insert(StringValue("Hello World".repeat(8))).unwrap();
+        let b = bs.insert(StringValue("orphaned".into())).unwrap();
+        let c = bs.insert(StringValue("Goodbye World".repeat(8))).unwrap();
+        let len0 = bs.file.metadata().unwrap().len();
+
+        // only the referenced cells remain; the relocated ones are reported
+        let mut relocated = Vec::new();
+        let reclaimed = bs.compact(&BTreeSet::from([a.offset, c.offset]), |old_offset, new_offset| {
+            relocated.push((old_offset, new_offset));
+            Ok(())
+        }).unwrap();
+        assert_eq!(relocated, vec![(c.offset, a.used)]);
+        assert_eq!(reclaimed, len0 - bs.file.metadata().unwrap().len());
+        assert!(reclaimed > b.allocated);
+        assert_eq!(bs.read_headers().unwrap().len(), 2);
+        assert_eq!(bs.read::<TypedValue>(a.offset).unwrap().1, StringValue("Hello World".repeat(8)));
+        assert_eq!(bs.read::<TypedValue>(a.used).unwrap().1, StringValue("Goodbye World".repeat(8)));
+    }
+
+    #[test]
+    fn test_compress() {
+        let bytes = "Hello World! ".repeat(50).into_bytes();
+        let packed = BLOBStore::compress(&bytes).unwrap();
+        assert!(packed.len() < bytes.len());
+        assert_eq!(BLOBStore::decode_compressed_lengths(&packed).unwrap(), (bytes.len(), packed.len() - 8));
+        assert_eq!(BLOBStore::decompress(&packed).unwrap(), bytes);
+    }
 
     #[test]
     fn test_crud() {
@@ -237,4 +587,46 @@ mod tests {
         println!("value3: {:?}", value);
         assert_eq!(value, StringValue("Goodbye World".into()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_free_and_reuse() {
+        let bs = BLOBStore::open(&Namespace::new("blobs", "reuse", "data")).unwrap();
+        bs.file.set_len(0).unwrap();
+        let a = bs.insert(StringValue("Hello World".repeat(4))).unwrap();
+        let b = bs.insert(StringValue("Goodbye World".into())).unwrap();
+        let len0 = bs.file.metadata().unwrap().len();
+
+        // the freed cell is reused by a subsequent (smaller) insert
+        bs.free(a.offset).unwrap();
+        assert_eq!(bs.read_header(a.offset).unwrap().used, 0);
+        let c = bs.insert(StringValue("Hello".into())).unwrap();
+        assert_eq!((c.offset, c.allocated), (a.offset, a.allocated));
+        assert_eq!(bs.file.metadata().unwrap().len(), len0);
+        assert_eq!(bs.read::<TypedValue>(c.offset).unwrap().1, StringValue("Hello".into()));
+        assert_eq!(bs.read::<TypedValue>(b.offset).unwrap().1, StringValue("Goodbye World".into()));
+
+        // whereas a larger insert is appended
+        let d = bs.insert(StringValue("Hello World".repeat(8))).unwrap();
+        assert_eq!(d.offset, len0);
+        assert_eq!(bs.read_headers().unwrap(), vec![c, b, d]);
+    }
+
+    #[test]
+    fn test_free_and_reuse_after_reopen() {
+        let ns = Namespace::new("blobs", "reopen", "data");
+        let bs = BLOBStore::open(&ns).unwrap();
+        bs.file.set_len(0).unwrap();
+        let a = bs.insert(StringValue("Hello World".repeat(4))).unwrap();
+        let b = bs.insert(StringValue("Goodbye World".into())).unwrap();
+        bs.free(a.offset).unwrap();
+        let len0 = bs.file.metadata().unwrap().len();
+
+        // the free-space map is rebuilt from the headers of the cells (e.g. after a restart)
+        BLOBStore::lock_free_cells().unwrap().remove(&bs.path);
+        let bs = BLOBStore::open(&ns).unwrap();
+        let c = bs.insert(StringValue("Hello".into())).unwrap();
+        assert_eq!((c.offset, c.allocated), (a.offset, a.allocated));
+        assert_eq!(bs.file.metadata().unwrap().len(), len0);
+        assert_eq!(bs.read_headers().unwrap(), vec![c, b]);
+    }
+}
@@ -0,0 +1,334 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// BTreeIndex class
+////////////////////////////////////////////////////////////////////
+
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use crate::columns::Column;
+use crate::data_types::DataType::{BooleanType, NumberType};
+use crate::errors::throw;
+use crate::number_kind::NumberKind::U64Kind;
+use crate::numbers::Numbers::U64Value;
+use crate::parameter::Parameter;
+use crate::row_collection::RowCollection;
+use crate::sorting::Sorting;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, Null, Number};
+
+/// the maximum number of entries per node
+const NODE_CAPACITY: usize = 64;
+
+/// the ID of the root node
+const ROOT_ID: usize = 0;
+
+/// Disk-based B+Tree index; maps the values of a column onto the IDs of the rows
+/// containing them, in key order. Each node occupies a row of the nodes table (e.g. "stocks.btree2").
+/// Entries are (key, row ID) pairs, so that duplicate keys remain distinct;
+/// removed entries are dropped from their leaf without rebalancing the tree.
+#[derive(Debug)]
+pub struct BTreeIndex {
+    nodes: Box<dyn RowCollection>,
+}
+
+impl BTreeIndex {
+    //////////////////////////////////////////////////////////
+    //  STATIC METHODS
+    //////////////////////////////////////////////////////////
+
+    /// Generates the columns of the nodes table based on the source column
+    pub fn create_btree_nodes_columns(src_column: &Column) -> Vec<Column> {
+        let mut params = vec![
+            Parameter::new("__leaf__", BooleanType),
+            Parameter::new("__count__", NumberType(U64Kind)),
+            Parameter::new("__next__", NumberType(U64Kind)),
+        ];
+        for n in 0..NODE_CAPACITY {
+            params.push(Parameter::new(format!("key{n}"), src_column.get_data_type().to_owned()));
+            params.push(Parameter::new(format!("row_id{n}"), NumberType(U64Kind)));
+        }
+        for n in 0..=NODE_CAPACITY {
+            params.push(Parameter::new(format!("child{n}"), NumberType(U64Kind)));
+        }
+        Column::from_parameters(&params)
+    }
+
+    /// Compares two entries by key, then by row ID; keys are ordered as they are by `order by`.
+    fn compare(a: &(TypedValue, usize), b: &(TypedValue, usize)) -> Ordering {
+        Self::compare_keys(&a.0, &b.0).then(a.1.cmp(&b.1))
+    }
+
+    fn compare_keys(a: &TypedValue, b: &TypedValue) -> Ordering {
+        Sorting::compare(std::slice::from_ref(a), std::slice::from_ref(b), &[false])
+    }
+
+    pub fn open(nodes: Box<dyn RowCollection>) -> Self {
+        Self { nodes }
+    }
+
+    //////////////////////////////////////////////////////////
+    //  INSTANCE METHODS
+    //////////////////////////////////////////////////////////
+
+    /// Returns the (key, row ID) entries whose keys fall within the bounds; in key order.
+    pub fn find_entries_in_range(
+        &self,
+        lower: Bound<&TypedValue>,
+        upper: Bound<&TypedValue>,
+    ) -> std::io::Result<Vec<(TypedValue, usize)>> {
+        // descend to the leaf which may contain the lowest key
+        let mut node = self.read_node(ROOT_ID)?;
+        while !node.is_leaf {
+            let position = match lower {
+                Bound::Included(key) | Bound::Excluded(key) =>
+                    Self::find_child_position(&node, &(key.to_owned(), 0)),
+                Bound::Unbounded => 0
+            };
+            node = self.read_node(node.children[position])?;
+        }
+
+        // scan the leaves until the highest key has been passed
+        let mut entries = Vec::new();
+        loop {
+            for entry in node.entries {
+                let is_above_lower = match lower {
+                    Bound::Included(key) => Self::compare_keys(&entry.0, key) != Ordering::Less,
+                    Bound::Excluded(key) => Self::compare_keys(&entry.0, key) == Ordering::Greater,
+                    Bound::Unbounded => true
+                };
+                let is_below_upper = match upper {
+                    Bound::Included(key) => Self::compare_keys(&entry.0, key) != Ordering::Greater,
+                    Bound::Excluded(key) => Self::compare_keys(&entry.0, key) == Ordering::Less,
+                    Bound::Unbounded => true
+                };
+                if !is_below_upper { return Ok(entries); }
+                if is_above_lower { entries.push(entry); }
+            }
+            match node.next {
+                Some(next_id) => node = self.read_node(next_id)?,
+                None => return Ok(entries)
+            }
+        }
+    }
+
+    /// Returns the IDs of the rows whose keys fall within the bounds; in key order.
+    pub fn find_row_ids_in_range(
+        &self,
+        lower: Bound<&TypedValue>,
+        upper: Bound<&TypedValue>,
+    ) -> std::io::Result<Vec<usize>> {
+        Ok(self.find_entries_in_range(lower, upper)?.into_iter().map(|(_, id)| id).collect())
+    }
+
+    /// Returns the position of the child node (within an internal node) which covers the entry
+    fn find_child_position(node: &Node, entry: &(TypedValue, usize)) -> usize {
+        node.entries.partition_point(|separator| Self::compare(separator, entry) != Ordering::Greater)
+    }
+
+    /// Inserts the (key, row ID) entry; should the root split, its contents are moved
+    /// to a new node so that the root remains the first node.
+    pub fn insert(&mut self, key: &TypedValue, row_id: usize) -> std::io::Result<()> {
+        if let Some((separator, right_id)) = self.insert_into(ROOT_ID, (key.to_owned(), row_id))? {
+            let left_id = self.nodes.len()?;
+            let left = self.read_node(ROOT_ID)?;
+            self.write_node(left_id, &left)?;
+            self.write_node(ROOT_ID, &Node {
+                is_leaf: false,
+                entries: vec![separator],
+                children: vec![left_id, right_id],
+                next: None,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Inserts the entry into the subtree; returning the separator and ID of the
+    /// new (right) sibling should the node split.
+    fn insert_into(
+        &mut self,
+        node_id: usize,
+        entry: (TypedValue, usize),
+    ) -> std::io::Result<Option<((TypedValue, usize), usize)>> {
+        let mut node = self.read_node(node_id)?;
+        if node.is_leaf {
+            let position = node.entries.partition_point(|other| Self::compare(other, &entry) == Ordering::Less);
+            if node.entries.get(position).is_some_and(|other| Self::compare(other, &entry) == Ordering::Equal) {
+                return Ok(None);
+            }
+            node.entries.insert(position, entry);
+        } else {
+            let position = Self::find_child_position(&node, &entry);
+            match self.insert_into(node.children[position], entry)? {
+                Some((separator, right_id)) => {
+                    node.entries.insert(position, separator);
+                    node.children.insert(position + 1, right_id);
+                }
+                None => return Ok(None)
+            }
+        }
+        if node.entries.len() <= NODE_CAPACITY {
+            self.write_node(node_id, &node)?;
+            return Ok(None);
+        }
+        self.split(node_id, node).map(Some)
+    }
+
+    /// Reads a node; nodes which have not yet been written are empty leaves.
+    fn read_node(&self, node_id: usize) -> std::io::Result<Node> {
+        if node_id >= self.nodes.len()? { return Ok(Node::default()); }
+        let (row, metadata) = self.nodes.read_row(node_id)?;
+        if !metadata.is_allocated { return Ok(Node::default()); }
+        let count = row[1].to_usize();
+        let is_leaf = matches!(row[0], Boolean(true));
+        Ok(Node {
+            is_leaf,
+            entries: (0..count)
+                .map(|n| (row[3 + 2 * n].to_owned(), row[4 + 2 * n].to_usize()))
+                .collect(),
+            children: match is_leaf {
+                true => Vec::new(),
+                false => (0..=count).map(|n| row[3 + 2 * NODE_CAPACITY + n].to_usize()).collect()
+            },
+            next: match &row[2] {
+                Number(number) => Some(number.to_usize()),
+                _ => None
+            },
+        })
+    }
+
+    /// Removes the (key, row ID) entry; returning true if it was found.
+    pub fn remove(&mut self, key: &TypedValue, row_id: usize) -> std::io::Result<bool> {
+        let entry = (key.to_owned(), row_id);
+        let mut node_id = ROOT_ID;
+        let mut node = self.read_node(node_id)?;
+        while !node.is_leaf {
+            node_id = node.children[Self::find_child_position(&node, &entry)];
+            node = self.read_node(node_id)?;
+        }
+        match node.entries.iter().position(|other| Self::compare(other, &entry) == Ordering::Equal) {
+            Some(position) => {
+                node.entries.remove(position);
+                self.write_node(node_id, &node)?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    /// Splits an overflowing node in two; the upper half is moved to a new node.
+    fn split(
+        &mut self,
+        node_id: usize,
+        mut node: Node,
+    ) -> std::io::Result<((TypedValue, usize), usize)> {
+        let right_id = self.nodes.len()?;
+        let middle = node.entries.len() / 2;
+        let (separator, right) = if node.is_leaf {
+            let entries = node.entries.split_off(middle);
+            let right = Node { is_leaf: true, entries, children: Vec::new(), next: node.next };
+            node.next = Some(right_id);
+            (right.entries[0].to_owned(), right)
+        } else {
+            let entries = node.entries.split_off(middle + 1);
+            let children = node.children.split_off(middle + 1);
+            let separator = node.entries.remove(middle);
+            (separator, Node { is_leaf: false, entries, children, next: None })
+        };
+        self.write_node(right_id, &right)?;
+        self.write_node(node_id, &node)?;
+        Ok((separator, right_id))
+    }
+
+    /// Writes a node to the nodes table
+    fn write_node(&mut self, node_id: usize, node: &Node) -> std::io::Result<()> {
+        let to_number = |n: usize| Number(U64Value(n as u64));
+        let mut values = vec![
+            Boolean(node.is_leaf),
+            to_number(node.entries.len()),
+            node.next.map(to_number).unwrap_or(Null),
+        ];
+        for n in 0..NODE_CAPACITY {
+            match node.entries.get(n) {
+                Some((key, row_id)) => values.extend([key.to_owned(), to_number(*row_id)]),
+                None => values.extend([Null, Null])
+            }
+        }
+        for n in 0..=NODE_CAPACITY {
+            values.push(node.children.get(n).map(|id| to_number(*id)).unwrap_or(Null));
+        }
+        match self.nodes.overwrite_row(node_id, Row::new(node_id, values)) {
+            ErrorValue(err) => throw(err),
+            _ => Ok(())
+        }
+    }
+}
+
+/// Represents a node of the tree; internal nodes hold separators (entries) and one more child
+/// than separators, whereas leaves hold entries and the ID of the next leaf.
+#[derive(Clone, Debug, PartialEq)]
+struct Node {
+    is_leaf: bool,
+    entries: Vec<(TypedValue, usize)>,
+    children: Vec<usize>,
+    next: Option<usize>,
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self { is_leaf: true, entries: Vec::new(), children: Vec::new(), next: None }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_row_collection::FileRowCollection;
+    use crate::namespaces::Namespace;
+    use crate::number_kind::NumberKind::I64Kind;
+    use crate::numbers::Numbers::I64Value;
+    use crate::testdata::make_quote_parameters;
+
+    fn create_btree_index(path: &str) -> BTreeIndex {
+        let frc = FileRowCollection::create_table(&Namespace::parse(path).unwrap(), &make_quote_parameters()).unwrap();
+        let columns = BTreeIndex::create_btree_nodes_columns(&Column::new("n", NumberType(I64Kind), Null, 0));
+        BTreeIndex::open(Box::new(frc.create_related_table(columns, "btree0").unwrap()))
+    }
+
+    #[test]
+    fn test_insert_then_find_in_range() {
+        // insert enough keys (in a scrambled order) to split the root several times
+        let mut index = create_btree_index("btree.range.stocks");
+        let count = NODE_CAPACITY * 10;
+        for n in 0..count {
+            let id = (n * 7919) % count;
+            index.insert(&Number(I64Value((id / 2) as i64)), id).unwrap();
+        }
+        let all = index.find_row_ids_in_range(Bound::Unbounded, Bound::Unbounded).unwrap();
+        assert_eq!(all, (0..count).collect::<Vec<_>>());
+
+        // duplicate keys are ordered by row ID
+        let (low, high) = (Number(I64Value(100)), Number(I64Value(103)));
+        assert_eq!(index.find_row_ids_in_range(Bound::Included(&low), Bound::Excluded(&high)).unwrap(),
+                   vec![200, 201, 202, 203, 204, 205]);
+        assert_eq!(index.find_row_ids_in_range(Bound::Excluded(&low), Bound::Included(&high)).unwrap(),
+                   vec![202, 203, 204, 205, 206, 207]);
+    }
+
+    #[test]
+    fn test_insert_then_remove() {
+        let mut index = create_btree_index("btree.remove.stocks");
+        for id in 0..(NODE_CAPACITY * 3) {
+            index.insert(&Number(I64Value(id as i64)), id).unwrap();
+        }
+        for id in (0..(NODE_CAPACITY * 3)).filter(|id| id % 3 != 0) {
+            assert!(index.remove(&Number(I64Value(id as i64)), id).unwrap());
+        }
+        assert!(!index.remove(&Number(I64Value(1)), 1).unwrap());
+        let (low, high) = (Number(I64Value(10)), Number(I64Value(20)));
+        assert_eq!(index.find_row_ids_in_range(Bound::Included(&low), Bound::Included(&high)).unwrap(),
+                   vec![12, 15, 18]);
+    }
+}
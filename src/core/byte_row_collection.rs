@@ -5,7 +5,10 @@
 
 use crate::byte_code_compiler::ByteCodeCompiler;
 use crate::columns::Column;
+use crate::expression::Conditions;
 use crate::field::FieldMetadata;
+use crate::machine::Machine;
+use crate::memory_indices::MemoryIndices;
 use crate::numbers::Numbers;
 use crate::numbers::Numbers::RowsAffected;
 use crate::row_collection::RowCollection;
@@ -22,6 +25,8 @@ pub struct ByteRowCollection {
     row_data: Vec<Vec<u8>>,
     record_size: usize,
     watermark: usize,
+    #[serde(skip)]
+    indices: MemoryIndices,
 }
 
 impl ByteRowCollection {
@@ -51,6 +56,7 @@ impl ByteRowCollection {
             watermark: rows.len(),
             columns,
             row_data: rows,
+            indices: MemoryIndices::default(),
         }
     }
 
@@ -58,6 +64,34 @@ impl ByteRowCollection {
     pub fn new(columns: Vec<Column>, capacity: usize) -> Self {
         Self::from_bytes(columns, Vec::new())
     }
+
+    /// Builds an ephemeral hash index upon the specified column
+    pub fn create_index(&mut self, column_id: usize) {
+        self.indices.create_index(column_id, self.get_rows());
+    }
+
+    /// Returns the IDs of the rows which may satisfy the condition via a hash index;
+    /// or [None] when no hash index is applicable.
+    pub fn find_row_ids_where(
+        &self,
+        machine: &Machine,
+        condition: &Option<Conditions>,
+    ) -> std::io::Result<Option<Vec<usize>>> {
+        self.indices.find_row_ids_where(machine, &self.columns, condition)
+    }
+
+    /// Performs the write operation upon the row, then reconciles the hash indices with its outcome.
+    fn write_with_indices<F>(&mut self, id: usize, write: F) -> TypedValue
+    where
+        F: FnOnce(&mut Self) -> TypedValue,
+    {
+        if self.indices.is_empty() { return write(self); }
+        let before = self.read_one(id).ok().flatten();
+        let outcome = write(self);
+        let after = self.read_one(id).ok().flatten();
+        self.indices.reconcile(id, &before, &after);
+        outcome
+    }
 }
 
 impl RowCollection for ByteRowCollection {
@@ -82,19 +116,21 @@ impl RowCollection for ByteRowCollection {
         column_id: usize,
         new_value: TypedValue,
     ) -> TypedValue {
-        let column = &self.columns[column_id];
-        let offset = self.convert_rowid_to_offset(id) + column.get_offset() as u64;
-        let buffer = column.get_data_type().encode_field(
-            &new_value,
-            &FieldMetadata::new(true),
-            column.get_fixed_size(),
-        );
-        let mut encoded_row = self.row_data[id].to_owned();
-        let start = offset as usize;
-        let end = start + buffer.len();
-        encoded_row[start..end].copy_from_slice(buffer.as_slice());
-        self.row_data[id] = encoded_row;
-        Number(RowsAffected(1))
+        self.write_with_indices(id, |brc| {
+            let column = &brc.columns[column_id];
+            let offset = brc.convert_rowid_to_offset(id) + column.get_offset() as u64;
+            let buffer = column.get_data_type().encode_field(
+                &new_value,
+                &FieldMetadata::new(true),
+                column.get_fixed_size(),
+            );
+            let mut encoded_row = brc.row_data[id].to_owned();
+            let start = offset as usize;
+            let end = start + buffer.len();
+            encoded_row[start..end].copy_from_slice(buffer.as_slice());
+            brc.row_data[id] = encoded_row;
+            Number(RowsAffected(1))
+        })
     }
 
     fn overwrite_field_metadata(
@@ -103,28 +139,34 @@ impl RowCollection for ByteRowCollection {
         column_id: usize,
         metadata: FieldMetadata,
     ) -> TypedValue {
-        let column = &self.columns[column_id];
-        self.row_data[id][column.get_offset()] = metadata.encode();
-        Number(RowsAffected(1))
+        self.write_with_indices(id, |brc| {
+            let column = &brc.columns[column_id];
+            brc.row_data[id][column.get_offset()] = metadata.encode();
+            Number(RowsAffected(1))
+        })
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
-        // resize the rows to prevent overflow
-        if self.row_data.len() <= id {
-            self.row_data.resize(id + 1, Vec::new());
-        }
+        self.write_with_indices(id, |brc| {
+            // resize the rows to prevent overflow
+            if brc.row_data.len() <= id {
+                brc.row_data.resize(id + 1, Vec::new());
+            }
 
-        // set the block, update the watermark
-        self.row_data[id] = ByteCodeCompiler::encode_row(&row, &self.columns);
-        if self.watermark <= id {
-            self.watermark = id + 1;
-        }
-        Number(RowsAffected(1))
+            // set the block, update the watermark
+            brc.row_data[id] = ByteCodeCompiler::encode_row(&row, &brc.columns);
+            if brc.watermark <= id {
+                brc.watermark = id + 1;
+            }
+            Number(RowsAffected(1))
+        })
     }
 
     fn overwrite_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
-        self.row_data[id][0] = metadata.encode();
-        Number(RowsAffected(1))
+        self.write_with_indices(id, |brc| {
+            brc.row_data[id][0] = metadata.encode();
+            Number(RowsAffected(1))
+        })
     }
 
     fn read_field(&self, id: usize, column_id: usize) -> TypedValue {
@@ -162,6 +204,9 @@ impl RowCollection for ByteRowCollection {
     fn resize(&mut self, new_size: usize) -> TypedValue {
         self.row_data.resize(new_size, Vec::new());
         self.watermark = new_size;
+        for column_id in self.indices.get_indexed_column_ids() {
+            self.create_index(column_id);
+        }
         Number(Numbers::Ack)
     }
 }
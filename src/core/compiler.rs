@@ -10,14 +10,15 @@ use crate::errors::throw;
 use crate::errors::Errors::{Exact, ExactNear, Syntax, TypeMismatch};
 use crate::errors::TypeMismatchErrors::{CodeBlockExpected, VariableExpected};
 use crate::expression::Conditions::*;
-use crate::expression::CreationEntity::{IndexEntity, TableEntity};
+use crate::expression::CreationEntity::{IndexEntity, MaterializedViewEntity, SequenceEntity, TableEntity, TriggerEntity, ViewEntity};
 use crate::expression::DatabaseOps::{Mutation, Queryable};
 use crate::expression::Expression::*;
-use crate::expression::MutateTarget::TableTarget;
-use crate::expression::Mutations::{Create, Declare, Drop, IntoNs, Undelete};
+use crate::expression::MutateTarget::{PartitionTarget, TableTarget, TriggerTarget, ViewTarget};
+use crate::expression::Mutations::{Create, Declare, Drop, IntoNs, NextVal, Refresh, Truncate, TruncatePartition, Undelete};
 use crate::expression::Queryables::Select;
 use crate::expression::*;
 use crate::numbers::Numbers::*;
+use crate::object_config::{ConstraintConfig, DeleteActions, IndexKinds, ReferenceConfig, TriggerEvents, TriggerTimings};
 use crate::parameter::Parameter;
 use crate::structures::HardStructure;
 use crate::structures::Structures::Hard;
@@ -279,8 +280,12 @@ impl Compiler {
                 "[-]" => self.parse_expression_1a(nts, |e| Directive(Directives::MustNotAck(e))),
                 "[~]" => self.parse_expression_1a(nts, |e| Directive(Directives::MustIgnoreAck(e))),
                 "Ack" => Ok((ACK, nts)),
+                "alter" => self.parse_keyword_alter(nts),
                 "append" => self.parse_keyword_append(nts),
+                "begin" => Ok((TransactionOp(TransactionOps::Begin), nts)),
+                "commit" => Ok((TransactionOp(TransactionOps::Commit), nts)),
                 "create" => self.parse_keyword_create(nts),
+                "declare" => self.parse_keyword_declare(nts),
                 "delete" => self.parse_keyword_delete(nts),
                 "DELETE" => self.parse_keyword_http(ts),
                 "drop" => self.parse_mutate_target(nts, |m| DatabaseOp(Mutation(Drop(m)))),
@@ -301,9 +306,12 @@ impl Compiler {
                 "limit" => throw(ExactNear("`from` is expected before `limit`: from stocks limit 5".into(), nts.current())),
                 "mod" => self.parse_keyword_mod(nts),
                 "NaN" => Ok((Literal(Number(NaNValue)), nts)),
+                "nextval" => self.parse_expression_1a(nts, |path| DatabaseOp(Mutation(NextVal { path }))),
                 "ns" => self.parse_expression_1a(nts, Ns),
                 "null" => Ok((NULL, nts)),
                 "overwrite" => self.parse_keyword_overwrite(nts),
+                "refresh" => self.parse_expression_1a(nts, |path| DatabaseOp(Mutation(Refresh { path }))),
+                "rollback" => Ok((TransactionOp(TransactionOps::Rollback), nts)),
                 "PATCH" => self.parse_keyword_http(ts),
                 "POST" => self.parse_keyword_http(ts),
                 "PUT" => self.parse_keyword_http(ts),
@@ -311,7 +319,9 @@ impl Compiler {
                 "select" => self.parse_keyword_select(nts),
                 "Struct" => self.parse_keyword_struct(nts),
                 "table" => self.parse_keyword_table(nts),
+                "transaction" => self.parse_expression_1a(nts, |code| TransactionOp(TransactionOps::Block(code))),
                 "true" => Ok((TRUE, nts)),
+                "truncate" => self.parse_keyword_truncate(nts),
                 "undefined" => Ok((UNDEFINED, nts)),
                 "undelete" => self.parse_keyword_undelete(nts),
                 "update" => self.parse_keyword_update(nts),
@@ -323,6 +333,69 @@ impl Compiler {
         } else { fail("Unexpected end of input") }
     }
 
+    /// Changes the layout of a table
+    /// ex: alter table stocks add column rating: i64 = 0, rename column last_sale to price
+    fn parse_keyword_alter(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (table, mut ts) = self.compile_next(ts.expect("table")?)?;
+        let mut alterations = Vec::new();
+        loop {
+            let (alteration, ats) = self.parse_alteration(ts)?;
+            alterations.push(alteration);
+            if !ats.is(",") { ts = ats; break; }
+            ts = ats.skip();
+        }
+        Ok((DatabaseOp(Mutation(Mutations::Alter { path: Box::new(table), alterations })), ts))
+    }
+
+    /// Parses a single change to the layout of a table
+    /// ex: drop column rating
+    fn parse_alteration(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Alteration, TokenSlice)> {
+        match ts.next() {
+            (Some(Atom { text, .. }), nts) => match text.as_str() {
+                "add" => {
+                    let (param, ts) = self.expect_parameter(nts.expect("column")?)?;
+                    Ok((Alteration::AddColumn(param), ts))
+                }
+                "alter" => {
+                    let ts = nts.expect("column")?;
+                    let (param, pts) = self.expect_parameter(ts.to_owned())?;
+                    match param.get_data_type() {
+                        VaryingType(..) => throw(ExactNear("Column type expected".into(), ts.current())),
+                        _ => Ok((Alteration::RetypeColumn(param), pts))
+                    }
+                }
+                "append" => Ok((Alteration::AppendOnly(true), nts.expect("only")?)),
+                "decrypt" => Ok((Alteration::Encryption(false), nts)),
+                "drop" => {
+                    let (name, ts) = self.expect_column_name(nts.expect("column")?)?;
+                    Ok((Alteration::DropColumn(name), ts))
+                }
+                "encrypt" => Ok((Alteration::Encryption(true), nts)),
+                "rename" => {
+                    let (old_name, ts) = self.expect_column_name(nts.expect("column")?)?;
+                    let (new_name, ts) = self.expect_column_name(ts.expect("to")?)?;
+                    Ok((Alteration::RenameColumn { old_name, new_name }, ts))
+                }
+                "reuse" => Ok((Alteration::AppendOnly(false), nts.expect("slots")?)),
+                _ => throw(ExactNear("Expected add, alter, append, decrypt, drop, encrypt, rename or reuse".into(), ts.current()))
+            }
+            _ => throw(ExactNear("Expected add, alter, append, decrypt, drop, encrypt, rename or reuse".into(), ts.current()))
+        }
+    }
+
+    fn expect_column_name(&mut self, ts: TokenSlice) -> std::io::Result<(String, TokenSlice)> {
+        match ts.next() {
+            (Some(Atom { text, .. }), ts) => Ok((text, ts)),
+            _ => throw(ExactNear("Column name expected".into(), ts.current()))
+        }
+    }
+
     /// Appends a new row to a table
     /// ex: append stocks select symbol: "ABC", exchange: "NYSE", last_sale: 0.1008
     fn parse_keyword_append(
@@ -341,24 +414,152 @@ impl Compiler {
     ) -> std::io::Result<(Expression, TokenSlice)> {
         if let (Some(t), ts) = ts.next() {
             match t.get_raw_value().as_str() {
-                "index" => self.parse_keyword_create_index(ts),
+                "index" => self.parse_keyword_create_index(ts, false),
+                "materialized" => self.parse_keyword_create_materialized_view(ts.expect("view")?),
+                "sequence" => self.parse_keyword_create_sequence(ts),
                 "table" => self.parse_keyword_create_table(ts),
+                "trigger" => self.parse_keyword_create_trigger(ts),
+                "unique" => self.parse_keyword_create_index(ts.expect("index")?, true),
+                "view" => self.parse_keyword_create_view(ts),
                 name => throw(ExactNear(format!("Syntax error: expect type identifier, got '{}'", name), ts.current()))
             }
         } else { fail("Unexpected end of input") }
     }
 
+    /// Parses an index creation expression
+    /// ex: create unique index ns("securities.nyse.stocks") on [symbol]
     fn parse_keyword_create_index(
         &mut self,
         ts: TokenSlice,
+        is_unique: bool,
     ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (index, columns, kind, ts) = self.parse_index_definition(ts)?;
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(index),
+            entity: IndexEntity { columns, is_unique, kind },
+        })), ts))
+    }
+
+    /// Parses a sequence creation expression
+    /// ex: create sequence ns("securities.nyse.order_ids") start with 1000 increment by 10
+    fn parse_keyword_create_sequence(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (sequence, ts) = self.compile_next(ts)?;
+        let (start, ts) = if ts.is("start") {
+            let (start, ts) = self.compile_next(ts.expect("start")?.expect("with")?)?;
+            (Some(Box::new(start)), ts)
+        } else { (None, ts) };
+        let (increment, ts) = if ts.is("increment") {
+            let (increment, ts) = self.compile_next(ts.expect("increment")?.expect("by")?)?;
+            (Some(Box::new(increment)), ts)
+        } else { (None, ts) };
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(sequence),
+            entity: SequenceEntity { increment, start },
+        })), ts))
+    }
+
+    /// Parses a trigger creation expression
+    /// ex: create trigger audit_appends after append on ns("securities.nyse.stocks") do fn(row) => ...
+    fn parse_keyword_create_trigger(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (name, ts) = match ts.next() {
+            (Some(Atom { text, .. }), ts) => (text, ts),
+            _ => return throw(ExactNear("Trigger name expected".into(), ts.current()))
+        };
+        let (timing, ts) = match ts.next() {
+            (Some(Atom { text, .. }), nts) => match TriggerTimings::from_code(&text) {
+                Some(timing) => (timing, nts),
+                None => return throw(ExactNear("Trigger timing expected (after or before)".into(), ts.current()))
+            }
+            _ => return throw(ExactNear("Trigger timing expected (after or before)".into(), ts.current()))
+        };
+        let (event, ts) = match ts.next() {
+            (Some(Atom { text, .. }), nts) => match TriggerEvents::from_code(&text) {
+                Some(event) => (event, nts),
+                None => return throw(ExactNear("Trigger event expected (append, delete or update)".into(), ts.current()))
+            }
+            _ => return throw(ExactNear("Trigger event expected (append, delete or update)".into(), ts.current()))
+        };
+        let (table, ts) = self.compile_next(ts.expect("on")?)?;
+        let (function, ts) = self.compile_next(ts.expect("do")?)?;
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(table),
+            entity: TriggerEntity { name, timing, event, function: Box::new(function) },
+        })), ts))
+    }
+
+    /// Parses a materialized view creation expression
+    /// ex: create materialized view ns("securities.nyse.high_flyers") refresh every 60000 as select symbol from ns("securities.nyse.stocks")
+    fn parse_keyword_create_materialized_view(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (view, ts) = self.compile_next(ts)?;
+        // refresh every 60000
+        let (refresh_interval, ts) = if ts.is("refresh") {
+            let (interval, ts) = self.compile_next(ts.expect("refresh")?.expect("every")?)?;
+            (Some(Box::new(interval)), ts)
+        } else { (None, ts) };
+        let (query, ts) = self.compile_next(ts.expect("as")?)?;
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(view),
+            entity: MaterializedViewEntity { query: Box::new(query), refresh_interval },
+        })), ts))
+    }
+
+    /// Parses a view creation expression
+    /// ex: create view ns("securities.nyse.high_flyers") as select symbol from ns("securities.nyse.stocks")
+    fn parse_keyword_create_view(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (view, ts) = self.compile_next(ts)?;
+        let (query, ts) = self.compile_next(ts.expect("as")?)?;
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(view),
+            entity: ViewEntity { query: Box::new(query) },
+        })), ts))
+    }
+
+    /// Parses an ephemeral index declaration
+    /// ex: declare index stocks on [symbol]
+    fn parse_keyword_declare(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (index, columns, kind, ts) = self.parse_index_definition(ts.expect("index")?)?;
+        Ok((DatabaseOp(Mutation(Declare {
+            path: Some(Box::new(index)),
+            entity: IndexEntity { columns, is_unique: false, kind },
+        })), ts))
+    }
+
+    /// Parses the table, columns and (optional) type of an index
+    /// ex: stocks on [last_sale] using btree
+    fn parse_index_definition(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, Vec<Expression>, IndexKinds, TokenSlice)> {
         let (index, ts) = self.compile_next(ts)?;
         let ts = ts.expect("on")?;
         if let (ArrayExpression(columns), ts) = self.compile_next(ts.to_owned())? {
-            Ok((DatabaseOp(Mutation(Create {
-                path: Box::new(index),
-                entity: IndexEntity { columns },
-            })), ts))
+            // using btree
+            let (kind, ts) = if ts.is("using") {
+                let ts = ts.expect("using")?;
+                match ts.next() {
+                    (Some(Atom { text, .. }), nts) => match IndexKinds::from_code(&text) {
+                        Some(kind) => (kind, nts),
+                        None => return throw(ExactNear("Index type expected (btree or hash)".into(), ts.current()))
+                    }
+                    _ => return throw(ExactNear("Index type expected (btree or hash)".into(), ts.current()))
+                }
+            } else { (IndexKinds::Hash, ts) };
+            Ok((index, columns, kind, ts))
         } else {
             throw(ExactNear("Columns expected".into(), ts.current()))
         }
@@ -369,9 +570,26 @@ impl Compiler {
         &mut self,
         ts: TokenSlice,
     ) -> std::io::Result<(Expression, TokenSlice)> {
-        // create table `stocks` (name: String, ..)
+        // create table `stocks` (name: String not null, ..)
         let (table, ts) = self.compile_next(ts)?;
-        if let (Parameters(columns), ts) = self.expect_parameters(ts.to_owned())? {
+        if ts.is("(") {
+            let (columns, constraints, ts) = self.expect_column_definitions(ts)?;
+            // partition by [exchange]
+            let (partitions, ts) =
+                if ts.is("partition") {
+                    let ts = ts.expect("partition")?.expect("by")?;
+                    match self.compile_next(ts.to_owned())? {
+                        (ArrayExpression(partitions), ts) => (partitions, ts),
+                        _ => return throw(ExactNear("Partition columns expected".into(), ts.current()))
+                    }
+                } else {
+                    (Vec::new(), ts)
+                };
+            // append only
+            let (is_append_only, ts) =
+                if ts.is("append") && ts.skip().is("only") { (true, ts.skip().skip()) } else { (false, ts) };
+            // encrypted
+            let (is_encrypted, ts) = if ts.is("encrypted") { (true, ts.skip()) } else { (false, ts) };
             // from { symbol: "ABC", exchange: "NYSE", last_sale: 67.89 }
             let (from, ts) =
                 if ts.is("from") {
@@ -383,7 +601,7 @@ impl Compiler {
                 };
             Ok((DatabaseOp(Mutation(Create {
                 path: Box::new(table),
-                entity: TableEntity { columns, from },
+                entity: TableEntity { columns, constraints, from, is_append_only, is_encrypted, partitions },
             })), ts))
         } else {
             throw(ExactNear("Expected column definitions".into(), ts.current()))
@@ -659,10 +877,15 @@ impl Compiler {
         let (fields, ts) = self.next_expression_list(ts)?;
         let fields = fields.expect("At least one field is required");
         let (from, ts) = self.next_keyword_expr("from", ts)?;
+        let (from, ts) = match from {
+            Some(from) if ts.is("as") => self.parse_as_of(from, ts).map(|(from, ts)| (Some(from), ts))?,
+            Some(from) => self.parse_keyword_select_joins(from, ts).map(|(from, ts)| (Some(from), ts))?,
+            None => (None, ts)
+        };
         let (condition, ts) = self.next_keyword_cond("where", ts)?;
         let (group_by, ts) = self.next_keyword_expression_list("group", "by", ts)?;
         let (having, ts) = self.next_keyword_expr("having", ts)?;
-        let (order_by, ts) = self.next_keyword_expression_list("order", "by", ts)?;
+        let (order_by, ts) = self.next_keyword_sort_list("order", "by", ts)?;
         let (limit, ts) = self.next_keyword_expr("limit", ts)?;
         Ok((DatabaseOp(Queryable(Select {
             fields,
@@ -675,6 +898,47 @@ impl Compiler {
         })), ts))
     }
 
+    /// Builds a language model from the join clauses of a 'select' statement:
+    /// ex: select s.symbol, e.name from s: stocks left join e: exchanges on s.exchange == e.code
+    fn parse_keyword_select_joins(
+        &mut self,
+        left: Expression,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        // determine the kind of join (if any)
+        let keyword = ts.get().map(|t| t.get_raw_value()).unwrap_or_default();
+        let (kind, ts) = match keyword.as_str() {
+            "join" => (JoinKinds::Inner, ts),
+            "inner" => (JoinKinds::Inner, ts.skip()),
+            "cross" => (JoinKinds::Cross, ts.skip()),
+            "full" => (JoinKinds::Full, ts.skip()),
+            "left" => (JoinKinds::Left, ts.skip()),
+            "right" => (JoinKinds::Right, ts.skip()),
+            _ => return Ok((left, ts))
+        };
+        let ts = match kind {
+            JoinKinds::Full | JoinKinds::Left | JoinKinds::Right if ts.is("outer") => ts.skip(),
+            _ => ts
+        }.expect("join")?;
+
+        // get the right-side table and the join condition
+        let (right, ts) = self.compile_next(ts)?;
+        let (condition, ts) = match kind {
+            JoinKinds::Cross => (None, ts),
+            _ => match self.next_keyword_cond("on", ts.to_owned())? {
+                (Some(condition), ts) => (Some(condition), ts),
+                (None, ts) => return throw(ExactNear("Expected keyword 'on'".into(), ts.current()))
+            }
+        };
+        let join = DatabaseOp(Queryable(Queryables::Join {
+            left: Box::new(left),
+            right: Box::new(right),
+            kind,
+            condition,
+        }));
+        self.parse_keyword_select_joins(join, ts)
+    }
+
     /// Builds a language model from a 'struct' statement:
     /// ex: Struct(symbol: String(8), exchange: String(8), last_sale: f64)
     /// ex: Struct(symbol: String(8) = "TRX", exchange: String(8) = "AMEX", last_sale: f64 = 17.69)
@@ -701,12 +965,30 @@ impl Compiler {
             } else {
                 (None, ts)
             };
-            Ok((DatabaseOp(Mutation(Declare(TableEntity { columns: params, from }))), ts))
+            Ok((DatabaseOp(Mutation(Declare { path: None, entity: TableEntity { columns: params, constraints: Vec::new(), from, is_append_only: false, is_encrypted: false, partitions: Vec::new() } })), ts))
         } else {
             throw(ExactNear("Expected column definitions".into(), ts.current()))
         }
     }
 
+    /// Builds a language model from a TRUNCATE statement:
+    /// ex: truncate stocks limit 5
+    /// ex: truncate partition ns("securities.all.stocks") ["NYSE"]
+    fn parse_keyword_truncate(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        if ts.is("partition") {
+            let (path, ts) = self.compile_next(ts.expect("partition")?)?;
+            let (key, ts) = self.compile_next(ts)?;
+            Ok((DatabaseOp(Mutation(TruncatePartition { path: Box::new(path), key: Box::new(key) })), ts))
+        } else {
+            let (path, ts) = self.compile_next(ts)?;
+            let (limit, ts) = self.next_keyword_expr("limit", ts)?;
+            Ok((DatabaseOp(Mutation(Truncate { path: Box::new(path), limit: limit.map(Box::new) })), ts))
+        }
+    }
+
     /// Builds a language model from an UNDELETE statement:
     /// ex: undelete from stocks where symbol == "BANG"
     fn parse_keyword_undelete(
@@ -752,6 +1034,7 @@ impl Compiler {
     /// Parses a mutate-target expression.
     /// ex: drop table ns('finance.securities.stocks')
     /// ex: drop index ns('finance.securities.stocks')
+    /// ex: drop trigger audit_appends on ns('finance.securities.stocks')
     fn parse_mutate_target(
         &mut self,
         ts: TokenSlice,
@@ -764,7 +1047,24 @@ impl Compiler {
                         let (expr, ts) = self.compile_next(ts)?;
                         Ok((f(TableTarget { path: Box::new(expr) }), ts))
                     }
-                    z => throw(ExactNear(format!("Invalid type `{}`, try `table` instead", z), ts.current()))
+                    "partition" => {
+                        let (path, ts) = self.compile_next(ts)?;
+                        let (key, ts) = self.compile_next(ts)?;
+                        Ok((f(PartitionTarget { path: Box::new(path), key: Box::new(key) }), ts))
+                    }
+                    "trigger" => match ts.next() {
+                        (Some(Atom { text: name, .. }), ts) => {
+                            let (path, ts) = self.compile_next(ts.expect("on")?)?;
+                            Ok((f(TriggerTarget { path: Box::new(path), name }), ts))
+                        }
+                        (_, ts) => throw(ExactNear("Trigger name expected".into(), ts.current()))
+                    }
+                    "materialized" | "view" => {
+                        let ts = if keyword == "materialized" { ts.expect("view")? } else { ts };
+                        let (expr, ts) = self.compile_next(ts)?;
+                        Ok((f(ViewTarget { path: Box::new(expr) }), ts))
+                    }
+                    z => throw(ExactNear(format!("Invalid type `{}`, try `table`, `partition`, `trigger` or `view` instead", z), ts.current()))
                 }
             }
             (_, ts) => throw(ExactNear("Syntax error".into(), ts.current()))
@@ -844,6 +1144,10 @@ impl Compiler {
         ts: TokenSlice,
     ) -> std::io::Result<(Expression, TokenSlice)> {
         match ts.to_owned() {
+            t if t.is("as") => {
+                let (as_of, ts) = self.parse_as_of(host, ts)?;
+                self.parse_queryable(as_of, ts)
+            }
             t if t.is("limit") => {
                 let (expr, ts) = self.compile_next(ts.skip())?;
                 self.parse_queryable(DatabaseOp(Queryable(Queryables::Limit { from: Box::new(host), limit: Box::new(expr) })), ts)
@@ -860,6 +1164,23 @@ impl Compiler {
         }
     }
 
+    /// Builds a language model from a point-in-time clause:
+    /// ex: from stocks as of version 3
+    fn parse_as_of(
+        &mut self,
+        host: Expression,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let ts = ts.expect("as")?.expect("of")?;
+        let (is_version, ts) = if ts.is("version") { (true, ts.skip()) } else { (false, ts) };
+        let (point, ts) = self.compile_next(ts)?;
+        Ok((DatabaseOp(Queryable(Queryables::AsOf {
+            from: Box::new(host),
+            point: Box::new(point),
+            is_version,
+        })), ts))
+    }
+
     fn error_expected_operator<A>(&self, symbol: &str) -> std::io::Result<(A, TokenSlice)> {
         fail(format!(r#"operator '{}' was expected; e.g.: ns("securities", "etf", "stocks")"#, symbol))
     }
@@ -935,14 +1256,29 @@ impl Compiler {
         name: &str,
         ts: TokenSlice,
     ) -> std::io::Result<(Expression, TokenSlice)> {
+        // is it a distinct count? e.g., count(distinct symbol)
+        if name == "count" && ts.is("(") && ts.skip().is("distinct") {
+            let ts = ts.expect("(")?.expect("distinct")?;
+            let (arg, ts) = self.compile_next(ts)?;
+            let fx = Box::new(Variable("count_distinct".to_string()));
+            Ok((FunctionCall { fx, args: vec![arg] }, ts.expect(")")?))
+        }
         // is it a function call? e.g., f(2, 3)
-        if ts.is("(") {
+        else if ts.is("(") {
             let (args, ts) = self.expect_arguments(ts)?;
             match name {
                 "iff" => self.expect_function_call_iff(args, ts),
                 name => Ok((FunctionCall { fx: Box::new(Variable(name.to_string())), args }, ts))
             }
         }
+        // is it a qualified column reference? e.g., s.symbol
+        else if ts.is(".") && ts.is_previous_adjacent() && ts.skip().has_more() && ts.skip().is_previous_adjacent() {
+            match ts.skip().next() {
+                (Some(Atom { text: column, .. }), ts) =>
+                    Ok((Variable(format!("{}.{}", name, column)), ts)),
+                (_, ts) => throw(ExactNear("Expected a column name".into(), ts.current()))
+            }
+        }
         // must be a variable. e.g., abc
         else {
             Ok((Variable(name.to_string()), ts))
@@ -1014,6 +1350,89 @@ impl Compiler {
         Ok((Parameters(parameters), ts.expect(")")?))
     }
 
+    /// Parses the column definitions of a table, including any column constraints
+    /// ex: (symbol: String(8) not null, last_sale: f64 = 0.0 check (last_sale >= 0.0))
+    fn expect_column_definitions(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Vec<Parameter>, Vec<ConstraintConfig>, TokenSlice)> {
+        let (mut columns, mut constraints) = (Vec::new(), Vec::new());
+        let mut ts = ts.expect("(")?;
+        let mut is_done = ts.is(")");
+        while !is_done {
+            // get the next column
+            let (column, mut ats) = self.expect_parameter(ts.to_owned())?;
+
+            // followed by any constraints: serial | not null | check (condition) | references ns(path)(column) | compressed
+            let (mut is_serial, mut is_not_null, mut check, mut reference) = (false, false, None, None);
+            let mut is_compressed = false;
+            loop {
+                if ats.is("compressed") {
+                    ats = ats.skip();
+                    is_compressed = true;
+                } else if ats.is("serial") || ats.is("auto_increment") {
+                    ats = ats.skip();
+                    is_serial = true;
+                } else if ats.is("not") {
+                    ats = ats.expect("not")?.expect("null")?;
+                    is_not_null = true;
+                } else if ats.is("check") {
+                    let (condition, nts) = self.compile_next(ats.expect("check")?)?;
+                    check = Some(condition);
+                    ats = nts;
+                } else if ats.is("references") {
+                    let (config, nts) = self.expect_reference(ats.expect("references")?)?;
+                    reference = Some(config);
+                    ats = nts;
+                } else { break; }
+            }
+            if is_serial || is_not_null || is_compressed || check.is_some() || reference.is_some() {
+                constraints.push(ConstraintConfig::new(column.get_name().to_string(), is_not_null, check)
+                    .with_compressed(is_compressed)
+                    .with_reference(reference)
+                    .with_serial(is_serial));
+            }
+            columns.push(column);
+
+            // are we done yet?
+            is_done = ats.is(")");
+            ts = if !is_done { ats.expect(",")? } else { ats };
+        }
+        Ok((columns, constraints, ts.expect(")")?))
+    }
+
+    /// Parses a column reference: ns("a.b.stocks")(symbol) [on delete cascade|restrict|set null]
+    fn expect_reference(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(ReferenceConfig, TokenSlice)> {
+        let (table, ts) = match self.compile_next(ts.to_owned())? {
+            (Ns(path), ts) => match *path {
+                Literal(StringValue(path)) => (path, ts),
+                _ => return throw(ExactNear("A namespace literal was expected".into(), ts.current()))
+            }
+            _ => return throw(ExactNear("A namespace was expected".into(), ts.current()))
+        };
+        let (column_name, ts) = match ts.expect("(")?.next() {
+            (Some(Atom { text: name, .. }), ts) => (name, ts.expect(")")?),
+            (_, ts) => return throw(ExactNear("A column name was expected".into(), ts.current()))
+        };
+        if !ts.is("on") {
+            return Ok((ReferenceConfig::new(table, column_name, DeleteActions::Restrict), ts));
+        }
+        let ts = ts.expect("on")?.expect("delete")?;
+        let (on_delete, ts) = if ts.is("cascade") {
+            (DeleteActions::Cascade, ts.skip())
+        } else if ts.is("restrict") {
+            (DeleteActions::Restrict, ts.skip())
+        } else if ts.is("set") {
+            (DeleteActions::SetNull, ts.skip().expect("null")?)
+        } else {
+            return throw(ExactNear("Expected cascade, restrict or set null".into(), ts.current()));
+        };
+        Ok((ReferenceConfig::new(table, column_name, on_delete), ts))
+    }
+
     fn expect_parameter(
         &mut self,
         ts: TokenSlice,
@@ -1170,6 +1589,24 @@ impl Compiler {
         }
     }
 
+    /// Returns the option of a list of sort [Expression]s (e.g. "order by symbol desc, exchange")
+    /// based the next tokens matching the specified keywords
+    fn next_keyword_sort_list(&mut self, keyword0: &str, keyword1: &str, ts: TokenSlice) -> std::io::Result<(Option<Vec<Expression>>, TokenSlice)> {
+        if ts.isnt(keyword0) || ts.skip().isnt(keyword1) { return Ok((None, ts)); }
+        let mut items = Vec::new();
+        let mut ts = ts.skip().skip();
+        loop {
+            let (item, _ts) = self.compile_next(ts)?;
+            let (item, _ts) =
+                if _ts.is("asc") { (Ascending(Box::new(item)), _ts.skip()) }
+                else if _ts.is("desc") { (Descending(Box::new(item)), _ts.skip()) }
+                else { (item, _ts) };
+            items.push(item);
+            if _ts.isnt(",") { return Ok((Some(items), _ts)); }
+            ts = _ts.skip();
+        }
+    }
+
     pub fn push(&mut self, expression: Expression) {
         //println!("push -> {:?}", expression);
         self.stack.push(expression)
@@ -1989,18 +2426,37 @@ mod tests {
     mod sql_tests {
         use crate::compiler::Compiler;
         use crate::data_types::DataType::{NumberType, StringType};
-        use crate::expression::Conditions::{Between, Betwixt, Equal, GreaterOrEqual, LessOrEqual, LessThan, Like};
-        use crate::expression::CreationEntity::{IndexEntity, TableEntity};
+        use crate::expression::Conditions::{Between, Betwixt, Equal, GreaterOrEqual, GreaterThan, LessOrEqual, LessThan, Like};
+        use crate::expression::CreationEntity::{IndexEntity, MaterializedViewEntity, SequenceEntity, TableEntity, ViewEntity};
         use crate::expression::DatabaseOps::{Mutation, Queryable};
-        use crate::expression::Expression::{ArrayExpression, Condition, DatabaseOp, From, JSONExpression, Literal, Ns, Variable, Via};
-        use crate::expression::MutateTarget::TableTarget;
-        use crate::expression::Mutations::{Create, Declare, Drop, IntoNs};
-        use crate::expression::{Mutations, Queryables};
-        use crate::number_kind::NumberKind::F64Kind;
+        use crate::expression::Expression::{ArrayExpression, AsValue, Ascending, CodeBlock, Condition, DatabaseOp, Descending, From, FunctionCall, JSONExpression, Literal, Ns, TransactionOp, Variable, Via};
+        use crate::expression::MutateTarget::{PartitionTarget, TableTarget, TriggerTarget, ViewTarget};
+        use crate::expression::Mutations::{Create, Declare, Drop, IntoNs, NextVal, Refresh, Truncate, TruncatePartition};
+        use crate::expression::{Alteration, JoinKinds, Mutations, Queryables, TransactionOps};
+        use crate::number_kind::NumberKind::{F32Kind, F64Kind, I64Kind};
+        use crate::object_config::{ConstraintConfig, DeleteActions, IndexKinds, ReferenceConfig};
         use crate::numbers::Numbers::{F64Value, I64Value};
         use crate::parameter::Parameter;
         use crate::typed_values::TypedValue::{Number, StringValue};
 
+        #[test]
+        fn test_alter_table() {
+            let opcodes = Compiler::build(r#"
+                alter table stocks add column rating: i64 = 0, drop column exchange,
+                    rename column last_sale to price, alter column price: f32
+            "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Mutation(Mutations::Alter {
+                path: Box::new(Variable("stocks".into())),
+                alterations: vec![
+                    Alteration::AddColumn(Parameter::with_default("rating", NumberType(I64Kind), Number(I64Value(0)))),
+                    Alteration::DropColumn("exchange".into()),
+                    Alteration::RenameColumn { old_name: "last_sale".into(), new_name: "price".into() },
+                    Alteration::RetypeColumn(Parameter::new("price", NumberType(F32Kind))),
+                ],
+            })));
+            assert_eq!(opcodes.to_code(), "alter table stocks add column rating: i64 := 0, drop column exchange, rename column last_sale to price, alter column price: f32");
+        }
+
         #[test]
         fn test_append_from_json_literal() {
             let opcodes = Compiler::build(r#"
@@ -2096,8 +2552,120 @@ mod tests {
                         Variable("symbol".into()),
                         Variable("exchange".into()),
                     ],
+                    is_unique: false,
+                    kind: IndexKinds::Hash,
+                },
+            })));
+        }
+
+        #[test]
+        fn test_create_btree_index_in_namespace() {
+            let code = Compiler::build(r#"
+                create index ns("compiler.create.stocks") on [last_sale] using btree
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.stocks".into()))))),
+                entity: IndexEntity {
+                    columns: vec![Variable("last_sale".into())],
+                    is_unique: false,
+                    kind: IndexKinds::BTree,
+                },
+            })));
+        }
+
+        #[test]
+        fn test_declare_index() {
+            let code = Compiler::build(r#"
+                declare index stocks on [symbol, exchange]
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Declare {
+                path: Some(Box::new(Variable("stocks".into()))),
+                entity: IndexEntity {
+                    columns: vec![Variable("symbol".into()), Variable("exchange".into())],
+                    is_unique: false,
+                    kind: IndexKinds::Hash,
+                },
+            })));
+        }
+
+        #[test]
+        fn test_create_unique_index_in_namespace() {
+            let code = Compiler::build(r#"
+                create unique index ns("compiler.create.stocks") on [symbol]
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.stocks".into()))))),
+                entity: IndexEntity {
+                    columns: vec![Variable("symbol".into())],
+                    is_unique: true,
+                    kind: IndexKinds::Hash,
+                },
+            })));
+        }
+
+        #[test]
+        fn test_create_table_with_constraints() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.constraints.stocks") (
+                    symbol: String(8) not null,
+                    exchange: String(8) = "NYSE",
+                    last_sale: f64 not null check (last_sale > 0.0))
+                "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.constraints.stocks".into()))))),
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::with_default("exchange", StringType(8), StringValue("NYSE".into())),
+                        Parameter::new("last_sale", NumberType(F64Kind)),
+                    ],
+                    constraints: vec![
+                        ConstraintConfig::new("symbol".into(), true, None),
+                        ConstraintConfig::new("last_sale".into(), true, Some(Condition(GreaterThan(
+                            Box::new(Variable("last_sale".into())),
+                            Box::new(Literal(Number(F64Value(0.0)))),
+                        )))),
+                    ],
+                    from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![],
                 },
             })));
+            assert_eq!(code.to_code(), r#"create table ns("compiler.constraints.stocks") (symbol: String(8) not null, exchange: String(8) := "NYSE", last_sale: f64 not null check (last_sale > 0.0))"#);
+        }
+
+        #[test]
+        fn test_create_table_with_references() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.references.orders") (
+                    symbol: String(8) not null references ns("compiler.references.stocks")(symbol) on delete cascade,
+                    broker: String(8) references ns("compiler.references.brokers")(name) on delete set null,
+                    quantity: i64)
+                "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.references.orders".into()))))),
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::new("broker", StringType(8)),
+                        Parameter::new("quantity", NumberType(I64Kind)),
+                    ],
+                    constraints: vec![
+                        ConstraintConfig::new("symbol".into(), true, None)
+                            .with_reference(Some(ReferenceConfig::new(
+                                "compiler.references.stocks".into(), "symbol".into(), DeleteActions::Cascade))),
+                        ConstraintConfig::new("broker".into(), false, None)
+                            .with_reference(Some(ReferenceConfig::new(
+                                "compiler.references.brokers".into(), "name".into(), DeleteActions::SetNull))),
+                    ],
+                    from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![],
+                },
+            })));
+            assert_eq!(code.to_code(), r#"create table ns("compiler.references.orders") (symbol: String(8) not null references ns("compiler.references.stocks")(symbol) on delete cascade, broker: String(8) references ns("compiler.references.brokers")(name) on delete set null, quantity: i64)"#);
         }
 
         #[test]
@@ -2117,7 +2685,36 @@ mod tests {
                         Parameter::with_default("exchange", StringType(8), StringValue("NYSE".into())),
                         Parameter::with_default("last_sale", NumberType(F64Kind), Number(F64Value(23.54))),
                     ],
+                    constraints: vec![],
+                    from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![],
+                },
+            })));
+        }
+
+        #[test]
+        fn test_create_partitioned_table_in_namespace() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.create.stocks") (
+                    symbol: String(8),
+                    exchange: String(8),
+                    last_sale: f64) partition by [exchange]
+                "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.stocks".into()))))),
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::new("exchange", StringType(8)),
+                        Parameter::new("last_sale", NumberType(F64Kind)),
+                    ],
+                    constraints: vec![],
                     from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![Variable("exchange".into())],
                 },
             })));
         }
@@ -2130,14 +2727,21 @@ mod tests {
                     exchange: String(8),
                     last_sale: f64)
             "#).unwrap();
-            assert_eq!(model, DatabaseOp(Mutation(Declare(TableEntity {
-                columns: vec![
-                    Parameter::new("symbol", StringType(8)),
-                    Parameter::new("exchange", StringType(8)),
-                    Parameter::new("last_sale", NumberType(F64Kind)),
-                ],
-                from: None,
-            }))));
+            assert_eq!(model, DatabaseOp(Mutation(Declare {
+                path: None,
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::new("exchange", StringType(8)),
+                        Parameter::new("last_sale", NumberType(F64Kind)),
+                    ],
+                    constraints: vec![],
+                    from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![],
+                },
+            })));
         }
 
         #[test]
@@ -2170,6 +2774,228 @@ mod tests {
             })))
         }
 
+        #[test]
+        fn test_create_view_in_namespace() {
+            let code = Compiler::build(r#"
+                create view ns("compiler.create.high_flyers") as
+                    select symbol from ns("compiler.create.stocks") where last_sale > 10.0
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.high_flyers".into()))))),
+                entity: ViewEntity {
+                    query: Box::new(Compiler::build(r#"
+                        select symbol from ns("compiler.create.stocks") where last_sale > 10.0
+                    "#).unwrap()),
+                },
+            })));
+        }
+
+        #[test]
+        fn test_create_materialized_view_in_namespace() {
+            let code = Compiler::build(r#"
+                create materialized view ns("compiler.create.high_flyers") refresh every 60000 as
+                    select symbol from ns("compiler.create.stocks") where last_sale > 10.0
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.high_flyers".into()))))),
+                entity: MaterializedViewEntity {
+                    query: Box::new(Compiler::build(r#"
+                        select symbol from ns("compiler.create.stocks") where last_sale > 10.0
+                    "#).unwrap()),
+                    refresh_interval: Some(Box::new(Literal(Number(I64Value(60000))))),
+                },
+            })));
+        }
+
+        #[test]
+        fn test_create_sequence_in_namespace() {
+            let code = Compiler::build(r#"
+                create sequence ns("compiler.create.order_ids") start with 1000 increment by 10
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.order_ids".into()))))),
+                entity: SequenceEntity {
+                    increment: Some(Box::new(Literal(Number(I64Value(10))))),
+                    start: Some(Box::new(Literal(Number(I64Value(1000))))),
+                },
+            })));
+            assert_eq!(code.to_code(), r#"create sequence ns("compiler.create.order_ids") start with 1000 increment by 10"#);
+        }
+
+        #[test]
+        fn test_create_and_drop_trigger() {
+            let code = Compiler::build(r#"
+                create trigger check_price before update on ns("compiler.create.stocks") do fn(row) => true
+            "#).unwrap();
+            assert_eq!(code.to_code(), r#"create trigger check_price before update on ns("compiler.create.stocks") do (fn(row) => true)"#);
+
+            let code = Compiler::build(r#"
+                drop trigger check_price on ns("compiler.create.stocks")
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Drop(TriggerTarget {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.stocks".into()))))),
+                name: "check_price".into(),
+            }))));
+            assert_eq!(code.to_code(), r#"drop trigger check_price on ns("compiler.create.stocks")"#);
+        }
+
+        #[test]
+        fn test_create_table_with_serial_column() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.create.orders") (id: i64 serial, symbol: String(8) not null)
+            "#).unwrap();
+            assert_eq!(code.to_code(), r#"create table ns("compiler.create.orders") (id: i64 serial, symbol: String(8) not null)"#);
+        }
+
+        #[test]
+        fn test_create_table_with_compressed_column() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.create.articles") (title: String(32) not null, body: String(1024) compressed)
+            "#).unwrap();
+            assert_eq!(code.to_code(), r#"create table ns("compiler.create.articles") (title: String(32) not null, body: String(1024) compressed)"#);
+        }
+
+        #[test]
+        fn test_create_encrypted_table() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.create.clients") (name: String(32) not null, ssn: String(11)) encrypted
+            "#).unwrap();
+            assert_eq!(code.to_code(), r#"create table ns("compiler.create.clients") (name: String(32) not null, ssn: String(11)) encrypted"#);
+        }
+
+        #[test]
+        fn test_create_append_only_table() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.create.events") (kind: String(8), at: Date) append only
+            "#).unwrap();
+            assert_eq!(code.to_code(), r#"create table ns("compiler.create.events") (kind: String(8), at: Date) append only"#);
+        }
+
+        #[test]
+        fn test_alter_table_append_only() {
+            let code = Compiler::build("alter table events append only").unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Mutations::Alter {
+                path: Box::new(Variable("events".into())),
+                alterations: vec![Alteration::AppendOnly(true)],
+            })));
+            assert_eq!(code.to_code(), "alter table events append only");
+            assert_eq!(Compiler::build("alter table events reuse slots").unwrap().to_code(), "alter table events reuse slots");
+        }
+
+        #[test]
+        fn test_alter_table_encryption() {
+            let code = Compiler::build("alter table clients encrypt").unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Mutations::Alter {
+                path: Box::new(Variable("clients".into())),
+                alterations: vec![Alteration::Encryption(true)],
+            })));
+            assert_eq!(code.to_code(), "alter table clients encrypt");
+            assert_eq!(Compiler::build("alter table clients decrypt").unwrap().to_code(), "alter table clients decrypt");
+        }
+
+        #[test]
+        fn test_nextval() {
+            let code = Compiler::build(r#"
+                nextval(ns("compiler.create.order_ids"))
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(NextVal {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.order_ids".into()))))),
+            })));
+            assert_eq!(code.to_code(), r#"nextval(ns("compiler.create.order_ids"))"#);
+        }
+
+        #[test]
+        fn test_refresh() {
+            let code = Compiler::build(r#"
+                refresh ns("compiler.create.high_flyers")
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Refresh {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.high_flyers".into()))))),
+            })));
+        }
+
+        #[test]
+        fn test_drop_view() {
+            let code = Compiler::build(r#"
+                drop view ns('finance.securities.high_flyers')
+            "#).unwrap();
+            assert_eq!(
+                code,
+                DatabaseOp(Mutation(Drop(ViewTarget {
+                    path: Box::new(Ns(Box::new(Literal(StringValue("finance.securities.high_flyers".into()))))),
+                })))
+            );
+        }
+
+        #[test]
+        fn test_drop_partition() {
+            let code = Compiler::build(r#"
+                drop partition ns('finance.securities.stocks') ["NYSE"]
+            "#).unwrap();
+            assert_eq!(
+                code,
+                DatabaseOp(Mutation(Drop(PartitionTarget {
+                    path: Box::new(Ns(Box::new(Literal(StringValue("finance.securities.stocks".into()))))),
+                    key: Box::new(ArrayExpression(vec![Literal(StringValue("NYSE".into()))])),
+                })))
+            );
+        }
+
+        #[test]
+        fn test_truncate() {
+            let code = Compiler::build(r#"
+                truncate stocks limit 5
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Truncate {
+                path: Box::new(Variable("stocks".into())),
+                limit: Some(Box::new(Literal(Number(I64Value(5))))),
+            })));
+        }
+
+        #[test]
+        fn test_truncate_partition() {
+            let code = Compiler::build(r#"
+                truncate partition ns('finance.securities.stocks') ["NYSE"]
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(TruncatePartition {
+                path: Box::new(Ns(Box::new(Literal(StringValue("finance.securities.stocks".into()))))),
+                key: Box::new(ArrayExpression(vec![Literal(StringValue("NYSE".into()))])),
+            })));
+        }
+
+        #[test]
+        fn test_transaction() {
+            let code = Compiler::build(r#"
+                begin
+                commit
+                rollback
+            "#).unwrap();
+            assert_eq!(code, CodeBlock(vec![
+                TransactionOp(TransactionOps::Begin),
+                TransactionOp(TransactionOps::Commit),
+                TransactionOp(TransactionOps::Rollback),
+            ]));
+        }
+
+        #[test]
+        fn test_transaction_block() {
+            let code = Compiler::build(r#"
+                transaction {
+                    delete from stocks where last_sale > 1.0
+                }
+            "#).unwrap();
+            assert_eq!(code, TransactionOp(TransactionOps::Block(Box::new(CodeBlock(vec![
+                DatabaseOp(Mutation(Mutations::Delete {
+                    path: Box::new(Variable("stocks".into())),
+                    condition: Some(GreaterThan(
+                        Box::new(Variable("last_sale".into())),
+                        Box::new(Literal(Number(F64Value(1.0)))),
+                    )),
+                    limit: None,
+                }))
+            ])))));
+        }
+
         #[test]
         fn test_drop_table() {
             let code = Compiler::build(r#"
@@ -2189,6 +3015,26 @@ mod tests {
             assert_eq!(opcodes, From(Box::new(Variable("stocks".into()))));
         }
 
+        #[test]
+        fn test_from_as_of_version() {
+            let opcodes = Compiler::build(r#"
+                from stocks as of version 3 where last_sale >= 1.0
+            "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Queryable(Queryables::Where {
+                from: Box::new(
+                    DatabaseOp(Queryable(Queryables::AsOf {
+                        from: Box::new(From(Box::new(Variable("stocks".into())))),
+                        point: Box::new(Literal(Number(I64Value(3)))),
+                        is_version: true,
+                    }))),
+                condition: GreaterOrEqual(
+                    Box::new(Variable("last_sale".into())),
+                    Box::new(Literal(Number(F64Value(1.0)))),
+                ),
+            })));
+            assert_eq!(opcodes.to_code(), "from stocks as of version 3 where last_sale >= 1");
+        }
+
         #[test]
         fn test_from_where_limit() {
             let opcodes = Compiler::build(r#"
@@ -2286,6 +3132,33 @@ mod tests {
                 })))
         }
 
+        #[test]
+        fn test_select_group_by_having() {
+            let opcodes = Compiler::build(r#"
+                select exchange, symbols: count(distinct symbol) from stocks
+                group by exchange
+                having symbols > 1
+                "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Queryable(Queryables::Select {
+                fields: vec![
+                    Variable("exchange".into()),
+                    AsValue("symbols".into(), Box::new(FunctionCall {
+                        fx: Box::new(Variable("count_distinct".into())),
+                        args: vec![Variable("symbol".into())],
+                    })),
+                ],
+                from: Some(Box::new(Variable("stocks".into()))),
+                condition: None,
+                group_by: Some(vec![Variable("exchange".into())]),
+                having: Some(Box::new(Condition(GreaterThan(
+                    Box::new(Variable("symbols".into())),
+                    Box::new(Literal(Number(I64Value(1)))),
+                )))),
+                order_by: None,
+                limit: None,
+            })))
+        }
+
         #[test]
         fn test_select_from_where_limit() {
             let opcodes = Compiler::build(r#"
@@ -2329,6 +3202,61 @@ mod tests {
             })))
         }
 
+        #[test]
+        fn test_select_join() {
+            let opcode = Compiler::build(r#"
+                select s.symbol, e.name from s: stocks left outer join e: exchanges on s.exchange == e.code
+                "#).unwrap();
+            assert_eq!(opcode, DatabaseOp(Queryable(Queryables::Select {
+                fields: vec![Variable("s.symbol".into()), Variable("e.name".into())],
+                from: Some(Box::new(DatabaseOp(Queryable(Queryables::Join {
+                    left: Box::new(AsValue("s".into(), Box::new(Variable("stocks".into())))),
+                    right: Box::new(AsValue("e".into(), Box::new(Variable("exchanges".into())))),
+                    kind: JoinKinds::Left,
+                    condition: Some(Equal(
+                        Box::new(Variable("s.exchange".into())),
+                        Box::new(Variable("e.code".into())),
+                    )),
+                })))),
+                condition: None,
+                group_by: None,
+                having: None,
+                order_by: None,
+                limit: None,
+            })));
+            assert_eq!(opcode.to_code(), "select s.symbol, e.name from s: stocks left join e: exchanges on s.exchange == e.code")
+        }
+
+        #[test]
+        fn test_select_cross_join() {
+            let opcode = Compiler::build(r#"
+                select a.n, b.m from a cross join b where a.n > b.m
+                "#).unwrap();
+            assert_eq!(opcode.to_code(), "select a.n, b.m from a cross join b where a.n > b.m")
+        }
+
+        #[test]
+        fn test_select_order_by_asc_desc() {
+            let opcode = Compiler::build(r#"
+                select symbol, exchange, last_sale from stocks
+                order by exchange desc, last_sale asc, symbol
+                "#).unwrap();
+            assert_eq!(opcode, DatabaseOp(Queryable(Queryables::Select {
+                fields: vec![Variable("symbol".into()), Variable("exchange".into()), Variable("last_sale".into())],
+                from: Some(Box::new(Variable("stocks".into()))),
+                condition: None,
+                group_by: None,
+                having: None,
+                order_by: Some(vec![
+                    Descending(Box::new(Variable("exchange".into()))),
+                    Ascending(Box::new(Variable("last_sale".into()))),
+                    Variable("symbol".into()),
+                ]),
+                limit: None,
+            })));
+            assert_eq!(opcode.to_code(), "select symbol, exchange, last_sale from stocks order by exchange desc, last_sale asc, symbol")
+        }
+
         #[test]
         fn test_undelete() {
             let opcodes = Compiler::build(r#"
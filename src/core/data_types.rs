@@ -243,6 +243,24 @@ impl DataType {
     //  INSTANCE METHODS
     ////////////////////////////////////////////////////////////////////
 
+    /// converts the value into a value of this datatype (e.g. i64 => f64)
+    pub fn convert(&self, value: &TypedValue) -> std::io::Result<TypedValue> {
+        match (self, value) {
+            (_, Null | Undefined) => Ok(value.clone()),
+            (BooleanType, Boolean(..)) => Ok(value.clone()),
+            (BooleanType, Number(n)) => Ok(Boolean(!n.is_effectively_zero())),
+            (NumberType(kind), Number(n)) => Ok(Number(kind.convert(n))),
+            (NumberType(kind), StringValue(s)) => match Numbers::from_string(s.trim().to_string()) {
+                Numbers::NaNValue => throw(TypeMismatch(UnsupportedType(self.clone(), value.get_type()))),
+                n => Ok(Number(kind.convert(&n)))
+            }
+            (StringType(..), StringValue(..)) => Ok(value.clone()),
+            (StringType(..), Boolean(..) | Number(..)) => Ok(StringValue(value.unwrap_value())),
+            (data_type, value) if value.get_type() == *data_type => Ok(value.clone()),
+            (data_type, value) => throw(TypeMismatch(UnsupportedType(data_type.clone(), value.get_type())))
+        }
+    }
+
     /// computes and returns the maximum physical size of the value of this datatype
     pub fn compute_fixed_size(&self) -> usize {
         use crate::data_types::DataType::*;
@@ -5,64 +5,159 @@
 
 use crate::byte_row_collection::ByteRowCollection;
 use crate::columns::Column;
+use crate::errors::throw;
+use crate::errors::Errors::{Exact, TypeMismatch};
+use crate::errors::TypeMismatchErrors::CollectionExpected;
 use crate::expression::{Conditions, Expression};
 use crate::field::FieldMetadata;
 use crate::file_row_collection::FileRowCollection;
 use crate::hybrid_row_collection::HybridRowCollection;
 use crate::machine::Machine;
+use crate::materialized_views::MaterializedViews;
 use crate::model_row_collection::ModelRowCollection;
 use crate::namespaces::Namespace;
 use crate::numbers::Numbers::RowsAffected;
 use crate::object_config::ObjectConfig;
 use crate::parameter::Parameter;
+use crate::partitioned_row_collection::PartitionedRowCollection;
 use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::structures::Row;
+use crate::transactions::Transaction;
+use crate::triggers::Triggers;
 use crate::typed_values::TypedValue;
-use crate::typed_values::TypedValue::Number;
+use crate::typed_values::TypedValue::{ErrorValue, Number, TableValue};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
 
 /// DataFrame is a logical representation of table
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Dataframe {
     Binary(ByteRowCollection),
     Disk(FileRowCollection),
-    Hybrid(HybridRowCollection),
+    Hybrid(Box<HybridRowCollection>),
     Model(ModelRowCollection),
+    Partitioned(Box<PartitionedRowCollection>),
 }
 
 impl Dataframe {
     /// Creates a new table within the specified namespace and having the specified columns
     pub fn create_table(ns: &Namespace, params: &Vec<Parameter>) -> std::io::Result<Self> {
-        let path = ns.get_table_file_path();
-        let columns = Column::from_parameters(params);
-        let config = ObjectConfig::build_table(params.clone());
-        config.save(&ns)?;
-        let file = Arc::new(FileRowCollection::table_file_create(ns)?);
-        Ok(Self::Disk(FileRowCollection::new(columns, file, path.as_str())))
+        Ok(Self::Disk(FileRowCollection::create_table(ns, params)?))
     }
 
-    /// deletes rows from the table based on a condition
+    /// Builds an ephemeral hash index upon the specified column of an in-memory table
+    pub fn create_memory_index(&mut self, column_id: usize) -> std::io::Result<()> {
+        match self {
+            Self::Binary(brc) => brc.create_index(column_id),
+            Self::Model(mrc) => mrc.create_index(column_id),
+            Self::Disk(..) | Self::Hybrid(..) | Self::Partitioned(..) =>
+                return throw(Exact("Ephemeral indexes require an in-memory table".to_string()))
+        }
+        Ok(())
+    }
+
+    /// Returns a read-only view of the table as of this moment; which is unaffected by
+    /// subsequent writes to the table. Only disk-based tables are versioned.
+    pub fn snapshot(self) -> std::io::Result<Self> {
+        match self {
+            Self::Disk(frc) => Ok(Self::Disk(frc.snapshot()?)),
+            other => Ok(other)
+        }
+    }
+
+    /// deletes rows from the table based on a condition; firing the table's delete triggers
     pub fn delete_where(
         mut self,
         machine: &Machine,
         condition: &Option<Conditions>,
         limit: TypedValue,
+        triggers: &Triggers,
     ) -> std::io::Result<TypedValue> {
         let mut deleted = 0;
-        for id in self.get_indices_with_limit(limit)? {
+        for id in self.get_indices_where(machine, condition, limit)? {
             // read an active row
             if let Some(row) = self.read_one(id)? {
                 // if the predicate matches the condition, delete the row.
                 if row.matches(machine, condition, self.get_columns()) {
-                    deleted += self.delete_row(id).to_result(|v| v.to_i64())?;
+                    deleted += triggers.delete_row(&mut self, id)?.to_result(|v| v.to_i64())?;
                 }
             }
         }
         Ok(Number(RowsAffected(deleted)))
     }
 
+    /// Returns the IDs of the rows which may satisfy the condition via an index
+    /// (e.g. "symbol == 'ABC'"); or [None] when the table has no applicable index.
+    pub fn find_row_ids_where(
+        &self,
+        machine: &Machine,
+        condition: &Option<Conditions>,
+    ) -> std::io::Result<Option<Vec<usize>>> {
+        match self {
+            Self::Binary(brc) => brc.find_row_ids_where(machine, condition),
+            Self::Disk(frc) => frc.find_row_ids_where(machine, condition),
+            Self::Hybrid(..) => Ok(None),
+            Self::Model(mrc) => mrc.find_row_ids_where(machine, condition),
+            Self::Partitioned(prc) => prc.find_row_ids_where(machine, condition),
+        }
+    }
+
+    /// Returns the IDs of the rows which may satisfy the condition; tables consult
+    /// their indices (e.g. "symbol == 'ABC'"), otherwise all row IDs within the limit are returned.
+    pub fn get_indices_where(
+        &self,
+        machine: &Machine,
+        condition: &Option<Conditions>,
+        limit: TypedValue,
+    ) -> std::io::Result<Vec<usize>> {
+        let range = self.get_indices_with_limit(limit)?;
+        match self.find_row_ids_where(machine, condition)? {
+            Some(row_ids) => Ok(row_ids.into_iter().filter(|id| range.contains(id)).collect()),
+            None => Ok(range.collect())
+        }
+    }
+
+    /// Opens the table or view residing within the specified namespace;
+    /// a view (e.g. "create view ns('a.b.v') as select ...") re-runs its query,
+    /// whereas a materialized view is refreshed whenever its scheduled refresh is due.
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        Transaction::ensure_visible(ns)?;
+        match ObjectConfig::load(ns)? {
+            config @ ObjectConfig::MaterializedViewConfig { .. } => {
+                if MaterializedViews::is_refresh_due(&config) {
+                    MaterializedViews::refresh(ns)?;
+                }
+                Ok(Self::Disk(FileRowCollection::open(ns)?))
+            }
+            ObjectConfig::TableConfig { partitions, .. } if !partitions.is_empty() =>
+                Ok(Self::Partitioned(Box::new(PartitionedRowCollection::open(ns)?))),
+            ObjectConfig::TableConfig { .. } => Ok(Self::Disk(FileRowCollection::open(ns)?)),
+            ObjectConfig::SequenceConfig { .. } => throw(TypeMismatch(CollectionExpected(ns.get_full_name()))),
+            ObjectConfig::ViewConfig { query } =>
+                match Machine::new_platform().evaluate(&query)? {
+                    (_, ErrorValue(err)) => throw(err),
+                    (_, TableValue(df)) => Ok(df),
+                    (_, z) => throw(TypeMismatch(CollectionExpected(z.to_code())))
+                }
+        }
+    }
+
+    /// Ensures the namespace may be modified; views are rejected, since their rows are
+    /// derived from (and discarded by) each run of their queries.
+    pub fn ensure_writable(ns: &Namespace) -> std::io::Result<()> {
+        match ObjectConfig::load(ns)? {
+            ObjectConfig::ViewConfig { .. } =>
+                throw(Exact(format!("View '{}' cannot be modified", ns.get_full_name()))),
+            _ => Ok(())
+        }
+    }
+
+    /// Opens the table residing within the specified namespace for modification (see [Dataframe::ensure_writable])
+    pub fn open_writable(ns: &Namespace) -> std::io::Result<Self> {
+        Self::ensure_writable(ns)?;
+        Self::open(ns)
+    }
+
     /// overwrites rows that match the supplied criteria
     pub fn overwrite_where(
         df: Dataframe,
@@ -71,10 +166,11 @@ impl Dataframe {
         values: &Vec<Expression>,
         condition: &Option<Conditions>,
         limit: TypedValue,
+        triggers: &Triggers,
     ) -> std::io::Result<(Dataframe, TypedValue)> {
         let mut overwritten = 0;
         let mut df = df;
-        for id in df.get_indices_with_limit(limit)? {
+        for id in df.get_indices_where(machine, condition, limit)? {
             // read an active row
             if let Some(row) = df.read_one(id)? {
                 // if the predicate matches the condition, overwrite the row.
@@ -83,7 +179,7 @@ impl Dataframe {
                         machine.with_row(df.get_columns(), &row).evaluate_as_atoms(fields)?;
                     if let (_, TypedValue::ArrayValue(my_values)) = machine.evaluate_array(values)? {
                         let new_row = row.transform(df.get_columns(), &my_fields, my_values.values())?;
-                        overwritten += df.overwrite_row(row.get_id(), new_row).to_result(|v| v.to_i64())?;
+                        overwritten += triggers.overwrite_row(&mut df, row.get_id(), new_row)?.to_result(|v| v.to_i64())?;
                     }
                 }
             }
@@ -117,7 +213,7 @@ impl Dataframe {
         Ok(Number(RowsAffected(restored)))
     }
 
-    /// updates rows that match the supplied criteria
+    /// updates rows that match the supplied criteria; firing the table's update triggers
     pub fn update_where(
         mut rc: Dataframe,
         ms: &Machine,
@@ -125,10 +221,11 @@ impl Dataframe {
         values: &Vec<Expression>,
         condition: &Option<Conditions>,
         limit: TypedValue,
+        triggers: &Triggers,
     ) -> std::io::Result<TypedValue> {
         let columns = rc.get_columns().clone();
         let mut updated = 0;
-        for id in rc.get_indices_with_limit(limit)? {
+        for id in rc.get_indices_where(ms, condition, limit)? {
             // read an active row
             if let Some(row) = rc.read_one(id)? {
                 // if the predicate matches the condition, update the row.
@@ -137,8 +234,10 @@ impl Dataframe {
                         ms.with_row(&columns, &row).evaluate_as_atoms(fields)?;
                     if let (_, TypedValue::ArrayValue(field_values)) = ms.evaluate_array(values)? {
                         let new_row = row.transform(&columns, &field_names, field_values.values())?;
-                        let result = rc.overwrite_row(id, new_row);
-                        if result.is_ok() { updated += 1 }
+                        match triggers.overwrite_row(&mut rc, id, new_row)? {
+                            TypedValue::ErrorValue(err) => return throw(err),
+                            result => if result.is_ok() { updated += 1 }
+                        }
                     }
                 }
             }
@@ -148,12 +247,43 @@ impl Dataframe {
 }
 
 impl RowCollection for Dataframe {
+    fn append_row(&mut self, row: Row) -> TypedValue {
+        match self {
+            Self::Binary(rc) => rc.append_row(row),
+            Self::Disk(rc) => rc.append_row(row),
+            Self::Hybrid(rc) => rc.append_row(row),
+            Self::Model(rc) => rc.append_row(row),
+            Self::Partitioned(rc) => rc.append_row(row),
+        }
+    }
+
+    fn append_rows(&mut self, rows: Vec<Row>) -> TypedValue {
+        match self {
+            Self::Binary(rc) => rc.append_rows(rows),
+            Self::Disk(rc) => rc.append_rows(rows),
+            Self::Hybrid(rc) => rc.append_rows(rows),
+            Self::Model(rc) => rc.append_rows(rows),
+            Self::Partitioned(rc) => rc.append_rows(rows),
+        }
+    }
+
+    fn describe(&self) -> TypedValue {
+        match self {
+            Self::Binary(rc) => rc.describe(),
+            Self::Disk(rc) => rc.describe(),
+            Self::Hybrid(rc) => rc.describe(),
+            Self::Model(rc) => rc.describe(),
+            Self::Partitioned(rc) => rc.describe(),
+        }
+    }
+
     fn get_columns(&self) -> &Vec<Column> {
         match self {
             Self::Binary(rc) => rc.get_columns(),
             Self::Disk(rc) => rc.get_columns(),
             Self::Hybrid(rc) => rc.get_columns(),
             Self::Model(rc) => rc.get_columns(),
+            Self::Partitioned(rc) => rc.get_columns(),
         }
     }
 
@@ -163,6 +293,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.get_record_size(),
             Self::Hybrid(rc) => rc.get_record_size(),
             Self::Model(rc) => rc.get_record_size(),
+            Self::Partitioned(rc) => rc.get_record_size(),
         }
     }
 
@@ -172,6 +303,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.get_rows(),
             Self::Hybrid(rc) => rc.get_rows(),
             Self::Model(rc) => rc.get_rows(),
+            Self::Partitioned(rc) => rc.get_rows(),
         }
     }
 
@@ -181,6 +313,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.len(),
             Self::Hybrid(rc) => rc.len(),
             Self::Model(rc) => rc.len(),
+            Self::Partitioned(rc) => rc.len(),
         }
     }
 
@@ -190,6 +323,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.overwrite_field(id, column_id, new_value),
             Self::Hybrid(rc) => rc.overwrite_field(id, column_id, new_value),
             Self::Model(rc) => rc.overwrite_field(id, column_id, new_value),
+            Self::Partitioned(rc) => rc.overwrite_field(id, column_id, new_value),
         }
     }
 
@@ -199,6 +333,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
             Self::Hybrid(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
             Self::Model(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
+            Self::Partitioned(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
         }
     }
 
@@ -208,6 +343,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.overwrite_row(id, row),
             Self::Hybrid(rc) => rc.overwrite_row(id, row),
             Self::Model(rc) => rc.overwrite_row(id, row),
+            Self::Partitioned(rc) => rc.overwrite_row(id, row),
         }
     }
 
@@ -217,6 +353,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.overwrite_row_metadata(id, metadata),
             Self::Hybrid(rc) => rc.overwrite_row_metadata(id, metadata),
             Self::Model(rc) => rc.overwrite_row_metadata(id, metadata),
+            Self::Partitioned(rc) => rc.overwrite_row_metadata(id, metadata),
         }
     }
 
@@ -226,6 +363,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.read_field(id, column_id),
             Self::Hybrid(rc) => rc.read_field(id, column_id),
             Self::Model(rc) => rc.read_field(id, column_id),
+            Self::Partitioned(rc) => rc.read_field(id, column_id),
         }
     }
 
@@ -235,6 +373,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.read_field_metadata(id, column_id),
             Self::Hybrid(rc) => rc.read_field_metadata(id, column_id),
             Self::Model(rc) => rc.read_field_metadata(id, column_id),
+            Self::Partitioned(rc) => rc.read_field_metadata(id, column_id),
         }
     }
 
@@ -244,6 +383,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.read_row(id),
             Self::Hybrid(rc) => rc.read_row(id),
             Self::Model(rc) => rc.read_row(id),
+            Self::Partitioned(rc) => rc.read_row(id),
         }
     }
 
@@ -253,6 +393,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.read_row_metadata(id),
             Self::Hybrid(rc) => rc.read_row_metadata(id),
             Self::Model(rc) => rc.read_row_metadata(id),
+            Self::Partitioned(rc) => rc.read_row_metadata(id),
         }
     }
 
@@ -262,6 +403,7 @@ impl RowCollection for Dataframe {
             Self::Disk(rc) => rc.resize(new_size),
             Self::Hybrid(rc) => rc.resize(new_size),
             Self::Model(rc) => rc.resize(new_size),
+            Self::Partitioned(rc) => rc.resize(new_size),
         }
     }
 }
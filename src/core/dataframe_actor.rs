@@ -13,29 +13,34 @@ use serde::{Deserialize, Serialize};
 use crate::columns::Column;
 use crate::dataframe::Dataframe;
 use crate::dataframe::Dataframe::Disk;
-use crate::file_row_collection::FileRowCollection;
 use crate::namespaces::Namespace;
 use crate::object_config::ObjectConfig;
 use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::structures::Row;
+use crate::transactions::Transaction;
+use crate::triggers::Triggers;
 
 // define the Dataframe I/O actor
 #[derive(Debug)]
 pub struct DataframeActor {
     resources: HashMap<String, Dataframe>,
+    /// the configuration generation of each cached dataframe (see [ObjectConfig::get_generation])
+    generations: HashMap<String, u64>,
 }
 
 impl DataframeActor {
     /// default constructor
     pub fn new() -> Self {
         DataframeActor {
-            resources: HashMap::new()
+            resources: HashMap::new(),
+            generations: HashMap::new(),
         }
     }
 
     fn append_row(&mut self, ns: &Namespace, row: Row) -> std::io::Result<usize> {
-        Ok(self.get_or_load_dataframe(ns)?.append_row(row).to_usize())
+        let triggers = Triggers::load(ns)?;
+        triggers.append_row(self.get_or_load_writable_dataframe(ns)?, row)?.to_result(|v| v.to_usize())
     }
 
     fn create_table(&mut self, ns: Namespace, cfg: ObjectConfig) -> std::io::Result<&mut Dataframe> {
@@ -43,7 +48,8 @@ impl DataframeActor {
     }
 
     fn delete_row(&mut self, ns: &Namespace, id: usize) -> std::io::Result<usize> {
-        Ok(self.get_or_load_dataframe(ns)?.delete_row(id).to_usize())
+        let triggers = Triggers::load(ns)?;
+        Ok(triggers.delete_row(self.get_or_load_writable_dataframe(ns)?, id)?.to_usize())
     }
 
     fn get_columns(&mut self, ns: &Namespace) -> std::io::Result<&Vec<Column>> {
@@ -61,20 +67,41 @@ impl DataframeActor {
                 Ok(x.insert({
                     let params = cfg.get_columns();
                     ObjectConfig::build_table(params.clone()).save(&ns)?;
-                    Dataframe::create_table(&ns, &params)?
+                    let df = Dataframe::create_table(&ns, &params)?;
+                    self.generations.insert(ns.id(), ObjectConfig::get_generation(&ns));
+                    df
                 }))
         }
     }
 
     fn get_or_load_dataframe(&mut self, ns: &Namespace) -> std::io::Result<&mut Dataframe> {
+        // the generation is read before (re-)opening, thus a concurrent change triggers a re-open
+        let generation = ObjectConfig::get_generation(ns);
+        Transaction::ensure_visible(ns)?;
+        let is_stale = self.generations.insert(ns.id(), generation) != Some(generation);
         match self.resources.entry(ns.id()) {
-            Entry::Occupied(v) => Ok(v.into_mut()),
-            Entry::Vacant(x) => Ok(x.insert(Disk(FileRowCollection::open(ns)?)))
+            // views are re-evaluated upon each access, whereas tables are re-opened
+            // once their configuration (e.g. their indices or constraints) has changed
+            Entry::Occupied(mut v) => {
+                if is_stale || !matches!(v.get(), Disk(..)) { v.insert(Dataframe::open(ns)?); }
+                Ok(v.into_mut())
+            }
+            Entry::Vacant(x) => Ok(x.insert(Dataframe::open(ns)?))
+        }
+    }
+
+    /// Returns the dataframe of a table which may be modified (see [Dataframe::ensure_writable])
+    fn get_or_load_writable_dataframe(&mut self, ns: &Namespace) -> std::io::Result<&mut Dataframe> {
+        Transaction::ensure_not_enlisted(ns)?;
+        if !matches!(self.resources.get(&ns.id()), Some(Disk(..))) {
+            Dataframe::ensure_writable(ns)?;
         }
+        self.get_or_load_dataframe(ns)
     }
 
     fn overwrite_row(&mut self, ns: &Namespace, row: Row) -> std::io::Result<usize> {
-        Ok(self.get_or_load_dataframe(ns)?.overwrite_row(row.get_id(), row).to_usize())
+        let triggers = Triggers::load(ns)?;
+        triggers.overwrite_row(self.get_or_load_writable_dataframe(ns)?, row.get_id(), row)?.to_result(|v| v.to_usize())
     }
 
     fn read_fully(
@@ -133,7 +160,8 @@ impl DataframeActor {
         ns: &Namespace,
         row: Row,
     ) -> std::io::Result<usize> {
-        Ok(self.get_or_load_dataframe(ns)?.update_row(row.get_id(), row).to_usize())
+        let triggers = Triggers::load(ns)?;
+        triggers.update_row(self.get_or_load_writable_dataframe(ns)?, row.get_id(), row)?.to_result(|v| v.to_usize())
     }
 }
 
@@ -210,8 +238,11 @@ macro_rules! append_row {
             ns: $ns.to_owned(),
             row: $row
         }).await
-            .map(|s|serde_json::from_str::<usize>(&s).unwrap())
             .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|match serde_json::from_str::<usize>(&s) {
+                Ok(n) => Ok(n),
+                Err(_) => Err(std::io::Error::new(std::io::ErrorKind::Other, s))
+            })
     }
 }
 
@@ -269,8 +300,11 @@ macro_rules! overwrite_row {
             ns: $ns.to_owned(),
             row: $row
         }).await
-            .map(|s|serde_json::from_str::<usize>(&s).unwrap())
             .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|match serde_json::from_str::<usize>(&s) {
+                Ok(n) => Ok(n),
+                Err(_) => Err(std::io::Error::new(std::io::ErrorKind::Other, s))
+            })
     }
 }
 
@@ -338,8 +372,11 @@ macro_rules! update_row {
             ns: $ns.to_owned(),
             row: $row
         }).await
-            .map(|s|serde_json::from_str::<usize>(&s).unwrap())
             .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|match serde_json::from_str::<usize>(&s) {
+                Ok(n) => Ok(n),
+                Err(_) => Err(std::io::Error::new(std::io::ErrorKind::Other, s))
+            })
     }
 }
 
@@ -353,8 +390,14 @@ mod tests {
 
     use actix::prelude::*;
 
+    use crate::compiler::Compiler;
     use crate::data_types::DataType::*;
+    use crate::expression::Expression::Condition;
+    use crate::file_row_collection::FileRowCollection;
+    use crate::interpreter::Interpreter;
+    use crate::machine::Machine;
     use crate::number_kind::NumberKind::F64Kind;
+    use crate::object_config::{ConstraintConfig, TriggerConfig, TriggerEvents, TriggerTimings};
     use crate::numbers::Numbers::*;
     use crate::testdata::{make_quote_columns, make_quote_parameters};
     use crate::typed_values::TypedValue::*;
@@ -457,6 +500,123 @@ mod tests {
         ]);
     }
 
+    #[actix::test]
+    async fn test_index_created_after_caching() {
+        let actor = DataframeActor::new().start();
+        let ns = Namespace::parse("dataframe.actor_indices.stocks").unwrap();
+        assert_eq!(1, create_table!(actor, ns, make_quote_parameters()).unwrap());
+        assert_eq!(0, append_row!(actor, ns, Row::new(0, vec![
+            StringValue("ABC".into()), StringValue("AMEX".into()), Number(F64Value(11.77)),
+        ])).unwrap());
+
+        // index the table while the actor holds it open
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"create index ns("dataframe.actor_indices.stocks") on [symbol]"#).unwrap();
+
+        // rows appended via the actor are found via the new index
+        assert_eq!(1, append_row!(actor, ns, Row::new(0, vec![
+            StringValue("UNO".into()), StringValue("OTC".into()), Number(F64Value(0.2456)),
+        ])).unwrap());
+        let frc = FileRowCollection::open(&ns).unwrap();
+        let condition = Compiler::build(r#"symbol == "UNO""#).unwrap();
+        let row_ids = match condition {
+            Condition(condition) => frc.find_row_ids_where(&Machine::new(), &Some(condition)).unwrap(),
+            other => panic!("Expected a condition: {:?}", other)
+        };
+        assert_eq!(row_ids, Some(vec![1]));
+    }
+
+    #[actix::test]
+    async fn test_unique_index_created_after_caching() {
+        let actor = DataframeActor::new().start();
+        let ns = Namespace::parse("dataframe.actor_unique.stocks").unwrap();
+        assert_eq!(1, create_table!(actor, ns, make_quote_parameters()).unwrap());
+        assert_eq!(0, append_row!(actor, ns, Row::new(0, vec![
+            StringValue("ABC".into()), StringValue("AMEX".into()), Number(F64Value(11.77)),
+        ])).unwrap());
+
+        // uniquely index the table while the actor holds it open
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"create unique index ns("dataframe.actor_unique.stocks") on [symbol]"#).unwrap();
+
+        // duplicate keys appended via the actor are rejected
+        assert!(append_row!(actor, ns, Row::new(0, vec![
+            StringValue("ABC".into()), StringValue("NYSE".into()), Number(F64Value(12.01)),
+        ])).is_err());
+        let (_, rows) = read_fully!(actor, ns).unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[actix::test]
+    async fn test_table_altered_after_caching() {
+        let actor = DataframeActor::new().start();
+        let ns = Namespace::parse("dataframe.actor_alter.stocks").unwrap();
+        assert_eq!(1, create_table!(actor, ns, make_quote_parameters()).unwrap());
+        assert_eq!(0, append_row!(actor, ns, Row::new(0, vec![
+            StringValue("ABC".into()), StringValue("AMEX".into()), Number(F64Value(11.77)),
+        ])).unwrap());
+
+        // alter the table while the actor holds it open
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"alter table ns("dataframe.actor_alter.stocks") drop column exchange"#).unwrap();
+
+        // the actor reads and writes the table in its new layout
+        assert_eq!(1, append_row!(actor, ns, Row::new(0, vec![
+            StringValue("UNO".into()), Number(F64Value(0.2456)),
+        ])).unwrap());
+        let (_, rows) = read_fully!(actor, ns).unwrap();
+        assert_eq!(rows, vec![
+            Row::new(0, vec![StringValue("ABC".into()), Number(F64Value(11.77))]),
+            Row::new(1, vec![StringValue("UNO".into()), Number(F64Value(0.2456))]),
+        ]);
+    }
+
+    #[actix::test]
+    async fn test_constraint_added_after_caching() {
+        let actor = DataframeActor::new().start();
+        let ns = Namespace::parse("dataframe.actor_constraints.stocks").unwrap();
+        assert_eq!(1, create_table!(actor, ns, make_quote_parameters()).unwrap());
+        assert_eq!(0, append_row!(actor, ns, Row::new(0, vec![
+            StringValue("ABC".into()), StringValue("AMEX".into()), Number(F64Value(11.77)),
+        ])).unwrap());
+
+        // constrain the table while the actor holds it open
+        let config = ObjectConfig::load(&ns).unwrap();
+        config.with_constraints(vec![ConstraintConfig::new("symbol".into(), true, None)]).save(&ns).unwrap();
+
+        // rows violating the new constraint are rejected by the actor
+        assert!(append_row!(actor, ns, Row::new(0, vec![
+            Null, StringValue("NYSE".into()), Number(F64Value(12.01)),
+        ])).is_err());
+        let (_, rows) = read_fully!(actor, ns).unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[actix::test]
+    async fn test_read_view() {
+        let actor = DataframeActor::new().start();
+        let ns = Namespace::parse("dataframe.read_view.stocks").unwrap();
+        let view_ns = Namespace::parse("dataframe.read_view.otc_stocks").unwrap();
+        assert_eq!(1, create_table!(actor, ns, make_quote_parameters()).unwrap());
+        ObjectConfig::build_view(Compiler::build(r#"
+            select symbol, last_sale from ns("dataframe.read_view.stocks") where exchange == "OTC"
+        "#).unwrap()).save(&view_ns).unwrap();
+
+        // each read of the view re-runs its query
+        for (n, symbol) in ["UNO", "GOTO"].iter().enumerate() {
+            assert_eq!(n, append_row!(actor, ns, Row::new(0, vec![
+                StringValue(symbol.to_string()), StringValue("OTC".into()), Number(F64Value(0.25)),
+            ])).unwrap());
+            let (_, rows) = read_fully!(actor, view_ns).unwrap();
+            assert_eq!(rows.len(), n + 1);
+        }
+
+        // whereas the view itself cannot be modified
+        assert!(append_row!(actor, view_ns, Row::new(0, vec![
+            StringValue("BIZ".into()), Number(F64Value(23.66)),
+        ])).is_err());
+    }
+
     #[actix::test]
     async fn test_namespaces() {
         let actor = DataframeActor::new().start();
@@ -483,4 +643,27 @@ mod tests {
         assert!(resp.contains(&"dataframe.namespaces1.stocks".to_string()));
         assert!(resp.contains(&"dataframe.namespaces2.stocks".to_string()));
     }
-}
\ No newline at end of file
+
+    #[actix::test]
+    async fn test_triggers() {
+        let actor = DataframeActor::new().start();
+        let ns = Namespace::parse("dataframe.actor_triggers.stocks").unwrap();
+        assert_eq!(1, create_table!(actor, ns, make_quote_parameters()).unwrap());
+
+        // reject rows having a non-positive price
+        let function = Compiler::build("fn(row) => (row::last_sale) > 0.0").unwrap();
+        ObjectConfig::load(&ns).unwrap().with_triggers(vec![
+            TriggerConfig::new("positive_price".into(), TriggerTimings::Before, TriggerEvents::Append, function),
+        ]).save(&ns).unwrap();
+
+        // only the row having a positive price is appended
+        assert_eq!(0, append_row!(actor, ns, Row::new(0, vec![
+            StringValue("ABC".into()), StringValue("AMEX".into()), Number(F64Value(11.77)),
+        ])).unwrap());
+        assert!(append_row!(actor, ns, Row::new(0, vec![
+            StringValue("BAD".into()), StringValue("AMEX".into()), Number(F64Value(-1.0)),
+        ])).is_err());
+        let (_, rows) = read_fully!(actor, ns).unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+}
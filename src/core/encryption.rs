@@ -0,0 +1,143 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// encryption module
+////////////////////////////////////////////////////////////////////
+
+use crate::machine::Machine;
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use shared_lib::fail;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// the environment variable containing the (hex-encoded) 256-bit encryption key
+pub const KEY_ENV_VAR: &str = "OXIDE_ENCRYPTION_KEY";
+
+/// the environment variable containing the path of the key file; which must reside outside of the home
+pub const KEY_FILE_ENV_VAR: &str = "OXIDE_ENCRYPTION_KEY_FILE";
+
+/// the length of the key (in bytes)
+const KEY_LEN: usize = 32;
+
+/// the length of the nonce preceding each encrypted payload
+const NONCE_LEN: usize = 12;
+
+/// the cipher of the key supplied to this process
+static CIPHER: OnceLock<Aes256Gcm> = OnceLock::new();
+
+/// At-rest Encryption; the rows of an encrypted table (e.g. "create table ... encrypted") are
+/// written as AES-256-GCM encrypted payloads (nonce|ciphertext) to the table's BLOB store.
+/// Each row's metadata indicates whether the row is encrypted, thus encrypted and plain rows
+/// may coexist (e.g. while an existing table is being migrated).
+/// The key is supplied via the environment (see [KEY_ENV_VAR]) or a key file (see [KEY_FILE_ENV_VAR]);
+/// it is never generated, nor stored alongside the data it protects (i.e. within the home).
+pub struct Encryption;
+
+impl Encryption {
+    /// Decrypts a payload previously encrypted via [Encryption::encrypt]
+    pub fn decrypt(payload: &[u8]) -> std::io::Result<Vec<u8>> {
+        if payload.len() < NONCE_LEN {
+            return fail(format!("Encrypted payload is truncated ({} bytes)", payload.len()));
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        match Self::get_cipher()?.decrypt(Nonce::from_slice(nonce), ciphertext) {
+            Ok(bytes) => Ok(bytes),
+            Err(..) => fail("Decryption failed: the encryption key does not match")
+        }
+    }
+
+    /// Encrypts the bytes => (nonce|ciphertext)
+    pub fn encrypt(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let ciphertext = match Self::get_cipher()?.encrypt(Nonce::from_slice(&nonce), bytes) {
+            Ok(ciphertext) => ciphertext,
+            Err(err) => return fail(format!("Encryption failed: {err}"))
+        };
+        let mut payload = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        payload.extend(nonce);
+        payload.extend(ciphertext);
+        Ok(payload)
+    }
+
+    /// Ensures an encryption key has been supplied; failing if it has not
+    pub fn ensure_key() -> std::io::Result<()> {
+        Self::get_cipher().map(|_| ())
+    }
+
+    /// Supplies the key directly (e.g. within tests); unless a key has been supplied already
+    #[cfg(test)]
+    pub fn install_key(key: &[u8; KEY_LEN]) {
+        CIPHER.get_or_init(|| Aes256Gcm::new_from_slice(key).unwrap());
+    }
+
+    fn get_cipher() -> std::io::Result<&'static Aes256Gcm> {
+        if let Some(cipher) = CIPHER.get() {
+            return Ok(cipher);
+        }
+        match Self::load_key()? {
+            Some(key) => match Aes256Gcm::new_from_slice(&key) {
+                Ok(cipher) => Ok(CIPHER.get_or_init(|| cipher)),
+                Err(..) => fail(format!("The encryption key must be {} bytes", KEY_LEN))
+            },
+            None => fail(format!("No encryption key: set {} or {}", KEY_ENV_VAR, KEY_FILE_ENV_VAR))
+        }
+    }
+
+    /// Reads the key file; which is rejected if it resides within the home, since
+    /// the key would then be exposed alongside the data it protects.
+    fn load_key_file(path: &Path) -> std::io::Result<String> {
+        let (path, home) = (fs::canonicalize(path)?, fs::canonicalize(Machine::oxide_home()));
+        if home.is_ok_and(|home| path.starts_with(home)) {
+            return fail(format!("The key file ({}) must reside outside of {}",
+                                path.display(), Machine::oxide_home()));
+        }
+        fs::read_to_string(path)
+    }
+
+    /// Loads the (hex-encoded) key from the environment or the key file
+    fn load_key() -> std::io::Result<Option<Vec<u8>>> {
+        let encoded = match (env::var(KEY_ENV_VAR), env::var(KEY_FILE_ENV_VAR)) {
+            (Ok(encoded), _) => encoded,
+            (Err(..), Ok(path)) => Self::load_key_file(Path::new(&path))?,
+            (Err(..), Err(..)) => return Ok(None)
+        };
+        match hex::decode(encoded.trim()) {
+            Ok(key) if key.len() == KEY_LEN => Ok(Some(key)),
+            Ok(key) => fail(format!("The encryption key must be {} bytes (found {})", KEY_LEN, key.len())),
+            Err(err) => fail(format!("The encryption key must be hex-encoded: {err}"))
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        Encryption::install_key(&[7; KEY_LEN]);
+        Encryption::ensure_key().unwrap();
+        let bytes = b"Hello World".to_vec();
+        let payload = Encryption::encrypt(&bytes).unwrap();
+        assert_eq!(payload.len(), NONCE_LEN + bytes.len() + 16);
+        assert!(!payload.windows(bytes.len()).any(|window| window == bytes.as_slice()));
+        assert_eq!(Encryption::decrypt(&payload).unwrap(), bytes);
+
+        // tampered payloads are rejected
+        let mut tampered = payload.to_owned();
+        tampered[NONCE_LEN] ^= 1;
+        assert!(Encryption::decrypt(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_key_file_within_home_is_rejected() {
+        let path = format!("{}/encryption.key", Machine::oxide_home());
+        fs::create_dir_all(Machine::oxide_home()).unwrap();
+        fs::write(&path, hex::encode([7; KEY_LEN])).unwrap();
+        let err = Encryption::load_key_file(Path::new(&path)).unwrap_err();
+        assert!(err.to_string().contains("must reside outside of"));
+    }
+}
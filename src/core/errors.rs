@@ -79,8 +79,10 @@ impl Display for TypeMismatchErrors {
 pub enum Errors {
     AssertionError(String, String),
     CannotSubtract(String, String),
+    CheckViolation(String, String),
     Exact(String),
     ExactNear(String, Token),
+    ForeignKeyViolation(String, String),
     HashTableOverflow(usize, String),
     IllegalExpression(String),
     IllegalOperator(Token),
@@ -88,10 +90,15 @@ pub enum Errors {
     InvalidNamespace(String),
     Multiple(Vec<Errors>),
     NotImplemented(String),
+    NotNullViolation(String),
     PackageNotFound(String),
     PlatformOpError(PlatformOps),
+    ReferencedKeyViolation(String, String),
+    ReplicaReadOnly(String, String),
     Syntax(String),
+    TriggerRejected(String),
     TypeMismatch(TypeMismatchErrors),
+    UniqueKeyViolation(String, String),
     UnsupportedPlatformOps(PlatformOps),
     ViewsCannotBeResized,
     WriteProtected,
@@ -105,6 +112,8 @@ impl Display for Errors {
                 write!(f, "Assertion Error: {a} was not {b}"),
             CannotSubtract(a, b) =>
                 write!(f, "Cannot subtract {b} from {a}"),
+            CheckViolation(column, condition) =>
+                write!(f, "Check constraint violation: {column} does not satisfy ({condition})"),
             PlatformOpError(op) =>
                 write!(f, "Conversion error: \"{}\"", op.to_code()),
             Exact(message) =>
@@ -112,6 +121,8 @@ impl Display for Errors {
             ExactNear(message, token) =>
                 write!(f, "{message} on line {} column {}",
                        token.get_line_number(), token.get_column_number()),
+            ForeignKeyViolation(reference, key) =>
+                write!(f, "Foreign key violation: ({key}) is not present in {reference}"),
             HashTableOverflow(rid, value) =>
                 write!(f, "Hash table overflow detected (rid: {rid}, key: {value})"),
             IllegalExpression(expr) =>
@@ -122,14 +133,24 @@ impl Display for Errors {
                 write!(f, "Invalid namespace reference {expr}"),
             NotImplemented(expr) =>
                 write!(f, "Not yet implemented - {expr}"),
+            NotNullViolation(column) =>
+                write!(f, "Not null constraint violation: {column} cannot be null"),
             PackageNotFound(name) =>
                 write!(f, "Package '{name}' not found"),
+            ReferencedKeyViolation(referrer, key) =>
+                write!(f, "Referenced key violation: ({key}) is still referenced by {referrer}"),
+            ReplicaReadOnly(name, primary) =>
+                write!(f, "Read-only replica: {name} may only be written via the primary ({primary})"),
             IndexOutOfRange(name, idx, len) =>
                 write!(f, "{name} index is out of range ({idx} >= {len})"),
             Syntax(message) =>
                 write!(f, "Syntax error: {message}"),
+            TriggerRejected(name) =>
+                write!(f, "Trigger rejection: the row was rejected by trigger '{name}'"),
             TypeMismatch(mismatch) =>
                 write!(f, "{}", mismatch),
+            UniqueKeyViolation(columns, key) =>
+                write!(f, "Unique key violation: ({columns}) = ({key}) already exists"),
             UnsupportedPlatformOps(pops) =>
                 write!(f, "Unsupported operation {}", pops.to_code()),
             Multiple(errors) =>
@@ -164,6 +185,8 @@ mod tests {
                "Assertion Error: true was not false");
         verify(CannotSubtract("a".into(), "b".into()),
                "Cannot subtract b from a");
+        verify(CheckViolation("last_sale".into(), "last_sale > 0".into()),
+               "Check constraint violation: last_sale does not satisfy (last_sale > 0)");
         verify(PlatformOpError(PlatformOps::UtilHex),
                "Conversion error: \"util::hex(x)\"");
         verify(Exact("Something bad happened".into()),
@@ -172,6 +195,8 @@ mod tests {
             "Something bad happened".into(),
             Token::operator(".".into(), 145, 146, 13, 5)),
                "Something bad happened on line 13 column 5");
+        verify(ForeignKeyViolation("a.b.stocks(symbol)".into(), "\"ABC\"".into()),
+               "Foreign key violation: (\"ABC\") is not present in a.b.stocks(symbol)");
         verify(HashTableOverflow(100, "AAA".into()),
                "Hash table overflow detected (rid: 100, key: AAA)");
         verify(IllegalExpression("2 ~ 3".into()),
@@ -185,10 +210,19 @@ mod tests {
         }), "Illegal use of operator '+'");
         verify(InvalidNamespace("a.b.c".into()), "Invalid namespace reference a.b.c");
         verify(NotImplemented("magic()".into()), "Not yet implemented - magic()");
+        verify(NotNullViolation("symbol".into()), "Not null constraint violation: symbol cannot be null");
         verify(PackageNotFound("wth".into()), "Package 'wth' not found");
         verify(IndexOutOfRange("bytes".into(), 5, 4),
                "bytes index is out of range (5 >= 4)");
+        verify(ReferencedKeyViolation("a.b.orders(symbol)".into(), "\"ABC\"".into()),
+               "Referenced key violation: (\"ABC\") is still referenced by a.b.orders(symbol)");
+        verify(ReplicaReadOnly("a.b.stocks".into(), "localhost:8080".into()),
+               "Read-only replica: a.b.stocks may only be written via the primary (localhost:8080)");
         verify(Syntax("cannot do it".into()), "Syntax error: cannot do it");
+        verify(TriggerRejected("check_price".into()),
+               "Trigger rejection: the row was rejected by trigger 'check_price'");
+        verify(UniqueKeyViolation("symbol".into(), "\"ABC\"".into()),
+               "Unique key violation: (symbol) = (\"ABC\") already exists");
     }
 
     #[test]
@@ -15,6 +15,7 @@ use crate::expression::Expression::*;
 use crate::inferences::Inferences;
 use crate::numbers::Numbers;
 use crate::numbers::Numbers::I64Value;
+use crate::object_config::{ConstraintConfig, IndexKinds, TriggerEvents, TriggerTimings};
 use crate::parameter::Parameter;
 use crate::row_collection::RowCollection;
 use crate::structures::Structures::{Firm, Soft};
@@ -75,15 +76,69 @@ pub enum DatabaseOps {
     Mutation(Mutations),
 }
 
+/// Represents a change to the layout of a table
+/// (e.g. "alter table stocks add column rating: i64 = 0")
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Alteration {
+    AddColumn(Parameter),
+    /// enables (or disables) the appending of rows to the end of the table only,
+    /// rather than filling the slots of deleted rows (e.g. "append only")
+    AppendOnly(bool),
+    DropColumn(String),
+    /// enables (or disables) the encryption of subsequently written rows (e.g. "encrypt")
+    Encryption(bool),
+    RenameColumn { old_name: String, new_name: String },
+    RetypeColumn(Parameter),
+}
+
+impl Alteration {
+    pub fn to_code(&self) -> String {
+        match self {
+            Alteration::AddColumn(param) => format!("add column {}", param.to_code()),
+            Alteration::AppendOnly(true) => "append only".to_string(),
+            Alteration::AppendOnly(false) => "reuse slots".to_string(),
+            Alteration::DropColumn(name) => format!("drop column {name}"),
+            Alteration::Encryption(true) => "encrypt".to_string(),
+            Alteration::Encryption(false) => "decrypt".to_string(),
+            Alteration::RenameColumn { old_name, new_name } =>
+                format!("rename column {old_name} to {new_name}"),
+            Alteration::RetypeColumn(param) => format!("alter column {}", param.to_code()),
+        }
+    }
+}
+
 /// Represents a Creation Entity
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum CreationEntity {
     IndexEntity {
         columns: Vec<Expression>,
+        is_unique: bool,
+        kind: IndexKinds,
+    },
+    MaterializedViewEntity {
+        query: Box<Expression>,
+        refresh_interval: Option<Box<Expression>>,
+    },
+    SequenceEntity {
+        increment: Option<Box<Expression>>,
+        start: Option<Box<Expression>>,
     },
     TableEntity {
         columns: Vec<Parameter>,
+        constraints: Vec<ConstraintConfig>,
         from: Option<Box<Expression>>,
+        is_append_only: bool,
+        is_encrypted: bool,
+        partitions: Vec<Expression>,
+    },
+    TriggerEntity {
+        name: String,
+        timing: TriggerTimings,
+        event: TriggerEvents,
+        function: Box<Expression>,
+    },
+    ViewEntity {
+        query: Box<Expression>,
     },
 }
 
@@ -110,15 +165,47 @@ impl Display for ImportOps {
     }
 }
 
+/// Represents the set of all Transaction Operations
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum TransactionOps {
+    Begin,
+    Block(Box<Expression>),
+    Commit,
+    Rollback,
+}
+
+/// Represents the kind of join between two tables
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum JoinKinds {
+    Cross,
+    Full,
+    Inner,
+    Left,
+    Right,
+}
+
+impl JoinKinds {
+    pub fn to_code(&self) -> String {
+        (match self {
+            JoinKinds::Cross => "cross join",
+            JoinKinds::Full => "full join",
+            JoinKinds::Inner => "join",
+            JoinKinds::Left => "left join",
+            JoinKinds::Right => "right join",
+        }).to_string()
+    }
+}
+
 /// Represents a data modification event
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Mutations {
+    Alter { path: Box<Expression>, alterations: Vec<Alteration> },
     Append {
         path: Box<Expression>,
         source: Box<Expression>,
     },
     Create { path: Box<Expression>, entity: CreationEntity },
-    Declare(CreationEntity),
+    Declare { path: Option<Box<Expression>>, entity: CreationEntity },
     Delete {
         path: Box<Expression>,
         condition: Option<Conditions>,
@@ -126,16 +213,22 @@ pub enum Mutations {
     },
     Drop(MutateTarget),
     IntoNs(Box<Expression>, Box<Expression>),
+    NextVal { path: Box<Expression> },
     Overwrite {
         path: Box<Expression>,
         source: Box<Expression>,
         condition: Option<Conditions>,
         limit: Option<Box<Expression>>,
     },
+    Refresh { path: Box<Expression> },
     Truncate {
         path: Box<Expression>,
         limit: Option<Box<Expression>>,
     },
+    TruncatePartition {
+        path: Box<Expression>,
+        key: Box<Expression>,
+    },
     Undelete {
         path: Box<Expression>,
         condition: Option<Conditions>,
@@ -155,14 +248,32 @@ pub enum MutateTarget {
     IndexTarget {
         path: Box<Expression>,
     },
+    PartitionTarget {
+        path: Box<Expression>,
+        key: Box<Expression>,
+    },
     TableTarget {
         path: Box<Expression>,
     },
+    TriggerTarget {
+        path: Box<Expression>,
+        name: String,
+    },
+    ViewTarget {
+        path: Box<Expression>,
+    },
 }
 
 /// Represents an enumeration of queryables
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Queryables {
+    AsOf { from: Box<Expression>, point: Box<Expression>, is_version: bool },
+    Join {
+        left: Box<Expression>,
+        right: Box<Expression>,
+        kind: JoinKinds,
+        condition: Option<Conditions>,
+    },
     Limit { from: Box<Expression>, limit: Box<Expression> },
     Select {
         fields: Vec<Expression>,
@@ -180,6 +291,7 @@ pub enum Queryables {
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Expression {
     ArrayExpression(Vec<Expression>),
+    Ascending(Box<Expression>),
     AsValue(String, Box<Expression>),
     // bitwise
     BitwiseAnd(Box<Expression>, Box<Expression>),
@@ -191,6 +303,7 @@ pub enum Expression {
     CodeBlock(Vec<Expression>),
     Condition(Conditions),
     DatabaseOp(DatabaseOps),
+    Descending(Box<Expression>),
     Directive(Directives),
     Divide(Box<Expression>, Box<Expression>),
     ElementAt(Box<Expression>, Box<Expression>),
@@ -234,6 +347,7 @@ pub enum Expression {
         verifications: Vec<Expression>,
     },
     SetVariable(String, Box<Expression>),
+    TransactionOp(TransactionOps),
     Tuple(Vec<Expression>),
     Variable(String),
     Via(Box<Expression>),
@@ -254,6 +368,7 @@ impl Expression {
         match expr {
             ArrayExpression(items) =>
                 format!("[{}]", items.iter().map(|i| Self::decompile(i)).collect::<Vec<String>>().join(", ")),
+            Ascending(expr) => format!("{} asc", Self::decompile(expr)),
             AsValue(name, expr) =>
                 format!("{}: {}", name, Self::decompile(expr)),
             BitwiseAnd(a, b) =>
@@ -268,6 +383,7 @@ impl Expression {
                 format!("{} >> {}", Self::decompile(a), Self::decompile(b)),
             CodeBlock(items) => Self::decompile_code_blocks(items),
             Condition(cond) => Self::decompile_cond(cond),
+            Descending(expr) => format!("{} desc", Self::decompile(expr)),
             Directive(d) => Self::decompile_directives(d),
             Divide(a, b) =>
                 format!("{} / {}", Self::decompile(a), Self::decompile(b)),
@@ -340,6 +456,7 @@ impl Expression {
             }
             SetVariable(name, value) =>
                 format!("{} := {}", name, Self::decompile(value)),
+            TransactionOp(op) => Self::decompile_transaction_ops(op),
             Variable(name) => name.to_string(),
             Via(expr) => format!("via {}", Self::decompile(expr)),
             While { condition, code } =>
@@ -394,6 +511,15 @@ impl Expression {
             .join(", ")
     }
 
+    /// Decompiles the column definitions of a table (e.g. "symbol: String(8) not null")
+    pub fn decompile_columns(params: &[Parameter], constraints: &[ConstraintConfig]) -> String {
+        params.iter().map(|p| match constraints.iter().find(|c| c.get_column_name() == p.get_name()) {
+            Some(constraint) => format!("{} {}", p.to_code(), constraint.to_code()),
+            None => p.to_code()
+        }).collect::<Vec<_>>()
+            .join(", ")
+    }
+
     pub fn decompile_parameters(params: &Vec<Parameter>) -> String {
         params.iter().map(|p| p.to_code())
             .collect::<Vec<_>>()
@@ -409,6 +535,15 @@ impl Expression {
         }
     }
 
+    pub fn decompile_transaction_ops(op: &TransactionOps) -> String {
+        match op {
+            TransactionOps::Begin => "begin".into(),
+            TransactionOps::Block(code) => format!("transaction {}", Self::decompile(code)),
+            TransactionOps::Commit => "commit".into(),
+            TransactionOps::Rollback => "rollback".into(),
+        }
+    }
+
     pub fn decompile_if_exists(if_exists: bool) -> String {
         (if if_exists { "if exists " } else { "" }).to_string()
     }
@@ -449,26 +584,74 @@ impl Expression {
 
     pub fn decompile_modifications(expr: &Mutations) -> String {
         match expr {
+            Mutations::Alter { path, alterations } =>
+                format!("alter table {} {}", Self::decompile(path), alterations.iter()
+                    .map(|alteration| alteration.to_code())
+                    .collect::<Vec<_>>()
+                    .join(", ")),
             Mutations::Append { path, source } =>
                 format!("append {} {}", Self::decompile(path), Self::decompile(source)),
             Mutations::Create { path, entity } =>
                 match entity {
-                    CreationEntity::IndexEntity { columns } =>
-                        format!("create index {} [{}]", Self::decompile(path), Self::decompile_list(columns)),
-                    CreationEntity::TableEntity { columns, from } =>
-                        format!("create table {} ({})", Self::decompile(path), Self::decompile_parameters(columns)),
+                    CreationEntity::IndexEntity { columns, is_unique, kind } =>
+                        format!("create {}index {} [{}]{}", if *is_unique { "unique " } else { "" },
+                                Self::decompile(path), Self::decompile_list(columns),
+                                match kind {
+                                    IndexKinds::Hash => String::new(),
+                                    kind => format!(" using {}", kind.to_code())
+                                }),
+                    CreationEntity::MaterializedViewEntity { query, refresh_interval } =>
+                        format!("create materialized view {}{} as {}", Self::decompile(path),
+                                refresh_interval.as_ref()
+                                    .map(|e| format!(" refresh every {}", Self::decompile(e)))
+                                    .unwrap_or_default(),
+                                Self::decompile(query)),
+                    CreationEntity::SequenceEntity { increment, start } =>
+                        format!("create sequence {}{}{}", Self::decompile(path),
+                                start.as_ref().map(|e| format!(" start with {}", Self::decompile(e))).unwrap_or_default(),
+                                increment.as_ref().map(|e| format!(" increment by {}", Self::decompile(e))).unwrap_or_default()),
+                    CreationEntity::TableEntity { columns, constraints, is_append_only, is_encrypted, partitions, .. } =>
+                        format!("create table {} ({}){}{}{}", Self::decompile(path), Self::decompile_columns(columns, constraints),
+                                if partitions.is_empty() { String::new() } else {
+                                    format!(" partition by [{}]", Self::decompile_list(partitions))
+                                },
+                                if *is_append_only { " append only" } else { "" },
+                                if *is_encrypted { " encrypted" } else { "" }),
+                    CreationEntity::TriggerEntity { name, timing, event, function } =>
+                        format!("create trigger {} {} {} on {} do {}", name, timing.to_code(), event.to_code(),
+                                Self::decompile(path), Self::decompile(function)),
+                    CreationEntity::ViewEntity { query } =>
+                        format!("create view {} as {}", Self::decompile(path), Self::decompile(query)),
                 }
-            Mutations::Declare(entity) =>
+            Mutations::Declare { path, entity } =>
                 match entity {
-                    CreationEntity::IndexEntity { columns } =>
-                        format!("index [{}]", Self::decompile_list(columns)),
-                    CreationEntity::TableEntity { columns, from } =>
+                    CreationEntity::IndexEntity { columns, .. } => match path {
+                        Some(path) =>
+                            format!("declare index {} [{}]", Self::decompile(path), Self::decompile_list(columns)),
+                        None => format!("index [{}]", Self::decompile_list(columns)),
+                    }
+                    CreationEntity::MaterializedViewEntity { query, .. } =>
+                        format!("materialized view {}", Self::decompile(query)),
+                    CreationEntity::SequenceEntity { .. } =>
+                        "sequence".to_string(),
+                    CreationEntity::TableEntity { columns, .. } =>
                         format!("table({})", Self::decompile_parameters(columns)),
+                    CreationEntity::TriggerEntity { name, .. } =>
+                        format!("trigger {}", name),
+                    CreationEntity::ViewEntity { query } =>
+                        format!("view {}", Self::decompile(query)),
                 }
+            Mutations::Drop(MutateTarget::PartitionTarget { path, key }) =>
+                format!("drop partition {} {}", Self::decompile(path), Self::decompile(key)),
+            Mutations::Drop(MutateTarget::TriggerTarget { path, name }) =>
+                format!("drop trigger {} on {}", name, Self::decompile(path)),
             Mutations::Drop(target) => {
                 let (kind, path) = match target {
                     MutateTarget::IndexTarget { path } => ("index", path),
+                    MutateTarget::PartitionTarget { path, .. } => ("partition", path),
                     MutateTarget::TableTarget { path } => ("table", path),
+                    MutateTarget::TriggerTarget { path, .. } => ("trigger", path),
+                    MutateTarget::ViewTarget { path } => ("view", path),
                 };
                 format!("drop {} {}", kind, Self::decompile(path))
             }
@@ -476,13 +659,19 @@ impl Expression {
                 format!("delete from {} where {}{}", Self::decompile(path), Self::decompile_cond_opt(condition), Self::decompile_opt(limit)),
             Mutations::IntoNs(a, b) =>
                 format!("{} ~> {}", Self::decompile(a), Self::decompile(b)),
+            Mutations::NextVal { path } =>
+                format!("nextval({})", Self::decompile(path)),
             Mutations::Overwrite { path, source, condition, limit } =>
                 format!("overwrite {} {}{}{}", Self::decompile(path), Self::decompile(source),
                         condition.to_owned().map(|e| format!(" where {}", Self::decompile_cond(&e))).unwrap_or("".into()),
                         limit.to_owned().map(|e| format!(" limit {}", Self::decompile(&e))).unwrap_or("".into()),
                 ),
+            Mutations::Refresh { path } =>
+                format!("refresh {}", Self::decompile(path)),
             Mutations::Truncate { path, limit } =>
                 format!("truncate {}{}", Self::decompile(path), Self::decompile_limit(limit)),
+            Mutations::TruncatePartition { path, key } =>
+                format!("truncate partition {} {}", Self::decompile(path), Self::decompile(key)),
             Mutations::Undelete { path, condition, limit } =>
                 format!("undelete from {} where {}{}", Self::decompile(path), Self::decompile_cond_opt(condition), Self::decompile_opt(limit)),
             Mutations::Update { path, source, condition, limit } =>
@@ -493,6 +682,12 @@ impl Expression {
 
     pub fn decompile_queryables(expr: &Queryables) -> String {
         match expr {
+            Queryables::AsOf { from, point, is_version } =>
+                format!("{} as of {}{}", Self::decompile(from), if *is_version { "version " } else { "" }, Self::decompile(point)),
+            Queryables::Join { left, right, kind, condition } =>
+                format!("{} {} {}{}", Self::decompile(left), kind.to_code(), Self::decompile(right),
+                        condition.to_owned().map(|c| format!(" on {}", Self::decompile_cond(&c))).unwrap_or("".into()),
+                ),
             Queryables::Limit { from: a, limit: b } =>
                 format!("{} limit {}", Self::decompile(a), Self::decompile(b)),
             Queryables::Where { from, condition } =>
@@ -639,7 +834,7 @@ fn to_ns(path: Expression) -> Expression {
 mod tests {
     use crate::data_types::DataType::{NumberType, StringType};
     use crate::expression::Conditions::*;
-    use crate::expression::CreationEntity::{IndexEntity, TableEntity};
+    use crate::expression::CreationEntity::{IndexEntity, MaterializedViewEntity, TableEntity, ViewEntity};
     use crate::expression::DatabaseOps::{Mutation, Queryable};
     use crate::expression::Expression::{AsValue, Literal};
     use crate::expression::*;
@@ -1075,6 +1270,8 @@ mod tests {
                     Variable("symbol".into()),
                     Variable("exchange".into()),
                 ],
+                is_unique: false,
+                kind: IndexKinds::Hash,
             },
         }));
         assert_eq!(
@@ -1082,6 +1279,48 @@ mod tests {
             r#"create index ns("compiler.create.stocks") [symbol, exchange]"#)
     }
 
+    #[test]
+    fn test_create_btree_index_in_namespace() {
+        let model = DatabaseOp(Mutation(Mutations::Create {
+            path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.stocks".into()))))),
+            entity: IndexEntity {
+                columns: vec![Variable("last_sale".into())],
+                is_unique: false,
+                kind: IndexKinds::BTree,
+            },
+        }));
+        assert_eq!(
+            Expression::decompile(&model),
+            r#"create index ns("compiler.create.stocks") [last_sale] using btree"#)
+    }
+
+    #[test]
+    fn test_create_materialized_view_in_namespace() {
+        let model = DatabaseOp(Mutation(Mutations::Create {
+            path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.high_flyers".into()))))),
+            entity: MaterializedViewEntity {
+                query: Box::new(From(Box::new(Variable("stocks".into())))),
+                refresh_interval: Some(Box::new(Literal(Number(I64Value(60000))))),
+            },
+        }));
+        assert_eq!(
+            Expression::decompile(&model),
+            r#"create materialized view ns("compiler.create.high_flyers") refresh every 60000 as from stocks"#)
+    }
+
+    #[test]
+    fn test_create_view_in_namespace() {
+        let model = DatabaseOp(Mutation(Mutations::Create {
+            path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.high_flyers".into()))))),
+            entity: ViewEntity {
+                query: Box::new(From(Box::new(Variable("stocks".into())))),
+            },
+        }));
+        assert_eq!(
+            Expression::decompile(&model),
+            r#"create view ns("compiler.create.high_flyers") as from stocks"#)
+    }
+
     #[test]
     fn test_create_table_in_namespace() {
         let ns_path = "compiler.create.stocks";
@@ -1093,7 +1332,11 @@ mod tests {
                     Parameter::with_default("exchange", StringType(8), StringValue("NYSE".into())),
                     Parameter::with_default("last_sale", NumberType(F64Kind), Number(F64Value(0.))),
                 ],
+                constraints: vec![],
                 from: None,
+                is_append_only: false,
+                is_encrypted: false,
+                partitions: vec![],
             },
         }));
         assert_eq!(
@@ -1101,16 +1344,68 @@ mod tests {
             r#"create table ns("compiler.create.stocks") (symbol: String(8) := "ABC", exchange: String(8) := "NYSE", last_sale: f64 := 0.0)"#)
     }
 
+    #[test]
+    fn test_create_partitioned_table_in_namespace() {
+        let model = DatabaseOp(Mutation(Mutations::Create {
+            path: Box::new(Ns(Box::new(Literal(StringValue("compiler.create.stocks".into()))))),
+            entity: TableEntity {
+                columns: vec![
+                    Parameter::new("symbol", StringType(8)),
+                    Parameter::new("exchange", StringType(8)),
+                ],
+                constraints: vec![],
+                from: None,
+                is_append_only: false,
+                is_encrypted: false,
+                partitions: vec![Variable("exchange".into())],
+            },
+        }));
+        assert_eq!(
+            Expression::decompile(&model),
+            r#"create table ns("compiler.create.stocks") (symbol: String(8), exchange: String(8)) partition by [exchange]"#)
+    }
+
+    #[test]
+    fn test_transaction_block() {
+        let model = TransactionOp(TransactionOps::Block(Box::new(CodeBlock(vec![
+            DatabaseOp(Mutation(Mutations::Truncate {
+                path: Box::new(Variable("stocks".into())),
+                limit: None,
+            })),
+        ]))));
+        assert_eq!(Expression::decompile(&model), "transaction {\ntruncate stocks\n}")
+    }
+
+    #[test]
+    fn test_declare_index() {
+        let model = DatabaseOp(Mutation(Mutations::Declare {
+            path: Some(Box::new(Variable("stocks".into()))),
+            entity: IndexEntity {
+                columns: vec![Variable("symbol".into())],
+                is_unique: false,
+                kind: IndexKinds::Hash,
+            },
+        }));
+        assert_eq!(Expression::decompile(&model), "declare index stocks [symbol]")
+    }
+
     #[test]
     fn test_declare_table() {
-        let model = DatabaseOp(Mutation(Mutations::Declare(TableEntity {
-            columns: vec![
-                Parameter::new("symbol", StringType(8)),
-                Parameter::new("exchange", StringType(8)),
-                Parameter::new("last_sale", NumberType(F64Kind)),
-            ],
-            from: None,
-        })));
+        let model = DatabaseOp(Mutation(Mutations::Declare {
+            path: None,
+            entity: TableEntity {
+                columns: vec![
+                    Parameter::new("symbol", StringType(8)),
+                    Parameter::new("exchange", StringType(8)),
+                    Parameter::new("last_sale", NumberType(F64Kind)),
+                ],
+                constraints: vec![],
+                from: None,
+                is_append_only: false,
+                is_encrypted: false,
+                partitions: vec![],
+            },
+        }));
         assert_eq!(
             Expression::decompile(&model),
             r#"table(symbol: String(8), exchange: String(8), last_sale: f64)"#)
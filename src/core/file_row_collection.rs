@@ -4,45 +4,83 @@
 ////////////////////////////////////////////////////////////////////
 
 use crate::blobs::{BLOBCellMetadata, BLOBStore};
+use crate::btree_index::BTreeIndex;
 use crate::byte_code_compiler::ByteCodeCompiler;
 use crate::columns::Column;
-use crate::data_types::DataType::NumberType;
+use crate::data_types::DataType::{BooleanType, NumberType};
+use crate::dataframe::Dataframe;
+use crate::dataframe::Dataframe::Model;
+use crate::encryption::Encryption;
+use crate::errors::Errors::{CheckViolation, NotNullViolation};
 use crate::errors::{throw, Errors};
 use crate::field;
 use crate::field::FieldMetadata;
+use crate::foreign_keys::ForeignKeys;
+use crate::free_slots::FreeSlots;
 use crate::machine::Machine;
+use crate::model_row_collection::ModelRowCollection;
 use crate::namespaces::Namespace;
 use crate::number_kind::NumberKind::U64Kind;
 use crate::numbers::Numbers;
-use crate::object_config::ObjectConfig;
+use crate::numbers::Numbers::{I64Value, RowId, RowsAffected, U64Value};
+use crate::expression::Expression::{Condition, Literal, Variable};
+use crate::expression::{Conditions, Expression};
+use crate::hash_table_row_collection::HashTableRowCollection;
+use crate::object_config::{ConstraintConfig, HashIndexConfig, IndexKinds, ObjectConfig};
 use crate::parameter::Parameter;
 use crate::platform::PlatformOps;
 use crate::row_collection::{RowCollection, RowEncoding};
+use crate::row_history::RowHistory;
 use crate::row_metadata::RowMetadata;
+use crate::sequences::Sequence;
+use crate::snapshots::{RowVersions, Snapshot};
+use crate::sorting::Sorting;
 use crate::structures::Row;
 use crate::typed_values::TypedValue;
-use crate::typed_values::TypedValue::{ErrorValue, Number};
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, Null, Number, TableValue, Undefined};
+use crate::write_ahead_log::WriteAheadLog;
 use log::error;
 use serde::de::Error;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use shared_lib::fail;
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Debug, Formatter};
 use std::fs;
 use std::fs::{File, OpenOptions};
+use std::ops::{Bound, Range};
 use std::os::unix::fs::FileExt;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, Weak};
+use uuid::Uuid;
+
+/// the length of the prefix of an encrypted row => (metadata|row ID|payload offset)
+const ENCRYPTED_ROW_LEN: usize = 17;
+
+/// the extension of the temporary file into which a table is rewritten (e.g. "stocks.rewrite")
+const REWRITE_EXTENSION: &str = "rewrite";
+
+/// the scratch tables within this process => (table path|[the files of its scratch tables])
+static SCRATCH_TABLES: OnceLock<Mutex<BTreeMap<String, Vec<Weak<File>>>>> = OnceLock::new();
 
 /// File-based RowCollection implementation
 #[derive(Clone)]
 pub struct FileRowCollection {
     blobs: BLOBStore,
     columns: Vec<Column>,
+    constraints: Vec<ConstraintConfig>,
     file: Arc<File>,
+    indices: Vec<HashIndexConfig>,
+    /// whether rows are only ever appended to the end of the table (see [FreeSlots])
+    is_append_only: bool,
+    /// whether the rows are encrypted when written (see [Encryption])
+    is_encrypted: bool,
     path: String,
     record_size: usize,
+    snapshot: Option<Arc<Snapshot>>,
+    versions: Option<Arc<RowVersions>>,
+    wal: Option<WriteAheadLog>,
 }
 
 impl FileRowCollection {
@@ -52,7 +90,131 @@ impl FileRowCollection {
         let columns = Column::from_parameters(params);
         ObjectConfig::build_table(params.clone()).save(ns)?;
         let file = Arc::new(Self::table_file_create(ns)?);
-        Ok(Self::new(columns, file, path.as_str()))
+        WriteAheadLog::discard(path.as_str())?;
+        RowHistory::discard(path.as_str())?;
+        FreeSlots::discard(path.as_str())?;
+        let wal = WriteAheadLog::open(path.as_str())?;
+        let versions = RowVersions::open(path.as_str())?;
+        Ok(Self::new(columns, file, path.as_str()).with_wal(wal).with_versions(versions))
+    }
+
+    /// Fills the missing (undefined) fields of the row with the column defaults;
+    /// whereas the missing (or null) fields of serial columns are drawn from their sequences.
+    pub fn apply_defaults(&self, row: Row) -> std::io::Result<Row> {
+        let mut values = Vec::with_capacity(self.columns.len());
+        for (value, column) in row.get_values().into_iter().zip(self.columns.iter()) {
+            values.push(self.apply_default(column, value)?);
+        }
+        Ok(Row::new(row.get_id(), values))
+    }
+
+    /// Fills a missing (undefined) field with the column default; whereas a missing (or null)
+    /// field of a serial column is drawn from its sequence.
+    fn apply_default(&self, column: &Column, value: TypedValue) -> std::io::Result<TypedValue> {
+        let is_serial = self.constraints.iter()
+            .any(|c| c.is_serial() && c.get_column_name() == column.get_name());
+        Ok(match (value, column.get_default_value()) {
+            (Null | Undefined, _) if is_serial => {
+                let next_value = Sequence::for_column(&self.path, column.get_name()).next_value()?;
+                column.get_data_type().convert(&Number(I64Value(next_value)))?
+            }
+            (Undefined, Undefined) => Null,
+            (Undefined, default_value) => default_value,
+            (value, _) => value
+        })
+    }
+
+    /// Searches the column constraints (e.g. "not null", "check (last_sale > 0)" or
+    /// "references ns('a.b.stocks')(symbol)") for one which the row does not satisfy;
+    /// where nulls satisfy any check or reference.
+    pub fn find_constraint_violation(&self, row: &Row) -> std::io::Result<Option<Errors>> {
+        for constraint in self.constraints.iter() {
+            let name = constraint.get_column_name();
+            let column_id = match self.columns.iter().position(|c| c.get_name() == name) {
+                Some(column_id) => column_id,
+                None => continue
+            };
+            let is_null = matches!(row.get(column_id), Null | Undefined);
+            if is_null && constraint.is_not_null() {
+                return Ok(Some(NotNullViolation(name.to_string())));
+            }
+            if let (false, Some(check)) = (is_null, constraint.get_check()) {
+                match Machine::empty().with_row(&self.columns, row).evaluate(check)? {
+                    (_, Boolean(true)) => {}
+                    (_, ErrorValue(err)) => return Ok(Some(err)),
+                    _ => return Ok(Some(CheckViolation(name.to_string(), check.to_code())))
+                }
+            }
+            if let (false, Some(reference)) = (is_null, constraint.get_reference()) {
+                if let Some(err) = ForeignKeys::find_violation(reference, &row.get(column_id))? {
+                    return Ok(Some(err));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn get_constraints(&self) -> &Vec<ConstraintConfig> {
+        &self.constraints
+    }
+
+    /// Indicates whether the table has any column constraints
+    pub fn has_constraints(&self) -> bool {
+        !self.constraints.is_empty()
+    }
+
+    /// Indicates whether the values of the column are compressed (e.g. "body: String(1024) compressed")
+    pub fn is_compressed(&self, column: &Column) -> bool {
+        self.constraints.iter().any(|c| c.is_compressed() && c.get_column_name() == column.get_name())
+    }
+
+    /// Indicates whether appends fill the slots of deleted rows (see [FreeSlots]); which applies
+    /// to the tables opened by namespace (rather than their related tables), unless append-only.
+    fn is_reusing_slots(&self) -> bool {
+        self.versions.is_some() && !self.is_append_only
+    }
+
+    /// Indicates whether the columns of the table are referenced by other tables
+    pub fn has_referrers(&self) -> bool {
+        self.constraints.iter().any(|c| !c.get_referrers().is_empty())
+    }
+
+    /// Augments the description of the table with the raw and compressed sizes
+    /// of the active values of its compressed columns
+    fn describe_compression(&self, described: &TypedValue) -> std::io::Result<TypedValue> {
+        // total the sizes of the compressed columns
+        let mut sizes = vec![(0, 0); self.columns.len()];
+        for id in 0..self.len()? {
+            let buffer = self.read_at(self.convert_rowid_to_offset(id), self.record_size)?;
+            // encrypted rows are stored as a single (uncompressed) payload
+            let metadata = RowMetadata::from_bytes(&buffer, 0);
+            if !metadata.is_allocated || metadata.is_encrypted { continue; }
+            for (column_id, column) in self.columns.iter().enumerate() {
+                if self.is_compressed(column) {
+                    let (raw_size, compressed_size) = self.blobs.decode_field_sizes(column, &buffer, column.get_offset())?;
+                    sizes[column_id].0 += raw_size as u64;
+                    sizes[column_id].1 += compressed_size as u64;
+                }
+            }
+        }
+
+        // append the sizes to the description
+        let mut params = PlatformOps::get_tools_describe_parameters();
+        params.push(Parameter::new("is_compressed", BooleanType));
+        params.push(Parameter::new("raw_size", NumberType(U64Kind)));
+        params.push(Parameter::new("compressed_size", NumberType(U64Kind)));
+        let rows = described.to_table()?.read_active_rows()?.into_iter()
+            .zip(self.columns.iter().zip(sizes))
+            .map(|(row, (column, (raw_size, compressed_size)))| {
+                let mut values = row.get_values();
+                match self.is_compressed(column) {
+                    true => values.extend([Boolean(true), Number(U64Value(raw_size)), Number(U64Value(compressed_size))]),
+                    false => values.extend([Boolean(false), Null, Null])
+                }
+                Row::new(row.get_id(), values)
+            })
+            .collect();
+        Ok(TableValue(Model(ModelRowCollection::from_parameters_and_rows(&params, &rows))))
     }
 
     pub fn get_related_filename(path: &str, extension: &str) -> (String, String) {
@@ -74,6 +236,70 @@ impl FileRowCollection {
         (base_path.to_string(), full_path)
     }
 
+    /// Creates (or truncates) a table file alongside this table (e.g. "stocks.btree0")
+    /// which shares this table's BLOB store.
+    pub fn create_related_table(
+        &self,
+        columns: Vec<Column>,
+        extension: &str,
+    ) -> std::io::Result<Self> {
+        // determine the base_path and full_path
+        let path = self.path.as_str();
+        let (base_path, full_path) =
+            Self::get_related_filename(path, extension);
+
+        // ensure the parent (base) directory exists
+        fs::create_dir_all(base_path)?;
+
+        // create and/or open the file, then truncate it
+        let file = OpenOptions::new().create(true).truncate(false).read(true).write(true).open(&full_path)?;
+        let frc = Self::new(columns, Arc::new(file), path)
+            .with_encrypted(self.is_encrypted)
+            .with_related_wal(&self.wal, &full_path);
+        frc.set_len(0)?;
+        Ok(frc)
+    }
+
+    /// Creates a uniquely named scratch table alongside this table (e.g. "stocks.scratch-5c0e...")
+    /// which shares this table's BLOB store; e.g. to hold the intermediate results of a query.
+    /// The file is unlinked once opened, thus it is removed when its last handle is dropped;
+    /// and since it does not outlive this process, its writes are not journaled. While the
+    /// scratch table is in use, the shared BLOB store cannot be reclaimed (see [FileRowCollection::compact_blobs]).
+    pub fn create_scratch_table(&self, columns: Vec<Column>) -> std::io::Result<Self> {
+        let extension = format!("scratch-{}", Uuid::new_v4().simple());
+        let (base_path, full_path) = Self::get_related_filename(self.path.as_str(), extension.as_str());
+        fs::create_dir_all(base_path)?;
+        let file = Arc::new(OpenOptions::new().create_new(true).read(true).write(true).open(&full_path)?);
+        fs::remove_file(&full_path)?;
+        let mut scratch_tables = Self::lock_scratch_tables()?;
+        let files = scratch_tables.entry(self.path.to_owned()).or_default();
+        files.retain(|file| file.strong_count() > 0);
+        files.push(Arc::downgrade(&file));
+        Ok(Self::new(columns, file, self.path.as_str()).with_encrypted(self.is_encrypted))
+    }
+
+    /// Opens (or creates) a table file alongside this table without truncating it
+    pub fn open_related_table(
+        &self,
+        columns: Vec<Column>,
+        extension: &str,
+    ) -> std::io::Result<Self> {
+        let path = self.path.as_str();
+        let (base_path, full_path) =
+            Self::get_related_filename(path, extension);
+        fs::create_dir_all(base_path)?;
+        let file = OpenOptions::new().create(true).read(true).write(true).open(&full_path)?;
+        Ok(Self::new(columns, Arc::new(file), path)
+            .with_encrypted(self.is_encrypted)
+            .with_related_wal(&self.wal, &full_path))
+    }
+
+    /// Deletes a table file previously created via [FileRowCollection::create_related_table]
+    pub fn delete_related_table(&self, extension: &str) -> std::io::Result<()> {
+        let (_, full_path) = Self::get_related_filename(self.path.as_str(), extension);
+        fs::remove_file(full_path)
+    }
+
     pub fn new(
         columns: Vec<Column>,
         file: Arc<File>,
@@ -85,8 +311,15 @@ impl FileRowCollection {
             record_size: Row::compute_record_size(&columns),
             columns,
             blobs,
+            constraints: Vec::new(),
             file,
+            indices: Vec::new(),
+            is_append_only: false,
+            is_encrypted: false,
             path: path.to_string(),
+            snapshot: None,
+            versions: None,
+            wal: None,
         }
     }
 
@@ -98,7 +331,15 @@ impl FileRowCollection {
         let cfg = ObjectConfig::load(&ns)?;
         let path = ns.get_table_file_path();
         let columns = Column::from_parameters(&cfg.get_columns());
-        Ok(Self::new(columns, Arc::new(file), path.as_str()))
+        let wal = WriteAheadLog::open(path.as_str())?;
+        let versions = RowVersions::open(path.as_str())?;
+        Ok(Self::new(columns, Arc::new(file), path.as_str())
+            .with_append_only(cfg.is_append_only())
+            .with_constraints(cfg.get_constraints())
+            .with_encrypted(cfg.is_encrypted())
+            .with_indices(cfg.get_indices())
+            .with_wal(wal)
+            .with_versions(versions))
     }
 
     pub fn open_or_create(ns: &Namespace) -> std::io::Result<Self> {
@@ -111,6 +352,9 @@ impl FileRowCollection {
                             PlatformOps::get_oxide_history_parameters()
                         );
                         cfg.save(&ns)?;
+                        WriteAheadLog::discard(ns.get_table_file_path().as_str())?;
+                        RowHistory::discard(ns.get_table_file_path().as_str())?;
+                        FreeSlots::discard(ns.get_table_file_path().as_str())?;
                         Self::open_file(ns, file)
                     }
                     Err(err) => fail(err.to_string())
@@ -120,6 +364,902 @@ impl FileRowCollection {
         }
     }
 
+    /// Rewrites the table into a new layout (e.g. "alter table"); where each row (and each
+    /// entry of the row history) is transformed into the new layout by the given function.
+    /// The indices of the rewritten table are rebuilt.
+    pub fn rewrite_table<F>(
+        ns: &Namespace,
+        config: ObjectConfig,
+        transform: F,
+    ) -> std::io::Result<Self>
+    where
+        F: Fn(Row) -> std::io::Result<Row>,
+    {
+        let frc0 = Self::open(ns)?;
+        if let Some(versions) = &frc0.versions {
+            if !versions.reclaim(&frc0)? {
+                return fail(format!("Table '{}' cannot be altered while snapshots are in progress", ns.get_full_name()));
+            }
+        }
+
+        // stream the rows, in the new layout, into a temporary file (e.g. "stocks.rewrite");
+        // whose writes are not journaled, since the file is discarded should the rewrite fail.
+        let path = ns.get_table_file_path();
+        let columns = Column::from_parameters(&config.get_columns());
+        let (base_path, temp_path) = Self::get_related_filename(path.as_str(), REWRITE_EXTENSION);
+        let file = OpenOptions::new().create(true).truncate(true).read(true).write(true).open(&temp_path)?;
+        let mut frc = Self::new(columns.clone(), Arc::new(file), path.as_str()).with_encrypted(config.is_encrypted());
+        for id in 0..frc0.len()? {
+            let (row, metadata) = frc0.read_row(id)?;
+            let row = match metadata.is_allocated {
+                true => transform(row)?.with_row_id(id),
+                false => Row::create(id, &columns)
+            };
+            frc.write_row(id, row).to_result(|_| ())?;
+            frc.write_row_metadata(id, metadata).to_result(|_| ())?;
+        }
+        RowHistory::rewrite(&frc0, &frc, &transform)?;
+
+        // flush the rewritten files (and their BLOBs) to disk, and retire the log entries of the
+        // original files; then rename the rewritten files over the originals. The configuration is
+        // saved last, which also causes the cached handles of the table to be re-opened.
+        match &frc0.wal {
+            Some(wal) => wal.checkpoint()?,
+            None => frc.file.sync_all()?
+        }
+        RowHistory::replace(path.as_str())?;
+        fs::rename(&temp_path, &path)?;
+        File::open(base_path)?.sync_all()?;
+        config.save(ns)?;
+
+        // rebuild the indices
+        let frc = Self::open(ns)?;
+        for column_id in frc.get_indexed_column_ids() { frc.rebuild_hash_index(column_id)?; }
+        for column_id in frc.get_ordered_column_ids() { frc.rebuild_ordered_index(column_id)?; }
+        Ok(frc)
+    }
+
+    /// Writes a row received from the primary of this table (e.g. by a follower); marking it
+    /// replicated. A missing row is written as deleted. Neither the defaults nor the constraints
+    /// are applied, since the primary has applied them already.
+    pub fn overwrite_replicated_row(&mut self, id: usize, row: Option<Row>) -> TypedValue {
+        let metadata = RowMetadata { is_replicated: true, ..RowMetadata::new(row.is_some()) };
+        let row = row.unwrap_or_else(|| Row::create(id, &self.columns));
+        self.write_with_indices(id, |frc| match frc.write_row(id, row) {
+            ErrorValue(err) => ErrorValue(err),
+            _ => frc.write_row_metadata(id, metadata)
+        })
+    }
+
+    /// Performs the writes within a single batch, if the table is journaled
+    pub fn batch<A, F>(&self, f: F) -> std::io::Result<A>
+    where
+        F: FnOnce() -> std::io::Result<A>,
+    {
+        match &self.wal {
+            Some(wal) => wal.batch(f)?,
+            None => f()
+        }
+    }
+
+    /// Compacts the BLOB store shared by this table and its related tables (e.g. its history
+    /// and indices); discarding the orphaned BLOBs, then relocating the remaining BLOBs toward
+    /// the start of the file and updating the references to them. Each relocation is journaled
+    /// as a single batch. Returns the number of bytes reclaimed.
+    pub fn compact_blobs(&self) -> std::io::Result<u64> {
+        let tables = self.open_blob_sharing_tables()?;
+        let references = Self::find_blob_references(&tables)?;
+        let referenced = references.keys().copied().collect::<BTreeSet<_>>();
+        self.blobs.compact(&referenced, |old_offset, new_offset| {
+            for (table, position) in references.get(&old_offset).into_iter().flatten() {
+                tables[*table].write_at(*position, &new_offset.to_be_bytes().to_vec())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns the in-use cells of the BLOB store which are no longer referenced by this table
+    /// or its related tables (e.g. the BLOBs of rows which have since been truncated).
+    pub fn find_orphaned_blobs(&self) -> std::io::Result<Vec<BLOBCellMetadata>> {
+        let references = Self::find_blob_references(&self.open_blob_sharing_tables()?)?;
+        Ok(self.blobs.read_headers()?.into_iter()
+            .filter(|header| header.used > 0 && !references.contains_key(&header.offset))
+            .collect())
+    }
+
+    /// Releases the orphaned BLOBs for reuse (see [FileRowCollection::find_orphaned_blobs]);
+    /// returning the number of bytes released.
+    pub fn reclaim_blobs(&self) -> std::io::Result<u64> {
+        let mut released = 0;
+        for header in self.find_orphaned_blobs()? {
+            self.blobs.free(header.offset)?;
+            released += header.allocated;
+        }
+        Ok(released)
+    }
+
+    /// Releases the row versions preserved on behalf of (since completed) snapshots
+    pub fn reclaim_versions(&self) -> TypedValue {
+        match &self.versions {
+            Some(versions) => match versions.reclaim(self) {
+                Ok(..) => Number(Numbers::Ack),
+                Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+            }
+            None => Number(Numbers::Ack)
+        }
+    }
+
+    /// Returns a read-only view of the table as of this moment; which is unaffected by
+    /// subsequent writes to the table. Snapshots do not use the table's indices.
+    pub fn snapshot(&self) -> std::io::Result<Self> {
+        match &self.versions {
+            Some(versions) => Ok(Self {
+                indices: Vec::new(),
+                snapshot: Some(Arc::new(Snapshot::begin(self, versions.clone())?)),
+                versions: None,
+                ..self.clone()
+            }),
+            None => fail(format!("Table '{}' does not support snapshots", self.path))
+        }
+    }
+
+    /// Returns the IDs of the rows which may satisfy the condition via an index; provided
+    /// the condition compares an indexed column to a constant (e.g. "symbol == 'ABC'"),
+    /// or, for ordered indices, bounds it (e.g. "last_sale between 1.0 and 5.0").
+    /// Returns [None] when no index is applicable.
+    pub fn find_row_ids_where(
+        &self,
+        machine: &Machine,
+        condition: &Option<Conditions>,
+    ) -> std::io::Result<Option<Vec<usize>>> {
+        let lookup = match condition {
+            Some(condition) => match self.decode_index_lookup(machine, condition)? {
+                Some(lookup) => lookup,
+                None => return Ok(None)
+            }
+            None => return Ok(None)
+        };
+        let mut row_ids = match lookup {
+            IndexLookup::Key(column_id, key) => self.find_row_ids_by_key(column_id, &key)?,
+            IndexLookup::Range(column_id, lower, upper) =>
+                self.open_ordered_index(column_id)?.find_row_ids_in_range(lower.as_ref(), upper.as_ref())?
+        };
+        row_ids.sort();
+        row_ids.dedup();
+        Ok(Some(row_ids))
+    }
+
+    /// Returns the IDs of the rows having the key via the column's hash (or ordered) index
+    fn find_row_ids_by_key(&self, column_id: usize, key: &TypedValue) -> std::io::Result<Vec<usize>> {
+        match self.get_indexed_column_ids().contains(&column_id) {
+            true => self.open_hash_index(column_id)?.find_row_ids_by_key(key),
+            false => self.open_ordered_index(column_id)?
+                .find_row_ids_in_range(Bound::Included(key), Bound::Included(key))
+        }
+    }
+
+    /// Returns the IDs of all rows in the order of the column's ordered index
+    /// (e.g. "order by last_sale desc"); rows having equal keys remain in row ID order.
+    pub fn find_ordered_row_ids(
+        &self,
+        column_id: usize,
+        is_descending: bool,
+    ) -> std::io::Result<Vec<usize>> {
+        let entries = self.open_ordered_index(column_id)?
+            .find_entries_in_range(Bound::Unbounded, Bound::Unbounded)?;
+        if !is_descending {
+            return Ok(entries.into_iter().map(|(_, id)| id).collect());
+        }
+        let mut groups: Vec<(TypedValue, Vec<usize>)> = Vec::new();
+        for (key, id) in entries {
+            match groups.last_mut() {
+                Some((last_key, ids)) if Self::is_same_key(last_key, &key) => ids.push(id),
+                _ => groups.push((key, vec![id]))
+            }
+        }
+        Ok(groups.into_iter().rev().flat_map(|(_, ids)| ids).collect())
+    }
+
+    /// Decodes a condition comparing an indexed column to a constant;
+    /// returning the key (or range of keys) to be looked up.
+    fn decode_index_lookup(
+        &self,
+        machine: &Machine,
+        condition: &Conditions,
+    ) -> std::io::Result<Option<IndexLookup>> {
+        use Bound::{Excluded, Included, Unbounded};
+        match condition {
+            Conditions::And(a, b) => {
+                for expr in [a, b] {
+                    if let Condition(condition) = expr.as_ref() {
+                        if let Some(lookup) = self.decode_index_lookup(machine, condition)? {
+                            return Ok(Some(lookup));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+            Conditions::Equal(a, b) => {
+                for (field, value) in [(a, b), (b, a)] {
+                    if let Some(column_id) = self.find_indexed_column_id(field, &self.get_indexed_column_ids()) {
+                        if self.is_constant(value) {
+                            return Ok(Some(IndexLookup::Key(column_id, self.evaluate_constant(machine, value)?)));
+                        }
+                    }
+                }
+                for (field, value) in [(a, b), (b, a)] {
+                    if let Some(column_id) = self.find_indexed_column_id(field, &self.get_ordered_column_ids()) {
+                        if self.is_constant(value) {
+                            let key = self.evaluate_constant(machine, value)?;
+                            return Ok(Some(IndexLookup::Range(column_id, Included(key.to_owned()), Included(key))));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+            Conditions::Between(a, b, c) | Conditions::Betwixt(a, b, c) => {
+                match self.find_indexed_column_id(a, &self.get_ordered_column_ids()) {
+                    Some(column_id) if self.is_constant(b) && self.is_constant(c) => {
+                        let (low, high) = (self.evaluate_constant(machine, b)?, self.evaluate_constant(machine, c)?);
+                        let upper = match condition {
+                            Conditions::Betwixt(..) => Excluded(high),
+                            _ => Included(high)
+                        };
+                        Ok(Some(IndexLookup::Range(column_id, Included(low), upper)))
+                    }
+                    _ => Ok(None)
+                }
+            }
+            Conditions::GreaterOrEqual(a, b) | Conditions::GreaterThan(a, b) |
+            Conditions::LessOrEqual(a, b) | Conditions::LessThan(a, b) => {
+                let ordered_column_ids = self.get_ordered_column_ids();
+                // normalize the comparison as "column <op> constant"
+                let (column_id, value, is_reversed) = match (
+                    self.find_indexed_column_id(a, &ordered_column_ids),
+                    self.find_indexed_column_id(b, &ordered_column_ids),
+                ) {
+                    (Some(column_id), _) if self.is_constant(b) => (column_id, b, false),
+                    (_, Some(column_id)) if self.is_constant(a) => (column_id, a, true),
+                    _ => return Ok(None)
+                };
+                let key = self.evaluate_constant(machine, value)?;
+                let is_greater = matches!(condition, Conditions::GreaterOrEqual(..) | Conditions::GreaterThan(..)) != is_reversed;
+                let bound = match condition {
+                    Conditions::GreaterOrEqual(..) | Conditions::LessOrEqual(..) => Included(key),
+                    _ => Excluded(key)
+                };
+                Ok(Some(match is_greater {
+                    true => IndexLookup::Range(column_id, bound, Unbounded),
+                    false => IndexLookup::Range(column_id, Unbounded, bound)
+                }))
+            }
+            _ => Ok(None)
+        }
+    }
+
+    /// Indicates whether the keys are equal in terms of sort order (e.g. 1 and 1.0)
+    fn is_same_key(a: &TypedValue, b: &TypedValue) -> bool {
+        Sorting::compare(std::slice::from_ref(a), std::slice::from_ref(b), &[false]) == Ordering::Equal
+    }
+
+    /// Evaluates a constant expression (e.g. "'ABC'")
+    fn evaluate_constant(&self, machine: &Machine, expr: &Expression) -> std::io::Result<TypedValue> {
+        match machine.evaluate(expr)? {
+            (_, ErrorValue(err)) => throw(err),
+            (_, value) => Ok(value)
+        }
+    }
+
+    /// Returns the index of the column referenced by the expression; provided it is one of the given columns.
+    fn find_indexed_column_id(&self, expr: &Expression, column_ids: &[usize]) -> Option<usize> {
+        match expr {
+            Variable(name) => column_ids.iter().copied().find(|id| self.columns[*id].get_name() == name),
+            _ => None
+        }
+    }
+
+    /// Determines whether the row's key duplicates that of another active row
+    /// within any of the unique indices; returning the violation if so.
+    /// As in SQL, keys containing nulls are never considered duplicates.
+    fn find_unique_violation(
+        &self,
+        id: usize,
+        row: &Option<Row>,
+    ) -> std::io::Result<Option<Errors>> {
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None)
+        };
+        for index in self.indices.iter().filter(|index| index.is_unique()) {
+            let column_ids = self.resolve_column_ids(index.get_indexed_column_names());
+            let key = column_ids.iter().map(|column_id| row[*column_id].to_owned()).collect::<Vec<_>>();
+            if key.is_empty() || key.iter().any(|value| matches!(value, Null | Undefined)) { continue; }
+            for other_id in self.find_row_ids_by_key(column_ids[0], &key[0])? {
+                if other_id == id { continue; }
+                if let Some(other) = self.read_one(other_id)? {
+                    if column_ids.iter().zip(key.iter()).all(|(column_id, value)| other[*column_id] == *value) {
+                        return Ok(Some(Errors::UniqueKeyViolation(
+                            index.get_indexed_column_names().join(", "),
+                            key.iter().map(|value| value.to_code()).collect::<Vec<_>>().join(", "),
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the indices of the columns having an index of the given kind
+    fn get_column_ids_of_kind(&self, kind: &IndexKinds) -> Vec<usize> {
+        let mut column_ids = self.indices.iter()
+            .filter(|index| index.get_kind() == kind)
+            .flat_map(|index| self.resolve_column_ids(index.get_indexed_column_names()))
+            .collect::<Vec<_>>();
+        column_ids.sort();
+        column_ids.dedup();
+        column_ids
+    }
+
+    /// Returns the indices of the columns having a hash index
+    pub fn get_indexed_column_ids(&self) -> Vec<usize> {
+        self.get_column_ids_of_kind(&IndexKinds::Hash)
+    }
+
+    /// Returns the indices of the columns having an ordered (B-Tree) index
+    pub fn get_ordered_column_ids(&self) -> Vec<usize> {
+        self.get_column_ids_of_kind(&IndexKinds::BTree)
+    }
+
+    /// Indicates whether the expression is independent of the table's columns
+    fn is_constant(&self, expr: &Expression) -> bool {
+        match expr {
+            Literal(..) => true,
+            Variable(name) => !self.columns.iter().any(|c| c.get_name() == name),
+            _ => false
+        }
+    }
+
+    /// Opens the hash index (e.g. "stocks.0") of the specified column
+    fn open_hash_index(&self, column_id: usize) -> std::io::Result<HashTableRowCollection> {
+        let keys_table = self.open_hash_keys_table(column_id)?;
+        Ok(HashTableRowCollection::open(column_id, Box::new(self.without_indices()), Box::new(keys_table)))
+    }
+
+    fn open_hash_keys_table(&self, column_id: usize) -> std::io::Result<Self> {
+        let keys_columns = HashTableRowCollection::create_hash_keys_columns(&self.columns[column_id]);
+        self.open_related_table(keys_columns, column_id.to_string().as_str())
+    }
+
+    /// Opens the ordered index (e.g. "stocks.btree2") of the specified column
+    fn open_ordered_index(&self, column_id: usize) -> std::io::Result<BTreeIndex> {
+        Ok(BTreeIndex::open(Box::new(self.open_btree_nodes_table(column_id)?)))
+    }
+
+    fn open_btree_nodes_table(&self, column_id: usize) -> std::io::Result<Self> {
+        let nodes_columns = BTreeIndex::create_btree_nodes_columns(&self.columns[column_id]);
+        self.open_related_table(nodes_columns, format!("btree{column_id}").as_str())
+    }
+
+    fn lock_scratch_tables() -> std::io::Result<MutexGuard<'static, BTreeMap<String, Vec<Weak<File>>>>> {
+        SCRATCH_TABLES.get_or_init(|| Mutex::new(BTreeMap::new())).lock()
+            .or_else(|err| fail(err.to_string()))
+    }
+
+    /// Opens the tables sharing this table's BLOB store; namely, the table itself, its history,
+    /// its preserved row versions and its indices. As the rows of scratch tables (e.g. the runs
+    /// of a sort) are not scanned, the BLOBs cannot be reclaimed while any of them are in use;
+    /// whereas partitioned tables are not supported.
+    fn open_blob_sharing_tables(&self) -> std::io::Result<Vec<Self>> {
+        if self.snapshot.is_some() {
+            return fail(format!("The BLOBs of '{}' cannot be reclaimed via a snapshot", self.path));
+        }
+        let has_scratch_tables = Self::lock_scratch_tables()?.get(&self.path)
+            .is_some_and(|files| files.iter().any(|file| file.strong_count() > 0));
+        if has_scratch_tables {
+            return fail(format!("The BLOBs of '{}' cannot be reclaimed while its scratch tables are in use", self.path));
+        }
+        let (base_path, partition_path) = Self::get_related_filename(&self.path, "part");
+        for entry in fs::read_dir(base_path)? {
+            if entry?.path().to_string_lossy().starts_with(partition_path.as_str()) {
+                return fail(format!("The BLOBs of the partitioned table '{}' cannot be reclaimed", self.path));
+            }
+        }
+        let mut tables = vec![self.without_indices(), RowHistory::open_store(self)?, RowVersions::open_store(self)?];
+        for column_id in self.get_indexed_column_ids() {
+            tables.push(self.open_hash_keys_table(column_id)?);
+        }
+        for column_id in self.get_ordered_column_ids() {
+            tables.push(self.open_btree_nodes_table(column_id)?);
+        }
+        Ok(tables)
+    }
+
+    /// Scans the rows (including the deleted rows) of the tables for references to BLOBs
+    /// => (BLOB offset|[(table index, position of the reference within the table file)])
+    fn find_blob_references(tables: &[Self]) -> std::io::Result<BTreeMap<u64, Vec<(usize, u64)>>> {
+        let mut references: BTreeMap<u64, Vec<(usize, u64)>> = BTreeMap::new();
+        for (index, table) in tables.iter().enumerate() {
+            for id in 0..table.len()? {
+                let row_offset = table.convert_rowid_to_offset(id);
+                let buffer = table.read_at(row_offset, table.record_size)?;
+                for (position, offset) in table.decode_blob_references(&buffer) {
+                    references.entry(offset).or_default().push((index, row_offset + position as u64));
+                }
+            }
+        }
+        Ok(references)
+    }
+
+    /// Decodes the references to BLOBs within an encoded row
+    /// => [(position of the reference within the row|BLOB offset)]
+    fn decode_blob_references(&self, buffer: &Vec<u8>) -> Vec<(usize, u64)> {
+        if buffer.len() < self.record_size { return Vec::new(); }
+        if RowMetadata::from_bytes(buffer, 0).is_encrypted {
+            let position = ENCRYPTED_ROW_LEN - 8;
+            return vec![(position, ByteCodeCompiler::decode_u8x8(buffer, position, u64::from_be_bytes))];
+        }
+        self.columns.iter()
+            .filter_map(|column| Self::decode_field_blob_reference(buffer, column.get_offset()))
+            .collect()
+    }
+
+    /// Decodes the reference to a BLOB (if any) of an encoded field (e.g. an overflowing value)
+    fn decode_field_blob_reference(buffer: &Vec<u8>, offset: usize) -> Option<(usize, u64)> {
+        match FieldMetadata::decode(buffer[offset]).is_external {
+            true => Some((offset + 1, ByteCodeCompiler::decode_u8x8(buffer, offset + 1, u64::from_be_bytes))),
+            false => None
+        }
+    }
+
+    /// Brings the indices up to date with a change of the row
+    /// from its previous state (before) to its current state (after).
+    fn reconcile_indices(
+        &self,
+        id: usize,
+        before: Option<Row>,
+        after: Option<Row>,
+    ) -> std::io::Result<()> {
+        for column_id in self.get_indexed_column_ids() {
+            let (old_key, new_key) =
+                (before.as_ref().map(|row| &row[column_id]), after.as_ref().map(|row| &row[column_id]));
+            if old_key == new_key { continue; }
+            let mut index = self.open_hash_index(column_id)?;
+            if let Some(key) = old_key {
+                if let ErrorValue(err) = index.unlink_row_key(id, key) { return throw(err); }
+            }
+            if let Some(key) = new_key {
+                if let ErrorValue(err) = index.link_row_key(id, key) { return throw(err); }
+            }
+        }
+        for column_id in self.get_ordered_column_ids() {
+            let (old_key, new_key) =
+                (before.as_ref().map(|row| &row[column_id]), after.as_ref().map(|row| &row[column_id]));
+            if old_key == new_key { continue; }
+            let mut index = self.open_ordered_index(column_id)?;
+            if let Some(key) = old_key { index.remove(key, id)?; }
+            if let Some(key) = new_key { index.insert(key, id)?; }
+        }
+        Ok(())
+    }
+
+    /// (Re)builds the hash index of the specified column from the table's active rows
+    pub fn rebuild_hash_index(&self, column_id: usize) -> std::io::Result<TypedValue> {
+        let keys_columns = HashTableRowCollection::create_hash_keys_columns(&self.columns[column_id]);
+        self.create_related_table(keys_columns, column_id.to_string().as_str())?;
+        let mut index = self.open_hash_index(column_id)?;
+        let mut linked = 0;
+        for id in self.get_indices()? {
+            if let Some(row) = self.read_one(id)? {
+                match index.link_row_key(id, &row[column_id]) {
+                    ErrorValue(err) => return throw(err),
+                    _ => linked += 1
+                }
+            }
+        }
+        Ok(Number(Numbers::RowsAffected(linked)))
+    }
+
+    /// (Re)builds the ordered index of the specified column from the table's active rows
+    pub fn rebuild_ordered_index(&self, column_id: usize) -> std::io::Result<TypedValue> {
+        let nodes_columns = BTreeIndex::create_btree_nodes_columns(&self.columns[column_id]);
+        self.create_related_table(nodes_columns, format!("btree{column_id}").as_str())?;
+        let mut index = self.open_ordered_index(column_id)?;
+        let mut inserted = 0;
+        for id in self.get_indices()? {
+            if let Some(row) = self.read_one(id)? {
+                index.insert(&row[column_id], id)?;
+                inserted += 1;
+            }
+        }
+        Ok(Number(Numbers::RowsAffected(inserted)))
+    }
+
+    /// Translates the column names into column indices; unknown names are ignored.
+    fn resolve_column_ids(&self, names: &[String]) -> Vec<usize> {
+        names.iter()
+            .filter_map(|name| self.columns.iter().position(|c| c.get_name() == name))
+            .collect()
+    }
+
+    fn with_append_only(self, is_append_only: bool) -> Self {
+        Self { is_append_only, ..self }
+    }
+
+    fn with_constraints(self, constraints: Vec<ConstraintConfig>) -> Self {
+        Self { constraints, ..self }
+    }
+
+    fn with_encrypted(self, is_encrypted: bool) -> Self {
+        Self { is_encrypted, ..self }
+    }
+
+    pub fn with_indices(self, indices: Vec<HashIndexConfig>) -> Self {
+        Self { indices, ..self }
+    }
+
+    fn without_indices(&self) -> Self {
+        Self { indices: Vec::new(), ..self.clone() }
+    }
+
+    /// Preserves the prior versions of the rows of this table on behalf of its snapshots
+    fn with_versions(self, versions: Arc<RowVersions>) -> Self {
+        Self { versions: Some(versions), ..self }
+    }
+
+    /// Journals the writes to this table (and its BLOB store) via the write-ahead log
+    fn with_wal(self, wal: WriteAheadLog) -> Self {
+        Self { blobs: self.blobs.with_wal(&wal), wal: Some(wal), ..self }
+    }
+
+    /// Journals the writes to a related table via the write-ahead log of this table (if any)
+    fn with_related_wal(self, wal: &Option<WriteAheadLog>, full_path: &str) -> Self {
+        match wal {
+            Some(wal) => self.with_wal(wal.for_file(full_path)),
+            None => self
+        }
+    }
+
+    /// Resizes the table file; journaling the change if a write-ahead log is present
+    fn set_len(&self, len: u64) -> std::io::Result<()> {
+        match &self.wal {
+            Some(wal) => wal.set_len(&self.file, len),
+            None => self.file.set_len(len)
+        }
+    }
+
+    /// Performs the write operation upon the row, then reconciles the hash indices with its outcome.
+    /// Should the outcome violate a unique index, the write is reverted.
+    fn write_with_indices<F>(&mut self, id: usize, write: F) -> TypedValue
+    where
+        F: FnOnce(&mut Self) -> TypedValue,
+    {
+        // the row and its index entries are journaled as a single batch
+        self.write_batched(|frc| frc.write_versioned(id..id + 1, |frc| frc.write_with_indices_unlogged(id, write)))
+    }
+
+    /// Performs the write operations as a single batch of the write-ahead log (if any)
+    fn write_batched<F>(&mut self, write: F) -> TypedValue
+    where
+        F: FnOnce(&mut Self) -> TypedValue,
+    {
+        match self.wal.clone() {
+            Some(wal) => wal.batch(|| write(self))
+                .unwrap_or_else(|err| ErrorValue(Errors::Exact(err.to_string()))),
+            None => write(self)
+        }
+    }
+
+    /// Performs the write operation upon the rows; preserving their prior versions
+    /// on behalf of any snapshots of this table in progress
+    fn write_versioned<F>(&mut self, ids: Range<usize>, write: F) -> TypedValue
+    where
+        F: FnOnce(&mut Self) -> TypedValue,
+    {
+        match self.versions.clone() {
+            Some(versions) => versions.preserve(self, ids, write),
+            None => write(self)
+        }
+    }
+
+    fn write_with_indices_unlogged<F>(&mut self, id: usize, write: F) -> TypedValue
+    where
+        F: FnOnce(&mut Self) -> TypedValue,
+    {
+        if self.indices.is_empty() { return write(self); }
+        let (len, (row0, rmd0)) = match self.len().and_then(|len| Ok((len, self.read_row(id)?))) {
+            Ok(result) => result,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        let outcome = write(self);
+        if let ErrorValue(..) = outcome { return outcome; }
+        let after = match self.read_one(id) {
+            Ok(after) => after,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        let violation = match self.find_unique_violation(id, &after) {
+            Ok(violation) => violation,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        if let Some(err) = violation {
+            // restore the row (or the table's length) to its previous state
+            let reverted = if id >= len {
+                self.set_len((len * self.record_size) as u64).map(|_| Number(Numbers::Ack))
+                    .unwrap_or_else(|err| ErrorValue(Errors::Exact(err.to_string())))
+            } else {
+                self.write_row(id, row0);
+                self.write_row_metadata(id, rmd0)
+            };
+            return if let ErrorValue(..) = reverted { reverted } else { ErrorValue(err) };
+        }
+        let before = if rmd0.is_allocated { Some(row0) } else { None };
+        match self.reconcile_indices(id, before, after) {
+            Ok(()) => outcome,
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    /// Resizes the table, unlinking any truncated rows from the indices
+    fn resize_unlogged(&mut self, new_size: usize) -> TypedValue {
+        // unlink any truncated rows from the indices
+        if !self.indices.is_empty() {
+            let len = match self.len() {
+                Ok(len) => len,
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            };
+            for id in new_size..len {
+                let outcome = self.read_one(id)
+                    .and_then(|before| self.reconcile_indices(id, before, None));
+                if let Err(err) = outcome { return ErrorValue(Errors::Exact(err.to_string())); }
+            }
+        }
+        let new_length = new_size as u64 * self.record_size as u64;
+        match self.set_len(new_length) {
+            Ok(..) => Number(Numbers::Ack),
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    /// Writes a field, bypassing the hash indices
+    fn write_field(
+        &mut self,
+        id: usize,
+        column_id: usize,
+        new_value: TypedValue,
+    ) -> TypedValue {
+        // the fields of encrypted rows are only written as part of the row
+        if self.is_encrypted_row(id) {
+            return self.rewrite_encrypted_row(id, |values| values[column_id] = new_value);
+        }
+        let column = &self.columns[column_id];
+        let offset = self.convert_rowid_to_offset(id) + column.get_offset() as u64;
+        let buffer = self.blobs.encode_field(column, &new_value, self.is_compressed(column))
+            .unwrap_or_else(|err| {
+                error!("Failed to write to {}@({id}, {column_id}): {} ({})", column.get_name(), err, new_value);
+                Self::empty_cell(column)
+            });
+        let replaced = self.read_at(offset, buffer.len())
+            .map(|replaced| Self::decode_field_blob_reference(&replaced, 0).into_iter().collect());
+        TypedValue::from_result(replaced.and_then(|replaced| self.write_releasing_blobs(offset, &buffer, replaced))
+            .map(Number))
+    }
+
+    /// Writes a field's metadata, bypassing the hash indices
+    fn write_field_metadata(
+        &mut self,
+        id: usize,
+        column_id: usize,
+        metadata: FieldMetadata,
+    ) -> TypedValue {
+        // encrypted rows do not retain the metadata of their fields; only their values
+        if self.is_encrypted_row(id) {
+            return self.rewrite_encrypted_row(id, |values| if !metadata.is_active {
+                values[column_id] = Null
+            });
+        }
+        let row_offset = self.convert_rowid_to_offset(id);
+        let column = &self.columns[column_id];
+        let column_offset = column.get_offset() as u64;
+        let cell_offset = row_offset + column_offset;
+        TypedValue::from_result(self.write_at(cell_offset, &[metadata.encode()].to_vec())
+            .map(|n| Number(n)))
+    }
+
+    /// Writes a row, bypassing the hash indices
+    fn write_row(&mut self, id: usize, row: Row) -> TypedValue {
+        if self.is_encrypted {
+            return TypedValue::from_result(self.write_encrypted_row(id, row));
+        }
+        let row_offset = self.convert_rowid_to_offset(id);
+        let encoded = self.encode_row(&row);
+        TypedValue::from_result(self.write_record(row_offset, &encoded).map(Number))
+    }
+
+    /// Encodes the row => (metadata|row ID|data); storing its large (or compressed) values
+    /// within the BLOB store.
+    fn encode_row(&self, row: &Row) -> Vec<u8> {
+        let capacity = self.get_record_size();
+        let mut encoded = Vec::with_capacity(capacity);
+        encoded.push(RowMetadata::new(true).encode());
+        encoded.extend(ByteCodeCompiler::encode_row_id(row.get_id()));
+        encoded.extend(self.columns.iter().zip(row.get_values().iter())
+            .flat_map(|(column, value)|
+                self.blobs.encode_field(column, value, self.is_compressed(column)).unwrap_or_else(|err| {
+                    error!("Failed to write row #{}: {err} ({})", row.get_id(), row.to_json_string(&self.columns));
+                    vec![]
+                })
+            ).collect::<Vec<_>>());
+        encoded.resize(capacity, 0u8);
+        encoded
+    }
+
+    /// Writes an encoded row at the offset; releasing the BLOBs of the row it replaced for reuse
+    fn write_record(&self, row_offset: u64, encoded: &Vec<u8>) -> std::io::Result<Numbers> {
+        let replaced = self.decode_blob_references(&self.read_at(row_offset, self.record_size)?);
+        self.write_releasing_blobs(row_offset, encoded, replaced)
+    }
+
+    /// Writes the bytes at the offset, then releases the BLOBs referenced by the bytes
+    /// they replaced => [(position of the reference|BLOB offset)]
+    fn write_releasing_blobs(
+        &self,
+        offset: u64,
+        bytes: &Vec<u8>,
+        replaced: Vec<(usize, u64)>,
+    ) -> std::io::Result<Numbers> {
+        let outcome = self.write_at(offset, bytes)?;
+        for (_, blob_offset) in replaced {
+            self.blobs.free(blob_offset)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Writes a row as an encrypted payload within the BLOB store => (metadata|row ID|payload offset)
+    fn write_encrypted_row(&mut self, id: usize, row: Row) -> std::io::Result<TypedValue> {
+        let capacity = self.get_record_size();
+        if capacity < ENCRYPTED_ROW_LEN {
+            return fail(format!("The rows of '{}' are too narrow to be encrypted", self.path));
+        }
+        let bytes = match bincode::serialize(&row.get_values()) {
+            Ok(bytes) => bytes,
+            Err(err) => return fail(err.to_string())
+        };
+        let key = self.blobs.insert_blob(Encryption::encrypt(&bytes)?)?;
+        let metadata = RowMetadata { is_blob: true, is_encrypted: true, ..RowMetadata::new(true) };
+        let mut encoded = Vec::with_capacity(capacity);
+        encoded.push(metadata.encode());
+        encoded.extend(ByteCodeCompiler::encode_row_id(row.get_id()));
+        encoded.extend(key.offset.to_be_bytes());
+        encoded.resize(capacity, 0u8);
+        self.write_record(self.convert_rowid_to_offset(id), &encoded).map(Number)
+    }
+
+    /// Reads the values of an encrypted row from the BLOB store
+    fn read_encrypted_values(&self, buffer: &Vec<u8>) -> std::io::Result<Vec<TypedValue>> {
+        let offset = ByteCodeCompiler::decode_u8x8(buffer, ENCRYPTED_ROW_LEN - 8, u64::from_be_bytes);
+        let (_, payload) = self.blobs.read_blob(offset)?;
+        match bincode::deserialize(&Encryption::decrypt(&payload)?) {
+            Ok(values) => Ok(values),
+            Err(err) => fail(err.to_string())
+        }
+    }
+
+    /// Indicates whether the row is stored encrypted
+    fn is_encrypted_row(&self, id: usize) -> bool {
+        self.is_encrypted ||
+            self.read_row_metadata(id).is_ok_and(|metadata| metadata.is_encrypted)
+    }
+
+    /// Modifies the values of a row, then rewrites it (e.g. encrypted); retaining its metadata
+    fn rewrite_encrypted_row<F>(&mut self, id: usize, modify: F) -> TypedValue
+    where
+        F: FnOnce(&mut Vec<TypedValue>),
+    {
+        let (row, metadata) = match self.read_row(id) {
+            Ok(result) => result,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        let mut values = row.get_values();
+        modify(&mut values);
+        match self.write_row(id, Row::new(id, values)) {
+            ErrorValue(err) => ErrorValue(err),
+            _ => self.write_row_metadata(id, metadata)
+        }
+    }
+
+    /// Writes a row's metadata, bypassing the hash indices; whereas the manner
+    /// in which the row is stored (e.g. encrypted) is retained.
+    fn write_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
+        let row_offset = self.convert_rowid_to_offset(id);
+        let stored = match self.read_row_metadata(id) {
+            Ok(stored) => stored,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        let metadata = RowMetadata { is_blob: stored.is_blob, is_encrypted: stored.is_encrypted, ..metadata };
+        TypedValue::from_result(self.write_at(row_offset, &[metadata.encode()].to_vec())
+            .map(|n| Number(n)))
+    }
+
+    /// Writes a row's metadata, bypassing the hash indices; whereupon the slot of
+    /// a deleted row is freed for reuse by subsequent appends (see [FreeSlots]).
+    fn write_row_metadata_freeing_slot(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
+        let is_freed = !metadata.is_allocated && self.is_reusing_slots()
+            && self.read_row_metadata(id).is_ok_and(|stored| stored.is_allocated);
+        match self.write_row_metadata(id, metadata) {
+            ErrorValue(err) => ErrorValue(err),
+            outcome if is_freed => match FreeSlots::release(self, id) {
+                Ok(()) => outcome,
+                Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+            },
+            outcome => outcome
+        }
+    }
+
+    /// Returns the slot of a deleted row to be filled by the next append, if any
+    /// => (position within the free slots|row ID)
+    fn find_free_slot(&self) -> std::io::Result<Option<(usize, usize)>> {
+        match self.is_reusing_slots() {
+            true => FreeSlots::find(self),
+            false => Ok(None)
+        }
+    }
+
+    /// Fills the slot of a deleted row with the row; whereby the row history records
+    /// the deletion of the slot's former row, followed by the insertion of the row.
+    fn fill_slot(&mut self, (position, id): (usize, usize), row: Row) -> TypedValue {
+        self.write_batched(|frc| {
+            if let Err(err) = RowHistory::append_deletion(frc, id) {
+                return ErrorValue(Errors::Exact(err.to_string()));
+            }
+            match frc.overwrite_row(id, row.with_row_id(id)) {
+                ErrorValue(err) => ErrorValue(err),
+                _ => match FreeSlots::take(frc, position) {
+                    Ok(()) => Number(RowId(id as u64)),
+                    Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+                }
+            }
+        })
+    }
+
+    /// Appends the rows to the end of the table as a single batch; where the rows are
+    /// verified against the constraints before any of them is written. The rows of tables
+    /// lacking indices are written via a single write, whereas the rows of indexed
+    /// (or encrypted) tables are written individually.
+    fn append_rows_to_end(&mut self, rows: Vec<Row>) -> TypedValue {
+        if rows.is_empty() { return Number(RowsAffected(0)); }
+        let len = match self.len() {
+            Ok(len) => len,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        let mut prepared = Vec::with_capacity(rows.len());
+        for (n, row) in rows.into_iter().enumerate() {
+            let row = match self.apply_defaults(row.with_row_id(len + n)) {
+                Ok(row) => row,
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            };
+            match self.find_constraint_violation(&row) {
+                Ok(None) => prepared.push(row),
+                Ok(Some(err)) => return ErrorValue(err),
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            }
+        }
+        let ids = len..len + prepared.len();
+        if !self.indices.is_empty() || self.is_encrypted {
+            return self.write_batched(|frc| {
+                for row in prepared {
+                    let id = row.get_id();
+                    if let ErrorValue(err) = frc.write_with_indices(id, |frc| frc.write_row(id, row)) {
+                        return ErrorValue(err);
+                    }
+                }
+                Number(RowsAffected(ids.len() as i64))
+            });
+        }
+        let encoded = prepared.iter().flat_map(|row| self.encode_row(row)).collect::<Vec<_>>();
+        let offset = self.convert_rowid_to_offset(len);
+        self.write_batched(|frc| frc.write_versioned(ids.to_owned(), |frc|
+            TypedValue::from_result(frc.write_at(offset, &encoded).map(|_| Number(RowsAffected(ids.len() as i64))))))
+    }
+
     /// convenience function to create, read or write a table file
     pub(crate) fn table_file_create(ns: &Namespace) -> std::io::Result<File> {
         fs::create_dir_all(ns.get_root_path())?;
@@ -134,6 +1274,14 @@ impl FileRowCollection {
     }
 }
 
+/// Represents a lookup which may be satisfied via an index
+enum IndexLookup {
+    /// a key of a hash-indexed column
+    Key(usize, TypedValue),
+    /// a range of keys of an ordered (B-Tree) indexed column
+    Range(usize, Bound<TypedValue>, Bound<TypedValue>),
+}
+
 impl Eq for FileRowCollection {}
 
 impl Ord for FileRowCollection {
@@ -161,23 +1309,63 @@ impl Debug for FileRowCollection {
 }
 
 impl RowCollection for FileRowCollection {
+    /// Appends the row; filling the slot of a deleted row, if any (see [FreeSlots])
+    fn append_row(&mut self, row: Row) -> TypedValue {
+        match self.find_free_slot() {
+            Ok(Some(slot)) => self.fill_slot(slot, row),
+            Ok(None) => match self.len() {
+                Ok(id) => match self.overwrite_row(id, row.with_row_id(id)) {
+                    ErrorValue(err) => ErrorValue(err),
+                    _ => Number(RowId(id as u64))
+                },
+                Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+            },
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    /// Appends the rows; filling the slots of deleted rows first (see [FreeSlots]),
+    /// whereas the remaining rows are appended to the end of the table as a single batch.
+    fn append_rows(&mut self, rows: Vec<Row>) -> TypedValue {
+        let mut affected_count = 0;
+        let mut remaining = Vec::new();
+        for row in rows {
+            let slot = match remaining.is_empty() {
+                true => self.find_free_slot(),
+                false => Ok(None)
+            };
+            match slot {
+                Ok(Some(slot)) => match self.fill_slot(slot, row) {
+                    ErrorValue(err) => return ErrorValue(err),
+                    _ => affected_count += 1
+                },
+                Ok(None) => remaining.push(row),
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            }
+        }
+        affected_count += remaining.len() as i64;
+        match self.append_rows_to_end(remaining) {
+            ErrorValue(err) => ErrorValue(err),
+            _ => Number(RowsAffected(affected_count))
+        }
+    }
+
     fn create_related_structure(
         &self,
         columns: Vec<Column>,
         extension: &str,
     ) -> std::io::Result<Box<dyn RowCollection>> {
-        // determine the base_path and full_path
-        let path = self.path.as_str();
-        let (base_path, full_path) =
-            Self::get_related_filename(path, extension);
-
-        // ensure the parent (base) directory exists
-        fs::create_dir_all(base_path)?;
+        Ok(Box::new(self.create_related_table(columns, extension)?))
+    }
 
-        // create and/or open the file
-        let file = OpenOptions::new().truncate(true).create(true).read(true).write(true).open(full_path)?;
-        let frc = Self::new(columns, Arc::new(file), path);
-        Ok(Box::new(frc))
+    /// Returns a table that describes the structure of the table; where tables having
+    /// compressed columns also report the raw and compressed sizes of those columns.
+    fn describe(&self) -> TypedValue {
+        let described = ModelRowCollection::new(self.columns.to_owned()).describe();
+        if !self.constraints.iter().any(|c| c.is_compressed()) {
+            return described;
+        }
+        TypedValue::from_result(self.describe_compression(&described))
     }
 
     fn get_columns(&self) -> &Vec<Column> { &self.columns }
@@ -189,7 +1377,10 @@ impl RowCollection for FileRowCollection {
     }
 
     fn len(&self) -> std::io::Result<usize> {
-        Ok((self.file.metadata()?.len() as usize) / self.record_size)
+        match &self.snapshot {
+            Some(snapshot) => Ok(snapshot.len()),
+            None => Ok((self.file.metadata()?.len() as usize) / self.record_size)
+        }
     }
 
     fn overwrite_field(
@@ -198,14 +1389,26 @@ impl RowCollection for FileRowCollection {
         column_id: usize,
         new_value: TypedValue,
     ) -> TypedValue {
-        let column = &self.columns[column_id];
-        let offset = self.convert_rowid_to_offset(id) + column.get_offset() as u64;
-        let buffer = self.blobs.encode_field(&column, &new_value)
-            .unwrap_or_else(|err| {
-                error!("Failed to write to {}@({id}, {column_id}): {} ({})", column.get_name(), err, new_value);
-                Self::empty_cell(column)
+        let new_value = match self.apply_default(&self.columns[column_id], new_value) {
+            Ok(new_value) => new_value,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        if self.has_constraints() {
+            let violation = self.read_one(id).and_then(|row| match row {
+                Some(row) => {
+                    let mut values = row.get_values();
+                    values[column_id] = new_value.to_owned();
+                    self.find_constraint_violation(&Row::new(id, values))
+                }
+                None => Ok(None)
             });
-        TypedValue::from_result(self.write_at(offset, &buffer).map(|n| Number(n)))
+            match violation {
+                Ok(None) => {}
+                Ok(Some(err)) => return ErrorValue(err),
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            }
+        }
+        self.write_with_indices(id, |frc| frc.write_field(id, column_id, new_value))
     }
 
     fn overwrite_field_metadata(
@@ -214,49 +1417,46 @@ impl RowCollection for FileRowCollection {
         column_id: usize,
         metadata: FieldMetadata,
     ) -> TypedValue {
-        let row_offset = self.convert_rowid_to_offset(id);
-        let column = &self.columns[column_id];
-        let column_offset = column.get_offset() as u64;
-        let cell_offset = row_offset + column_offset;
-        TypedValue::from_result(self.write_at(cell_offset, &[metadata.encode()].to_vec())
-            .map(|n| Number(n)))
+        self.write_with_indices(id, |frc| frc.write_field_metadata(id, column_id, metadata))
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
-        let row_offset = self.convert_rowid_to_offset(id);
-        let capacity = self.get_record_size();
-        let blobs = &self.blobs;
-
-        // encode the row => (metadata|row ID|data)
-        let mut encoded = Vec::with_capacity(capacity);
-        encoded.push(RowMetadata::new(true).encode());
-        encoded.extend(ByteCodeCompiler::encode_row_id(row.get_id()));
-        encoded.extend(self.columns.iter().zip(row.get_values().iter())
-            .flat_map(|(column, value)|
-                blobs.encode_field(column, value).unwrap_or_else(|err| {
-                    error!("Failed to write row #{id}: {err} ({})", row.to_json_string(&self.columns));
-                    vec![]
-                })
-            ).collect::<Vec<_>>());
-        encoded.resize(capacity, 0u8);
-
-        // write the row
-        TypedValue::from_result(self.write_at(row_offset, &encoded)
-            .map(|n| Number(n)))
+        let row = match self.apply_defaults(row) {
+            Ok(row) => row,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        match self.find_constraint_violation(&row) {
+            Ok(None) => self.write_with_indices(id, |frc| frc.write_row(id, row)),
+            Ok(Some(err)) => ErrorValue(err),
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
     }
 
     fn overwrite_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
-        let row_offset = self.convert_rowid_to_offset(id);
-        TypedValue::from_result(self.write_at(row_offset, &[metadata.encode()].to_vec())
-            .map(|n| Number(n)))
+        // deleting a referenced row affects the rows referencing it
+        if !metadata.is_allocated && self.has_referrers() {
+            let (table, constraints) = (Dataframe::Disk(self.clone()), self.constraints.to_owned());
+            return ForeignKeys::delete_row(&table, &constraints, id, ||
+                self.write_with_indices(id, |frc| frc.write_row_metadata_freeing_slot(id, metadata)));
+        }
+        self.write_with_indices(id, |frc| frc.write_row_metadata_freeing_slot(id, metadata))
     }
 
     fn read_field(&self, id: usize, column_id: usize) -> TypedValue {
+        // the fields of encrypted rows are only readable as part of the row
+        match self.read_row_metadata(id) {
+            Ok(metadata) if metadata.is_encrypted => return match self.read_row(id) {
+                Ok((row, _)) => row.get(column_id),
+                Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+            },
+            Ok(..) => {}
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        }
         let column = &self.columns[column_id];
         let row_offset = self.convert_rowid_to_offset(id);
         let cell_offset = row_offset + column.get_offset() as u64;
         match self.read_at(cell_offset, column.get_fixed_size()) {
-            Ok(buffer) => column.get_data_type().decode_field_value(&buffer, 0),
+            Ok(buffer) => self.blobs.decode_field(column, &buffer, 0),
             Err(err) => ErrorValue(Errors::Exact(err.to_string()))
         }
     }
@@ -266,6 +1466,11 @@ impl RowCollection for FileRowCollection {
         id: usize,
         column_id: usize,
     ) -> std::io::Result<FieldMetadata> {
+        // the fields of encrypted rows are active unless null
+        if self.read_row_metadata(id)?.is_encrypted {
+            let (row, _) = self.read_row(id)?;
+            return Ok(FieldMetadata::new(row.get(column_id) != Null));
+        }
         let column = &self.columns[column_id];
         let row_offset = self.convert_rowid_to_offset(id);
         let cell_offset = row_offset + column.get_offset() as u64;
@@ -285,18 +1490,12 @@ impl RowCollection for FileRowCollection {
         }
         let rmd = RowMetadata::from_bytes(&buffer, 0);
         let id = ByteCodeCompiler::decode_row_id(&buffer, 1);
-        let values = columns.iter().map(|column| {
-            let fmd = FieldMetadata::decode(buffer[column.get_offset()]);
-            if fmd.is_external {
-                let offset = NumberType(U64Kind).decode_field_value(&buffer, column.get_offset()).to_u64();
-                let (_, value) = self.blobs.read(offset)
-                    .unwrap_or_else(|err| (BLOBCellMetadata::new(0, 0, 0), ErrorValue(Errors::Exact(err.to_string()))));
-                value
-            } else {
-                let data_type = column.get_data_type();
-                data_type.decode_field_value(&buffer, column.get_offset())
-            }
-        }).collect();
+        if rmd.is_encrypted {
+            return Ok((Row::new(id, self.read_encrypted_values(&buffer)?), rmd));
+        }
+        let values = columns.iter()
+            .map(|column| self.blobs.decode_field(column, &buffer, column.get_offset()))
+            .collect();
         Ok((Row::new(id, values), rmd))
     }
 
@@ -307,25 +1506,35 @@ impl RowCollection for FileRowCollection {
     }
 
     fn resize(&mut self, new_size: usize) -> TypedValue {
-        let new_length = new_size as u64 * self.record_size as u64;
-        match self.file.set_len(new_length) {
-            Ok(..) => Number(Numbers::Ack),
-            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        let truncated = new_size..usize::MAX;
+        match self.wal.clone() {
+            Some(wal) => wal.batch(|| self.write_versioned(truncated, |frc| frc.resize_unlogged(new_size)))
+                .unwrap_or_else(|err| ErrorValue(Errors::Exact(err.to_string()))),
+            None => self.write_versioned(truncated, |frc| frc.resize_unlogged(new_size))
         }
     }
 }
 
 impl RowEncoding for FileRowCollection {
     fn read_at(&self, offset: u64, count: usize) -> std::io::Result<Vec<u8>> {
-        let mut buffer: Vec<u8> = vec![0u8; count];
-        match self.file.read_at(&mut buffer, offset) {
-            Ok(_n_bytes) => Ok(buffer),
-            Err(err) => throw(Errors::Exact(err.to_string()))
+        let read_current = || {
+            let mut buffer: Vec<u8> = vec![0u8; count];
+            match self.file.read_at(&mut buffer, offset) {
+                Ok(_n_bytes) => Ok(buffer),
+                Err(err) => throw(Errors::Exact(err.to_string()))
+            }
+        };
+        match &self.snapshot {
+            Some(snapshot) => snapshot.read_at(offset, count, read_current),
+            None => read_current()
         }
     }
 
     fn write_at(&self, offset: u64, bytes: &Vec<u8>) -> std::io::Result<Numbers> {
-        let _n_bytes = self.file.write_at(bytes.as_slice(), offset)?;
+        match &self.wal {
+            Some(wal) => wal.write_at(&self.file, bytes.as_slice(), offset)?,
+            None => { let _n_bytes = self.file.write_at(bytes.as_slice(), offset)?; }
+        }
         Ok(Numbers::RowsAffected(1))
     }
 }
@@ -362,13 +1571,73 @@ impl<'de> Deserialize<'de> for FileRowCollection {
 
 #[cfg(test)]
 mod tests {
+    use crate::data_types::DataType::StringType;
+    use crate::encryption::Encryption;
+    use crate::expression::Conditions::{Between, Equal, GreaterThan};
+    use crate::expression::Expression::{Literal, Variable};
     use crate::file_row_collection::FileRowCollection;
+    use crate::machine::Machine;
     use crate::namespaces::Namespace;
-    use crate::numbers::Numbers::F64Value;
+    use crate::numbers::Numbers::{F64Value, RowsAffected, U64Value};
+    use crate::object_config::{ConstraintConfig, HashIndexConfig, IndexKinds, ObjectConfig};
+    use crate::parameter::Parameter;
     use crate::row_collection::RowCollection;
+    use crate::row_history::RowHistory;
     use crate::structures::Row;
-    use crate::testdata::make_quote_parameters;
-    use crate::typed_values::TypedValue::{Number, StringValue};
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use crate::typed_values::TypedValue::{Boolean, Null, Number, StringValue};
+
+    #[test]
+    fn test_blob_space_reclamation() {
+        let ns = Namespace::parse("frc.blobs.stocks").unwrap();
+        let _ = std::fs::remove_file(format!("{}.blob", ns.get_table_file_path()));
+        let mut frc = create_file_row_collection("frc.blobs.stocks");
+        let quote = |id: usize, n: usize| make_quote(id, &format!("LONG_SYMBOL_{n:04}"), "NYSE", n as f64);
+        frc.append_rows((0..4).map(|id| quote(id, id)).collect());
+        let cells = frc.blobs.read_headers().unwrap().len();
+
+        // the replaced value's cell is reused (e.g. by the history of the row)
+        frc.overwrite_row(0, quote(0, 100));
+        assert_eq!(frc.blobs.read_headers().unwrap().len(), cells + 1);
+        assert!(frc.find_orphaned_blobs().unwrap().is_empty());
+
+        // the cells of truncated rows are orphaned, until reclaimed
+        frc.delete_row(1);
+        frc.resize(3);
+        assert_eq!(frc.find_orphaned_blobs().unwrap().len(), 1);
+        assert!(frc.reclaim_blobs().unwrap() > 0);
+        assert!(frc.find_orphaned_blobs().unwrap().is_empty());
+
+        // compaction relocates the cells, whereas the rows and their history are unaffected
+        let history = RowHistory::read_row_history(&frc, 0).unwrap();
+        assert!(frc.compact_blobs().unwrap() > 0);
+        assert!(frc.blobs.read_headers().unwrap().iter().all(|header| header.used > 0));
+        assert_eq!(frc.read_active_rows().unwrap(), vec![quote(0, 100), quote(2, 2)]);
+        assert_eq!(RowHistory::read_row_history(&frc, 0).unwrap(), history);
+        frc.undelete_row(1);
+        assert_eq!(frc.read_one(1).unwrap(), Some(quote(1, 1)));
+    }
+
+    #[test]
+    fn test_blob_reclamation_with_scratch_tables() {
+        let ns = Namespace::parse("frc.blobs_scratch.stocks").unwrap();
+        let _ = std::fs::remove_file(format!("{}.blob", ns.get_table_file_path()));
+        let mut frc = create_file_row_collection("frc.blobs_scratch.stocks");
+        let quote = |id: usize, n: usize| make_quote(id, &format!("LONG_SYMBOL_{n:04}"), "NYSE", n as f64);
+        frc.append_rows((0..4).map(|id| quote(id, id)).collect());
+
+        // the BLOBs cannot be reclaimed while a scratch table (which may reference them) is in use
+        let mut scratch = frc.create_scratch_table(frc.get_columns().to_owned()).unwrap();
+        scratch.append_row(quote(0, 3));
+        assert_eq!(frc.compact_blobs().unwrap_err().to_string(), format!(
+            "The BLOBs of '{}' cannot be reclaimed while its scratch tables are in use", ns.get_table_file_path()));
+        assert!(frc.reclaim_blobs().is_err());
+
+        // whereas once it has been dropped, they can
+        drop(scratch);
+        assert!(frc.compact_blobs().is_ok());
+        assert_eq!(frc.read_active_rows().unwrap(), (0..4).map(|id| quote(id, id)).collect::<Vec<_>>());
+    }
 
     #[test]
     fn test_column_overflow() {
@@ -383,6 +1652,176 @@ mod tests {
         assert_eq!(row0, row1)
     }
 
+    #[test]
+    fn test_compressed_columns() {
+        // create a table having a compressed column
+        let ns = Namespace::parse("frc.compressed.articles").unwrap();
+        FileRowCollection::create_table(&ns, &vec![
+            Parameter::new("title", StringType(16)),
+            Parameter::new("body", StringType(256)),
+        ]).unwrap();
+        let config = ObjectConfig::load(&ns).unwrap();
+        config.with_constraints(vec![ConstraintConfig::new("body".into(), false, None).with_compressed(true)])
+            .save(&ns).unwrap();
+        let mut frc = FileRowCollection::open(&ns).unwrap();
+
+        // write a repetitive (inline), a large (external) and a small (raw) body
+        let numbers = (0..300).map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+        let rows = vec![
+            Row::new(0, vec![StringValue("repeated".into()), StringValue("lorem ipsum ".repeat(20))]),
+            Row::new(1, vec![StringValue("numbers".into()), StringValue(numbers)]),
+            Row::new(2, vec![StringValue("small".into()), StringValue("hi".into())]),
+        ];
+        frc.append_rows(rows.clone());
+
+        // the values are transparently decompressed
+        assert_eq!(frc.read_active_rows().unwrap(), rows);
+        for row in rows.iter() {
+            assert_eq!(frc.read_field(row.get_id(), 1), row.get(1));
+        }
+        let compressed = (0..3).map(|id| frc.read_field_metadata(id, 1).unwrap())
+            .map(|fmd| (fmd.is_compressed, fmd.is_external))
+            .collect::<Vec<_>>();
+        assert_eq!(compressed, vec![(true, false), (true, true), (false, false)]);
+
+        // the description reports the raw and compressed sizes
+        let described = frc.describe().to_table().unwrap().read_active_rows().unwrap();
+        let title = described[0].get_values();
+        assert_eq!(title[4..].to_vec(), vec![Boolean(false), Null, Null]);
+        let body = described[1].get_values();
+        assert_eq!(body[4], Boolean(true));
+        match (&body[5], &body[6]) {
+            (Number(U64Value(raw_size)), Number(U64Value(compressed_size))) =>
+                assert!(compressed_size < raw_size),
+            other => panic!("Unexpected sizes {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_encrypted_rows() {
+        // write a plain row, then encrypt the table and write another row
+        let ns = Namespace::parse("frc.encrypted.stocks").unwrap();
+        let mut frc = create_file_row_collection("frc.encrypted.stocks");
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        Encryption::install_key(&[7; 32]);
+        ObjectConfig::load(&ns).unwrap().with_encrypted(true).save(&ns).unwrap();
+        let mut frc = FileRowCollection::open(&ns).unwrap();
+        frc.append_row(make_quote(1, "SECRET", "NYSE", 0.2456));
+
+        // the plain and encrypted rows coexist
+        let encrypted = (0..2).map(|id| frc.read_row_metadata(id).unwrap().is_encrypted).collect::<Vec<_>>();
+        assert_eq!(encrypted, vec![false, true]);
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "SECRET", "NYSE", 0.2456),
+        ]);
+
+        // neither the table nor its blobs contain the encrypted values
+        let path = ns.get_table_file_path();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.windows(3).any(|window| window == b"ABC"));
+        assert!(!bytes.windows(6).any(|window| window == b"SECRET"));
+        let blob_bytes = std::fs::read(format!("{path}.blob")).unwrap();
+        assert!(!blob_bytes.windows(6).any(|window| window == b"SECRET"));
+
+        // the fields of encrypted rows may be read and written
+        assert_eq!(frc.read_field(1, 0), StringValue("SECRET".into()));
+        frc.overwrite_field(1, 2, Number(F64Value(0.25)));
+        assert_eq!(frc.read_one(1).unwrap(), Some(make_quote(1, "SECRET", "NYSE", 0.25)));
+
+        // deleting and restoring an encrypted row retains its encryption
+        frc.delete_row(1);
+        assert_eq!(frc.read_active_rows().unwrap(), vec![make_quote(0, "ABC", "AMEX", 11.77)]);
+        frc.undelete_row(1);
+        assert!(frc.read_row_metadata(1).unwrap().is_encrypted);
+        assert_eq!(frc.read_one(1).unwrap(), Some(make_quote(1, "SECRET", "NYSE", 0.25)));
+    }
+
+    #[test]
+    fn test_hash_index_maintenance() {
+        // create a table having a hash index on exchange
+        let ns = Namespace::parse("frc.hash_index.stocks").unwrap();
+        create_file_row_collection("frc.hash_index.stocks");
+        let config = ObjectConfig::load(&ns).unwrap();
+        config.with_indices(vec![HashIndexConfig::new(vec!["exchange".into()], false)]).save(&ns).unwrap();
+        let mut frc = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(frc.get_indexed_column_ids(), vec![1]);
+        assert_eq!(frc.rebuild_hash_index(1).unwrap(), Number(RowsAffected(0)));
+
+        // append, overwrite and delete some rows
+        frc.append_rows(vec![
+            make_quote(0, "ABC", "NYSE", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+        ]);
+        frc.overwrite_row(1, make_quote(1, "UNO", "NYSE", 0.2456));
+        frc.delete_row(2);
+        let condition = Some(Equal(
+            Box::new(Variable("exchange".into())),
+            Box::new(Literal(StringValue("NYSE".into()))),
+        ));
+        let machine = Machine::empty();
+        assert_eq!(frc.find_row_ids_where(&machine, &condition).unwrap(), Some(vec![0, 1]));
+
+        // undelete and truncate
+        frc.undelete_row(2);
+        assert_eq!(frc.find_row_ids_where(&machine, &condition).unwrap(), Some(vec![0, 1, 2]));
+        frc.resize(2);
+        assert_eq!(frc.find_row_ids_where(&machine, &condition).unwrap(), Some(vec![0, 1]));
+
+        // non-indexed columns do not use the index
+        let condition = Some(Equal(
+            Box::new(Variable("symbol".into())),
+            Box::new(Literal(StringValue("ABC".into()))),
+        ));
+        assert_eq!(frc.find_row_ids_where(&machine, &condition).unwrap(), None);
+    }
+
+    #[test]
+    fn test_ordered_index_maintenance() {
+        // create a table having an ordered index on last_sale
+        let ns = Namespace::parse("frc.ordered_index.stocks").unwrap();
+        create_file_row_collection("frc.ordered_index.stocks");
+        let config = ObjectConfig::load(&ns).unwrap();
+        config.with_indices(vec![
+            HashIndexConfig::new(vec!["last_sale".into()], false).with_kind(IndexKinds::BTree)
+        ]).save(&ns).unwrap();
+        let mut frc = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(frc.get_ordered_column_ids(), vec![2]);
+        assert_eq!(frc.get_indexed_column_ids(), Vec::<usize>::new());
+
+        // append, overwrite and delete some rows
+        frc.append_rows(vec![
+            make_quote(0, "ABC", "NYSE", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+        ]);
+        frc.overwrite_row(1, make_quote(1, "UNO", "OTC", 11.77));
+        frc.delete_row(2);
+        assert_eq!(frc.find_ordered_row_ids(2, false).unwrap(), vec![3, 0, 1]);
+        assert_eq!(frc.find_ordered_row_ids(2, true).unwrap(), vec![0, 1, 3]);
+
+        // range conditions use the index
+        let machine = Machine::empty();
+        let condition = Some(GreaterThan(
+            Box::new(Literal(Number(F64Value(12.0)))),
+            Box::new(Variable("last_sale".into())),
+        ));
+        assert_eq!(frc.find_row_ids_where(&machine, &condition).unwrap(), Some(vec![0, 1, 3]));
+        let condition = Some(Between(
+            Box::new(Variable("last_sale".into())),
+            Box::new(Literal(Number(F64Value(1.0)))),
+            Box::new(Literal(Number(F64Value(30.0)))),
+        ));
+        assert_eq!(frc.find_row_ids_where(&machine, &condition).unwrap(), Some(vec![0, 1]));
+
+        // rebuilding the index yields the same order
+        assert_eq!(frc.rebuild_ordered_index(2).unwrap(), Number(RowsAffected(3)));
+        assert_eq!(frc.find_ordered_row_ids(2, false).unwrap(), vec![3, 0, 1]);
+    }
+
     fn create_file_row_collection(path: &str) -> FileRowCollection {
         FileRowCollection::create_table(
             &Namespace::parse(path).unwrap(),
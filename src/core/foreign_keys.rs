@@ -0,0 +1,309 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// foreign keys module
+////////////////////////////////////////////////////////////////////
+
+use crate::dataframe::Dataframe;
+use crate::errors::Errors::{Exact, ForeignKeyViolation, InvalidNamespace, ReferencedKeyViolation};
+use crate::errors::{throw, Errors};
+use crate::expression::Conditions;
+use crate::expression::Expression::{Literal, Variable};
+use crate::machine::Machine;
+use crate::namespaces::Namespace;
+use crate::object_config::{ConstraintConfig, DeleteActions, ObjectConfig, ReferenceConfig};
+use crate::replication::Replication;
+use crate::row_collection::RowCollection;
+use crate::structures::Row;
+use crate::transactions::Transaction;
+use crate::triggers::Triggers;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{ErrorValue, Null, Undefined};
+use std::path::Path;
+
+/// the rows of a table referencing a key => (referrer|referencing column ID|row IDs|key)
+type ReferencingRows<'a> = (&'a ReferenceConfig, usize, Vec<usize>, TypedValue);
+
+/// Foreign Keys; the columns of a table may reference the column of another table
+/// (e.g. "symbol: String(8) references ns('a.b.stocks')(symbol) on delete cascade").
+/// Each reference is registered (as a referrer) with the constraints of the referenced column,
+/// so that deleting a referenced row may be restricted, cascaded or nulled.
+pub struct ForeignKeys;
+
+impl ForeignKeys {
+    /// Performs the deletion of the row; the rows of other tables referencing the keys of the
+    /// row are verified beforehand ("on delete restrict"), and deleted ("on delete cascade")
+    /// or nulled ("on delete set null") afterward. Keys still held by another row are unaffected.
+    pub fn delete_row<F>(
+        table: &Dataframe,
+        constraints: &[ConstraintConfig],
+        id: usize,
+        delete: F,
+    ) -> TypedValue
+    where
+        F: FnOnce() -> TypedValue,
+    {
+        let row = match table.read_one(id) {
+            Ok(Some(row)) => row,
+            Ok(None) => return delete(),
+            Err(err) => return ErrorValue(Exact(err.to_string()))
+        };
+        match Self::find_restrict_violation(table, constraints, &row) {
+            Ok(None) => {}
+            Ok(Some(err)) => return ErrorValue(err),
+            Err(err) => return ErrorValue(Exact(err.to_string()))
+        }
+        let outcome = delete();
+        if let ErrorValue(..) = outcome { return outcome; }
+        match Self::apply_delete_actions(table, constraints, &row) {
+            Ok(None) => outcome,
+            Ok(Some(err)) => ErrorValue(err),
+            Err(err) => ErrorValue(Exact(err.to_string()))
+        }
+    }
+
+    /// Determines whether the referenced table contains the key of the referencing column
+    /// (e.g. "references ns('a.b.stocks')(symbol)"); returning the violation if not.
+    pub fn find_violation(reference: &ReferenceConfig, key: &TypedValue) -> std::io::Result<Option<Errors>> {
+        let table = Dataframe::open(&Namespace::parse(reference.get_table())?)?;
+        match Self::find_row_ids(&table, reference.get_column_name(), key)?.is_empty() {
+            true => Ok(Some(ForeignKeyViolation(Self::describe(reference), key.to_code()))),
+            false => Ok(None)
+        }
+    }
+
+    /// Registers the references of the table's columns with the referenced tables
+    pub fn link(ns: &Namespace, constraints: &[ConstraintConfig]) -> std::io::Result<()> {
+        for constraint in constraints {
+            let reference = match constraint.get_reference() {
+                Some(reference) => reference,
+                None => continue
+            };
+            let other_ns = Namespace::parse(reference.get_table())?;
+            let config = match Path::new(&other_ns.get_config_file_path()).exists() {
+                true => ObjectConfig::load(&other_ns)?,
+                false => return throw(InvalidNamespace(reference.get_table().to_string()))
+            };
+            let column_name = reference.get_column_name();
+            if !config.get_columns().iter().any(|p| p.get_name() == column_name) {
+                return throw(Exact(format!("Column {column_name} was not found in {}", other_ns)));
+            }
+
+            // replace any prior registration of the referencing column
+            let referrer = ReferenceConfig::new(
+                ns.get_full_name(),
+                constraint.get_column_name().to_string(),
+                reference.get_on_delete().to_owned(),
+            );
+            let mut other_constraints = config.get_constraints();
+            let index = match other_constraints.iter().position(|c| c.get_column_name() == column_name) {
+                Some(index) => index,
+                None => {
+                    other_constraints.push(ConstraintConfig::new(column_name.to_string(), false, None));
+                    other_constraints.len() - 1
+                }
+            };
+            let mut referrers = other_constraints[index].get_referrers().iter()
+                .filter(|r| r.get_table() != referrer.get_table() || r.get_column_name() != referrer.get_column_name())
+                .cloned()
+                .collect::<Vec<_>>();
+            referrers.push(referrer);
+            other_constraints[index] = other_constraints[index].to_owned().with_referrers(referrers);
+            ObjectConfig::load(&other_ns)?.with_constraints(other_constraints).save(&other_ns)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes ("on delete cascade") or nulls ("on delete set null") the rows referencing
+    /// the keys of the deleted row; like a mutation of the referencing table, the writes
+    /// join the active transaction (if any) and fire the referencing table's triggers.
+    fn apply_delete_actions(
+        table: &Dataframe,
+        constraints: &[ConstraintConfig],
+        row: &Row,
+    ) -> std::io::Result<Option<Errors>> {
+        let transaction = Transaction::get_active();
+        for (referrer, column_id, row_ids, _) in Self::find_referencing_rows(table, constraints, row)? {
+            if let DeleteActions::Restrict = referrer.get_on_delete() { continue; }
+            let other_ns = Namespace::parse(referrer.get_table())?;
+            Replication::ensure_writable(&other_ns)?;
+            match &transaction {
+                Some(tx) => tx.enlist(&other_ns)?,
+                None => Transaction::ensure_not_enlisted(&other_ns)?
+            }
+            let (mut other, triggers) = (Dataframe::open_writable(&other_ns)?, Triggers::load(&other_ns)?);
+            for other_id in row_ids {
+                let outcome = match (referrer.get_on_delete(), other.read_one(other_id)?) {
+                    (DeleteActions::Cascade, _) => triggers.delete_row(&mut other, other_id)?,
+                    (DeleteActions::SetNull, Some(other_row)) => {
+                        let mut values = other_row.get_values();
+                        values[column_id] = Null;
+                        triggers.overwrite_row(&mut other, other_id, other_row.with_values(values))?
+                    }
+                    _ => continue
+                };
+                if let ErrorValue(err) = outcome { return Ok(Some(err)); }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the referenced column (e.g. "a.b.stocks(symbol)")
+    fn describe(reference: &ReferenceConfig) -> String {
+        format!("{}({})", reference.get_table(), reference.get_column_name())
+    }
+
+    /// Returns the rows of other tables referencing the keys of the row
+    fn find_referencing_rows<'a>(
+        table: &Dataframe,
+        constraints: &'a [ConstraintConfig],
+        row: &Row,
+    ) -> std::io::Result<Vec<ReferencingRows<'a>>> {
+        let mut found = Vec::new();
+        for constraint in constraints.iter().filter(|c| !c.get_referrers().is_empty()) {
+            let name = constraint.get_column_name();
+            let key = match table.get_columns().iter().position(|c| c.get_name() == name) {
+                Some(column_id) => row.get(column_id),
+                None => continue
+            };
+            if matches!(key, Null | Undefined) { continue; }
+
+            // the key remains referenceable while another row holds it
+            if Self::find_row_ids(table, name, &key)?.iter().any(|other_id| *other_id != row.get_id()) {
+                continue;
+            }
+            for referrer in constraint.get_referrers() {
+                // referencing tables which have since been dropped are ignored
+                let other_ns = Namespace::parse(referrer.get_table())?;
+                if !Path::new(&other_ns.get_config_file_path()).exists() { continue; }
+                let other = Dataframe::open(&other_ns)?;
+                let column_id = match other.get_columns().iter().position(|c| c.get_name() == referrer.get_column_name()) {
+                    Some(column_id) => column_id,
+                    None => continue
+                };
+                let row_ids = Self::find_row_ids(&other, referrer.get_column_name(), &key)?;
+                if !row_ids.is_empty() {
+                    found.push((referrer, column_id, row_ids, key.to_owned()));
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// Determines whether rows of other tables which restrict deletion ("on delete restrict")
+    /// reference the keys of the row; returning the violation if so.
+    fn find_restrict_violation(
+        table: &Dataframe,
+        constraints: &[ConstraintConfig],
+        row: &Row,
+    ) -> std::io::Result<Option<Errors>> {
+        for (referrer, _, _, key) in Self::find_referencing_rows(table, constraints, row)? {
+            if let DeleteActions::Restrict = referrer.get_on_delete() {
+                return Ok(Some(ReferencedKeyViolation(Self::describe(referrer), key.to_code())));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the IDs of the active rows whose column holds the key;
+    /// via an index of the column when available, otherwise by scanning the table.
+    fn find_row_ids(table: &Dataframe, column_name: &str, key: &TypedValue) -> std::io::Result<Vec<usize>> {
+        let column_id = match table.get_columns().iter().position(|c| c.get_name() == column_name) {
+            Some(column_id) => column_id,
+            None => return Ok(Vec::new())
+        };
+        let condition = Some(Conditions::Equal(
+            Box::new(Variable(column_name.to_string())),
+            Box::new(Literal(key.to_owned())),
+        ));
+        let candidates = match table.find_row_ids_where(&Machine::empty(), &condition)? {
+            Some(row_ids) => row_ids,
+            None => table.get_indices()?.collect()
+        };
+        let mut row_ids = Vec::new();
+        for id in candidates {
+            if let Some(row) = table.read_one(id)? {
+                if row.get(column_id) == *key { row_ids.push(id); }
+            }
+        }
+        Ok(row_ids)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::DataType::{NumberType, StringType};
+    use crate::file_row_collection::FileRowCollection;
+    use crate::number_kind::NumberKind::F64Kind;
+    use crate::numbers::Numbers::{F64Value, RowId};
+    use crate::object_config::HashIndexConfig;
+    use crate::parameter::Parameter;
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use crate::typed_values::TypedValue::{Number, StringValue};
+
+    fn create_orders(ns: &Namespace, stocks_ns: &Namespace, on_delete: DeleteActions) -> FileRowCollection {
+        let constraints = vec![
+            ConstraintConfig::new("symbol".into(), false, None)
+                .with_reference(Some(ReferenceConfig::new(stocks_ns.get_full_name(), "symbol".into(), on_delete)))
+        ];
+        FileRowCollection::create_table(ns, &vec![
+            Parameter::new("symbol", StringType(8)),
+            Parameter::new("quantity", NumberType(F64Kind)),
+        ]).unwrap();
+        ObjectConfig::load(ns).unwrap().with_constraints(constraints.to_owned()).save(ns).unwrap();
+        ForeignKeys::link(ns, &constraints).unwrap();
+        FileRowCollection::open(ns).unwrap()
+    }
+
+    fn make_order(id: usize, symbol: &str, quantity: f64) -> Row {
+        Row::new(id, vec![StringValue(symbol.into()), Number(F64Value(quantity))])
+    }
+
+    #[test]
+    fn test_references_are_verified() {
+        let stocks_ns = Namespace::parse("foreign_keys.verify.stocks").unwrap();
+        let mut stocks = FileRowCollection::create_table(&stocks_ns, &make_quote_parameters()).unwrap();
+        ObjectConfig::load(&stocks_ns).unwrap()
+            .with_indices(vec![HashIndexConfig::new(vec!["symbol".into()], true)])
+            .save(&stocks_ns).unwrap();
+        stocks.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        stocks.rebuild_hash_index(0).unwrap();
+
+        let orders_ns = Namespace::parse("foreign_keys.verify.orders").unwrap();
+        let mut orders = create_orders(&orders_ns, &stocks_ns, DeleteActions::Restrict);
+        assert_eq!(orders.append_row(make_order(0, "ABC", 100.0)), Number(RowId(0)));
+        assert_eq!(orders.append_row(make_order(0, "XYZ", 50.0)),
+                   ErrorValue(ForeignKeyViolation("foreign_keys.verify.stocks(symbol)".into(), "\"XYZ\"".into())));
+        assert_eq!(orders.append_row(Row::new(0, vec![Null, Number(F64Value(25.0))])), Number(RowId(1)));
+
+        // the referenced row cannot be deleted while it is referenced
+        let mut stocks = FileRowCollection::open(&stocks_ns).unwrap();
+        assert_eq!(stocks.delete_row(0),
+                   ErrorValue(ReferencedKeyViolation("foreign_keys.verify.orders(symbol)".into(), "\"ABC\"".into())));
+        orders.delete_row(0);
+        assert_eq!(stocks.delete_row(0).to_usize(), 1);
+    }
+
+    #[test]
+    fn test_delete_cascade_and_set_null() {
+        let stocks_ns = Namespace::parse("foreign_keys.cascade.stocks").unwrap();
+        let mut stocks = FileRowCollection::create_table(&stocks_ns, &make_quote_parameters()).unwrap();
+        stocks.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        stocks.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+
+        let cascades_ns = Namespace::parse("foreign_keys.cascade.orders").unwrap();
+        let mut cascades = create_orders(&cascades_ns, &stocks_ns, DeleteActions::Cascade);
+        cascades.append_row(make_order(0, "ABC", 100.0));
+        cascades.append_row(make_order(1, "UNO", 50.0));
+
+        let nulls_ns = Namespace::parse("foreign_keys.cascade.trades").unwrap();
+        let mut nulls = create_orders(&nulls_ns, &stocks_ns, DeleteActions::SetNull);
+        nulls.append_row(make_order(0, "ABC", 25.0));
+
+        let mut stocks = FileRowCollection::open(&stocks_ns).unwrap();
+        assert_eq!(stocks.delete_row(0).to_usize(), 1);
+        assert_eq!(cascades.read_active_rows().unwrap(), vec![make_order(1, "UNO", 50.0)]);
+        assert_eq!(nulls.read_active_rows().unwrap(), vec![Row::new(0, vec![Null, Number(F64Value(25.0))])]);
+    }
+}
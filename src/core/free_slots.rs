@@ -0,0 +1,163 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// free slots module
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::data_types::DataType::NumberType;
+use crate::file_row_collection::FileRowCollection;
+use crate::number_kind::NumberKind::U64Kind;
+use crate::numbers::Numbers::U64Value;
+use crate::parameter::Parameter;
+use crate::row_collection::RowCollection;
+use crate::structures::Row;
+use crate::typed_values::TypedValue::Number;
+use std::fs;
+use std::io::ErrorKind;
+
+/// the extension of the table file containing the free slots of a table (e.g. "stocks.slots")
+const SLOTS_EXTENSION: &str = "slots";
+
+/// Free Slots; a stack of the IDs of the deleted rows of a table (e.g. "stocks.slots"),
+/// whose slots are filled by subsequent appends. Since a deleted row may have since been
+/// restored (or truncated), a slot is verified to be free before it is reused; whereas
+/// stale slots are discarded. The rows of append-only tables (e.g. "create table ... append only")
+/// are always appended to the end of the table, thus their row IDs are monotonic.
+pub struct FreeSlots;
+
+impl FreeSlots {
+    /// Removes the free slots of the table (e.g. when the table is re-created)
+    pub fn discard(table_path: &str) -> std::io::Result<()> {
+        let (_, full_path) = FileRowCollection::get_related_filename(table_path, SLOTS_EXTENSION);
+        match fs::remove_file(full_path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(())
+        }
+    }
+
+    /// Returns the most recently freed slot which remains free => (position within the store|row ID);
+    /// discarding any stale slots freed after it.
+    pub fn find(frc: &FileRowCollection) -> std::io::Result<Option<(usize, usize)>> {
+        let mut store = Self::open_store(frc)?;
+        let (len, store_len) = (frc.len()?, store.len()?);
+        let mut position = store_len;
+        while position > 0 {
+            let id = store.read_field(position - 1, 0).to_usize();
+            if id < len && !frc.read_row_metadata(id)?.is_allocated { break; }
+            position -= 1;
+        }
+        if position < store_len {
+            store.resize(position).to_result(|_| ())?;
+        }
+        match position {
+            0 => Ok(None),
+            position => Ok(Some((position - 1, store.read_field(position - 1, 0).to_usize())))
+        }
+    }
+
+    /// Records the slot of a deleted row as free
+    pub fn release(frc: &FileRowCollection, id: usize) -> std::io::Result<()> {
+        let mut store = Self::open_store(frc)?;
+        let position = store.len()?;
+        store.overwrite_row(position, Row::new(position, vec![Number(U64Value(id as u64))])).to_result(|_| ())
+    }
+
+    /// Removes a slot previously returned by [FreeSlots::find] once it has been filled
+    pub fn take(frc: &FileRowCollection, position: usize) -> std::io::Result<()> {
+        Self::open_store(frc)?.resize(position).to_result(|_| ())
+    }
+
+    fn open_store(frc: &FileRowCollection) -> std::io::Result<FileRowCollection> {
+        let columns = Column::from_parameters(&vec![Parameter::new("_id", NumberType(U64Kind))]);
+        frc.open_related_table(columns, SLOTS_EXTENSION)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespaces::Namespace;
+    use crate::numbers::Numbers::{RowId, RowsAffected};
+    use crate::object_config::ObjectConfig;
+    use crate::row_history::RowHistory;
+    use crate::testdata::{make_quote, make_quote_parameters};
+
+    #[test]
+    fn test_appends_fill_free_slots() {
+        let ns = Namespace::parse("free_slots.reuse.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1442),
+        ]);
+        frc.delete_row(0);
+        frc.delete_row(2);
+        frc.delete_row(3);
+
+        // restored and truncated rows are not reused
+        frc.undelete_row(2);
+        frc.resize(3);
+
+        // the slot of the remaining deleted row is filled, then the table grows
+        assert_eq!(frc.append_row(make_quote(0, "BOOM", "NASDAQ", 0.0872)), Number(RowId(0)));
+        assert_eq!(frc.append_row(make_quote(0, "JET", "NASDAQ", 32.12)), Number(RowId(3)));
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "BOOM", "NASDAQ", 0.0872),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "JET", "NASDAQ", 32.12),
+        ]);
+        assert_eq!(FreeSlots::find(&frc).unwrap(), None);
+    }
+
+    #[test]
+    fn test_append_rows_fill_free_slots_first() {
+        let ns = Namespace::parse("free_slots.append_rows.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+        frc.delete_row(0);
+
+        // the free slot is filled, then the remaining rows are appended
+        assert_eq!(frc.append_rows(vec![
+            make_quote(0, "BOOM", "NASDAQ", 0.0872),
+            make_quote(0, "JET", "NASDAQ", 32.12),
+            make_quote(0, "GOTO", "OTC", 0.1442),
+        ]), Number(RowsAffected(3)));
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "BOOM", "NASDAQ", 0.0872),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "JET", "NASDAQ", 32.12),
+            make_quote(3, "GOTO", "OTC", 0.1442),
+        ]);
+
+        // the reuse of the slot reads as a deletion followed by an insertion
+        let (_, changes) = RowHistory::read_changes(&frc, 0).unwrap();
+        let events = changes.iter()
+            .map(|change| (change[2].unwrap_value(), change[3].to_usize()))
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![
+            ("insert".to_string(), 0), ("insert".to_string(), 1), ("delete".to_string(), 0),
+            ("insert".to_string(), 0), ("insert".to_string(), 2), ("insert".to_string(), 3),
+        ]);
+    }
+
+    #[test]
+    fn test_append_only() {
+        let ns = Namespace::parse("free_slots.append_only.stocks").unwrap();
+        FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        ObjectConfig::load(&ns).unwrap().with_append_only(true).save(&ns).unwrap();
+        let mut frc = FileRowCollection::open(&ns).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.delete_row(0);
+
+        // the row IDs remain monotonic
+        assert_eq!(frc.append_row(make_quote(0, "UNO", "OTC", 0.2456)), Number(RowId(1)));
+        assert_eq!(FreeSlots::find(&frc).unwrap(), None);
+    }
+}
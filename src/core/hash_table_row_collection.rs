@@ -25,6 +25,12 @@ use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::*;
 use log::warn;
 
+/// the number of key slots per hash bucket
+const BUCKET_DEPTH: u64 = 100;
+
+/// the default number of hash buckets
+const DEFAULT_BUCKET_COUNT: u64 = 100_000;
+
 /// Hash-Table-based RowCollection implementation
 #[derive(Debug)]
 pub struct HashTableRowCollection {
@@ -41,7 +47,7 @@ impl HashTableRowCollection {
     //////////////////////////////////////////////////////////
 
     /// Generates the columns for the index base on the source column
-    fn create_hash_keys_columns(src_column: &Column) -> Vec<Column> {
+    pub fn create_hash_keys_columns(src_column: &Column) -> Vec<Column> {
         Column::from_parameters(&vec![
             Parameter::new("__row_id__", NumberType(U64Kind)),
             src_column.to_parameter()
@@ -95,7 +101,7 @@ impl HashTableRowCollection {
         key_column_index: usize,
         data_table: Box<dyn RowCollection>,
     ) -> std::io::Result<HashTableRowCollection> {
-        let bucket_count = 100_000;
+        let bucket_count = DEFAULT_BUCKET_COUNT;
         let bucket_depth = bucket_count / 10;
         let src_column = &data_table.get_columns()[key_column_index];
         let keys_columns = Self::create_hash_keys_columns(src_column);
@@ -104,6 +110,17 @@ impl HashTableRowCollection {
         Ok(Self::create_with_tables_and_options(key_column_index, bucket_count, bucket_depth, data_table, keys_table))
     }
 
+    /// Returns a hash-table over a pre-existing (e.g. persisted) keys table
+    pub fn open(
+        key_column_index: usize,
+        data_table: Box<dyn RowCollection>,
+        keys_table: Box<dyn RowCollection>,
+    ) -> HashTableRowCollection {
+        let bucket_count = DEFAULT_BUCKET_COUNT;
+        let bucket_depth = bucket_count / 10;
+        Self::create_with_tables_and_options(key_column_index, bucket_count, bucket_depth, data_table, keys_table)
+    }
+
     //////////////////////////////////////////////////////////
     //  INSTANCE METHODS
     //////////////////////////////////////////////////////////
@@ -138,16 +155,86 @@ impl HashTableRowCollection {
 
     /// Translates a key into its hash-key row offset
     fn convert_key_to_row_id(&self, key: &TypedValue) -> usize {
-        let bucket_depth = 100;
-        let start = (key.hash_code() % self.bucket_count) * bucket_depth;
+        let start = (key.hash_code() % self.bucket_count) * BUCKET_DEPTH;
         start as usize
     }
 
     /// Translates a key into its hash-key row offset
     fn convert_key_to_row_id_range(&self, key: &TypedValue) -> Range<u64> {
-        let bucket_depth = 100;
-        let start = (key.hash_code() % self.bucket_count) * bucket_depth;
-        start..start + bucket_depth
+        let start = (key.hash_code() % self.bucket_count) * BUCKET_DEPTH;
+        start..start + BUCKET_DEPTH
+    }
+
+    /// Returns the IDs of all data rows containing the key. Unlike [HashTableRowCollection::find_row_by_key],
+    /// a key may be linked to many rows, so the probe continues until an unused key slot is reached.
+    pub fn find_row_ids_by_key(&self, key: &TypedValue) -> std::io::Result<Vec<usize>> {
+        let mut data_row_ids = Vec::new();
+        for keys_row_id in self.get_probe_sequence(key) {
+            match self.read_key_slot(keys_row_id)? {
+                KeySlot::Unused => break,
+                KeySlot::Vacated => {}
+                KeySlot::Occupied(data_row_id, value) =>
+                    if value == *key { data_row_ids.push(data_row_id) }
+            }
+        }
+        Ok(data_row_ids)
+    }
+
+    /// Returns the key slots to be probed for the key; beginning with the key's bucket,
+    /// and wrapping around at the end of the keys table.
+    fn get_probe_sequence(&self, key: &TypedValue) -> impl Iterator<Item=usize> {
+        let slot_count = (self.bucket_count * BUCKET_DEPTH) as usize;
+        let start = self.convert_key_to_row_id(key);
+        (0..slot_count).map(move |n| (start + n) % slot_count)
+    }
+
+    /// Links the data row ID to the key; a key may be linked to any number of rows.
+    pub fn link_row_key(&mut self, data_row_id: usize, key: &TypedValue) -> TypedValue {
+        let mut vacancy = None;
+        for keys_row_id in self.get_probe_sequence(key) {
+            match self.read_key_slot(keys_row_id) {
+                Ok(KeySlot::Unused) => {
+                    vacancy = vacancy.or(Some(keys_row_id));
+                    break;
+                }
+                Ok(KeySlot::Vacated) => vacancy = vacancy.or(Some(keys_row_id)),
+                Ok(KeySlot::Occupied(id, value)) if id == data_row_id && value == *key =>
+                    return Number(Numbers::RowsAffected(0)),
+                Ok(KeySlot::Occupied(..)) => {}
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            }
+        }
+        match vacancy {
+            Some(keys_row_id) => {
+                let keys_row = Self::create_hash_keys_row(data_row_id, keys_row_id, self.keys_table.get_columns(), key);
+                self.keys_table.overwrite_row(keys_row_id, keys_row)
+            }
+            None => ErrorValue(HashTableOverflow(data_row_id, key.unwrap_value()))
+        }
+    }
+
+    /// Reads a slot of the keys table
+    fn read_key_slot(&self, keys_row_id: usize) -> std::io::Result<KeySlot> {
+        let (row, metadata) = self.keys_table.read_row(keys_row_id)?;
+        Ok(match (metadata.is_allocated, &row[0]) {
+            (true, Number(number)) => KeySlot::Occupied(number.to_usize(), row[1].to_owned()),
+            (_, Null | Undefined) => KeySlot::Unused,
+            _ => KeySlot::Vacated
+        })
+    }
+
+    /// Unlinks the data row ID from the key
+    pub fn unlink_row_key(&mut self, data_row_id: usize, key: &TypedValue) -> TypedValue {
+        for keys_row_id in self.get_probe_sequence(key) {
+            match self.read_key_slot(keys_row_id) {
+                Ok(KeySlot::Unused) => break,
+                Ok(KeySlot::Occupied(id, value)) if id == data_row_id && value == *key =>
+                    return self.keys_table.delete_row(keys_row_id),
+                Ok(_) => {}
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            }
+        }
+        Number(Numbers::RowsAffected(0))
     }
 
     /// Performs a fast lookup (O(1)) via hash index on the key column
@@ -253,6 +340,16 @@ impl HashTableRowCollection {
     }
 }
 
+/// Represents the state of a slot within the keys table
+enum KeySlot {
+    /// the slot has never held a key; it terminates a probe sequence.
+    Unused,
+    /// the slot's key has been unlinked; it may be reused.
+    Vacated,
+    /// the slot links a data row ID to a key.
+    Occupied(usize, TypedValue),
+}
+
 impl RowCollection for HashTableRowCollection {
     fn delete_row(&mut self, id: usize) -> TypedValue {
         let key_value = self.read_field(id, self.key_column_index);
@@ -456,6 +553,31 @@ mod tests {
         //assert_eq!(hkrc.find_row_by_key(&StringValue("CRT".into())).unwrap(), None);
     }
 
+    #[test]
+    fn test_link_then_find_row_ids_by_key() {
+        // link several rows to the same key (e.g. a non-unique index on exchange)
+        let ns = Namespace::new("hash_key", "link_unlink", "stocks");
+        let frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let keys_columns = HashTableRowCollection::create_hash_keys_columns(&frc.get_columns()[1]);
+        let keys_table = frc.create_related_structure(keys_columns, "1").unwrap();
+        let mut hkrc = HashTableRowCollection::open(1, Box::new(frc), keys_table);
+        let (nyse, otc) = (StringValue("NYSE".into()), StringValue("OTC".into()));
+        for (data_row_id, key) in [(0, &nyse), (1, &otc), (2, &nyse), (3, &nyse)] {
+            assert_eq!(hkrc.link_row_key(data_row_id, key), Number(RowsAffected(1)));
+        }
+
+        // re-linking an existing pair has no effect
+        assert_eq!(hkrc.link_row_key(2, &nyse), Number(RowsAffected(0)));
+        assert_eq!(hkrc.find_row_ids_by_key(&nyse).unwrap(), vec![0, 2, 3]);
+        assert_eq!(hkrc.find_row_ids_by_key(&otc).unwrap(), vec![1]);
+
+        // unlink a row, then verify the vacated slot is reused
+        assert_eq!(hkrc.unlink_row_key(2, &nyse), Number(RowsAffected(1)));
+        assert_eq!(hkrc.find_row_ids_by_key(&nyse).unwrap(), vec![0, 3]);
+        assert_eq!(hkrc.link_row_key(4, &nyse), Number(RowsAffected(1)));
+        assert_eq!(hkrc.find_row_ids_by_key(&nyse).unwrap(), vec![0, 4, 3]);
+    }
+
     #[ignore]
     #[test]
     fn test_performance() {
@@ -7,7 +7,7 @@ use crate::arrays::Array;
 use crate::data_types::DataType;
 use crate::data_types::DataType::*;
 use crate::expression::Expression::*;
-use crate::expression::{DatabaseOps, Expression, Mutations};
+use crate::expression::{DatabaseOps, Expression, Mutations, TransactionOps};
 use crate::number_kind::NumberKind;
 use crate::platform::PlatformOps;
 use crate::typed_values::TypedValue;
@@ -22,6 +22,7 @@ impl Inferences {
     pub fn infer(expr: &Expression) -> DataType {
         match expr {
             ArrayExpression(items) => ArrayType(items.len()),
+            Ascending(e) => Inferences::infer(e),
             AsValue(_, e) => Inferences::infer(e),
             BitwiseAnd(a, b) => Inferences::infer_a_or_b(a, b),
             BitwiseOr(a, b) => Inferences::infer_a_or_b(a, b),
@@ -30,6 +31,7 @@ impl Inferences {
             BitwiseXor(a, b) => Inferences::infer_a_or_b(a, b),
             CodeBlock(ops) => ops.last().map(Inferences::infer).unwrap_or(VaryingType(vec![])),
             Condition(..) => BooleanType,
+            Descending(e) => Inferences::infer(e),
             Directive(..) => NumberType(NumberKind::AckKind),
             Divide(a, b) => Inferences::infer_a_or_b(a, b),
             ElementAt(..) => VaryingType(vec![]),
@@ -81,6 +83,8 @@ impl Inferences {
             Return(a) => Self::infer_all(a),
             Scenario { .. } => NumberType(NumberKind::AckKind),
             SetVariable(..) => NumberType(NumberKind::AckKind),
+            TransactionOp(TransactionOps::Block(code)) => Inferences::infer(code),
+            TransactionOp(..) => NumberType(NumberKind::AckKind),
             Variable(..) => VaryingType(vec![]),
             Via(..) => TableType(vec![], 0),
             While { .. } => VaryingType(vec![]),
@@ -6,6 +6,7 @@
 use crate::compiler::Compiler;
 use crate::expression::Expression;
 use crate::machine::Machine;
+use crate::transactions::Transaction;
 use crate::typed_values::TypedValue;
 use num_traits::real::Real;
 use serde::{Deserialize, Serialize};
@@ -49,7 +50,9 @@ impl Interpreter {
 
     /// Executes the supplied source code returning the result of the evaluation
     pub fn invoke(&mut self, opcode: &Expression) -> std::io::Result<TypedValue> {
-        let (machine, result) = self.machine.evaluate(&opcode)?;
+        let (machine, result) = Transaction::within(self.machine.get_transaction(), || {
+            self.machine.evaluate(opcode)
+        })?;
         self.machine = machine;
         Ok(result)
     }
@@ -541,8 +544,8 @@ mod tests {
                 "|-------------------------|",
                 "| id | symbol | last_sale |",
                 "|-------------------------|",
-                "| 1  | UNO    | 0.2456    |",
-                "| 3  | GOTO   | 0.1428    |",
+                "| 0  | BOOM   | 0.0872    |",
+                "| 1  | GOTO   | 0.1428    |",
                 "|-------------------------|"]);
         }
 
@@ -571,7 +574,7 @@ mod tests {
                 "| id | symbol | exchange | last_sale |",
                 "|------------------------------------|",
                 "| 0  | ABC    | AMEX     | 11.77     |",
-                "| 2  | BIZ    | NYSE     | 23.66     |",
+                "| 1  | BIZ    | NYSE     | 23.66     |",
                 "|------------------------------------|"
             ]);
         }
@@ -0,0 +1,295 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// joins module
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::dataframe::Dataframe;
+use crate::dataframe::Dataframe::Model;
+use crate::errors::throw;
+use crate::errors::Errors::TypeMismatch;
+use crate::errors::TypeMismatchErrors::CollectionExpected;
+use crate::expression::Expression::*;
+use crate::expression::{Conditions, Expression, JoinKinds};
+use crate::machine::Machine;
+use crate::model_row_collection::ModelRowCollection;
+use crate::row_collection::RowCollection;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, NamespaceValue, Null, TableValue, Undefined};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::ops::Deref;
+
+/// Join facility for `select` queries
+/// (e.g. "from s: stocks left join e: exchanges on s.exchange == e.code").
+/// The columns of an aliased (or named) table are qualified by the alias (e.g. "s.symbol").
+pub struct Joins;
+
+impl Joins {
+    /// Joins the left and right tables; equality conditions (e.g. "s.exchange == e.code")
+    /// are satisfied via a hash join, whereas all other conditions are evaluated per row pair.
+    pub fn join(
+        ms: &Machine,
+        left: &Expression,
+        right: &Expression,
+        kind: &JoinKinds,
+        condition: &Option<Conditions>,
+    ) -> std::io::Result<Dataframe> {
+        let (left_columns, left_rows) = Self::resolve_source(ms, left)?;
+        let (right_columns, right_rows) = Self::resolve_source(ms, right)?;
+        let columns = Self::combine_columns(&left_columns, &right_columns);
+
+        // determine the matching right rows for each left row
+        let matches = match condition {
+            None => vec![(0..right_rows.len()).collect::<Vec<_>>(); left_rows.len()],
+            Some(condition) => match Self::decode_equi_keys(condition, &left_columns, &right_columns) {
+                Some(keys) => Self::hash_join(ms, &keys, &left_columns, &left_rows, &right_columns, &right_rows)?,
+                None => Self::nested_loop_join(ms, condition, &columns, &left_rows, &right_rows)?
+            }
+        };
+
+        // produce the joined rows
+        let (left_nulls, right_nulls) = (vec![Null; left_columns.len()], vec![Null; right_columns.len()]);
+        let mut is_right_matched = vec![false; right_rows.len()];
+        let mut dest = ModelRowCollection::new(columns);
+        for (left_row, right_ids) in left_rows.iter().zip(matches) {
+            for right_id in right_ids.iter() {
+                is_right_matched[*right_id] = true;
+                Self::append_joined_row(&mut dest, &left_row.get_values(), &right_rows[*right_id].get_values())?;
+            }
+            if right_ids.is_empty() && matches!(kind, JoinKinds::Left | JoinKinds::Full) {
+                Self::append_joined_row(&mut dest, &left_row.get_values(), &right_nulls)?;
+            }
+        }
+        if matches!(kind, JoinKinds::Right | JoinKinds::Full) {
+            for (right_row, is_matched) in right_rows.iter().zip(is_right_matched) {
+                if !is_matched {
+                    Self::append_joined_row(&mut dest, &left_nulls, &right_row.get_values())?;
+                }
+            }
+        }
+        Ok(Model(dest))
+    }
+
+    fn append_joined_row(
+        dest: &mut ModelRowCollection,
+        left_values: &Vec<TypedValue>,
+        right_values: &Vec<TypedValue>,
+    ) -> std::io::Result<()> {
+        let mut values = left_values.to_owned();
+        values.extend(right_values.to_owned());
+        match dest.append_row(Row::new(0, values)) {
+            ErrorValue(err) => throw(err),
+            _ => Ok(())
+        }
+    }
+
+    /// Produces the columns of the joined table
+    fn combine_columns(left: &[Column], right: &[Column]) -> Vec<Column> {
+        let mut offset = Row::overhead();
+        let mut columns = Vec::with_capacity(left.len() + right.len());
+        for column in left.iter().chain(right.iter()) {
+            let new_column = Column::new(column.get_name(), column.get_data_type().to_owned(), Null, offset);
+            offset += new_column.get_fixed_size();
+            columns.push(new_column);
+        }
+        columns
+    }
+
+    /// Decomposes the condition into pairs of left and right key expressions;
+    /// provided it consists solely of equalities (e.g. "s.exchange == e.code && s.year == e.year").
+    fn decode_equi_keys(
+        condition: &Conditions,
+        left: &Vec<Column>,
+        right: &Vec<Column>,
+    ) -> Option<Vec<(Expression, Expression)>> {
+        match condition {
+            Conditions::And(a, b) => match (a.deref(), b.deref()) {
+                (Condition(a), Condition(b)) => {
+                    let mut keys = Self::decode_equi_keys(a, left, right)?;
+                    keys.extend(Self::decode_equi_keys(b, left, right)?);
+                    Some(keys)
+                }
+                _ => None
+            }
+            Conditions::Equal(a, b) if Self::is_bound_to(a, left) && Self::is_bound_to(b, right) =>
+                Some(vec![(a.deref().to_owned(), b.deref().to_owned())]),
+            Conditions::Equal(a, b) if Self::is_bound_to(b, left) && Self::is_bound_to(a, right) =>
+                Some(vec![(b.deref().to_owned(), a.deref().to_owned())]),
+            _ => None
+        }
+    }
+
+    fn evaluate_key(
+        ms: &Machine,
+        keys: &Vec<Expression>,
+        columns: &Vec<Column>,
+        row: &Row,
+    ) -> std::io::Result<Vec<TypedValue>> {
+        let ms = ms.with_row(columns, row);
+        let mut key = Vec::with_capacity(keys.len());
+        for expr in keys {
+            match ms.evaluate(expr)? {
+                (_, ErrorValue(err)) => return throw(err),
+                (_, value) => key.push(value)
+            }
+        }
+        Ok(key)
+    }
+
+    /// Builds a hash table over the right rows' keys, then probes it with each left row's key.
+    /// As in SQL, keys containing nulls never match.
+    fn hash_join(
+        ms: &Machine,
+        keys: &[(Expression, Expression)],
+        left_columns: &Vec<Column>,
+        left_rows: &[Row],
+        right_columns: &Vec<Column>,
+        right_rows: &[Row],
+    ) -> std::io::Result<Vec<Vec<usize>>> {
+        let (left_keys, right_keys): (Vec<_>, Vec<_>) = keys.iter().cloned().unzip();
+
+        // build phase
+        let mut right_key_values = Vec::with_capacity(right_rows.len());
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (right_id, row) in right_rows.iter().enumerate() {
+            let key = Self::evaluate_key(ms, &right_keys, right_columns, row)?;
+            if !Self::is_null_key(&key) {
+                buckets.entry(Self::hash_key(&key)).or_default().push(right_id);
+            }
+            right_key_values.push(key);
+        }
+
+        // probe phase
+        let mut matches = Vec::with_capacity(left_rows.len());
+        for row in left_rows {
+            let key = Self::evaluate_key(ms, &left_keys, left_columns, row)?;
+            matches.push(match buckets.get(&Self::hash_key(&key)) {
+                Some(right_ids) if !Self::is_null_key(&key) => right_ids.iter()
+                    .filter(|right_id| right_key_values[**right_id] == key)
+                    .copied()
+                    .collect(),
+                _ => Vec::new()
+            });
+        }
+        Ok(matches)
+    }
+
+    fn hash_key(key: &[TypedValue]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for value in key { hasher.write_u64(value.hash_code()); }
+        hasher.finish()
+    }
+
+    /// Indicates whether the expression can be evaluated solely via the given columns
+    fn is_bound_to(expr: &Expression, columns: &Vec<Column>) -> bool {
+        match expr {
+            Literal(..) => true,
+            Variable(name) => columns.iter().any(|c| c.get_name() == name),
+            Divide(a, b) | Minus(a, b) | Modulo(a, b) | Multiply(a, b) | Plus(a, b) =>
+                Self::is_bound_to(a, columns) && Self::is_bound_to(b, columns),
+            Neg(a) => Self::is_bound_to(a, columns),
+            _ => false
+        }
+    }
+
+    fn is_null_key(key: &[TypedValue]) -> bool {
+        key.iter().any(|v| matches!(v, Null | Undefined))
+    }
+
+    /// Evaluates the condition against every pair of left and right rows
+    fn nested_loop_join(
+        ms: &Machine,
+        condition: &Conditions,
+        columns: &Vec<Column>,
+        left_rows: &[Row],
+        right_rows: &[Row],
+    ) -> std::io::Result<Vec<Vec<usize>>> {
+        let mut matches = Vec::with_capacity(left_rows.len());
+        for left_row in left_rows {
+            let mut right_ids = Vec::new();
+            for (right_id, right_row) in right_rows.iter().enumerate() {
+                let mut values = left_row.get_values();
+                values.extend(right_row.get_values());
+                match ms.with_row(columns, &Row::new(0, values)).evaluate_cond(condition)? {
+                    (_, Boolean(true)) => right_ids.push(right_id),
+                    (_, ErrorValue(err)) => return throw(err),
+                    _ => {}
+                }
+            }
+            matches.push(right_ids);
+        }
+        Ok(matches)
+    }
+
+    /// Evaluates a join source (e.g. "s: stocks"); returning its (qualified) columns and rows.
+    fn resolve_source(
+        ms: &Machine,
+        expr: &Expression,
+    ) -> std::io::Result<(Vec<Column>, Vec<Row>)> {
+        let (alias, expr) = match expr {
+            AsValue(alias, expr) => (Some(alias.to_string()), expr.deref()),
+            Variable(name) => (Some(name.to_string()), expr),
+            expr => (None, expr)
+        };
+        let (alias, df) = match ms.evaluate(expr)? {
+            (_, NamespaceValue(ns)) =>
+                (alias.or(Some(ns.name.to_string())), Dataframe::open(&ns)?),
+            (_, TableValue(df)) => (alias, df),
+            (_, ErrorValue(err)) => return throw(err),
+            (_, other) => return throw(TypeMismatch(CollectionExpected(other.to_code())))
+        };
+        let columns = df.get_columns().iter()
+            .map(|c| match &alias {
+                Some(alias) => Column::new(format!("{}.{}", alias, c.get_name()),
+                                           c.get_data_type().to_owned(), c.get_default_value(), c.get_offset()),
+                None => c.to_owned()
+            })
+            .collect();
+        Ok((columns, df.get_rows()))
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use crate::columns::Column;
+    use crate::data_types::DataType::StringType;
+    use crate::expression::Conditions::{Equal, GreaterThan};
+    use crate::expression::Expression::{Literal, Variable};
+    use crate::expression::JoinKinds;
+    use crate::joins::Joins;
+    use crate::numbers::Numbers::F64Value;
+    use crate::typed_values::TypedValue::{Number, StringValue};
+
+    fn make_columns(names: Vec<&str>) -> Vec<Column> {
+        names.iter().map(|name| Column::new(*name, StringType(8), StringValue("".into()), 0)).collect()
+    }
+
+    #[test]
+    fn test_decode_equi_keys() {
+        let (left, right) = (make_columns(vec!["s.exchange"]), make_columns(vec!["e.code"]));
+        let condition = Equal(
+            Box::new(Variable("e.code".into())),
+            Box::new(Variable("s.exchange".into())),
+        );
+        assert_eq!(Joins::decode_equi_keys(&condition, &left, &right), Some(vec![
+            (Variable("s.exchange".into()), Variable("e.code".into()))
+        ]));
+
+        let condition = GreaterThan(
+            Box::new(Variable("s.exchange".into())),
+            Box::new(Literal(Number(F64Value(1.0)))),
+        );
+        assert_eq!(Joins::decode_equi_keys(&condition, &left, &right), None);
+    }
+
+    #[test]
+    fn test_join_kind_to_code() {
+        assert_eq!(JoinKinds::Inner.to_code(), "join");
+        assert_eq!(JoinKinds::Left.to_code(), "left join");
+        assert_eq!(JoinKinds::Cross.to_code(), "cross join");
+    }
+}
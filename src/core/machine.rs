@@ -11,6 +11,7 @@ use regex::Error;
 use reqwest::multipart::{Form, Part};
 use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::convert::{From, Into};
 use std::fs::File;
@@ -30,7 +31,7 @@ use crate::data_types::DataType;
 use crate::data_types::DataType::{ArrayType, FunctionType, StringType, StructureType, TableType, VaryingType};
 
 use crate::dataframe::Dataframe;
-use crate::dataframe::Dataframe::{Disk, Model};
+use crate::dataframe::Dataframe::Model;
 use crate::descriptor::Descriptor;
 use crate::errors::Errors::*;
 use crate::errors::TypeMismatchErrors::{FunctionArgsExpected, OutcomeExpected, ParameterExpected, StructExpected, UnsupportedType};
@@ -40,8 +41,7 @@ use crate::expression::CreationEntity::{IndexEntity, TableEntity};
 use crate::expression::Expression::*;
 use crate::expression::MutateTarget::{IndexTarget, TableTarget};
 use crate::expression::{Conditions, Expression, ImportOps, ACK, UNDEFINED};
-use crate::expression::{DatabaseOps, Directives, Mutations, Queryables};
-use crate::file_row_collection::FileRowCollection;
+use crate::expression::{DatabaseOps, Directives, Mutations, Queryables, TransactionOps};
 use crate::inferences::Inferences;
 use crate::model_row_collection::ModelRowCollection;
 use crate::namespaces::Namespace;
@@ -57,14 +57,21 @@ use crate::structures::Structures::{Firm, Hard, Soft};
 use crate::structures::*;
 use crate::table_renderer::TableRenderer;
 use crate::testdata::verify_exact_table_where;
+use crate::transactions::Transaction;
 use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::*;
 use shared_lib::fail;
 
+thread_local! {
+    /// overrides $OXIDE_HOME for the current thread (see [Machine::with_oxide_home])
+    static THREAD_OXIDE_HOME: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 /// Represents the state of the machine.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Machine {
     stack: Vec<TypedValue>,
+    transaction: Option<Transaction>,
     variables: HashMap<String, TypedValue>,
 }
 
@@ -77,14 +84,15 @@ impl Machine {
     /// (lowest-level constructor) creates a new state machine
     fn build(
         stack: Vec<TypedValue>,
+        transaction: Option<Transaction>,
         variables: HashMap<String, TypedValue>,
     ) -> Self {
-        Self { stack, variables }
+        Self { stack, transaction, variables }
     }
 
     /// creates a new completely empty state machine
     pub fn empty() -> Self {
-        Self::build(Vec::new(), HashMap::new())
+        Self::build(Vec::new(), None, HashMap::new())
     }
 
     /// creates a new state machine prepopulated with platform packages
@@ -123,7 +131,20 @@ impl Machine {
     }
 
     pub fn oxide_home() -> String {
-        env::var("OXIDE_HOME").unwrap_or("./oxide_db".to_string())
+        THREAD_OXIDE_HOME.with_borrow(|home| home.to_owned())
+            .unwrap_or_else(|| env::var("OXIDE_HOME").unwrap_or("./oxide_db".to_string()))
+    }
+
+    /// Performs the operation with the given home directory in place of $OXIDE_HOME for the
+    /// current thread (e.g. a follower replicating into its own home)
+    pub fn with_oxide_home<F, T>(home: &str, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let previous = THREAD_OXIDE_HOME.replace(Some(home.to_string()));
+        let result = f();
+        THREAD_OXIDE_HOME.set(previous);
+        result
     }
 
     ////////////////////////////////////////////////////////////////
@@ -138,6 +159,7 @@ impl Machine {
         use crate::expression::Expression::*;
         match expression {
             ArrayExpression(items) => self.evaluate_array(items),
+            Ascending(expr) => self.evaluate(expr),
             AsValue(name, expr) => {
                 let (machine, tv) = self.evaluate(expr)?;
                 Ok((machine.with_variable(name, tv.to_owned()), tv))
@@ -155,6 +177,7 @@ impl Machine {
             CodeBlock(ops) => Ok(self.evaluate_scope(ops)),
             Condition(condition) => self.evaluate_cond(condition),
             DatabaseOp(op) => query_engine::evaluate(self, op),
+            Descending(expr) => self.evaluate(expr),
             Directive(d) => self.do_directive(d),
             Divide(a, b) =>
                 self.do_inline_2(a, b, |aa, bb| aa / bb),
@@ -204,6 +227,7 @@ impl Machine {
                 let (machine, value) = self.evaluate(expr)?;
                 Ok((machine.set(name, value), Number(Ack)))
             }
+            TransactionOp(op) => self.do_transaction_op(op),
             Variable(name) => Ok((self.to_owned(), self.get_or_else(&name, || Undefined))),
             Via(src) => do_table_or_view_query(self, src, &True, &Undefined),
             While { condition, code } =>
@@ -332,12 +356,13 @@ impl Machine {
     }
 
     /// evaluates the specified [Expression]; returning a [TypedValue] result.
+    /// Each statement is performed on behalf of the transaction in progress (if any).
     fn evaluate_scope(&self, ops: &Vec<Expression>) -> (Self, TypedValue) {
         ops.iter().fold((self.to_owned(), Undefined),
-                        |(m, _), op| match m.evaluate(op) {
-                            Ok((m, ErrorValue(msg))) => (m, ErrorValue(msg)),
+                        |(m, _), op| match Transaction::within(m.get_transaction(), || m.evaluate(op)) {
+                            Ok((m, ErrorValue(msg))) => (m.with_transaction_aborted(), ErrorValue(msg)),
                             Ok((m, tv)) => (m, tv),
-                            Err(err) => (m, ErrorValue(Exact(err.to_string())))
+                            Err(err) => (m.with_transaction_aborted(), ErrorValue(Exact(err.to_string())))
                         })
     }
 
@@ -855,10 +880,10 @@ impl Machine {
             }
             NamespaceValue(ns) => {
                 let id = index.to_usize();
-                let frc = FileRowCollection::open(&ns)?;
-                match frc.read_one(id)? {
-                    Some(row) => Structured(Firm(row, frc.get_columns().clone())),
-                    None => Structured(Firm(Row::create(id, frc.get_columns()), frc.get_columns().to_owned()))
+                let df = Dataframe::open(&ns)?;
+                match df.read_one(id)? {
+                    Some(row) => Structured(Firm(row, df.get_columns().clone())),
+                    None => Structured(Firm(Row::create(id, df.get_columns()), df.get_columns().to_owned()))
                 }
             }
             StringValue(string) => {
@@ -1038,6 +1063,52 @@ impl Machine {
         }
     }
 
+    /// evaluates the specified [TransactionOps]; returning a [TypedValue] result.
+    fn do_transaction_op(
+        &self,
+        op: &TransactionOps,
+    ) -> std::io::Result<(Self, TypedValue)> {
+        match (op, self.transaction.to_owned()) {
+            (TransactionOps::Begin, None) =>
+                Ok((self.with_transaction(Some(Transaction::begin())), Number(Ack))),
+            (TransactionOps::Begin, Some(..)) =>
+                throw(Exact("A transaction is already in progress".to_string())),
+            // a nested block joins the enclosing transaction
+            (TransactionOps::Block(code), Some(..)) => self.evaluate(code),
+            (TransactionOps::Block(code), None) => {
+                let ms = self.with_transaction(Some(Transaction::begin()));
+                match ms.evaluate(code) {
+                    Ok((ms, ErrorValue(err))) => {
+                        let (machine, _) = ms.do_transaction_op(&TransactionOps::Rollback)?;
+                        Ok((machine, ErrorValue(err)))
+                    }
+                    Ok((ms, result)) => {
+                        let (machine, outcome) = ms.do_transaction_op(&TransactionOps::Commit)?;
+                        Ok((machine, if outcome.is_ok() { result } else { outcome }))
+                    }
+                    Err(err) => {
+                        ms.do_transaction_op(&TransactionOps::Rollback)?;
+                        Err(err)
+                    }
+                }
+            }
+            (TransactionOps::Commit, Some(tx)) if tx.is_aborted() => {
+                tx.rollback()?;
+                Ok((self.with_transaction(None), ErrorValue(Exact("Transaction rolled back due to prior errors".to_string()))))
+            }
+            (TransactionOps::Commit, Some(tx)) => {
+                tx.commit()?;
+                Ok((self.with_transaction(None), Number(Ack)))
+            }
+            (TransactionOps::Rollback, Some(tx)) => {
+                tx.rollback()?;
+                Ok((self.with_transaction(None), Number(Ack)))
+            }
+            (TransactionOps::Commit | TransactionOps::Rollback, None) =>
+                throw(Exact("No transaction is in progress".to_string())),
+        }
+    }
+
     fn do_while(
         &self,
         condition: &Expression,
@@ -1081,7 +1152,7 @@ impl Machine {
     ) -> std::io::Result<A> {
         match table {
             NamespaceValue(ns) =>
-                f(Box::new(FileRowCollection::open(&ns)?)),
+                f(Box::new(Dataframe::open(ns)?)),
             TableValue(rcv) => f(Box::new(rcv.to_owned())),
             z => throw(Exact(format!("{} is not a table", z)))
         }
@@ -1093,7 +1164,7 @@ impl Machine {
         f: fn(Dataframe) -> std::io::Result<A>,
     ) -> std::io::Result<A> {
         match table {
-            NamespaceValue(ns) => f(Disk(FileRowCollection::open(&ns)?)),
+            NamespaceValue(ns) => f(Dataframe::open(ns)?),
             TableValue(rc) => f(rc.to_owned()),
             z => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), z.get_type())))
         }
@@ -1149,6 +1220,10 @@ impl Machine {
         self.get(name).unwrap_or(default())
     }
 
+    pub fn get_transaction(&self) -> Option<&Transaction> {
+        self.transaction.as_ref()
+    }
+
     pub fn get_variables(&self) -> Vec<(&String, &TypedValue)> {
         self.variables.iter().collect::<Vec<_>>()
     }
@@ -1169,7 +1244,7 @@ impl Machine {
     pub fn set(&self, name: &str, value: TypedValue) -> Self {
         let mut variables = self.variables.to_owned();
         variables.insert(name.to_string(), value);
-        Self::build(self.stack.to_owned(), variables)
+        Self::build(self.stack.to_owned(), self.transaction.to_owned(), variables)
     }
 
     pub fn show(columns: &Vec<Column>, rows: &Vec<Row>) {
@@ -1197,6 +1272,18 @@ impl Machine {
             })
     }
 
+    pub fn with_transaction(&self, transaction: Option<Transaction>) -> Self {
+        Self::build(self.stack.to_owned(), transaction, self.variables.to_owned())
+    }
+
+    /// marks the active transaction (if any) as rollback-only
+    fn with_transaction_aborted(&self) -> Self {
+        match &self.transaction {
+            Some(tx) => self.with_transaction(Some(tx.to_owned().abort())),
+            None => self.to_owned()
+        }
+    }
+
     pub fn with_tuples(&self, tuples: Vec<(&str, TypedValue)>) -> Self {
         tuples.iter().fold(self.to_owned(), |ms, (name, value)| {
             ms.with_variable(name, value.to_owned())
@@ -1206,7 +1293,7 @@ impl Machine {
     pub fn with_variable(&self, name: &str, value: TypedValue) -> Self {
         let mut variables = self.variables.to_owned();
         variables.insert(name.to_string(), value);
-        Self::build(self.stack.to_owned(), variables)
+        Self::build(self.stack.to_owned(), self.transaction.to_owned(), variables)
     }
 }
 
@@ -1223,6 +1310,7 @@ mod tests {
     use crate::expression::MutateTarget::TableTarget;
     use crate::expression::Queryables;
     use crate::expression::{FALSE, NULL, TRUE};
+    use crate::file_row_collection::FileRowCollection;
     use crate::number_kind::NumberKind::I64Kind;
     use crate::table_renderer::TableRenderer;
     use crate::testdata::*;
@@ -1580,6 +1668,7 @@ mod tests {
         use crate::expression::Mutations::{Append, Create, Declare, Drop, Overwrite, Truncate, Update};
         use crate::expression::{DatabaseOps, Mutations};
         use crate::number_kind::NumberKind::F64Kind;
+        use crate::object_config::IndexKinds;
         use crate::testdata::{make_quote, make_quote_columns, make_quote_descriptors};
 
         #[test]
@@ -1686,7 +1775,11 @@ mod tests {
                 path: Box::new(Ns(Box::new(Literal(StringValue("machine.create.stocks".into()))))),
                 entity: TableEntity {
                     columns: make_quote_parameters(),
+                    constraints: vec![],
                     from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![],
                 },
             }));
 
@@ -1717,7 +1810,11 @@ mod tests {
                 path: Box::new(Ns(Box::new(Literal(StringValue(path.into()))))),
                 entity: TableEntity {
                     columns: make_quote_parameters(),
+                    constraints: vec![],
                     from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![],
                 },
             }))).unwrap();
             assert_eq!(result, Number(Ack));
@@ -1730,6 +1827,8 @@ mod tests {
                         Variable("symbol".into()),
                         Variable("exchange".into()),
                     ],
+                    is_unique: false,
+                    kind: IndexKinds::Hash,
                 },
             }));
             let (_, result) = machine.evaluate(&model).unwrap();
@@ -1744,14 +1843,21 @@ mod tests {
 
         #[test]
         fn test_declare_table() {
-            let model = DatabaseOp(Mutation(Declare(TableEntity {
-                columns: vec![
-                    Parameter::new("symbol", StringType(8)),
-                    Parameter::new("exchange", StringType(8)),
-                    Parameter::new("last_sale", NumberType(F64Kind)),
-                ],
-                from: None,
-            })));
+            let model = DatabaseOp(Mutation(Declare {
+                path: None,
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::new("exchange", StringType(8)),
+                        Parameter::new("last_sale", NumberType(F64Kind)),
+                    ],
+                    constraints: vec![],
+                    from: None,
+                    is_append_only: false,
+                    is_encrypted: false,
+                    partitions: vec![],
+                },
+            }));
 
             let machine = Machine::empty();
             let (_, result) = machine.evaluate(&model).unwrap();
@@ -6,14 +6,19 @@
 use shared_lib::cnv_error;
 use std::env;
 use std::string::ToString;
+use crate::machine::Machine;
 use crate::oxide_server::start_http_server;
 use crate::repl::{read_line_from_stdin, REPLState};
+use crate::replication::Replication;
 use crate::terminal::TerminalState;
+use crate::transactions::Transaction;
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
 
+mod aggregates;
 mod arrays;
 mod blobs;
+mod btree_index;
 mod byte_code_compiler;
 mod byte_row_collection;
 mod columns;
@@ -23,15 +28,21 @@ mod dataframe;
 mod dataframe_actor;
 mod data_types;
 mod descriptor;
+mod encryption;
 mod errors;
 mod expression;
 mod field;
 mod file_row_collection;
+mod foreign_keys;
+mod free_slots;
 mod hash_table_row_collection;
 mod hybrid_row_collection;
 mod inferences;
 mod interpreter;
+mod joins;
 mod machine;
+mod materialized_views;
+mod memory_indices;
 mod model_row_collection;
 mod namespaces;
 mod number_kind;
@@ -39,13 +50,19 @@ mod numbers;
 mod object_config;
 mod oxide_server;
 mod parameter;
+mod partitioned_row_collection;
 mod platform;
 mod query_engine;
 mod readme;
 mod repl;
+mod replication;
 mod row_collection;
+mod row_history;
 mod row_metadata;
+mod sequences;
 mod server;
+mod snapshots;
+mod sorting;
 mod structures;
 mod table_renderer;
 mod template;
@@ -54,15 +71,19 @@ mod testdata;
 mod token_slice;
 mod tokenizer;
 mod tokens;
+mod transactions;
+mod triggers;
 mod typed_values;
 mod websockets;
+mod write_ahead_log;
 
-const LOCAL_HOST: String = "0.0.0.0".to_string();
+const LOCAL_HOST: &str = "0.0.0.0";
 
 /// Represents an enumeration of Application Modes
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 enum ApplicationModes {
     EmbeddedSession(u16),
+    FollowerSession(u16, String, u16),
     OfflineSession,
     RemoteSession(String, u16),
     StartupFailure(String),
@@ -74,7 +95,7 @@ impl ApplicationModes {
         match args.as_slice() {
             [_, port] if is_u16(port) =>
                 match parse_u16(port) {
-                    Ok(port) => ApplicationModes::RemoteSession(LOCAL_HOST, port),
+                    Ok(port) => ApplicationModes::RemoteSession(LOCAL_HOST.to_string(), port),
                     Err(err) => ApplicationModes::StartupFailure(err.to_string())
                 }
             [_, action, port] if action == "embedded" && is_u16(port) =>
@@ -82,6 +103,11 @@ impl ApplicationModes {
                     Ok(port) => ApplicationModes::EmbeddedSession(port),
                     Err(err) => ApplicationModes::StartupFailure(err.to_string())
                 }
+            [_, action, port, host, primary_port] if action == "follower" && is_u16(port) && is_u16(primary_port) =>
+                match (parse_u16(port), parse_u16(primary_port)) {
+                    (Ok(port), Ok(primary_port)) => ApplicationModes::FollowerSession(port, host.into(), primary_port),
+                    (Err(err), _) | (_, Err(err)) => ApplicationModes::StartupFailure(err.to_string())
+                }
             [_, host, port] if is_u16(port) =>
                 match parse_u16(port) {
                     Ok(port) => ApplicationModes::RemoteSession(host.into(), port),
@@ -104,8 +130,16 @@ async fn main() -> std::io::Result<()> {
     match ApplicationModes::parse(env::args().collect()) {
         ApplicationModes::EmbeddedSession(port) => {
             println!("Starting embedded Oxide service on port {port}...");
+            Transaction::recover()?;
+            start_http_server(port);
+            start_online_session(LOCAL_HOST, port).await?
+        }
+        ApplicationModes::FollowerSession(port, host, primary_port) => {
+            println!("Starting Oxide follower of {host}:{primary_port} on port {port}...");
+            Transaction::recover()?;
+            Replication::follow(host.as_str(), primary_port, Machine::oxide_home().as_str(), None)?;
             start_http_server(port);
-            start_online_session(LOCAL_HOST.as_str(), port).await?
+            start_online_session(LOCAL_HOST, port).await?
         }
         ApplicationModes::RemoteSession(host, port) => {
             println!("Connecting to remote Oxide service at {host}:{port}...");
@@ -113,6 +147,7 @@ async fn main() -> std::io::Result<()> {
         }
         ApplicationModes::OfflineSession => {
             println!("Starting offline Oxide service...");
+            Transaction::recover()?;
             start_offline_session()?
         }
         ApplicationModes::StartupFailure(message) => {
@@ -177,6 +212,14 @@ mod tests {
         assert_eq!(args, ApplicationModes::EmbeddedSession(8754));
     }
 
+    #[test]
+    fn test_parse_args_follower_session() {
+        let args = ApplicationModes::parse(vec![
+            "oxide".into(), "follower".into(), "8755".into(), "roadrunner.acme.com".into(), "8754".into()
+        ]);
+        assert_eq!(args, ApplicationModes::FollowerSession(8755, "roadrunner.acme.com".into(), 8754));
+    }
+
     #[test]
     fn test_parse_args_offline_session() {
         let args = ApplicationModes::parse(vec![
@@ -191,7 +234,7 @@ mod tests {
         let args = ApplicationModes::parse(vec![
             "oxide".into(), "8754".into()
         ]);
-        assert_eq!(args, ApplicationModes::RemoteSession(LOCAL_HOST, 8754));
+        assert_eq!(args, ApplicationModes::RemoteSession(LOCAL_HOST.to_string(), 8754));
     }
 
     #[test]
@@ -0,0 +1,255 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// materialized views module
+////////////////////////////////////////////////////////////////////
+
+use std::ops::Deref;
+
+use chrono::Local;
+
+use crate::aggregates::Aggregates;
+use crate::dataframe::Dataframe;
+use crate::dataframe::Dataframe::Model;
+use crate::errors::throw;
+use crate::errors::Errors::{Exact, TypeMismatch};
+use crate::errors::TypeMismatchErrors::CollectionExpected;
+use crate::expression::DatabaseOps::Queryable;
+use crate::expression::Expression::{AsValue, DatabaseOp, From, Literal, Ns};
+use crate::expression::{Expression, Queryables};
+use crate::file_row_collection::FileRowCollection;
+use crate::machine::Machine;
+use crate::model_row_collection::ModelRowCollection;
+use crate::namespaces::Namespace;
+use crate::object_config::{ObjectConfig, ViewSource};
+use crate::row_collection::RowCollection;
+use crate::row_history::RowHistory;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{ErrorValue, NamespaceValue, TableValue};
+
+/// Materialized view facility (e.g. "create materialized view ns('a.b.v') as select ...");
+/// the result of the query is stored within a table residing under the view's namespace,
+/// and is recomputed upon refresh (e.g. "refresh ns('a.b.v')").
+pub struct MaterializedViews;
+
+impl MaterializedViews {
+    /// Creates the materialized view, then populates it from the query
+    pub fn create(
+        ns: &Namespace,
+        query: &Expression,
+        refresh_interval: Option<i64>,
+    ) -> std::io::Result<TypedValue> {
+        let ms = Machine::new_platform();
+        let sources = Self::find_sources(&ms, query)?;
+        let df = Self::evaluate_query(&ms, query)?;
+        let columns = df.get_columns().iter().map(|c| c.to_parameter()).collect::<Vec<_>>();
+        let mut frc = FileRowCollection::create_table(ns, &columns)?;
+        let result = frc.append_rows(df.get_rows());
+        ObjectConfig::MaterializedViewConfig {
+            columns,
+            query: query.to_owned(),
+            refresh_interval,
+            last_refreshed: Local::now().timestamp_millis(),
+            sources,
+        }.save(ns)?;
+        Ok(result)
+    }
+
+    /// Indicates whether a scheduled refresh of the materialized view is due
+    pub fn is_refresh_due(config: &ObjectConfig) -> bool {
+        match config {
+            ObjectConfig::MaterializedViewConfig { refresh_interval: Some(interval), last_refreshed, .. } =>
+                Local::now().timestamp_millis() - last_refreshed >= *interval,
+            _ => false
+        }
+    }
+
+    /// Recomputes the materialized view; whenever the query merely filters and/or projects
+    /// a single source table, whose history shows that rows were only appended to it since
+    /// the last refresh (i.e. no prior row was updated or deleted), solely the newly appended
+    /// rows are evaluated.
+    pub fn refresh(ns: &Namespace) -> std::io::Result<TypedValue> {
+        let (columns, query, refresh_interval, old_sources) = match ObjectConfig::load(ns)? {
+            ObjectConfig::MaterializedViewConfig { columns, query, refresh_interval, sources, .. } =>
+                (columns, query, refresh_interval, sources),
+            _ => return throw(Exact(format!("{} is not a materialized view", ns)))
+        };
+
+        // recompute the view
+        let ms = Machine::new_platform();
+        let sources = Self::find_sources(&ms, &query)?;
+        let mut frc = FileRowCollection::open(ns)?;
+        let incremental_source = match Self::find_incremental_source(&query, &old_sources, &sources) {
+            Some((source_ns, watermark, version)) => match ObjectConfig::load(&source_ns) {
+                Ok(ObjectConfig::TableConfig { .. }) => {
+                    let source = FileRowCollection::open(&source_ns)?;
+                    Self::is_appended_since(&source, watermark, version)?.then_some((source, watermark))
+                }
+                _ => None
+            }
+            None => None
+        };
+        let result = match incremental_source {
+            Some((source, watermark)) => {
+                let mut new_rows = Vec::new();
+                for id in watermark..source.len()? {
+                    if let Some(row) = source.read_one(id)? {
+                        new_rows.push(Row::new(new_rows.len(), row.get_values()));
+                    }
+                }
+                let delta = Model(ModelRowCollection::from_columns_and_rows(source.get_columns(), &new_rows));
+                let df = Self::evaluate_query(&ms, &Self::with_source(&query, delta))?;
+                frc.append_rows(df.get_rows())
+            }
+            None => {
+                let df = Self::evaluate_query(&ms, &query)?;
+                if let ErrorValue(err) = frc.resize(0) { return throw(err); }
+                frc.append_rows(df.get_rows())
+            }
+        };
+
+        // record the refresh
+        ObjectConfig::MaterializedViewConfig {
+            columns,
+            query,
+            refresh_interval,
+            last_refreshed: Local::now().timestamp_millis(),
+            sources,
+        }.save(ns)?;
+        Ok(result)
+    }
+
+    fn evaluate_query(ms: &Machine, query: &Expression) -> std::io::Result<Dataframe> {
+        match ms.evaluate(query)? {
+            (_, ErrorValue(err)) => throw(err),
+            (_, TableValue(df)) => Ok(df),
+            (_, NamespaceValue(ns)) => Dataframe::open(&ns),
+            (_, z) => throw(TypeMismatch(CollectionExpected(z.to_code())))
+        }
+    }
+
+    /// Determines the source (the watermark from which its rows are new, and its version as of
+    /// the last refresh), provided the query permits the view to be refreshed incrementally
+    /// (e.g. "select symbol from ns('a.b.stocks') where ...").
+    fn find_incremental_source(
+        query: &Expression,
+        old_sources: &[ViewSource],
+        sources: &[ViewSource],
+    ) -> Option<(Namespace, usize, usize)> {
+        match query {
+            DatabaseOp(Queryable(Queryables::Select {
+                fields, from: Some(from), group_by: None, having: None, order_by: None, limit: None, ..
+            })) if matches!(from.deref(), Ns(..)) && !fields.iter().any(Aggregates::contains_any) =>
+                match (old_sources, sources) {
+                    ([old], [new]) if old.get_namespace() == new.get_namespace()
+                        && old.get_row_count() <= new.get_row_count() =>
+                        Namespace::parse(old.get_namespace()).ok()
+                            .map(|ns| (ns, old.get_row_count(), old.get_version())),
+                    _ => None
+                }
+            _ => None
+        }
+    }
+
+    /// Determines whether the rows of the source were only appended since the given version;
+    /// i.e. none of the rows preceding the watermark has since been written (e.g. updated or deleted).
+    fn is_appended_since(source: &FileRowCollection, watermark: usize, version: usize) -> std::io::Result<bool> {
+        Ok(RowHistory::read_writes(source, version)?.iter().all(|(id, _)| *id >= watermark))
+    }
+
+    /// Determines the namespaces read by the query, and their current number of rows (and versions)
+    fn find_sources(ms: &Machine, query: &Expression) -> std::io::Result<Vec<ViewSource>> {
+        fn gather(ms: &Machine, expr: &Expression, sources: &mut Vec<ViewSource>) -> std::io::Result<()> {
+            match expr {
+                AsValue(_, expr) | From(expr) => gather(ms, expr, sources),
+                DatabaseOp(Queryable(queryable)) => match queryable {
+                    Queryables::Join { left, right, .. } => {
+                        gather(ms, left, sources)?;
+                        gather(ms, right, sources)
+                    }
+                    Queryables::AsOf { from, .. } | Queryables::Limit { from, .. } | Queryables::Where { from, .. } =>
+                        gather(ms, from, sources),
+                    Queryables::Select { from: Some(from), .. } => gather(ms, from, sources),
+                    Queryables::Select { from: None, .. } => Ok(())
+                }
+                Ns(..) => {
+                    if let (_, NamespaceValue(ns)) = ms.evaluate(expr)? {
+                        let (row_count, version) = match ObjectConfig::load(&ns)? {
+                            ObjectConfig::ViewConfig { .. } => (0, 0),
+                            _ => {
+                                let frc = FileRowCollection::open(&ns)?;
+                                (frc.len()?, RowHistory::find_latest_version(&frc)?)
+                            }
+                        };
+                        sources.push(ViewSource::new(ns.id(), row_count, version));
+                    }
+                    Ok(())
+                }
+                _ => Ok(())
+            }
+        }
+        let mut sources = Vec::new();
+        gather(ms, query, &mut sources)?;
+        Ok(sources)
+    }
+
+    /// Substitutes the source of the query with the given table
+    fn with_source(query: &Expression, df: Dataframe) -> Expression {
+        match query {
+            DatabaseOp(Queryable(Queryables::Select { fields, condition, group_by, having, order_by, limit, .. })) =>
+                DatabaseOp(Queryable(Queryables::Select {
+                    fields: fields.to_owned(),
+                    from: Some(Box::new(Literal(TableValue(df)))),
+                    condition: condition.to_owned(),
+                    group_by: group_by.to_owned(),
+                    having: having.to_owned(),
+                    order_by: order_by.to_owned(),
+                    limit: limit.to_owned(),
+                })),
+            query => query.to_owned()
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::testdata::make_quote_parameters;
+
+    #[test]
+    fn test_find_incremental_source() {
+        let (stocks, other) = ("materialized.incremental.stocks", "materialized.incremental.other");
+        let sources = |name: &str, row_count: usize| vec![ViewSource::new(name.into(), row_count, 3)];
+        let query = Compiler::build(r#"
+            select symbol from ns("materialized.incremental.stocks") where last_sale > 1.0
+        "#).unwrap();
+        assert_eq!(
+            MaterializedViews::find_incremental_source(&query, &sources(stocks, 2), &sources(stocks, 5)),
+            Some((Namespace::parse(stocks).unwrap(), 2, 3)));
+        assert_eq!(MaterializedViews::find_incremental_source(&query, &sources(stocks, 5), &sources(stocks, 2)), None);
+        assert_eq!(MaterializedViews::find_incremental_source(&query, &sources(stocks, 2), &sources(other, 5)), None);
+
+        // ordered and aggregate queries are always recomputed
+        for code in [
+            r#"select symbol from ns("materialized.incremental.stocks") order by symbol"#,
+            r#"select total: count(symbol) from ns("materialized.incremental.stocks")"#,
+        ] {
+            let query = Compiler::build(code).unwrap();
+            assert_eq!(MaterializedViews::find_incremental_source(&query, &sources(stocks, 2), &sources(stocks, 5)), None);
+        }
+    }
+
+    #[test]
+    fn test_find_sources() {
+        let ns = Namespace::parse("materialized.sources.stocks").unwrap();
+        FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let query = Compiler::build(r#"
+            select symbol from ns("materialized.sources.stocks") where last_sale > 1.0
+        "#).unwrap();
+        assert_eq!(MaterializedViews::find_sources(&Machine::new_platform(), &query).unwrap(), vec![
+            ViewSource::new("materialized.sources.stocks".into(), 0, 0)
+        ]);
+    }
+}
@@ -0,0 +1,185 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// MemoryIndices class
+////////////////////////////////////////////////////////////////////
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::columns::Column;
+use crate::errors::throw;
+use crate::expression::Expression::{Condition, Literal, Variable};
+use crate::expression::{Conditions, Expression};
+use crate::machine::Machine;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::ErrorValue;
+
+/// Ephemeral hash indices of an in-memory table (e.g. "create index stocks on [symbol]");
+/// each maps the hash codes of a column's values onto the IDs of the rows containing them.
+/// As the indices are derived from the rows, they take part in neither comparisons nor serialization.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryIndices {
+    indices: Vec<(usize, HashMap<u64, Vec<usize>>)>,
+}
+
+impl MemoryIndices {
+    /// (Re)builds the hash index of the specified column from the given rows
+    pub fn create_index(&mut self, column_id: usize, rows: Vec<Row>) {
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        for row in rows {
+            buckets.entry(row[column_id].hash_code()).or_default().push(row.get_id());
+        }
+        self.indices.retain(|(id, _)| *id != column_id);
+        self.indices.push((column_id, buckets));
+    }
+
+    /// Decodes an equality condition between an indexed column and a constant;
+    /// returning the column's index and the key.
+    fn decode_indexed_key(
+        &self,
+        machine: &Machine,
+        columns: &[Column],
+        condition: &Conditions,
+    ) -> std::io::Result<Option<(usize, TypedValue)>> {
+        let is_constant = |expr: &Expression| match expr {
+            Literal(..) => true,
+            Variable(name) => !columns.iter().any(|c| c.get_name() == name),
+            _ => false
+        };
+        match condition {
+            Conditions::And(a, b) => {
+                for expr in [a, b] {
+                    if let Condition(condition) = expr.as_ref() {
+                        if let Some(result) = self.decode_indexed_key(machine, columns, condition)? {
+                            return Ok(Some(result));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+            Conditions::Equal(a, b) => {
+                for (field, value) in [(a, b), (b, a)] {
+                    if let Variable(name) = field.as_ref() {
+                        match self.get_indexed_column_ids().into_iter()
+                            .find(|id| columns[*id].get_name() == name) {
+                            Some(column_id) if is_constant(value) =>
+                                return match machine.evaluate(value)? {
+                                    (_, ErrorValue(err)) => throw(err),
+                                    (_, key) => Ok(Some((column_id, key)))
+                                },
+                            _ => {}
+                        }
+                    }
+                }
+                Ok(None)
+            }
+            _ => Ok(None)
+        }
+    }
+
+    /// Returns the IDs of the rows which may satisfy the condition via a hash index; provided
+    /// the condition compares an indexed column to a constant (e.g. "symbol == 'ABC'").
+    /// Returns [None] when no hash index is applicable.
+    pub fn find_row_ids_where(
+        &self,
+        machine: &Machine,
+        columns: &[Column],
+        condition: &Option<Conditions>,
+    ) -> std::io::Result<Option<Vec<usize>>> {
+        let (column_id, key) = match condition {
+            Some(condition) => match self.decode_indexed_key(machine, columns, condition)? {
+                Some(result) => result,
+                None => return Ok(None)
+            }
+            None => return Ok(None)
+        };
+        let mut row_ids = self.indices.iter()
+            .find(|(id, _)| *id == column_id)
+            .and_then(|(_, buckets)| buckets.get(&key.hash_code()))
+            .cloned()
+            .unwrap_or_default();
+        row_ids.sort();
+        row_ids.dedup();
+        Ok(Some(row_ids))
+    }
+
+    /// Returns the indices of the columns having a hash index
+    pub fn get_indexed_column_ids(&self) -> Vec<usize> {
+        self.indices.iter().map(|(column_id, _)| *column_id).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Brings the hash indices up to date with a change of the row
+    /// from its previous state (before) to its current state (after).
+    pub fn reconcile(&mut self, id: usize, before: &Option<Row>, after: &Option<Row>) {
+        for (column_id, buckets) in self.indices.iter_mut() {
+            let (old_key, new_key) =
+                (before.as_ref().map(|row| &row[*column_id]), after.as_ref().map(|row| &row[*column_id]));
+            if old_key == new_key { continue; }
+            if let Some(key) = old_key {
+                if let Some(row_ids) = buckets.get_mut(&key.hash_code()) {
+                    row_ids.retain(|row_id| *row_id != id);
+                }
+            }
+            if let Some(key) = new_key {
+                buckets.entry(key.hash_code()).or_default().push(id);
+            }
+        }
+    }
+}
+
+impl Eq for MemoryIndices {}
+
+impl Ord for MemoryIndices {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl PartialEq for MemoryIndices {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl PartialOrd for MemoryIndices {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use crate::expression::Conditions::Equal;
+    use crate::expression::Expression::{Literal, Variable};
+    use crate::machine::Machine;
+    use crate::memory_indices::MemoryIndices;
+    use crate::testdata::{make_quote, make_quote_columns};
+    use crate::typed_values::TypedValue::StringValue;
+
+    #[test]
+    fn test_create_index_then_reconcile() {
+        let mut indices = MemoryIndices::default();
+        indices.create_index(1, vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+        ]);
+        indices.reconcile(1, &Some(make_quote(1, "UNO", "OTC", 0.2456)), &Some(make_quote(1, "UNO", "NYSE", 0.2456)));
+        indices.reconcile(3, &Some(make_quote(3, "GOTO", "OTC", 0.1428)), &None);
+        let condition = |exchange: &str| Some(Equal(
+            Box::new(Literal(StringValue(exchange.into()))),
+            Box::new(Variable("exchange".into())),
+        ));
+        let (machine, columns) = (Machine::empty(), make_quote_columns());
+        assert_eq!(indices.find_row_ids_where(&machine, &columns, &condition("NYSE")).unwrap(), Some(vec![1, 2]));
+        assert_eq!(indices.find_row_ids_where(&machine, &columns, &condition("OTC")).unwrap(), Some(vec![]));
+        assert_eq!(indices.find_row_ids_where(&machine, &columns, &None).unwrap(), None);
+    }
+}
@@ -6,7 +6,10 @@
 use crate::byte_code_compiler::ByteCodeCompiler;
 use crate::columns::Column;
 use crate::descriptor::Descriptor;
+use crate::expression::Conditions;
 use crate::field::FieldMetadata;
+use crate::machine::Machine;
+use crate::memory_indices::MemoryIndices;
 use crate::numbers::Numbers;
 use crate::parameter::Parameter;
 use crate::row_collection::RowCollection;
@@ -23,6 +26,8 @@ pub struct ModelRowCollection {
     row_data: Vec<(Row, RowMetadata)>,
     record_size: usize,
     watermark: usize,
+    #[serde(skip)]
+    indices: MemoryIndices,
 }
 
 impl ModelRowCollection {
@@ -91,6 +96,7 @@ impl ModelRowCollection {
             watermark: 0,
             columns,
             row_data: Vec::new(),
+            indices: MemoryIndices::default(),
         }
     }
 
@@ -101,8 +107,37 @@ impl ModelRowCollection {
             watermark: row_data.len(),
             columns,
             row_data,
+            indices: MemoryIndices::default(),
         }
     }
+
+    /// Builds an ephemeral hash index upon the specified column
+    pub fn create_index(&mut self, column_id: usize) {
+        self.indices.create_index(column_id, self.get_rows());
+    }
+
+    /// Returns the IDs of the rows which may satisfy the condition via a hash index;
+    /// or [None] when no hash index is applicable.
+    pub fn find_row_ids_where(
+        &self,
+        machine: &Machine,
+        condition: &Option<Conditions>,
+    ) -> std::io::Result<Option<Vec<usize>>> {
+        self.indices.find_row_ids_where(machine, &self.columns, condition)
+    }
+
+    /// Performs the write operation upon the row, then reconciles the hash indices with its outcome.
+    fn write_with_indices<F>(&mut self, id: usize, write: F) -> TypedValue
+    where
+        F: FnOnce(&mut Self) -> TypedValue,
+    {
+        if self.indices.is_empty() { return write(self); }
+        let before = self.read_one(id).ok().flatten();
+        let outcome = write(self);
+        let after = self.read_one(id).ok().flatten();
+        self.indices.reconcile(id, &before, &after);
+        outcome
+    }
 }
 
 impl RowCollection for ModelRowCollection {
@@ -133,18 +168,20 @@ impl RowCollection for ModelRowCollection {
         column_id: usize,
         new_value: TypedValue,
     ) -> TypedValue {
-        let (row, meta) = &self.row_data[id];
-        let rows_affected = if meta.is_allocated {
-            let old_values = row.get_values();
-            let new_values = old_values.iter().enumerate()
-                .map(|(n, v)| {
-                    if n == column_id { new_value.to_owned() } else { v.to_owned() }
-                }).collect();
-            let new_row = Row::new(row.get_id(), new_values);
-            self.row_data[id] = (new_row, meta.to_owned());
-            1
-        } else { 0 };
-        Number(Numbers::RowsAffected(rows_affected))
+        self.write_with_indices(id, |mrc| {
+            let (row, meta) = &mrc.row_data[id];
+            let rows_affected = if meta.is_allocated {
+                let old_values = row.get_values();
+                let new_values = old_values.iter().enumerate()
+                    .map(|(n, v)| {
+                        if n == column_id { new_value.to_owned() } else { v.to_owned() }
+                    }).collect();
+                let new_row = Row::new(row.get_id(), new_values);
+                mrc.row_data[id] = (new_row, meta.to_owned());
+                1
+            } else { 0 };
+            Number(Numbers::RowsAffected(rows_affected))
+        })
     }
 
     fn overwrite_field_metadata(
@@ -153,37 +190,43 @@ impl RowCollection for ModelRowCollection {
         column_id: usize,
         metadata: FieldMetadata,
     ) -> TypedValue {
-        // get the old and new values
-        let (row, rmd) = &self.row_data[id];
-        let old_value = row[column_id].to_owned();
-        let new_value = if metadata.is_active { old_value } else { Null };
-
-        // build a new row
-        let mut new_values = row.get_values();
-        new_values[column_id] = new_value;
-        let new_row = row.with_values(new_values);
-
-        // update the row to reflect enabling/disabling a field
-        self.row_data[id] = (new_row, rmd.to_owned());
-        Number(Numbers::RowsAffected(1))
+        self.write_with_indices(id, |mrc| {
+            // get the old and new values
+            let (row, rmd) = &mrc.row_data[id];
+            let old_value = row[column_id].to_owned();
+            let new_value = if metadata.is_active { old_value } else { Null };
+
+            // build a new row
+            let mut new_values = row.get_values();
+            new_values[column_id] = new_value;
+            let new_row = row.with_values(new_values);
+
+            // update the row to reflect enabling/disabling a field
+            mrc.row_data[id] = (new_row, rmd.to_owned());
+            Number(Numbers::RowsAffected(1))
+        })
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
-        // resize the rows to prevent overflow
-        if self.row_data.len() <= id {
-            self.row_data.resize(id + 1, (Row::create(id, &self.columns), RowMetadata::new(false)));
-        }
+        self.write_with_indices(id, |mrc| {
+            // resize the rows to prevent overflow
+            if mrc.row_data.len() <= id {
+                mrc.row_data.resize(id + 1, (Row::create(id, &mrc.columns), RowMetadata::new(false)));
+            }
 
-        // set the block, update the watermark
-        self.row_data[id] = (row.with_row_id(id), RowMetadata::new(true));
-        if self.watermark <= id { self.watermark = id + 1; }
-        Number(Numbers::RowsAffected(1))
+            // set the block, update the watermark
+            mrc.row_data[id] = (row.with_row_id(id), RowMetadata::new(true));
+            if mrc.watermark <= id { mrc.watermark = id + 1; }
+            Number(Numbers::RowsAffected(1))
+        })
     }
 
     fn overwrite_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
-        let (row, _) = self.row_data[id].to_owned();
-        self.row_data[id] = (row, metadata.to_owned());
-        Number(Numbers::RowsAffected(1))
+        self.write_with_indices(id, |mrc| {
+            let (row, _) = mrc.row_data[id].to_owned();
+            mrc.row_data[id] = (row, metadata.to_owned());
+            Number(Numbers::RowsAffected(1))
+        })
     }
 
     fn read_field(&self, id: usize, column_id: usize) -> TypedValue {
@@ -222,6 +265,9 @@ impl RowCollection for ModelRowCollection {
     fn resize(&mut self, new_size: usize) -> TypedValue {
         self.row_data.resize(new_size, (Row::create(new_size, &self.columns), RowMetadata::new(true)));
         self.watermark = new_size;
+        for column_id in self.indices.get_indexed_column_ids() {
+            self.create_index(column_id);
+        }
         Number(Numbers::Ack)
     }
 }
@@ -232,6 +278,9 @@ mod tests {
     use crate::byte_code_compiler::ByteCodeCompiler;
     use crate::columns::Column;
     use crate::dataframe::Dataframe::Model;
+    use crate::expression::Conditions::Equal;
+    use crate::expression::Expression::{Literal, Variable};
+    use crate::machine::Machine;
     use crate::model_row_collection::ModelRowCollection;
     use crate::numbers::Numbers::U64Value;
     use crate::row_collection::RowCollection;
@@ -253,6 +302,20 @@ mod tests {
         assert_eq!(ModelRowCollection::decode(phys_columns, encoded), mrc)
     }
 
+    #[test]
+    fn test_find_row_ids_via_index() {
+        let (mut mrc, _) = create_data_set();
+        mrc.create_index(1);
+        mrc.overwrite_row(2, make_quote(2, "BIZ", "OTC", 9.775));
+        let condition = Some(Equal(
+            Box::new(Variable("exchange".into())),
+            Box::new(Literal(StringValue("OTC".into()))),
+        ));
+        let machine = Machine::empty();
+        assert_eq!(mrc.find_row_ids_where(&machine, &condition).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(mrc.find_row_ids_where(&machine, &None).unwrap(), None);
+    }
+
     #[test]
     fn test_from_table() {
         let rc: Box<dyn RowCollection> = Box::new(create_data_set().0);
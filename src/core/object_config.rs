@@ -3,23 +3,61 @@
 // ObjectConfig enumeration
 ////////////////////////////////////////////////////////////////////
 
+use std::collections::HashMap;
 use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
 
 use serde::{Deserialize, Serialize};
+use shared_lib::fail;
 
 use crate::cnv_error;
 use crate::descriptor::Descriptor;
+use crate::expression::Expression;
 use crate::namespaces::Namespace;
 use crate::object_config::ObjectConfig::TableConfig;
 use crate::parameter::Parameter;
 
+/// the number of times the configuration of each namespace has been saved by this process
+/// => (config file path|generation); see [ObjectConfig::get_generation].
+static GENERATIONS: OnceLock<Mutex<HashMap<String, u64>>> = OnceLock::new();
+
+/// the number of times any configuration has been saved or deleted by this process;
+/// see [ObjectConfig::get_catalog_generation].
+static CATALOG_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 /// Oxide Object Configuration
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum ObjectConfig {
+    MaterializedViewConfig {
+        columns: Vec<Parameter>,
+        query: Expression,
+        refresh_interval: Option<i64>,
+        last_refreshed: i64,
+        sources: Vec<ViewSource>,
+    },
     TableConfig {
         columns: Vec<Parameter>,
+        #[serde(default)]
+        constraints: Vec<ConstraintConfig>,
         indices: Vec<HashIndexConfig>,
+        /// indicates whether rows are only ever appended to the end of the table, rather than
+        /// filling the slots of deleted rows (e.g. "create table ... append only")
+        #[serde(default)]
+        is_append_only: bool,
+        /// indicates whether the rows are encrypted when written (e.g. "create table ... encrypted")
+        #[serde(default)]
+        is_encrypted: bool,
         partitions: Vec<String>,
+        #[serde(default)]
+        triggers: Vec<TriggerConfig>,
+    },
+    SequenceConfig {
+        increment: i64,
+        start: i64,
+    },
+    ViewConfig {
+        query: Expression,
     },
 }
 
@@ -28,23 +66,47 @@ impl ObjectConfig {
     pub fn build_table(columns: Vec<Parameter>) -> Self {
         ObjectConfig::TableConfig {
             columns,
+            constraints: vec![],
             indices: vec![],
+            is_append_only: false,
+            is_encrypted: false,
             partitions: vec![],
+            triggers: vec![],
         }
     }
 
+    /// instantiates a new sequence configuration.
+    pub fn build_sequence(start: i64, increment: i64) -> Self {
+        ObjectConfig::SequenceConfig { increment, start }
+    }
+
+    /// instantiates a new view configuration.
+    pub fn build_view(query: Expression) -> Self {
+        ObjectConfig::ViewConfig { query }
+    }
+
     /// Deletes a dataframe configuration from disk.
     pub fn delete(ns: &Namespace) -> std::io::Result<()> {
-        fs::remove_file(ns.get_config_file_path())
+        fs::remove_file(ns.get_config_file_path())?;
+        CATALOG_GENERATION.fetch_add(1, Ordering::SeqCst);
+        Ok(())
     }
 
     pub fn get_columns(&self) -> Vec<Parameter> {
         match self {
+            ObjectConfig::MaterializedViewConfig { columns, .. } => columns.clone(),
             ObjectConfig::TableConfig { columns, .. } => columns.clone(),
             _ => vec![]
         }
     }
 
+    pub fn get_constraints(&self) -> Vec<ConstraintConfig> {
+        match self {
+            ObjectConfig::TableConfig { constraints, .. } => constraints.clone(),
+            _ => vec![]
+        }
+    }
+
     pub fn get_indices(&self) -> Vec<HashIndexConfig> {
         match self {
             ObjectConfig::TableConfig { indices, .. } => indices.clone(),
@@ -54,6 +116,8 @@ impl ObjectConfig {
 
     pub fn get_descriptors(&self) -> Vec<Descriptor> {
         match self {
+            ObjectConfig::MaterializedViewConfig { columns, .. } =>
+                Descriptor::from_parameters(columns),
             ObjectConfig::TableConfig { columns, .. } =>
                 Descriptor::from_parameters(&columns),
             _ => vec![]
@@ -67,6 +131,50 @@ impl ObjectConfig {
         }
     }
 
+    pub fn get_triggers(&self) -> Vec<TriggerConfig> {
+        match self {
+            ObjectConfig::TableConfig { triggers, .. } => triggers.clone(),
+            _ => vec![]
+        }
+    }
+
+    /// Indicates whether rows are only ever appended to the end of the table
+    pub fn is_append_only(&self) -> bool {
+        match self {
+            ObjectConfig::TableConfig { is_append_only, .. } => *is_append_only,
+            _ => false
+        }
+    }
+
+    /// Indicates whether the rows of the table are encrypted when written
+    pub fn is_encrypted(&self) -> bool {
+        match self {
+            ObjectConfig::TableConfig { is_encrypted, .. } => *is_encrypted,
+            _ => false
+        }
+    }
+
+    /// Returns the number of times any configuration has been saved or deleted by this process;
+    /// which allows the objects of the home to be re-scanned only once they may have changed
+    /// (e.g. by a replication feed).
+    pub fn get_catalog_generation() -> u64 {
+        CATALOG_GENERATION.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of times the configuration of the namespace has been saved by this
+    /// process; which allows cached table handles (e.g. those of the dataframe actor) to detect that the
+    /// configuration (e.g. its indices or constraints) has changed since they were opened.
+    pub fn get_generation(ns: &Namespace) -> u64 {
+        Self::lock_generations()
+            .map(|generations| generations.get(&ns.get_config_file_path()).copied().unwrap_or(0))
+            .unwrap_or(0)
+    }
+
+    fn lock_generations() -> std::io::Result<MutexGuard<'static, HashMap<String, u64>>> {
+        GENERATIONS.get_or_init(|| Mutex::new(HashMap::new())).lock()
+            .or_else(|err| fail(err.to_string()))
+    }
+
     /// Loads a dataframe configuration from disk.
     pub fn load(ns: &Namespace) -> std::io::Result<Self> {
         let config_string = fs::read_to_string(ns.get_config_file_path())?;
@@ -77,30 +185,294 @@ impl ObjectConfig {
     pub fn save(&self, ns: &Namespace) -> std::io::Result<()> {
         let json_string = serde_json::to_string(&self)?;
         fs::create_dir_all(&ns.get_root_path())?;
-        fs::write(&ns.get_config_file_path(), json_string)
+        fs::write(ns.get_config_file_path(), json_string)?;
+        *Self::lock_generations()?.entry(ns.get_config_file_path()).or_insert(0) += 1;
+        CATALOG_GENERATION.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn with_constraints(self, constraints: Vec<ConstraintConfig>) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, indices, is_append_only, is_encrypted, partitions, triggers, .. } => {
+                TableConfig {
+                    columns,
+                    constraints,
+                    indices,
+                    is_append_only,
+                    is_encrypted,
+                    partitions,
+                    triggers,
+                }
+            }
+            config => config
+        }
+    }
+
+    pub fn with_append_only(self, is_append_only: bool) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, constraints, indices, is_encrypted, partitions, triggers, .. } => {
+                TableConfig {
+                    columns,
+                    constraints,
+                    indices,
+                    is_append_only,
+                    is_encrypted,
+                    partitions,
+                    triggers,
+                }
+            }
+            config => config
+        }
+    }
+
+    pub fn with_encrypted(self, is_encrypted: bool) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, constraints, indices, is_append_only, partitions, triggers, .. } => {
+                TableConfig {
+                    columns,
+                    constraints,
+                    indices,
+                    is_append_only,
+                    is_encrypted,
+                    partitions,
+                    triggers,
+                }
+            }
+            config => config
+        }
     }
 
     pub fn with_indices(self, indices: Vec<HashIndexConfig>) -> Self {
         match self {
-            ObjectConfig::TableConfig { columns, partitions, .. } => {
+            ObjectConfig::TableConfig { columns, constraints, is_append_only, is_encrypted, partitions, triggers, .. } => {
                 TableConfig {
                     columns,
+                    constraints,
                     indices,
+                    is_append_only,
+                    is_encrypted,
                     partitions,
+                    triggers,
                 }
             }
+            config => config
         }
     }
 
     pub fn with_partitions(self, partitions: Vec<String>) -> Self {
         match self {
-            ObjectConfig::TableConfig { columns, indices, .. } => {
+            ObjectConfig::TableConfig { columns, constraints, indices, is_append_only, is_encrypted, triggers, .. } => {
                 TableConfig {
                     columns,
+                    constraints,
                     indices,
+                    is_append_only,
+                    is_encrypted,
                     partitions,
+                    triggers,
                 }
             }
+            config => config
+        }
+    }
+
+    pub fn with_triggers(self, triggers: Vec<TriggerConfig>) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, constraints, indices, is_append_only, is_encrypted, partitions, .. } => {
+                TableConfig {
+                    columns,
+                    constraints,
+                    indices,
+                    is_append_only,
+                    is_encrypted,
+                    partitions,
+                    triggers,
+                }
+            }
+            config => config
+        }
+    }
+}
+
+/// Records a namespace read by a materialized view's query, and the number of rows
+/// it contained (and its version; i.e. that of its latest change) as of the last refresh.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct ViewSource {
+    namespace: String,
+    row_count: usize,
+    #[serde(default)]
+    version: usize,
+}
+
+impl ViewSource {
+    pub fn new(namespace: String, row_count: usize, version: usize) -> Self {
+        ViewSource { namespace, row_count, version }
+    }
+
+    pub fn get_namespace(&self) -> &String {
+        &self.namespace
+    }
+
+    pub fn get_row_count(&self) -> usize {
+        self.row_count
+    }
+
+    pub fn get_version(&self) -> usize {
+        self.version
+    }
+}
+
+/// Represents the constraints of a column (e.g. "last_sale: f64 not null check (last_sale > 0)")
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct ConstraintConfig {
+    column_name: String,
+    is_not_null: bool,
+    check: Option<Expression>,
+    /// indicates whether the column's values are compressed when written (e.g. "body: String(1024) compressed")
+    #[serde(default)]
+    is_compressed: bool,
+    /// indicates whether missing values are drawn from the column's sequence (e.g. "id: i64 serial")
+    #[serde(default)]
+    is_serial: bool,
+    /// the column of another table which the column references (e.g. "references ns('a.b.stocks')(symbol)")
+    #[serde(default)]
+    reference: Option<ReferenceConfig>,
+    /// the columns of other tables which reference the column
+    #[serde(default)]
+    referrers: Vec<ReferenceConfig>,
+}
+
+impl ConstraintConfig {
+    /// Creates a new column constraint configuration
+    pub fn new(column_name: String, is_not_null: bool, check: Option<Expression>) -> Self {
+        ConstraintConfig {
+            column_name,
+            is_not_null,
+            check,
+            is_compressed: false,
+            is_serial: false,
+            reference: None,
+            referrers: vec![],
+        }
+    }
+
+    pub fn get_check(&self) -> Option<&Expression> {
+        self.check.as_ref()
+    }
+
+    pub fn get_column_name(&self) -> &String {
+        &self.column_name
+    }
+
+    pub fn get_reference(&self) -> Option<&ReferenceConfig> {
+        self.reference.as_ref()
+    }
+
+    pub fn get_referrers(&self) -> &Vec<ReferenceConfig> {
+        &self.referrers
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.is_compressed
+    }
+
+    pub fn is_not_null(&self) -> bool {
+        self.is_not_null
+    }
+
+    pub fn is_serial(&self) -> bool {
+        self.is_serial
+    }
+
+    /// Returns the column modifiers (e.g. "not null check (last_sale > 0)")
+    pub fn to_code(&self) -> String {
+        let mut modifiers = Vec::new();
+        if self.is_serial { modifiers.push("serial".to_string()); }
+        if self.is_not_null { modifiers.push("not null".to_string()); }
+        if let Some(check) = &self.check { modifiers.push(format!("check ({})", check.to_code())); }
+        if let Some(reference) = &self.reference { modifiers.push(format!("references {}", reference.to_code())); }
+        if self.is_compressed { modifiers.push("compressed".to_string()); }
+        modifiers.join(" ")
+    }
+
+    pub fn with_column_name(self, column_name: String) -> Self {
+        ConstraintConfig { column_name, ..self }
+    }
+
+    pub fn with_compressed(self, is_compressed: bool) -> Self {
+        ConstraintConfig { is_compressed, ..self }
+    }
+
+    pub fn with_reference(self, reference: Option<ReferenceConfig>) -> Self {
+        ConstraintConfig { reference, ..self }
+    }
+
+    pub fn with_referrers(self, referrers: Vec<ReferenceConfig>) -> Self {
+        ConstraintConfig { referrers, ..self }
+    }
+
+    pub fn with_serial(self, is_serial: bool) -> Self {
+        ConstraintConfig { is_serial, ..self }
+    }
+}
+
+/// Represents the action taken upon the referencing rows when a referenced row is deleted
+/// (e.g. "references ns('a.b.stocks')(symbol) on delete cascade")
+#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum DeleteActions {
+    /// the referencing rows are deleted
+    Cascade,
+    /// the deletion is rejected while referencing rows exist
+    #[default]
+    Restrict,
+    /// the referencing columns are set to null
+    SetNull,
+}
+
+impl DeleteActions {
+    pub fn to_code(&self) -> String {
+        match self {
+            DeleteActions::Cascade => "cascade",
+            DeleteActions::Restrict => "restrict",
+            DeleteActions::SetNull => "set null",
+        }.to_string()
+    }
+}
+
+/// Represents a reference to (or from) the column of another table
+/// (e.g. "references ns('a.b.stocks')(symbol) on delete set null")
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct ReferenceConfig {
+    /// the namespace of the other table (e.g. "a.b.stocks")
+    table: String,
+    column_name: String,
+    #[serde(default)]
+    on_delete: DeleteActions,
+}
+
+impl ReferenceConfig {
+    /// Creates a new column reference configuration
+    pub fn new(table: String, column_name: String, on_delete: DeleteActions) -> Self {
+        ReferenceConfig { table, column_name, on_delete }
+    }
+
+    pub fn get_column_name(&self) -> &String {
+        &self.column_name
+    }
+
+    pub fn get_on_delete(&self) -> &DeleteActions {
+        &self.on_delete
+    }
+
+    pub fn get_table(&self) -> &String {
+        &self.table
+    }
+
+    /// Returns the reference (e.g. "ns(\"a.b.stocks\")(symbol) on delete cascade")
+    pub fn to_code(&self) -> String {
+        match self.on_delete {
+            DeleteActions::Restrict => format!("ns(\"{}\")({})", self.table, self.column_name),
+            ref action => format!("ns(\"{}\")({}) on delete {}", self.table, self.column_name, action.to_code()),
         }
     }
 }
@@ -109,12 +481,146 @@ impl ObjectConfig {
 pub struct HashIndexConfig {
     indexed_column_names: Vec<String>,
     is_unique: bool,
+    #[serde(default)]
+    kind: IndexKinds,
 }
 
 impl HashIndexConfig {
     /// Creates a new Hash-Index configuration
     pub fn new(indexed_column_names: Vec<String>, is_unique: bool) -> Self {
-        HashIndexConfig { indexed_column_names, is_unique }
+        HashIndexConfig { indexed_column_names, is_unique, kind: IndexKinds::Hash }
+    }
+
+    pub fn get_indexed_column_names(&self) -> &Vec<String> {
+        &self.indexed_column_names
+    }
+
+    pub fn get_kind(&self) -> &IndexKinds {
+        &self.kind
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.is_unique
+    }
+
+    pub fn with_kind(self, kind: IndexKinds) -> Self {
+        HashIndexConfig { kind, ..self }
+    }
+}
+
+/// Represents the kind of index (e.g. "create index stocks on [last_sale] using btree")
+#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum IndexKinds {
+    /// ordered (B-Tree) index; supports range conditions and ordered scans
+    BTree,
+    /// hash index; supports equality conditions
+    #[default]
+    Hash,
+}
+
+impl IndexKinds {
+    /// Parses an index kind (e.g. "btree")
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_lowercase().as_str() {
+            "btree" => Some(IndexKinds::BTree),
+            "hash" => Some(IndexKinds::Hash),
+            _ => None
+        }
+    }
+
+    pub fn to_code(&self) -> String {
+        match self {
+            IndexKinds::BTree => "btree",
+            IndexKinds::Hash => "hash",
+        }.to_string()
+    }
+}
+
+/// Represents a table trigger; an Oxide function which is invoked with each row
+/// written to the table (e.g. "create trigger audit after append on ns('a.b.stocks') do fn(row) => ...")
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct TriggerConfig {
+    name: String,
+    timing: TriggerTimings,
+    event: TriggerEvents,
+    function: Expression,
+}
+
+impl TriggerConfig {
+    /// Creates a new trigger configuration
+    pub fn new(name: String, timing: TriggerTimings, event: TriggerEvents, function: Expression) -> Self {
+        TriggerConfig { name, timing, event, function }
+    }
+
+    pub fn get_event(&self) -> &TriggerEvents {
+        &self.event
+    }
+
+    pub fn get_function(&self) -> &Expression {
+        &self.function
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_timing(&self) -> &TriggerTimings {
+        &self.timing
+    }
+}
+
+/// Represents the kind of write which fires a trigger (e.g. "before update")
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum TriggerEvents {
+    Append,
+    Delete,
+    Update,
+}
+
+impl TriggerEvents {
+    /// Parses a trigger event (e.g. "append")
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_lowercase().as_str() {
+            "append" => Some(TriggerEvents::Append),
+            "delete" => Some(TriggerEvents::Delete),
+            "update" => Some(TriggerEvents::Update),
+            _ => None
+        }
+    }
+
+    pub fn to_code(&self) -> String {
+        match self {
+            TriggerEvents::Append => "append",
+            TriggerEvents::Delete => "delete",
+            TriggerEvents::Update => "update",
+        }.to_string()
+    }
+}
+
+/// Represents when a trigger is fired relative to the write (e.g. "before update")
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum TriggerTimings {
+    /// fired prior to the write; the trigger may modify or reject the row
+    Before,
+    /// fired once the row has been written
+    After,
+}
+
+impl TriggerTimings {
+    /// Parses a trigger timing (e.g. "before")
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_lowercase().as_str() {
+            "after" => Some(TriggerTimings::After),
+            "before" => Some(TriggerTimings::Before),
+            _ => None
+        }
+    }
+
+    pub fn to_code(&self) -> String {
+        match self {
+            TriggerTimings::After => "after",
+            TriggerTimings::Before => "before",
+        }.to_string()
     }
 }
 
@@ -148,9 +654,33 @@ mod tests {
                 Parameter::new("exchange", StringType(8)),
                 Parameter::with_default("last_sale", NumberType(F64Kind), Number(F64Value(0.0))),
             ],
+            constraints: Vec::new(),
             indices: Vec::new(),
+            is_append_only: false,
+            is_encrypted: false,
             partitions: Vec::new(),
+            triggers: Vec::new(),
         });
         Ok(())
     }
+
+    #[test]
+    fn test_view_config_load_and_save() -> io::Result<()> {
+        let query = Expression::Variable("stocks".into());
+        let ns = Namespace::parse("securities.other_otc.stocks_view")?;
+        ObjectConfig::build_view(query.to_owned()).save(&ns)?;
+        let cfg = ObjectConfig::load(&ns)?;
+        assert_eq!(cfg, ObjectConfig::ViewConfig { query });
+        assert_eq!(cfg.get_columns(), Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_kind_defaults_to_hash() {
+        let config: HashIndexConfig = serde_json::from_str(
+            r#"{"indexed_column_names":["symbol"],"is_unique":false}"#).unwrap();
+        assert_eq!(config.get_kind(), &IndexKinds::Hash);
+        assert_eq!(IndexKinds::from_code("BTree"), Some(IndexKinds::BTree));
+        assert_eq!(config.with_kind(IndexKinds::BTree).get_kind().to_code(), "btree");
+    }
 }
\ No newline at end of file
@@ -14,6 +14,7 @@ use crate::expression::Expression::Literal;
 use crate::interpreter::Interpreter;
 use crate::namespaces::Namespace;
 use crate::object_config::ObjectConfig;
+use crate::replication::Replication;
 use crate::row_metadata::RowMetadata;
 use crate::server::SystemInfoJs;
 use crate::structures::Row;
@@ -108,7 +109,8 @@ pub async fn handle_index(_session: Session) -> impl Responder {
 pub async fn handle_config_delete(
     path: web::Path<(String, String, String)>
 ) -> impl Responder {
-    match ObjectConfig::delete(&Namespace::new(&path.0, &path.1, &path.2)) {
+    let ns = Namespace::new(&path.0, &path.1, &path.2);
+    match Replication::ensure_writable(&ns).and_then(|_| ObjectConfig::delete(&ns)) {
         Ok(_) => HttpResponse::Ok().finish(),
         Err(err) => {
             error!("error {}", err.to_string());
@@ -142,6 +144,7 @@ pub async fn handle_config_post(
                     data: web::Json<ObjectConfig>,
                     path: web::Path<(String, String, String)>) -> std::io::Result<usize> {
         let ns = Namespace::new(&path.0, &path.1, &path.2);
+        Replication::ensure_writable(&ns)?;
         Ok(create_table_from_config!(get_shared_state(&req)?.actor, ns, data.0)?)
     }
     match intern(req, data, path).await {
@@ -210,7 +213,7 @@ pub async fn handle_row_post(
         Ok(outcome) => HttpResponse::Ok().json(outcome),
         Err(err) => {
             error!("error {}", err.to_string());
-            HttpResponse::InternalServerError().finish()
+            HttpResponse::InternalServerError().body(err.to_string())
         }
     }
 }
@@ -226,7 +229,7 @@ pub async fn handle_row_put(
         Ok(outcome) => HttpResponse::Ok().json(outcome),
         Err(err) => {
             error!("error {}", err.to_string());
-            HttpResponse::InternalServerError().finish()
+            HttpResponse::InternalServerError().body(err.to_string())
         }
     }
 }
@@ -313,6 +316,7 @@ async fn append_row(
     path: web::Path<(String, String, String, usize)>,
 ) -> std::io::Result<usize> {
     let ns = Namespace::new(&path.0, &path.1, &path.2);
+    Replication::ensure_writable(&ns)?;
     let actor = get_shared_state(&req)?.actor.to_owned();
     let columns = get_columns!(actor, ns)?;
     append_row!(actor, ns, Row::from_json(&columns, &data.0))
@@ -323,6 +327,7 @@ async fn delete_row_by_id(
     path: web::Path<(String, String, String, usize)>,
 ) -> std::io::Result<usize> {
     let (ns, id) = (Namespace::new(&path.0, &path.1, &path.2), path.3);
+    Replication::ensure_writable(&ns)?;
     let actor = get_shared_state(&req)?.actor.to_owned();
     delete_row!(actor, ns, id)
 }
@@ -370,6 +375,7 @@ async fn overwrite_row_by_id(
     path: web::Path<(String, String, String, usize)>,
 ) -> std::io::Result<usize> {
     let (ns, id) = (Namespace::new(&path.0, &path.1, &path.2), path.3);
+    Replication::ensure_writable(&ns)?;
     let actor = get_shared_state(&req)?.actor.to_owned();
     let columns = get_columns!(actor, ns)?;
     overwrite_row!(actor, ns, Row::from_json(&columns, &data.0).with_row_id(id))
@@ -381,6 +387,7 @@ async fn update_row_by_id(
     path: web::Path<(String, String, String, usize)>,
 ) -> std::io::Result<usize> {
     let (ns, id) = (Namespace::new(&path.0, &path.1, &path.2), path.3);
+    Replication::ensure_writable(&ns)?;
     let actor = get_shared_state(&req)?.actor.to_owned();
     let columns = get_columns!(actor, ns)?;
     update_row!(actor, ns, Row::from_json(&columns, &data.0).with_row_id(id))
@@ -414,6 +421,8 @@ mod tests {
     use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 
     use super::*;
+    use crate::file_row_collection::FileRowCollection;
+    use crate::object_config::{ConstraintConfig, HashIndexConfig};
     use crate::testdata::make_quote_parameters;
     use crate::typed_values::TypedValue;
     use crate::typed_values::TypedValue::StringValue;
@@ -438,7 +447,7 @@ mod tests {
         let resp = test::call_service(&mut app, req).await;
         assert!(resp.status().is_success());
         let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
-        assert_eq!(body, r#"{"TableConfig":{"columns":[{"name":"symbol","data_type":{"StringType":8},"default_value":"Null"},{"name":"exchange","data_type":{"StringType":8},"default_value":"Null"},{"name":"last_sale","data_type":{"NumberType":"F64Kind"},"default_value":"Null"}],"indices":[],"partitions":[]}}"#);
+        assert_eq!(body, r#"{"TableConfig":{"columns":[{"name":"symbol","data_type":{"StringType":8},"default_value":"Null"},{"name":"exchange","data_type":{"StringType":8},"default_value":"Null"},{"name":"last_sale","data_type":{"NumberType":"F64Kind"},"default_value":"Null"}],"constraints":[],"indices":[],"is_append_only":false,"is_encrypted":false,"partitions":[],"triggers":[]}}"#);
 
         // DELETE the config
         let req = test::TestRequest::delete().uri(&ns_uri(database, schema, name)).to_request();
@@ -544,6 +553,98 @@ mod tests {
         assert_eq!(body, "{}");
     }
 
+    #[actix::test]
+    async fn test_unique_index_rejects_duplicates() {
+        // create a table having a unique index on symbol
+        let mut app = test::init_service(web_routes!(SharedState::new())).await;
+        let (database, schema, name) = ("web", "dataframe", "unique");
+        let ns = Namespace::new(database, schema, name);
+        FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        ObjectConfig::load(&ns).unwrap()
+            .with_indices(vec![HashIndexConfig::new(vec!["symbol".into()], true)])
+            .save(&ns).unwrap();
+
+        // POST a new stock quote
+        let req = test::TestRequest::post().uri(&row_uri(database, schema, name, 0))
+            .set_json(&json!({"symbol":"ATOM","exchange":"NYSE","last_sale":24.17}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+
+        // POST a stock quote having the same symbol
+        let req = test::TestRequest::post().uri(&row_uri(database, schema, name, 0))
+            .set_json(&json!({"symbol":"ATOM","exchange":"AMEX","last_sale":13.66}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_server_error());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert_eq!(body, r#"Unique key violation: (symbol) = ("ATOM") already exists"#);
+
+        // POST then PUT a stock quote having the same symbol
+        let req = test::TestRequest::post().uri(&row_uri(database, schema, name, 0))
+            .set_json(&json!({"symbol":"BABY","exchange":"NYSE","last_sale":13.66}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let req = test::TestRequest::put().uri(&row_uri(database, schema, name, 1))
+            .set_json(&json!({"symbol":"ATOM","exchange":"NYSE","last_sale":13.66}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_server_error());
+
+        // verify the rows are unchanged
+        let req = test::TestRequest::get().uri(&range_uri(database, schema, name, 0, 3)).to_request();
+        let resp = test::call_service(&mut app, req).await;
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let json_value: Value = serde_json::from_str(body.as_str()).unwrap();
+        assert_eq!(json_value, json!([
+            {"symbol":"ATOM","exchange":"NYSE","last_sale":24.17},
+            {"symbol":"BABY","exchange":"NYSE","last_sale":13.66}
+        ]));
+    }
+
+    #[actix::test]
+    async fn test_constraints_reject_invalid_rows() {
+        // create a table requiring a symbol
+        let mut app = test::init_service(web_routes!(SharedState::new())).await;
+        let (database, schema, name) = ("web", "dataframe", "constraints");
+        let ns = Namespace::new(database, schema, name);
+        FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        ObjectConfig::load(&ns).unwrap()
+            .with_constraints(vec![ConstraintConfig::new("symbol".into(), true, None)])
+            .save(&ns).unwrap();
+
+        // POST a stock quote without a symbol
+        let req = test::TestRequest::post().uri(&row_uri(database, schema, name, 0))
+            .set_json(&json!({"exchange":"NYSE","last_sale":24.17}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_server_error());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert_eq!(body, "Not null constraint violation: symbol cannot be null");
+
+        // POST a stock quote, then PUT it without a symbol
+        let req = test::TestRequest::post().uri(&row_uri(database, schema, name, 0))
+            .set_json(&json!({"symbol":"ATOM","exchange":"NYSE","last_sale":24.17}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let req = test::TestRequest::put().uri(&row_uri(database, schema, name, 0))
+            .set_json(&json!({"exchange":"AMEX","last_sale":13.66}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_server_error());
+
+        // verify the rows are unchanged
+        let req = test::TestRequest::get().uri(&range_uri(database, schema, name, 0, 2)).to_request();
+        let resp = test::call_service(&mut app, req).await;
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let json_value: Value = serde_json::from_str(body.as_str()).unwrap();
+        assert_eq!(json_value, json!([
+            {"symbol":"ATOM","exchange":"NYSE","last_sale":24.17}
+        ]));
+    }
+
     #[actix::test]
     async fn test_handle_system_info() {
         // set up the sessions
@@ -0,0 +1,625 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// PartitionedRowCollection class
+////////////////////////////////////////////////////////////////////
+
+use crate::cnv_error;
+use crate::columns::Column;
+use crate::dataframe::Dataframe;
+use crate::errors::{throw, Errors};
+use crate::expression::Expression::{Condition, Literal, Variable};
+use crate::expression::{Conditions, Expression};
+use crate::field::FieldMetadata;
+use crate::file_row_collection::FileRowCollection;
+use crate::foreign_keys::ForeignKeys;
+use crate::machine::Machine;
+use crate::namespaces::Namespace;
+use crate::numbers::Numbers::{Ack, RowsAffected};
+use crate::object_config::ObjectConfig;
+use crate::parameter::Parameter;
+use crate::row_collection::RowCollection;
+use crate::row_metadata::RowMetadata;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, Null, Number};
+use crate::write_ahead_log::WriteAheadLog;
+use serde::de::Error;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::ErrorKind;
+use std::os::unix::fs::FileExt;
+use std::sync::Arc;
+
+/// the size of a locator entry => (partition number + 1|local row ID)
+const LOCATOR_ENTRY_SIZE: usize = 16;
+
+/// Partitioned RowCollection implementation (e.g. "create table ns('a.b.stocks') (...) partition by [exchange]");
+/// the rows sharing a partition key (e.g. "NYSE") reside within their own table file under the
+/// namespace root (e.g. "stocks.part0"), whereas the locator file (e.g. "stocks.locator") maps
+/// each row ID onto its partition and its position within it.
+#[derive(Clone)]
+pub struct PartitionedRowCollection {
+    columns: Vec<Column>,
+    key_column_ids: Vec<usize>,
+    locator: Arc<File>,
+    /// the log of the table, via which the locator is written
+    locator_wal: WriteAheadLog,
+    next_partition_no: usize,
+    ns: Namespace,
+    partitions: Vec<Partition>,
+    table: FileRowCollection,
+}
+
+impl PartitionedRowCollection {
+    /// Creates a new table within the specified namespace, which is partitioned by the specified columns
+    pub fn create_table(
+        ns: &Namespace,
+        params: &Vec<Parameter>,
+        partition_columns: Vec<String>,
+    ) -> std::io::Result<Self> {
+        FileRowCollection::create_table(ns, params)?;
+        ObjectConfig::load(ns)?.with_partitions(partition_columns).save(ns)?;
+        OpenOptions::new().truncate(true).create(true).write(true)
+            .open(ns.get_file_path("locator"))?;
+        PartitionManifest::default().save(ns)?;
+        Self::open(ns)
+    }
+
+    /// Drops the partition having the specified key (e.g. ["NYSE"]), including its table file;
+    /// returning the number of rows removed.
+    pub fn drop_partition(&mut self, key: &[TypedValue]) -> std::io::Result<TypedValue> {
+        let index = match self.find_partition(key) {
+            Some(index) => index,
+            None => return Ok(Number(RowsAffected(0)))
+        };
+        let removed = self.detach_partition(index)?;
+        let partition = self.partitions.remove(index);
+        self.table.delete_related_table(&Self::get_extension(partition.partition_no))?;
+        self.save_manifest()?;
+        Ok(Number(RowsAffected(removed)))
+    }
+
+    /// Deletes the table files of all partitions, as well as the locator and manifest
+    pub fn drop_partitions(self) -> std::io::Result<()> {
+        for partition in self.partitions.iter() {
+            self.table.delete_related_table(&Self::get_extension(partition.partition_no))?;
+        }
+        fs::remove_file(self.ns.get_file_path("locator"))?;
+        fs::remove_file(self.ns.get_file_path("partitions"))
+    }
+
+    /// Returns the IDs of the rows residing within the partitions which may satisfy the condition
+    /// (e.g. "exchange == 'NYSE'"); or [None] when no partition can be ruled out.
+    pub fn find_row_ids_where(
+        &self,
+        machine: &Machine,
+        condition: &Option<Conditions>,
+    ) -> std::io::Result<Option<Vec<usize>>> {
+        let condition = match condition {
+            Some(condition) => condition,
+            None => return Ok(None)
+        };
+        let eligible = self.partitions.iter().enumerate()
+            .filter(|(_, partition)| {
+                let machine = self.key_column_ids.iter().zip(partition.key.iter())
+                    .fold(machine.to_owned(), |machine, (column_id, value)| {
+                        machine.with_variable(self.columns[*column_id].get_name(), value.to_owned())
+                    });
+                self.may_match(&machine, condition)
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if eligible.len() == self.partitions.len() { return Ok(None); }
+
+        // gather the IDs of the rows within the eligible partitions
+        let mut row_ids = Vec::new();
+        for id in 0..self.len()? {
+            if let Some((index, _)) = self.locate(id)? {
+                if eligible.contains(&index) { row_ids.push(id) }
+            }
+        }
+        Ok(Some(row_ids))
+    }
+
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        let config = ObjectConfig::load(ns)?;
+        let table = FileRowCollection::open(ns)?;
+        let columns = table.get_columns().to_owned();
+        let key_column_ids = config.get_partitions()
+            .map(|names| names.iter()
+                .filter_map(|name| columns.iter().position(|c| c.get_name() == name))
+                .collect())
+            .unwrap_or_default();
+        let locator = OpenOptions::new().create(true).truncate(false).read(true).write(true)
+            .open(ns.get_file_path("locator"))?;
+        let mut prc = Self {
+            columns,
+            key_column_ids,
+            locator: Arc::new(locator),
+            locator_wal: WriteAheadLog::open(&ns.get_table_file_path())?.for_file(&ns.get_file_path("locator")),
+            next_partition_no: 0,
+            ns: ns.to_owned(),
+            partitions: Vec::new(),
+            table,
+        };
+        prc.reload()?;
+        Ok(prc)
+    }
+
+    /// Removes all rows from the partition having the specified key (e.g. ["NYSE"]);
+    /// returning the number of rows removed.
+    pub fn truncate_partition(&mut self, key: &[TypedValue]) -> std::io::Result<TypedValue> {
+        let index = match self.find_partition(key) {
+            Some(index) => index,
+            None => return Ok(Number(RowsAffected(0)))
+        };
+        let removed = self.detach_partition(index)?;
+        if let ErrorValue(err) = self.partitions[index].frc.resize(0) { return throw(err); }
+        Ok(Number(RowsAffected(removed)))
+    }
+
+    /// Unlinks the rows of the partition from the locator; returning the number of active rows unlinked
+    fn detach_partition(&mut self, index: usize) -> std::io::Result<i64> {
+        let mut detached = 0;
+        for id in 0..self.len()? {
+            if let Some((row_index, local_id)) = self.locate(id)? {
+                if row_index == index {
+                    if self.partitions[index].frc.read_row_metadata(local_id)?.is_allocated {
+                        detached += 1;
+                    }
+                    self.write_locator(id, None)?;
+                }
+            }
+        }
+        Ok(detached)
+    }
+
+    /// Returns the position of the partition having the specified key; creating it if necessary
+    fn find_or_create_partition(&mut self, key: Vec<TypedValue>) -> std::io::Result<usize> {
+        if let Some(index) = self.find_partition(&key) { return Ok(index); }
+
+        // the partition may have been created elsewhere
+        self.reload()?;
+        if let Some(index) = self.find_partition(&key) { return Ok(index); }
+
+        let partition_no = self.next_partition_no;
+        let frc = self.table.create_related_table(self.columns.to_owned(), &Self::get_extension(partition_no))?;
+        self.partitions.push(Partition { key, partition_no, frc });
+        self.next_partition_no += 1;
+        self.save_manifest()?;
+        Ok(self.partitions.len() - 1)
+    }
+
+    fn find_partition(&self, key: &[TypedValue]) -> Option<usize> {
+        self.partitions.iter().position(|partition| partition.key == key)
+    }
+
+    fn get_extension(partition_no: usize) -> String {
+        format!("part{}", partition_no)
+    }
+
+    /// Indicates whether a condition solely references partition columns and constants
+    fn is_key_only(&self, condition: &Conditions) -> bool {
+        use Conditions::*;
+        let operands = match condition {
+            And(a, b) | Contains(a, b) | Equal(a, b) | GreaterOrEqual(a, b) | GreaterThan(a, b) |
+            LessOrEqual(a, b) | LessThan(a, b) | Like(a, b) | NotEqual(a, b) | Or(a, b) => vec![a, b],
+            Between(a, b, c) | Betwixt(a, b, c) => vec![a, b, c],
+            Not(a) => vec![a],
+            False | True => vec![],
+        };
+        operands.iter().all(|expr| match expr.as_ref() {
+            Condition(condition) => self.is_key_only(condition),
+            Literal(..) => true,
+            Variable(name) =>
+                self.key_column_ids.iter().any(|id| self.columns[*id].get_name() == name)
+                    || !self.columns.iter().any(|c| c.get_name() == name),
+            _ => false
+        })
+    }
+
+    fn key_of(&self, row: &Row) -> Vec<TypedValue> {
+        self.key_column_ids.iter().map(|id| row[*id].to_owned()).collect()
+    }
+
+    /// Returns the partition (index) and local row ID of the specified row ID
+    fn locate(&self, id: usize) -> std::io::Result<Option<(usize, usize)>> {
+        let mut buffer = [0u8; LOCATOR_ENTRY_SIZE];
+        let n_bytes = self.locator.read_at(&mut buffer, (id * LOCATOR_ENTRY_SIZE) as u64)?;
+        if n_bytes < LOCATOR_ENTRY_SIZE { return Ok(None); }
+        let (partition_no, local_id) = buffer.split_at(8);
+        let partition_no = u64::from_be_bytes(partition_no.try_into().map_err(|e| cnv_error!(e))?) as usize;
+        let local_id = u64::from_be_bytes(local_id.try_into().map_err(|e| cnv_error!(e))?) as usize;
+        if partition_no == 0 { return Ok(None); }
+        Ok(self.partitions.iter()
+            .position(|partition| partition.partition_no == partition_no - 1)
+            .map(|index| (index, local_id)))
+    }
+
+    /// Indicates whether rows having the partition key (bound within the machine) may satisfy
+    /// the condition; sub-conditions referencing non-partition columns are presumed satisfiable.
+    fn may_match(&self, machine: &Machine, condition: &Conditions) -> bool {
+        let may_match = |expr: &Expression| match expr {
+            Condition(condition) => self.may_match(machine, condition),
+            _ => true
+        };
+        match condition {
+            Conditions::And(a, b) => may_match(a) && may_match(b),
+            Conditions::Or(a, b) => may_match(a) || may_match(b),
+            condition if self.is_key_only(condition) =>
+                !matches!(machine.evaluate_cond(condition), Ok((_, Boolean(false)))),
+            _ => true
+        }
+    }
+
+    /// (Re)loads the partitions from the manifest
+    fn reload(&mut self) -> std::io::Result<()> {
+        let manifest = PartitionManifest::load(&self.ns)?;
+        let mut partitions = Vec::new();
+        for PartitionEntry { key, partition_no } in manifest.partitions {
+            let frc = self.table.open_related_table(self.columns.to_owned(), &Self::get_extension(partition_no))?;
+            partitions.push(Partition { key, partition_no, frc });
+        }
+        self.next_partition_no = manifest.next_partition_no;
+        self.partitions = partitions;
+        Ok(())
+    }
+
+    fn resize_partitions(&mut self, new_size: usize) -> std::io::Result<TypedValue> {
+        if new_size == 0 {
+            for partition in self.partitions.iter_mut() {
+                if let ErrorValue(err) = partition.frc.resize(0) { return throw(err); }
+            }
+        } else {
+            // unlink the truncated rows from their partitions
+            for id in new_size..self.len()? {
+                if let Some((index, local_id)) = self.locate(id)? {
+                    if let ErrorValue(err) = self.partitions[index].frc.delete_row(local_id) { return throw(err); }
+                }
+            }
+        }
+        self.locator_wal.set_len(&self.locator, (new_size * LOCATOR_ENTRY_SIZE) as u64)?;
+        Ok(Number(Ack))
+    }
+
+    fn save_manifest(&self) -> std::io::Result<()> {
+        PartitionManifest {
+            next_partition_no: self.next_partition_no,
+            partitions: self.partitions.iter().map(|partition| PartitionEntry {
+                key: partition.key.to_owned(),
+                partition_no: partition.partition_no,
+            }).collect(),
+        }.save(&self.ns)
+    }
+
+    fn write_locator(&self, id: usize, location: Option<(usize, usize)>) -> std::io::Result<()> {
+        let (partition_no, local_id) = match location {
+            Some((index, local_id)) => (self.partitions[index].partition_no + 1, local_id),
+            None => (0, 0)
+        };
+        let mut buffer = Vec::with_capacity(LOCATOR_ENTRY_SIZE);
+        buffer.extend((partition_no as u64).to_be_bytes());
+        buffer.extend((local_id as u64).to_be_bytes());
+        self.locator_wal.write_at(&self.locator, &buffer, (id * LOCATOR_ENTRY_SIZE) as u64)
+    }
+
+    /// Writes the row into the partition matching its key; should the key differ from that of
+    /// the partition currently containing the row, the row is moved into the matching partition.
+    fn write_row(&mut self, id: usize, row: Row) -> std::io::Result<TypedValue> {
+        let row = self.table.apply_defaults(row)?;
+        if let Some(err) = self.table.find_constraint_violation(&row)? {
+            return Ok(ErrorValue(err));
+        }
+        let index = self.find_or_create_partition(self.key_of(&row))?;
+        match self.locate(id)? {
+            Some((old_index, local_id)) if old_index == index =>
+                Ok(self.partitions[index].frc.overwrite_row(local_id, row.with_row_id(id))),
+            location => {
+                let local_id = self.partitions[index].frc.len()?;
+                let result = self.partitions[index].frc.overwrite_row(local_id, row.with_row_id(id));
+                if let ErrorValue(err) = result { return throw(err); }
+                self.write_locator(id, Some((index, local_id)))?;
+                if let Some((old_index, old_local_id)) = location {
+                    if let ErrorValue(err) = self.partitions[old_index].frc.delete_row(old_local_id) {
+                        return throw(err);
+                    }
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    /// Writes the metadata of the row to its partition
+    fn write_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
+        match self.locate(id) {
+            Ok(Some((index, local_id))) =>
+                self.partitions[index].frc.overwrite_row_metadata(local_id, metadata),
+            Ok(None) => Number(RowsAffected(0)),
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+}
+
+impl Eq for PartitionedRowCollection {}
+
+impl Ord for PartitionedRowCollection {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ns.cmp(&other.ns)
+    }
+}
+
+impl PartialEq for PartitionedRowCollection {
+    fn eq(&self, other: &Self) -> bool {
+        self.ns == other.ns
+    }
+}
+
+impl PartialOrd for PartitionedRowCollection {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Debug for PartitionedRowCollection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PartitionedRowCollection({})", self.ns)
+    }
+}
+
+impl RowCollection for PartitionedRowCollection {
+    fn get_columns(&self) -> &Vec<Column> { &self.columns }
+
+    fn get_record_size(&self) -> usize { self.table.get_record_size() }
+
+    fn get_rows(&self) -> Vec<Row> {
+        self.iter().collect()
+    }
+
+    fn len(&self) -> std::io::Result<usize> {
+        Ok((self.locator.metadata()?.len() as usize) / LOCATOR_ENTRY_SIZE)
+    }
+
+    fn overwrite_field(&mut self, id: usize, column_id: usize, new_value: TypedValue) -> TypedValue {
+        // changing a partition column moves the row into another partition, whereas
+        // the constraints of the table are verified against the row as a whole
+        if self.key_column_ids.contains(&column_id) || self.table.has_constraints() {
+            return match self.read_one(id) {
+                Ok(Some(row)) => {
+                    let mut values = row.get_values();
+                    values[column_id] = new_value;
+                    self.overwrite_row(id, Row::new(id, values))
+                }
+                Ok(None) => Number(RowsAffected(0)),
+                Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+            };
+        }
+        match self.locate(id) {
+            Ok(Some((index, local_id))) =>
+                self.partitions[index].frc.overwrite_field(local_id, column_id, new_value),
+            Ok(None) => Number(RowsAffected(0)),
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    fn overwrite_field_metadata(&mut self, id: usize, column_id: usize, metadata: FieldMetadata) -> TypedValue {
+        match self.locate(id) {
+            Ok(Some((index, local_id))) =>
+                self.partitions[index].frc.overwrite_field_metadata(local_id, column_id, metadata),
+            Ok(None) => Number(RowsAffected(0)),
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
+        TypedValue::from_result(self.write_row(id, row))
+    }
+
+    fn overwrite_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
+        // deleting a referenced row affects the rows referencing it
+        if !metadata.is_allocated && self.table.has_referrers() {
+            let table = Dataframe::Partitioned(Box::new(self.clone()));
+            let constraints = self.table.get_constraints().to_owned();
+            return ForeignKeys::delete_row(&table, &constraints, id, ||
+                self.write_row_metadata(id, metadata));
+        }
+        self.write_row_metadata(id, metadata)
+    }
+
+    fn read_field(&self, id: usize, column_id: usize) -> TypedValue {
+        match self.locate(id) {
+            Ok(Some((index, local_id))) => self.partitions[index].frc.read_field(local_id, column_id),
+            Ok(None) => Null,
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    fn read_field_metadata(&self, id: usize, column_id: usize) -> std::io::Result<FieldMetadata> {
+        match self.locate(id)? {
+            Some((index, local_id)) => self.partitions[index].frc.read_field_metadata(local_id, column_id),
+            None => Ok(FieldMetadata::new(false))
+        }
+    }
+
+    fn read_row(&self, id: usize) -> std::io::Result<(Row, RowMetadata)> {
+        match self.locate(id)? {
+            Some((index, local_id)) => {
+                let (row, rmd) = self.partitions[index].frc.read_row(local_id)?;
+                Ok((row.with_row_id(id), rmd))
+            }
+            None => Ok((Row::create(id, &self.columns), RowMetadata::new(false)))
+        }
+    }
+
+    fn read_row_metadata(&self, id: usize) -> std::io::Result<RowMetadata> {
+        match self.locate(id)? {
+            Some((index, local_id)) => self.partitions[index].frc.read_row_metadata(local_id),
+            None => Ok(RowMetadata::new(false))
+        }
+    }
+
+    fn resize(&mut self, new_size: usize) -> TypedValue {
+        TypedValue::from_result(self.resize_partitions(new_size))
+    }
+}
+
+impl Serialize for PartitionedRowCollection {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("PartitionedRowCollection", 1)?;
+        state.serialize_field("ns", &self.ns)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for PartitionedRowCollection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // define a helper struct for deserialization
+        #[derive(Deserialize)]
+        struct PartitionedRowCollectionHelper {
+            ns: Namespace,
+        }
+
+        let helper = PartitionedRowCollectionHelper::deserialize(deserializer)?;
+        PartitionedRowCollection::open(&helper.ns).map_err(D::Error::custom)
+    }
+}
+
+/// Represents a partition and its table file
+#[derive(Clone)]
+struct Partition {
+    key: Vec<TypedValue>,
+    partition_no: usize,
+    frc: FileRowCollection,
+}
+
+/// Records the partitions of a table (e.g. "stocks.partitions")
+#[derive(Default, Serialize, Deserialize)]
+struct PartitionManifest {
+    next_partition_no: usize,
+    partitions: Vec<PartitionEntry>,
+}
+
+impl PartitionManifest {
+    fn load(ns: &Namespace) -> std::io::Result<Self> {
+        match fs::read_to_string(ns.get_file_path("partitions")) {
+            Ok(json_string) => serde_json::from_str(&json_string).map_err(|e| cnv_error!(e)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err)
+        }
+    }
+
+    fn save(&self, ns: &Namespace) -> std::io::Result<()> {
+        let json_string = serde_json::to_string(&self)?;
+        fs::create_dir_all(ns.get_root_path())?;
+        fs::write(ns.get_file_path("partitions"), json_string)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartitionEntry {
+    key: Vec<TypedValue>,
+    partition_no: usize,
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::expression::Expression::Condition;
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use crate::typed_values::TypedValue::StringValue;
+
+    #[test]
+    fn test_append_then_read_rows() {
+        let ns = Namespace::parse("prc.routing.stocks").unwrap();
+        let mut prc = PartitionedRowCollection::create_table(
+            &ns, &make_quote_parameters(), vec!["exchange".into()]).unwrap();
+        prc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+        ]);
+        for partition_no in 0..3 {
+            assert!(std::path::Path::new(&ns.get_file_path(&format!("part{}", partition_no))).exists());
+        }
+
+        // the rows retain their IDs across partitions
+        let prc = PartitionedRowCollection::open(&ns).unwrap();
+        assert_eq!(prc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+        ]);
+    }
+
+    #[test]
+    fn test_drop_and_truncate_partitions() {
+        let ns = Namespace::parse("prc.retention.stocks").unwrap();
+        let mut prc = PartitionedRowCollection::create_table(
+            &ns, &make_quote_parameters(), vec!["exchange".into()]).unwrap();
+        prc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+        ]);
+        assert_eq!(prc.drop_partition(&[StringValue("OTC".into())]).unwrap(), Number(RowsAffected(2)));
+        assert!(!std::path::Path::new(&ns.get_file_path("part1")).exists());
+        assert_eq!(prc.truncate_partition(&[StringValue("AMEX".into())]).unwrap(), Number(RowsAffected(1)));
+        assert_eq!(prc.truncate_partition(&[StringValue("OTC".into())]).unwrap(), Number(RowsAffected(0)));
+        assert_eq!(prc.read_active_rows().unwrap(), vec![
+            make_quote(2, "BIZ", "NYSE", 23.66),
+        ]);
+    }
+
+    #[test]
+    fn test_move_row_between_partitions() {
+        let ns = Namespace::parse("prc.moves.stocks").unwrap();
+        let mut prc = PartitionedRowCollection::create_table(
+            &ns, &make_quote_parameters(), vec!["exchange".into()]).unwrap();
+        prc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+        prc.overwrite_field(1, 1, StringValue("AMEX".into()));
+        assert_eq!(prc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "AMEX", 0.2456),
+        ]);
+        assert_eq!(prc.truncate_partition(&[StringValue("OTC".into())]).unwrap(), Number(RowsAffected(0)));
+    }
+
+    #[test]
+    fn test_find_row_ids_where() {
+        let ns = Namespace::parse("prc.pruning.stocks").unwrap();
+        let mut prc = PartitionedRowCollection::create_table(
+            &ns, &make_quote_parameters(), vec!["exchange".into()]).unwrap();
+        prc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+        ]);
+        let find = |code: &str| match Compiler::build(code).unwrap() {
+            Condition(condition) => prc.find_row_ids_where(&Machine::empty(), &Some(condition)).unwrap(),
+            other => panic!("Expected condition, got {:?}", other)
+        };
+        assert_eq!(find(r#"exchange == "OTC""#), Some(vec![1, 3]));
+        assert_eq!(find(r#"(exchange == "OTC") && (last_sale > 0.2)"#), Some(vec![1, 3]));
+        assert_eq!(find(r#"(exchange == "AMEX") || (exchange == "NYSE")"#), Some(vec![0, 2]));
+        assert_eq!(find(r#"(exchange == "OTC") || (last_sale > 0.2)"#), None);
+        assert_eq!(find("last_sale > 0.2"), None);
+    }
+}
@@ -10,6 +10,7 @@ use crate::compiler::Compiler;
 use crate::data_types::DataType;
 use crate::data_types::DataType::*;
 
+use crate::dataframe::Dataframe;
 use crate::dataframe::Dataframe::{Disk, Model};
 use crate::descriptor::Descriptor;
 use crate::errors::Errors::*;
@@ -24,7 +25,9 @@ use crate::numbers::Numbers::*;
 use crate::oxide_server;
 use crate::parameter::Parameter;
 use crate::platform::PlatformOps::*;
+use crate::replication::Replication;
 use crate::row_collection::RowCollection;
+use crate::row_history::{RowHistory, HISTORY_RETENTION_MILLIS};
 use crate::structures::Row;
 use crate::structures::Structure;
 use crate::structures::Structures::{Hard, Soft};
@@ -93,6 +96,7 @@ pub enum PlatformOps {
     OxideHistory,
     OxideHome,
     OxidePrintln,
+    OxidePromote,
     OxideReset,
     OxideUUID,
     OxideVersion,
@@ -109,9 +113,12 @@ pub enum PlatformOps {
     StrSubstring,
     StrToString,
     // tools package
+    ToolsChanges,
     ToolsCompact,
     ToolsDescribe,
     ToolsFetch,
+    ToolsHistory,
+    ToolsReclaim,
     ToolsReverse,
     ToolsScan,
     ToolsToArray,
@@ -143,7 +150,7 @@ pub enum PlatformOps {
     WwwURLEncode,
 }
 
-pub const PLATFORM_OPCODES: [PlatformOps; 71] = {
+pub const PLATFORM_OPCODES: [PlatformOps; 75] = {
     use PlatformOps::*;
     [
         // cal
@@ -157,13 +164,13 @@ pub const PLATFORM_OPCODES: [PlatformOps; 71] = {
         OsCall, OsClear, OsCurrentDir, OsEnv,
         // oxide
         OxideCompile, OxideEval, OxideHelp, OxideHistory, OxideHome,
-        OxidePrintln, OxideReset, OxideUUID, OxideVersion,
+        OxidePrintln, OxidePromote, OxideReset, OxideUUID, OxideVersion,
         // str
         StrEndsWith, StrFormat, StrIndexOf, StrJoin, StrLeft, StrLen,
         StrRight, StrSplit, StrStartsWith, StrSubstring, StrToString,
         // tools
-        ToolsCompact, ToolsDescribe, ToolsFetch, ToolsReverse, ToolsScan,
-        ToolsToArray, ToolsToCSV, ToolsToJSON, ToolsToTable,
+        ToolsChanges, ToolsCompact, ToolsDescribe, ToolsFetch, ToolsHistory,
+        ToolsReclaim, ToolsReverse, ToolsScan, ToolsToArray, ToolsToCSV, ToolsToJSON, ToolsToTable,
         // util
         UtilBase64, UtilBinary, UtilHex, UtilMD5, UtilToASCII, UtilToDate,
         UtilToF32, UtilToF64,
@@ -224,6 +231,7 @@ impl PlatformOps {
             PlatformOps::OxideHistory => Self::do_oxide_history(ms, args),
             PlatformOps::OxideHome => self.adapter_fn0(ms, args, Self::do_oxide_home),
             PlatformOps::OxidePrintln => self.adapter_fn1(ms, args, Self::do_io_stdout),
+            PlatformOps::OxidePromote => self.adapter_fn0(ms, args, Self::do_oxide_promote),
             PlatformOps::OxideReset => self.adapter_fn0(ms, args, Self::do_oxide_reset),
             PlatformOps::OxideUUID => Self::do_util_uuid(ms, args),
             PlatformOps::OxideVersion => self.adapter_fn0(ms, args, Self::do_oxide_version),
@@ -238,9 +246,12 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => self.adapter_fn2(ms, args, Self::do_str_start_with),
             PlatformOps::StrSubstring => self.adapter_fn3(ms, args, Self::do_str_substring),
             PlatformOps::StrToString => self.adapter_fn1(ms, args, Self::do_str_to_string),
+            PlatformOps::ToolsChanges => self.adapter_fn2(ms, args, Self::do_tools_changes),
             PlatformOps::ToolsCompact => self.adapter_fn1(ms, args, Self::do_tools_compact),
             PlatformOps::ToolsDescribe => self.adapter_fn1(ms, args, Self::do_tools_describe),
             PlatformOps::ToolsFetch => self.adapter_fn2(ms, args, Self::do_tools_fetch),
+            PlatformOps::ToolsHistory => self.adapter_fn2(ms, args, Self::do_tools_history),
+            PlatformOps::ToolsReclaim => self.adapter_fn1(ms, args, Self::do_tools_reclaim),
             PlatformOps::ToolsReverse => self.adapter_fn1(ms, args, Self::do_tools_reverse),
             PlatformOps::ToolsScan => self.adapter_fn1(ms, args, Self::do_tools_scan),
             PlatformOps::ToolsToArray => self.adapter_fn1(ms, args, Self::do_tools_to_array),
@@ -306,6 +317,7 @@ impl PlatformOps {
             PlatformOps::OxideHelp => "Integrated help function",
             PlatformOps::OxideHistory => "Returns all commands successfully executed during the session",
             PlatformOps::OxideHome => "Returns the Oxide home directory path",
+            PlatformOps::OxidePromote => "Promotes a follower to a primary; making its tables writable",
             PlatformOps::OxideReset => "Clears the scope of all user-defined objects",
             PlatformOps::OxideUUID => "Returns a random 128-bit UUID",
             PlatformOps::OxideVersion => "Returns the Oxide version",
@@ -320,9 +332,12 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => "Returns true if string `a` starts with string `b`",
             PlatformOps::StrSubstring => "Returns a substring of string `s` from `m` to `n`",
             PlatformOps::StrToString => "Converts a value to its text-based representation",
-            PlatformOps::ToolsCompact => "Shrinks a table by removing deleted rows",
+            PlatformOps::ToolsChanges => "Returns the changes made to a table since a sequence number",
+            PlatformOps::ToolsCompact => "Shrinks a table by removing deleted rows and unused BLOB space",
             PlatformOps::ToolsDescribe => "Describes a table or structure",
             PlatformOps::ToolsFetch => "Retrieves a raw structure from a table",
+            PlatformOps::ToolsHistory => "Returns every past value of a row of a table",
+            PlatformOps::ToolsReclaim => "Releases the unreferenced BLOB space of a table for reuse",
             PlatformOps::ToolsReverse => "Returns a reverse copy of a table, string or array",
             PlatformOps::ToolsScan => "Returns existence metadata for a table",
             PlatformOps::ToolsToArray => "Converts a collection into an array",
@@ -464,6 +479,7 @@ impl PlatformOps {
             PlatformOps::OxideHelp => r#"from oxide::help() limit 3"#,
             PlatformOps::OxideHistory => "from oxide::history() limit 3",
             PlatformOps::OxideHome => "oxide::home()",
+            PlatformOps::OxidePromote => "oxide::promote()",
             PlatformOps::OxideReset => "oxide::reset()",
             PlatformOps::OxideUUID => "oxide::uuid()",
             PlatformOps::OxideVersion => "oxide::version()",
@@ -478,6 +494,16 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => "str::starts_with('Hello World', 'World')",
             PlatformOps::StrSubstring => "str::substring('Hello World', 0, 5)",
             PlatformOps::StrToString => "str::to_string(125.75)",
+            PlatformOps::ToolsChanges => r#"
+                [+] import tools
+                [+] stocks := ns("platform.changes.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "BOOM", exchange: "NYSE", last_sale: 56.88 }] ~> stocks
+                [+] update stocks via { last_sale: 13.07 } where symbol == "ABC"
+                [+] delete from stocks where symbol == "BOOM"
+                [+] select _sequence, _event, _row_id from changes(stocks, 1)
+            "#,
             PlatformOps::ToolsCompact => r#"
                 [+] stocks := ns("platform.compact.stocks")
                 [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
@@ -502,6 +528,25 @@ impl PlatformOps {
                      { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
                 [+] tools::fetch(stocks, 2)
             "#,
+            PlatformOps::ToolsHistory => r#"
+                [+] import tools
+                [+] stocks := ns("platform.history.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "BOOM", exchange: "NYSE", last_sale: 56.88 }] ~> stocks
+                [+] update stocks via { last_sale: 13.07 } where symbol == "ABC"
+                [+] delete from stocks where symbol == "ABC"
+                [+] history(stocks, 0)
+            "#,
+            PlatformOps::ToolsReclaim => r#"
+                [+] import tools
+                [+] stocks := ns("platform.reclaim.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "BOOM_BOOM_BOOM", exchange: "NYSE", last_sale: 56.88 }] ~> stocks
+                [+] truncate stocks limit 1
+                [+] stocks:::reclaim()
+            "#,
             PlatformOps::ToolsReverse => r#"
                 import tools
                 to_table(reverse(['cat', 'dog', 'ferret', 'mouse']))
@@ -619,6 +664,7 @@ impl PlatformOps {
             PlatformOps::OxideHistory => "history",
             PlatformOps::OxideHome => "home",
             PlatformOps::OxidePrintln => "println",
+            PlatformOps::OxidePromote => "promote",
             PlatformOps::OxideReset => "reset",
             PlatformOps::KungFuTypeOf => "type_of",
             PlatformOps::OxideUUID => "uuid",
@@ -634,9 +680,12 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => "starts_with",
             PlatformOps::StrSubstring => "substring",
             PlatformOps::StrToString => "to_string",
+            PlatformOps::ToolsChanges => "changes",
             PlatformOps::ToolsCompact => "compact",
             PlatformOps::ToolsDescribe => "describe",
             PlatformOps::ToolsFetch => "fetch",
+            PlatformOps::ToolsHistory => "history",
+            PlatformOps::ToolsReclaim => "reclaim",
             PlatformOps::ToolsReverse => "reverse",
             PlatformOps::ToolsScan => "scan",
             PlatformOps::ToolsToArray => "to_array",
@@ -680,7 +729,7 @@ impl PlatformOps {
             KungFuAssert | KungFuFeature | KungFuMatches | KungFuTypeOf => "kungfu",
             // oxide
             OxideCompile | OxideEval | OxideHelp | OxideHistory |
-            OxideHome | OxidePrintln | OxideReset | OxideUUID | OxideVersion => "oxide",
+            OxideHome | OxidePrintln | OxidePromote | OxideReset | OxideUUID | OxideVersion => "oxide",
             // os
             OsCall | OsClear | OsCurrentDir | OsEnv => "os",
             // str
@@ -688,8 +737,8 @@ impl PlatformOps {
             StrLeft | StrLen | StrRight | StrSplit |
             StrStartsWith | StrSubstring | StrToString => "str",
             // tools
-            ToolsCompact | ToolsDescribe | ToolsFetch | ToolsReverse | ToolsScan |
-            ToolsToArray | ToolsToCSV | ToolsToJSON | ToolsToTable => "tools",
+            ToolsChanges | ToolsCompact | ToolsDescribe | ToolsFetch | ToolsHistory | ToolsReclaim |
+            ToolsReverse | ToolsScan | ToolsToArray | ToolsToCSV | ToolsToJSON | ToolsToTable => "tools",
             // util
             UtilBase64 | UtilBinary | UtilHex | UtilMD5 | UtilToASCII | UtilToDate |
             UtilToF32 | UtilToF64 |
@@ -705,7 +754,7 @@ impl PlatformOps {
         use PlatformOps::*;
         match self {
             // zero-parameter
-            OsCurrentDir | OsEnv | OxideHome | OxideReset | OsClear |
+            OsCurrentDir | OsEnv | OxideHome | OxidePromote | OxideReset | OsClear |
             OxideHelp | OxideHistory | OxideVersion | CalDate | OxideUUID
             => vec![],
             // single-parameter (boolean)
@@ -728,7 +777,7 @@ impl PlatformOps {
             OxideCompile | OxideEval | StrLen | WwwURLDecode | WwwURLEncode
             => vec![StringType(0)],
             // single-parameter (table)
-            ToolsCompact | ToolsDescribe | ToolsReverse | ToolsScan |
+            ToolsCompact | ToolsDescribe | ToolsReclaim | ToolsReverse | ToolsScan |
             ToolsToArray | ToolsToCSV | ToolsToJSON
             => vec![TableType(Vec::new(), 0)],
             // two-parameter (lazy, lazy)
@@ -744,7 +793,7 @@ impl PlatformOps {
             KungFuFeature
             => vec![StringType(0), StructureType(vec![])],
             // two-parameter (table, u64)
-            ToolsFetch
+            ToolsChanges | ToolsFetch | ToolsHistory
             => vec![TableType(vec![], 0), NumberType(U64Kind)],
             // two-parameter (array, string)
             StrJoin
@@ -810,11 +859,11 @@ impl PlatformOps {
             UtilToU8 => NumberType(U8Kind),
             UtilToU16 => NumberType(U16Kind),
             UtilToU32 => NumberType(U32Kind),
-            UtilToU64 => NumberType(U64Kind),
+            UtilToU64 | ToolsReclaim => NumberType(U64Kind),
             UtilToU128 | OxideUUID => NumberType(U128Kind),
             // outcome
             IoFileCreate | KungFuAssert | OxidePrintln |
-            OsClear | OxideReset | WwwServe => NumberType(AckKind),
+            OsClear | OxidePromote | OxideReset | WwwServe => NumberType(AckKind),
             // string
             IoStdErr | IoStdOut | KungFuTypeOf | OsCall | OsCurrentDir |
             OxideEval | OxideHome | StrFormat | StrJoin | StrLeft | StrRight |
@@ -825,7 +874,7 @@ impl PlatformOps {
             OsEnv => TableType(Self::get_os_env_parameters(), 0),
             OxideHelp => TableType(Self::get_oxide_help_parameters(), 0),
             OxideHistory => TableType(Self::get_oxide_history_parameters(), 0),
-            ToolsCompact | ToolsFetch | ToolsReverse | ToolsScan |
+            ToolsChanges | ToolsCompact | ToolsFetch | ToolsHistory | ToolsReverse | ToolsScan |
             ToolsToTable => TableType(Vec::new(), 0),
             ToolsDescribe => TableType(Self::get_tools_describe_parameters(), 0),
         }
@@ -1222,6 +1271,14 @@ impl PlatformOps {
         (ms, StringValue(Machine::oxide_home()))
     }
 
+    /// Stops following the primary (i.e. manual failover); the tables of the home become writable
+    fn do_oxide_promote(ms: Machine) -> (Machine, TypedValue) {
+        match Replication::promote(Machine::oxide_home().as_str()) {
+            Ok(..) => (ms, Number(Ack)),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
     fn do_oxide_reset(_ms: Machine) -> (Machine, TypedValue) {
         (Machine::new_platform(), Number(Ack))
     }
@@ -1435,15 +1492,33 @@ impl PlatformOps {
         (ms, StringValue(a.unwrap_value()))
     }
 
+    /// Compacts the table, then releases any row versions no longer required by its snapshots,
+    /// and the row history older than its retention period; and finally, compacts the BLOB
+    /// store of the table.
+    fn compact_disk_table(mut frc: FileRowCollection) -> TypedValue {
+        let before = Local::now().timestamp_millis() - HISTORY_RETENTION_MILLIS;
+        match frc.compact() {
+            ErrorValue(err) => ErrorValue(err),
+            outcome => match frc.reclaim_versions() {
+                ErrorValue(err) => ErrorValue(err),
+                _ => match RowHistory::reclaim(&frc, before).and_then(|_| frc.compact_blobs()) {
+                    Ok(..) => outcome,
+                    Err(err) => ErrorValue(Exact(err.to_string()))
+                }
+            }
+        }
+    }
+
     fn do_tools_compact(ms: Machine, table: &TypedValue) -> (Machine, TypedValue) {
         match table {
             ErrorValue(err) => (ms, ErrorValue(err.to_owned())),
             NamespaceValue(ns) => {
                 match FileRowCollection::open(&ns) {
-                    Ok(mut frc) => (ms, frc.compact()),
+                    Ok(frc) => (ms, Self::compact_disk_table(frc)),
                     Err(err) => (ms, ErrorValue(Exact(err.to_string())))
                 }
             }
+            TableValue(Disk(frc)) => (ms, Self::compact_disk_table(frc.to_owned())),
             TableValue(rcv) => (ms, rcv.to_owned().compact()),
             z => (ms, ErrorValue(TypeMismatch(CollectionExpected(z.to_code()))))
         }
@@ -1456,8 +1531,8 @@ impl PlatformOps {
         match item {
             ErrorValue(message) => (ms, ErrorValue(message.to_owned())),
             NamespaceValue(ns) =>
-                match FileRowCollection::open(&ns) {
-                    Ok(frc) => (ms, frc.describe()),
+                match Dataframe::open(ns) {
+                    Ok(df) => (ms, df.describe()),
                     Err(err) => (ms, ErrorValue(Exact(err.to_string())))
                 }
             Structured(Hard(sh)) => (ms, sh.to_table().describe()),
@@ -1502,6 +1577,61 @@ impl PlatformOps {
         }
     }
 
+    fn do_tools_changes(
+        ms: Machine,
+        table: &TypedValue,
+        since: &TypedValue,
+    ) -> (Machine, TypedValue) {
+        let since = since.to_usize();
+        let result = match table {
+            NamespaceValue(ns) => FileRowCollection::open(ns)
+                .and_then(|frc| RowHistory::read_changes(&frc, since)),
+            TableValue(Disk(frc)) => RowHistory::read_changes(frc, since),
+            other =>
+                return (ms, ErrorValue(TypeMismatch(TableExpected("Table()".to_string(), other.to_code()))))
+        };
+        match result {
+            Ok((columns, rows)) =>
+                (ms, TableValue(Model(ModelRowCollection::from_columns_and_rows(&columns, &rows)))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
+    fn do_tools_history(
+        ms: Machine,
+        table: &TypedValue,
+        row_id: &TypedValue,
+    ) -> (Machine, TypedValue) {
+        let id = row_id.to_usize();
+        let result = match table {
+            NamespaceValue(ns) => FileRowCollection::open(ns)
+                .and_then(|frc| RowHistory::read_row_history(&frc, id)),
+            TableValue(Disk(frc)) => RowHistory::read_row_history(frc, id),
+            other =>
+                return (ms, ErrorValue(TypeMismatch(TableExpected("Table()".to_string(), other.to_code()))))
+        };
+        match result {
+            Ok((columns, rows)) =>
+                (ms, TableValue(Model(ModelRowCollection::from_columns_and_rows(&columns, &rows)))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
+    /// Releases the BLOBs no longer referenced by a table (or its related tables) for reuse;
+    /// returning the number of bytes released.
+    fn do_tools_reclaim(ms: Machine, table: &TypedValue) -> (Machine, TypedValue) {
+        let result = match table {
+            NamespaceValue(ns) => FileRowCollection::open(ns).and_then(|frc| frc.reclaim_blobs()),
+            TableValue(Disk(frc)) => frc.reclaim_blobs(),
+            other =>
+                return (ms, ErrorValue(TypeMismatch(TableExpected("Table()".to_string(), other.to_code()))))
+        };
+        match result {
+            Ok(released) => (ms, Number(U64Value(released))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
     fn do_tools_reverse(ms: Machine, value: &TypedValue) -> (Machine, TypedValue) {
         match value {
             ArrayValue(a) => (ms, ArrayValue(a.rev())),
@@ -1901,6 +2031,7 @@ mod tests {
         assert_eq!(OxideHistory.to_code(), "oxide::history()");
         assert_eq!(OxideHome.to_code(), "oxide::home()");
         assert_eq!(OxidePrintln.to_code(), "oxide::println(s: String(0))");
+        assert_eq!(OxidePromote.to_code(), "oxide::promote()");
         assert_eq!(OxideReset.to_code(), "oxide::reset()");
         assert_eq!(OxideUUID.to_code(), "oxide::uuid()");
         assert_eq!(OxideVersion.to_code(), "oxide::version()");
@@ -1917,9 +2048,12 @@ mod tests {
         assert_eq!(StrSubstring.to_code(), "str::substring(s: String(0), m: i64, n: i64)");
         assert_eq!(StrToString.to_code(), "str::to_string(x)");
         // tools
+        assert_eq!(ToolsChanges.to_code(), "tools::changes(t: Table(), n: u64)");
         assert_eq!(ToolsCompact.to_code(), "tools::compact(t: Table())");
         assert_eq!(ToolsDescribe.to_code(), "tools::describe(t: Table())");
         assert_eq!(ToolsFetch.to_code(), "tools::fetch(t: Table(), n: u64)");
+        assert_eq!(ToolsHistory.to_code(), "tools::history(t: Table(), n: u64)");
+        assert_eq!(ToolsReclaim.to_code(), "tools::reclaim(t: Table())");
         assert_eq!(ToolsReverse.to_code(), "tools::reverse(t: Table())");
         assert_eq!(ToolsScan.to_code(), "tools::scan(t: Table())");
         assert_eq!(ToolsToArray.to_code(), "tools::to_array(t: Table())");
@@ -2407,6 +2541,12 @@ mod tests {
             verify_exact(r#"oxide::println("Hello World")"#, Number(Ack));
         }
 
+        #[test]
+        fn test_oxide_promote() {
+            // an ordinary server (i.e. not a follower) is unaffected
+            verify_exact("oxide::promote()", Number(Ack));
+        }
+
         #[test]
         fn test_oxide_version() {
             verify_exact(
@@ -2760,6 +2900,75 @@ mod tests {
             ]);
         }
 
+        #[test]
+        fn test_tools_changes() {
+            verify_exact_table_with_ids(r#"
+                [+] import tools
+                [+] stocks := ns("platform.changes.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "BOOM", exchange: "NYSE", last_sale: 56.88 }] ~> stocks
+                [+] update stocks via { last_sale: 13.07 } where symbol == "ABC"
+                [+] delete from stocks where symbol == "BOOM"
+                [+] select _sequence, _event, _row_id from changes(stocks, 1)
+            "#, vec![
+                "|-----------------------------------|",
+                "| id | _sequence | _event | _row_id |",
+                "|-----------------------------------|",
+                "| 0  | 2         | insert | 1       |",
+                "| 1  | 3         | update | 0       |",
+                "| 2  | 4         | delete | 1       |",
+                "|-----------------------------------|"
+            ]);
+        }
+
+        #[test]
+        fn test_tools_history() {
+            verify_exact_table_with_ids(r#"
+                [+] import tools
+                [+] stocks := ns("platform.history.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "BOOM", exchange: "NYSE", last_sale: 56.88 }] ~> stocks
+                [+] update stocks via { last_sale: 13.07 } where symbol == "ABC"
+                [+] delete from stocks where symbol == "ABC"
+                [+] select _version, _active, symbol, last_sale from history(stocks, 0)
+            "#, vec![
+                "|----------------------------------------------|",
+                "| id | _version | _active | symbol | last_sale |",
+                "|----------------------------------------------|",
+                "| 0  | 1        | true    | ABC    | 12.49     |",
+                "| 1  | 3        | true    | ABC    | 13.07     |",
+                "| 2  | 4        | false   | ABC    | 13.07     |",
+                "|----------------------------------------------|"
+            ]);
+        }
+
+        #[test]
+        fn test_tools_reclaim() {
+            let mut interpreter = Interpreter::new();
+            let released = interpreter.evaluate(r#"
+                [+] import tools
+                [+] stocks := ns("platform.reclaim.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "BOOM_BOOM_BOOM", exchange: "NYSE", last_sale: 56.88 }] ~> stocks
+                [+] truncate stocks limit 1
+                [+] reclaim(stocks)
+            "#).unwrap();
+            assert!(matches!(released, Number(U64Value(n)) if n > 0));
+
+            // once released, the BLOBs are not released again; nor are the remaining rows affected
+            interpreter = verify_where(interpreter, "stocks:::reclaim()", Number(U64Value(0)));
+            verify_exact_table_where(interpreter, "from stocks", vec![
+                "|------------------------------------|",
+                "| id | symbol | exchange | last_sale |",
+                "|------------------------------------|",
+                "| 0  | ABC    | AMEX     | 12.49     |",
+                "|------------------------------------|"
+            ]);
+        }
+
         #[test]
         fn test_tools_reverse_arrays() {
             verify_exact_table_with_ids(r#"
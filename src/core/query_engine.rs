@@ -3,41 +3,56 @@
 // QueryEngine classes
 ////////////////////////////////////////////////////////////////////
 
+use crate::aggregates::Aggregates;
 use crate::columns::Column;
 use crate::cursor::Cursor;
 use crate::data_types::DataType;
-use crate::data_types::DataType::{TableType, VaryingType};
+use crate::data_types::DataType::{NumberType, TableType, VaryingType};
 
 use crate::dataframe::Dataframe;
 use crate::dataframe::Dataframe::*;
+use crate::encryption::Encryption;
 use crate::errors::throw;
+use crate::errors::Errors;
 use crate::errors::Errors::*;
 use crate::errors::TypeMismatchErrors::{CollectionExpected, QueryableExpected, TableExpected, UnsupportedType};
 use crate::expression::Conditions::True;
-use crate::expression::CreationEntity::{IndexEntity, TableEntity};
+use crate::expression::CreationEntity::{IndexEntity, MaterializedViewEntity, SequenceEntity, TableEntity, TriggerEntity, ViewEntity};
 use crate::expression::DatabaseOps::Mutation;
 use crate::expression::Expression::*;
-use crate::expression::MutateTarget::{IndexTarget, TableTarget};
+use crate::expression::MutateTarget::{IndexTarget, PartitionTarget, TableTarget, TriggerTarget, ViewTarget};
 use crate::expression::Mutations::Declare;
-use crate::expression::{Conditions, DatabaseOps, Expression, Mutations, Queryables};
+use crate::expression::{Alteration, Conditions, DatabaseOps, Expression, Mutations, Queryables};
 use crate::file_row_collection::FileRowCollection;
+use crate::foreign_keys::ForeignKeys;
 use crate::inferences::Inferences;
+use crate::joins::Joins;
 use crate::machine::Machine;
+use crate::materialized_views::MaterializedViews;
 use crate::model_row_collection::ModelRowCollection;
 use crate::namespaces::Namespace;
-use crate::numbers::Numbers::Ack;
+use crate::number_kind::NumberKind::{DateKind, U64Kind};
+use crate::numbers::Numbers::{Ack, DateValue, I64Value};
 use crate::numbers::Numbers::RowsAffected;
-use crate::object_config::{HashIndexConfig, ObjectConfig};
+use crate::object_config::{ConstraintConfig, HashIndexConfig, IndexKinds, ObjectConfig, TriggerConfig, TriggerEvents, TriggerTimings};
 use crate::parameter::Parameter;
+use crate::partitioned_row_collection::PartitionedRowCollection;
+use crate::replication::Replication;
 use crate::row_collection::RowCollection;
+use crate::row_history::RowHistory;
+use crate::sequences::Sequence;
+use crate::sorting::{Sorting, SORT_BUFFER_SIZE};
 use crate::structures::Row;
 use crate::structures::Structure;
 use crate::structures::Structures::Soft;
+use crate::transactions::Transaction;
+use crate::triggers::Triggers;
 use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::*;
+use crate::write_ahead_log::WriteAheadLog;
 use serde::{Deserialize, Serialize};
 use shared_lib::fail;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::From;
 use std::fs;
 use std::ops::Deref;
@@ -59,6 +74,10 @@ pub fn do_inquiry(
 ) -> std::io::Result<(Machine, TypedValue)> {
     use crate::expression::Queryables::*;
     match expression {
+        AsOf { from, point, is_version } =>
+            do_table_as_of(ms, from, point, *is_version),
+        Join { left, right, kind, condition } =>
+            Ok((ms.clone(), TableValue(Joins::join(ms, left, right, kind, condition)?))),
         Limit { from, limit } => {
             let (ms, limit) = ms.evaluate(limit)?;
             do_table_or_view_query(&ms, from, &True, &limit)
@@ -70,30 +89,88 @@ pub fn do_inquiry(
     }
 }
 
+/// Reads the state of a table as of a past time or version
+/// (e.g. "from ns('a.b.stocks') as of version 3")
+fn do_table_as_of(
+    ms: &Machine,
+    from: &Expression,
+    point: &Expression,
+    is_version: bool,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let src = match from {
+        From(src) => src.deref(),
+        src => src
+    };
+    let (ms, frc) = match ms.evaluate(src)? {
+        (_, ErrorValue(err)) => return throw(err),
+        (ms, NamespaceValue(ns)) => (ms, FileRowCollection::open(&ns)?),
+        (ms, TableValue(Disk(frc))) => (ms, frc),
+        (_, other) => return throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), other.get_type())))
+    };
+    let (ms, version) = match (ms.evaluate(point)?, is_version) {
+        ((_, ErrorValue(err)), _) => return throw(err),
+        ((ms, Number(n)), true) => (ms, n.to_usize()),
+        ((ms, Number(DateValue(millis))), false) => (ms, RowHistory::find_version_at(&frc, millis)?),
+        ((ms, StringValue(date)), false) => match TypedValue::wrap_value(&date) {
+            Ok(Number(DateValue(millis))) => (ms, RowHistory::find_version_at(&frc, millis)?),
+            _ => return throw(TypeMismatch(UnsupportedType(NumberType(DateKind), StringValue(date).get_type())))
+        }
+        ((_, other), true) => return throw(TypeMismatch(UnsupportedType(NumberType(U64Kind), other.get_type()))),
+        ((_, other), false) => return throw(TypeMismatch(UnsupportedType(NumberType(DateKind), other.get_type()))),
+    };
+    let rows = RowHistory::read_as_of(&frc, version)?;
+    Ok((ms, TableValue(Model(ModelRowCollection::from_columns_and_rows(frc.get_columns(), &rows)))))
+}
+
 pub fn do_mutation(
     ms: &Machine,
     mutation: &Mutations,
 ) -> std::io::Result<(Machine, TypedValue)> {
     use crate::expression::Mutations::*;
-    match mutation {
+    do_replica_check(ms, mutation)?;
+    do_transaction_enlist(ms, mutation)?;
+    // the writes caused by the mutation (e.g. "on delete cascade") join its transaction
+    Transaction::within(ms.get_transaction(), || match mutation {
+        Alter { path, alterations } =>
+            do_table_alter(ms, path, alterations),
         Append { path, source } =>
             do_table_row_append(&ms, path, source),
-        Create { path, entity: IndexEntity { columns } } =>
-            do_table_create_index(&ms, path, columns),
-        Create { path, entity: TableEntity { columns, from } } =>
-            do_table_create_table(&ms, path, columns, from),
-        Declare(IndexEntity { columns }) =>
-            do_table_declare_index(&ms, columns),
-        Declare(TableEntity { columns, from }) =>
+        Create { path, entity: IndexEntity { columns, is_unique, kind } } =>
+            do_table_create_index(&ms, path, columns, *is_unique, kind),
+        Create { path, entity: MaterializedViewEntity { query, refresh_interval } } =>
+            do_table_create_materialized_view(ms, path, query, refresh_interval),
+        Create { path, entity: SequenceEntity { increment, start } } =>
+            do_sequence_create(ms, path, start, increment),
+        Create { path, entity: TableEntity { columns, constraints, is_append_only, is_encrypted, partitions, .. } } =>
+            do_table_create_table(ms, path, columns, constraints, *is_append_only, *is_encrypted, partitions),
+        Create { path, entity: TriggerEntity { name, timing, event, function } } =>
+            do_trigger_create(ms, path, name, timing, event, function),
+        Create { path, entity: ViewEntity { query } } =>
+            do_table_create_view(ms, path, query),
+        Declare { path, entity: IndexEntity { columns, kind, .. } } =>
+            do_table_declare_index(ms, path, columns, kind),
+        Declare { entity: TableEntity { columns, from, .. }, .. } =>
             do_table_declare_table(&ms, columns, from),
+        Declare { entity: MaterializedViewEntity { .. } | ViewEntity { .. }, .. } =>
+            throw(Exact("Views must be created within a namespace".to_string())),
+        Declare { entity: SequenceEntity { .. }, .. } =>
+            throw(Exact("Sequences must be created within a namespace".to_string())),
+        Declare { entity: TriggerEntity { .. }, .. } =>
+            throw(Exact("Triggers must be created on a table within a namespace".to_string())),
         Delete { path, condition, limit } =>
             do_table_row_delete(&ms, path, condition, limit),
         Drop(IndexTarget { path }) => do_table_drop(&ms, path),
+        Drop(PartitionTarget { path, key }) =>
+            do_table_partition_op(ms, path, key, PartitionedRowCollection::drop_partition),
         Drop(TableTarget { path }) => do_table_drop(&ms, path),
+        Drop(TriggerTarget { path, name }) => do_trigger_drop(ms, path, name),
+        Drop(ViewTarget { path }) => do_view_drop(ms, path),
         IntoNs(source, target) =>
             do_table_into(&ms, target, source),
+        NextVal { path } => do_sequence_next_value(ms, path),
         Overwrite { path, source, condition, limit } =>
             do_table_row_overwrite(&ms, path, source, condition, limit),
+        Refresh { path } => do_table_refresh(ms, path),
         Truncate { path, limit } =>
             match limit {
                 None => do_table_row_resize(&ms, path, Boolean(false)),
@@ -102,10 +179,59 @@ pub fn do_mutation(
                     do_table_row_resize(&ms, path, limit)
                 }
             }
+        TruncatePartition { path, key } =>
+            do_table_partition_op(ms, path, key, PartitionedRowCollection::truncate_partition),
         Undelete { path, condition, limit } =>
             do_table_row_undelete(&ms, path, condition, limit),
         Update { path, source, condition, limit } =>
             do_table_row_update(&ms, path, source, condition, limit),
+    })
+}
+
+/// Rejects mutations of the tables of a follower; which may only be written via its primary
+fn do_replica_check(
+    ms: &Machine,
+    mutation: &Mutations,
+) -> std::io::Result<()> {
+    if !Replication::is_follower() { return Ok(()); }
+    match get_mutation_path(mutation) {
+        Some(path) => match ms.evaluate(path)? {
+            (_, NamespaceValue(ns)) => Replication::ensure_writable(&ns),
+            _ => Ok(())
+        }
+        None => Ok(())
+    }
+}
+
+/// Enlists the namespace targeted by the mutation within the active transaction (if any);
+/// otherwise, ensures the namespace is not enlisted within another session's transaction.
+fn do_transaction_enlist(
+    ms: &Machine,
+    mutation: &Mutations,
+) -> std::io::Result<()> {
+    let path = match (mutation, get_mutation_path(mutation)) {
+        // like SQL sequences, the values handed out are not reclaimed by a rollback
+        (Mutations::NextVal { .. }, _) | (_, None) => return Ok(()),
+        (_, Some(path)) => path
+    };
+    match (ms.evaluate(path)?, ms.get_transaction()) {
+        ((_, NamespaceValue(ns)), Some(tx)) => tx.enlist(&ns),
+        ((_, NamespaceValue(ns)), None) => Transaction::ensure_not_enlisted(&ns),
+        _ => Ok(())
+    }
+}
+
+/// Returns the expression of the namespace targeted by the mutation (if any)
+fn get_mutation_path(mutation: &Mutations) -> Option<&Expression> {
+    use crate::expression::Mutations::*;
+    match mutation {
+        Alter { path, .. } | Append { path, .. } | Create { path, .. } | Delete { path, .. } | Overwrite { path, .. } |
+        NextVal { path } | Refresh { path } | Truncate { path, .. } | TruncatePartition { path, .. } |
+        Undelete { path, .. } | Update { path, .. } => Some(path.deref()),
+        Drop(IndexTarget { path } | PartitionTarget { path, .. } | TableTarget { path } | TriggerTarget { path, .. } |
+        ViewTarget { path }) => Some(path.deref()),
+        IntoNs(_, target) => Some(target.deref()),
+        Declare { .. } => None
     }
 }
 
@@ -120,9 +246,9 @@ fn do_table_into(
             do_rows_from_query(&ms, source, table)?,
         Literal(TableValue(rc)) => (machine, rc.get_rows()),
         Literal(NamespaceValue(ns)) => {
-            (machine, FileRowCollection::open(ns)?.read_active_rows()?)
+            (machine, Dataframe::open(ns)?.read_active_rows()?)
         }
-        DatabaseOp(Mutation(Declare(TableEntity { columns, from }))) =>
+        DatabaseOp(Mutation(Declare { entity: TableEntity { columns, from, .. }, .. })) =>
             do_rows_from_table_declaration(&machine, table, from, columns)?,
         source =>
             do_rows_from_query(&ms, source, table)?,
@@ -131,7 +257,8 @@ fn do_table_into(
     // write the rows to the target
     let mut inserted = 0;
     let mut rc = expect_row_collection(&machine, table)?;
-    match rc.append_rows(rows) {
+    let triggers = Triggers::for_table(&machine.evaluate(table)?.1)?;
+    match triggers.append_rows(rc.as_mut(), rows)? {
         ErrorValue(err) => return throw(err),
         Number(oc) => inserted += oc.to_i64(),
         _ => {}
@@ -162,7 +289,7 @@ fn do_table_row_resize(
     limit: TypedValue,
 ) -> std::io::Result<(Machine, TypedValue)> {
     let (machine, table) = ms.evaluate(table)?;
-    match table.to_table_value() {
+    match table.to_writable_table_value() {
         ErrorValue(err) => Ok((machine, ErrorValue(err))),
         TableValue(mut df) => Ok((machine, df.resize(limit.to_usize()))),
         other => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), other.get_type())))
@@ -176,14 +303,28 @@ fn do_table_row_append(
 ) -> std::io::Result<(Machine, TypedValue)> {
     // evaluate the table expression (table_expr)
     let (ms, table) = ms.evaluate(table_expr)?;
-    match table.to_table_value() {
+    match table.to_writable_table_value() {
         TableValue(mut df) => {
             // evaluate the query expression (from_expr)
             let (ms, result) = ms.evaluate(from_expr)?;
             match result.to_table_value() {
                 TableValue(src) => {
+                    // align the fields of the source rows with the columns of the table (by name);
+                    // so that the missing fields may be filled with their defaults
+                    let (src_columns, columns) = (src.get_columns().to_owned(), df.get_columns().to_owned());
+                    let is_aligned = src_columns.len() == columns.len() ||
+                        !src_columns.iter().all(|sc| columns.iter().any(|c| c.get_name() == sc.get_name()));
+                    let rows = match is_aligned {
+                        true => src.read_active_rows()?,
+                        false => src.read_active_rows()?.iter()
+                            .map(|row| Row::from_tuples(row.get_id(), &columns, &src_columns.iter()
+                                .map(|c| c.get_name().to_string()).zip(row.get_values()).collect()))
+                            .collect()
+                    };
+
                     // write the rows to the dataframe
-                    Ok((ms, df.append_rows(src.read_active_rows()?)))
+                    let triggers = Triggers::for_table(&table)?;
+                    Ok((ms, triggers.append_rows(&mut df, rows)?))
                 }
                 _ => throw(TypeMismatch(QueryableExpected(from_expr.to_code())))
             }
@@ -200,8 +341,11 @@ fn do_table_row_delete(
 ) -> std::io::Result<(Machine, TypedValue)> {
     let (ms, limit) = ms.evaluate_opt(limit)?;
     let (ms, table) = ms.evaluate(from)?;
-    match table.to_table_value() {
-        TableValue(mut rc) => Ok((ms.clone(), rc.delete_where(&ms, &condition, limit)?)),
+    match table.to_writable_table_value() {
+        TableValue(rc) => {
+            let triggers = Triggers::for_table(&table)?;
+            Ok((ms.clone(), rc.delete_where(&ms, condition, limit, &triggers)?))
+        }
         other => Ok((ms, ErrorValue(TypeMismatch(UnsupportedType(TableType(vec![], 0), other.get_type())))))
     }
 }
@@ -216,10 +360,11 @@ fn do_table_row_overwrite(
     let (machine, limit) = ms.evaluate_opt(limit)?;
     let (machine, tv_table) = machine.evaluate(table)?;
     let (fields, values) = expect_via(&ms, &table, &source)?;
-    match tv_table.to_table_value() {
+    match tv_table.to_writable_table_value() {
         ErrorValue(err) => Ok((machine, ErrorValue(err))),
         TableValue(rc) => {
-            let (_, overwritten) = Dataframe::overwrite_where(rc, &machine, &fields, &values, condition, limit)?;
+            let triggers = Triggers::for_table(&tv_table)?;
+            let (_, overwritten) = Dataframe::overwrite_where(rc, &machine, &fields, &values, condition, limit, &triggers)?;
             Ok((machine, overwritten))
         }
         other => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), other.get_type())))
@@ -258,7 +403,7 @@ fn do_table_row_undelete(
 ) -> std::io::Result<(Machine, TypedValue)> {
     let (ms, limit) = ms.evaluate_opt(limit)?;
     let (ms, table) = ms.evaluate(from)?;
-    match table.to_table_value() {
+    match table.to_writable_table_value() {
         ErrorValue(err) => Ok((ms, ErrorValue(err))),
         TableValue(mut rc) =>
             match rc.undelete_where(&ms, &condition, limit) {
@@ -279,73 +424,367 @@ fn do_table_row_update(
     let (ms, limit) = ms.evaluate_opt(limit)?;
     let (ms, tv_table) = ms.evaluate(table)?;
     let (fields, values) = expect_via(&ms, &table, &source)?;
-    match tv_table.to_table_value() {
+    match tv_table.to_writable_table_value() {
         ErrorValue(err) => Ok((ms, ErrorValue(err))),
-        TableValue(rc) =>
-            match Dataframe::update_where(rc, &ms, &fields, &values, &condition, limit) {
+        TableValue(rc) => {
+            let triggers = Triggers::for_table(&tv_table)?;
+            match Dataframe::update_where(rc, &ms, &fields, &values, condition, limit, &triggers) {
                 Ok(modified) => Ok((ms, modified)),
                 Err(err) => throw(Exact(err.to_string())),
             }
+        }
         other => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), other.get_type())))
     }
 }
 
+/// Alters the layout of a table (e.g. "alter table stocks add column rating: i64 = 0")
+fn do_table_alter(
+    ms: &Machine,
+    table: &Expression,
+    alterations: &Vec<Alteration>,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, ns) = match ms.evaluate(table)? {
+        (_, ErrorValue(err)) => return throw(err),
+        (machine, NamespaceValue(ns)) => (machine, ns),
+        (_, z) => return throw(TypeMismatch(CollectionExpected(z.to_code())))
+    };
+
+    // load the configuration
+    let config = ObjectConfig::load(&ns)?;
+    match config.get_partitions() {
+        None => return throw(Exact(format!("{} is not a table", ns))),
+        Some(partitions) if !partitions.is_empty() =>
+            return throw(Exact("Alterations are not supported by partitioned tables".to_string())),
+        Some(..) => {}
+    }
+
+    // determine the new layout => (parameter|source column ID)
+    let mut layout = config.get_columns().into_iter().enumerate()
+        .map(|(column_id, param)| (param, Some(column_id)))
+        .collect::<Vec<_>>();
+    let mut constraints = config.get_constraints();
+    let mut indices = config.get_indices();
+    let mut is_append_only = config.is_append_only();
+    let mut is_encrypted = config.is_encrypted();
+    let find_column = |layout: &Vec<(Parameter, Option<usize>)>, name: &str| -> std::io::Result<usize> {
+        match layout.iter().position(|(param, _)| param.get_name() == name) {
+            Some(position) => Ok(position),
+            None => fail(column_not_found(name, &Column::from_parameters(
+                &layout.iter().map(|(param, _)| param.to_owned()).collect())))
+        }
+    };
+    for alteration in alterations {
+        match alteration {
+            Alteration::AddColumn(param) => {
+                if find_column(&layout, param.get_name()).is_ok() {
+                    return throw(Exact(format!("Column {} already exists", param.get_name())));
+                }
+                layout.push((param.to_owned(), None));
+            }
+            Alteration::AppendOnly(enabled) => is_append_only = *enabled,
+            Alteration::DropColumn(name) => {
+                layout.remove(find_column(&layout, name)?);
+                constraints.retain(|constraint| constraint.get_column_name() != name);
+                indices.retain(|index| !index.get_indexed_column_names().contains(name));
+            }
+            Alteration::Encryption(enabled) => {
+                if *enabled { Encryption::ensure_key()?; }
+                is_encrypted = *enabled;
+            }
+            Alteration::RenameColumn { old_name, new_name } => {
+                let position = find_column(&layout, old_name)?;
+                if find_column(&layout, new_name).is_ok() {
+                    return throw(Exact(format!("Column {new_name} already exists")));
+                }
+                let (param, column_id) = layout[position].to_owned();
+                layout[position] = (Parameter::with_default(new_name, param.get_data_type(), param.get_default_value()), column_id);
+                if constraints.iter().any(|c| c.is_serial() && c.get_column_name() == old_name) {
+                    Sequence::rename_column(&ns.get_table_file_path(), old_name, new_name)?;
+                }
+                constraints = constraints.into_iter().map(|constraint| match constraint.get_column_name() == old_name {
+                    true => constraint.with_column_name(new_name.to_owned()),
+                    false => constraint
+                }).collect();
+                indices = indices.into_iter().map(|index| {
+                    let names = index.get_indexed_column_names().iter()
+                        .map(|name| if name == old_name { new_name.to_owned() } else { name.to_owned() })
+                        .collect();
+                    HashIndexConfig::new(names, index.is_unique()).with_kind(index.get_kind().to_owned())
+                }).collect();
+            }
+            Alteration::RetypeColumn(param) => {
+                let position = find_column(&layout, param.get_name())?;
+                let (param0, column_id) = layout[position].to_owned();
+                let default_value = match param.get_default_value() {
+                    Null | Undefined => param0.get_default_value(),
+                    default_value => default_value
+                };
+                layout[position] = (Parameter::with_default(param.get_name(), param.get_data_type(), default_value), column_id);
+            }
+        }
+    }
+    if layout.is_empty() {
+        return throw(Exact(format!("{} must retain at least one column", ns)));
+    }
+
+    // rewrite the table into the new layout (encrypting or decrypting the existing rows)
+    let params0 = config.get_columns();
+    let params = layout.iter().map(|(param, _)| param.to_owned()).collect::<Vec<_>>();
+    let config = ObjectConfig::build_table(params).with_constraints(constraints.to_owned()).with_indices(indices)
+        .with_triggers(config.get_triggers()).with_append_only(is_append_only).with_encrypted(is_encrypted);
+    FileRowCollection::rewrite_table(&ns, config, |row| {
+        let mut values = Vec::with_capacity(layout.len());
+        for (param, column_id) in &layout {
+            let data_type = param.get_data_type();
+            values.push(match column_id {
+                Some(column_id) if params0[*column_id].get_data_type() == data_type => row[*column_id].to_owned(),
+                Some(column_id) => data_type.convert(&row[*column_id])?,
+                None => data_type.convert(&param.get_default_value())?,
+            });
+        }
+        Ok(Row::new(row.get_id(), values))
+    })?;
+
+    // re-register the references of any renamed columns
+    ForeignKeys::link(&ns, &constraints)?;
+    Ok((machine, Number(Ack)))
+}
+
 fn do_table_create_index(
     ms: &Machine,
     index: &Expression,
     columns: &Vec<Expression>,
+    is_unique: bool,
+    kind: &IndexKinds,
 ) -> std::io::Result<(Machine, TypedValue)> {
     let (machine, result) = ms.evaluate(index)?;
     match result {
         ErrorValue(msg) => throw(msg),
         Null | Undefined => Ok((machine, result)),
-        TableValue(_rcv) =>
-            throw(Exact("Memory collections do not yet support indexes".to_string())),
+        TableValue(..) if is_unique =>
+            throw(Exact("Unique indexes are not supported by in-memory tables".to_string())),
+        TableValue(df) => do_table_create_memory_index(&machine, index, df, columns, kind),
         NamespaceValue(ns) => {
             // evaluate the columns
             let (machine, columns) = ms.evaluate_as_atoms(columns)?;
 
             // load the configuration
             let config = ObjectConfig::load(&ns)?;
+            if config.get_partitions().is_some_and(|partitions| !partitions.is_empty()) {
+                return throw(Exact("Indexes are not supported by partitioned tables".to_string()));
+            }
+            let table_columns = Column::from_parameters(&config.get_columns());
+            if let Some(name) = columns.iter().find(|name| !table_columns.iter().any(|c| c.get_name() == *name)) {
+                return fail(column_not_found(name, &table_columns));
+            }
+
+            // a unique index cannot be created over duplicate keys
+            let index_config = HashIndexConfig::new(columns.to_owned(), is_unique).with_kind(kind.to_owned());
+            if is_unique {
+                if let Some(err) = find_duplicate_key(&FileRowCollection::open(&ns)?, &index_config)? {
+                    return throw(err);
+                }
+            }
 
             // update the indices
             let mut indices = config.get_indices();
-            indices.push(HashIndexConfig::new(columns, false));
+            indices.push(index_config);
 
             // update the configuration
             let updated_config = config.with_indices(indices);
             updated_config.save(&ns)?;
+
+            // build the index of each column from the existing rows
+            let frc = FileRowCollection::open(&ns)?;
+            for (column_id, column) in table_columns.iter().enumerate() {
+                if columns.iter().any(|name| name == column.get_name()) {
+                    match kind {
+                        IndexKinds::BTree => frc.rebuild_ordered_index(column_id)?,
+                        IndexKinds::Hash => frc.rebuild_hash_index(column_id)?
+                    };
+                }
+            }
             Ok((machine, Number(Ack)))
         }
         z => throw(TypeMismatch(CollectionExpected(z.to_code())))
     }
 }
 
+/// Searches the table's active rows for a key (e.g. "symbol") which occurs more than once;
+/// keys containing nulls are not considered.
+fn find_duplicate_key(
+    frc: &FileRowCollection,
+    index_config: &HashIndexConfig,
+) -> std::io::Result<Option<Errors>> {
+    let names = index_config.get_indexed_column_names();
+    let column_ids = names.iter()
+        .filter_map(|name| frc.get_columns().iter().position(|c| c.get_name() == name))
+        .collect::<Vec<_>>();
+    let mut keys = BTreeSet::new();
+    for row in frc.iter() {
+        let key = column_ids.iter().map(|id| row[*id].to_owned()).collect::<Vec<_>>();
+        if key.iter().any(|value| matches!(value, Null | Undefined)) { continue; }
+        if let Some(key) = keys.replace(key) {
+            return Ok(Some(UniqueKeyViolation(
+                names.join(", "),
+                key.iter().map(|value| value.to_code()).collect::<Vec<_>>().join(", "),
+            )));
+        }
+    }
+    Ok(None)
+}
+
 fn do_table_create_table(
     ms: &Machine,
     table: &Expression,
     columns: &Vec<Parameter>,
-    from: &Option<Box<Expression>>,
+    constraints: &Vec<ConstraintConfig>,
+    is_append_only: bool,
+    is_encrypted: bool,
+    partitions: &Vec<Expression>,
 ) -> std::io::Result<(Machine, TypedValue)> {
     let (machine, result) = ms.evaluate(table)?;
     match result.to_owned() {
         Null | Undefined => Ok((machine, result)),
         ErrorValue(err) => Ok((machine, ErrorValue(err))),
         TableValue(_rcv) => throw(Exact("Memory collections do not 'create' keyword".to_string())),
+        NamespaceValue(..) if is_encrypted && !partitions.is_empty() =>
+            throw(Exact("Encryption is not supported by partitioned tables".to_string())),
+        NamespaceValue(ns) if !partitions.is_empty() => {
+            // create table ns("a.b.stocks") (...) partition by [exchange]
+            let (machine, partitions) = machine.evaluate_as_atoms(partitions)?;
+            let table_columns = Column::from_parameters(columns);
+            if let Some(name) = partitions.iter().find(|name| !table_columns.iter().any(|c| c.get_name() == *name)) {
+                return fail(column_not_found(name, &table_columns));
+            }
+            PartitionedRowCollection::create_table(&ns, columns, partitions)?;
+            do_table_apply_constraints(&ns, constraints)?;
+            Ok((machine, Number(Ack)))
+        }
         NamespaceValue(ns) => {
             FileRowCollection::create_table(&ns, columns)?;
+            do_table_apply_constraints(&ns, constraints)?;
+            if is_encrypted {
+                Encryption::ensure_key()?;
+                ObjectConfig::load(&ns)?.with_encrypted(true).save(&ns)?;
+            }
+            if is_append_only {
+                ObjectConfig::load(&ns)?.with_append_only(true).save(&ns)?;
+            }
             Ok((machine, Number(Ack)))
         }
         x => throw(TypeMismatch(CollectionExpected(x.to_code())))
     }
 }
 
+/// Saves the column constraints of a newly created table; registering its references
+/// with the referenced tables, and restarting the sequences of its serial columns.
+fn do_table_apply_constraints(ns: &Namespace, constraints: &Vec<ConstraintConfig>) -> std::io::Result<()> {
+    ObjectConfig::load(ns)?.with_constraints(constraints.to_owned()).save(ns)?;
+    ForeignKeys::link(ns, constraints)?;
+    for constraint in constraints.iter().filter(|c| c.is_serial()) {
+        Sequence::for_column(&ns.get_table_file_path(), constraint.get_column_name()).restart()?;
+    }
+    Ok(())
+}
+
+/// Creates a materialized view within a namespace (e.g. "create materialized view ns('a.b.v') as select ...");
+/// the optional refresh interval (in milliseconds) schedules its subsequent refreshes.
+fn do_table_create_materialized_view(
+    ms: &Machine,
+    view: &Expression,
+    query: &Expression,
+    refresh_interval: &Option<Box<Expression>>,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, result) = ms.evaluate(view)?;
+    let (machine, refresh_interval) = match refresh_interval {
+        Some(interval) => match machine.evaluate(interval)? {
+            (_, ErrorValue(err)) => return throw(err),
+            (machine, interval) => (machine, Some(interval.to_i64()))
+        }
+        None => (machine, None)
+    };
+    match result {
+        ErrorValue(err) => throw(err),
+        NamespaceValue(ns) => {
+            if let Ok(ObjectConfig::TableConfig { .. }) = ObjectConfig::load(&ns) {
+                return throw(Exact(format!("A table already exists within {}", ns)));
+            }
+            Ok((machine, MaterializedViews::create(&ns, query, refresh_interval)?))
+        }
+        z => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
+/// Stores the query as a view within a namespace (e.g. "create view ns('a.b.v') as select ...");
+/// the query is re-run whenever the view is read.
+fn do_table_create_view(
+    ms: &Machine,
+    view: &Expression,
+    query: &Expression,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, result) = ms.evaluate(view)?;
+    match result {
+        ErrorValue(err) => throw(err),
+        NamespaceValue(ns) => {
+            if let Ok(ObjectConfig::TableConfig { .. }) = ObjectConfig::load(&ns) {
+                return throw(Exact(format!("A table already exists within {}", ns)));
+            }
+            ObjectConfig::build_view(query.to_owned()).save(&ns)?;
+            Ok((machine, Number(Ack)))
+        }
+        z => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
+/// Builds ephemeral hash indices upon the columns of an in-memory table;
+/// a table variable (e.g. "stocks") is rebound to the indexed table.
+fn do_table_create_memory_index(
+    ms: &Machine,
+    index: &Expression,
+    mut df: Dataframe,
+    columns: &Vec<Expression>,
+    kind: &IndexKinds,
+) -> std::io::Result<(Machine, TypedValue)> {
+    if *kind != IndexKinds::Hash {
+        return throw(Exact("Only hash indexes are supported by in-memory tables".to_string()));
+    }
+
+    // evaluate the columns
+    let (machine, columns) = ms.evaluate_as_atoms(columns)?;
+    let table_columns = df.get_columns().to_owned();
+    if let Some(name) = columns.iter().find(|name| !table_columns.iter().any(|c| c.get_name() == *name)) {
+        return fail(column_not_found(name, &table_columns));
+    }
+
+    // build the index of each column
+    for (column_id, column) in table_columns.iter().enumerate() {
+        if columns.iter().any(|name| name == column.get_name()) {
+            df.create_memory_index(column_id)?;
+        }
+    }
+    let machine = match index {
+        Variable(name) => machine.with_variable(name, TableValue(df)),
+        _ => machine
+    };
+    Ok((machine, Number(Ack)))
+}
+
 fn do_table_declare_index(
     ms: &Machine,
+    index: &Option<Box<Expression>>,
     columns: &Vec<Expression>,
+    kind: &IndexKinds,
 ) -> std::io::Result<(Machine, TypedValue)> {
-    // TODO determine how to implement
-    throw(NotImplemented("declare index".to_string()))
+    let index = match index {
+        Some(index) => index,
+        None => return throw(Exact("A table is required (e.g. \"declare index stocks on [symbol]\")".to_string()))
+    };
+    match ms.evaluate(index)? {
+        (_, ErrorValue(err)) => throw(err),
+        (machine, TableValue(df)) => do_table_create_memory_index(&machine, index, df, columns, kind),
+        (_, z) => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
 }
 
 fn do_table_declare_table(
@@ -357,18 +796,155 @@ fn do_table_declare_table(
     Ok((ms.to_owned(), TableValue(Model(ModelRowCollection::with_rows(columns, Vec::new())))))
 }
 
+/// Creates a sequence within a namespace (e.g. "create sequence ns('a.b.order_ids') start with 1000 increment by 10")
+fn do_sequence_create(
+    ms: &Machine,
+    sequence: &Expression,
+    start: &Option<Box<Expression>>,
+    increment: &Option<Box<Expression>>,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let evaluate = |expr: &Option<Box<Expression>>| match expr {
+        Some(expr) => match ms.evaluate(expr)? {
+            (_, ErrorValue(err)) => throw(err),
+            (_, value) => Ok(value.to_i64())
+        }
+        None => Ok(1)
+    };
+    let (start, increment) = (evaluate(start)?, evaluate(increment)?);
+    if increment == 0 {
+        return throw(Exact("The increment of a sequence cannot be zero".to_string()));
+    }
+    match ms.evaluate(sequence)? {
+        (_, ErrorValue(err)) => throw(err),
+        (machine, NamespaceValue(ns)) => {
+            if let Ok(ObjectConfig::TableConfig { .. }) = ObjectConfig::load(&ns) {
+                return throw(Exact(format!("A table already exists within {}", ns)));
+            }
+            Sequence::create(&ns, start, increment)?;
+            Ok((machine, Number(Ack)))
+        }
+        (_, z) => throw(InvalidNamespace(z.to_code()))
+    }
+}
+
+/// Returns the next value of a sequence (e.g. "nextval(ns('a.b.order_ids'))")
+fn do_sequence_next_value(ms: &Machine, sequence: &Expression) -> std::io::Result<(Machine, TypedValue)> {
+    match ms.evaluate(sequence)? {
+        (_, ErrorValue(err)) => throw(err),
+        (machine, NamespaceValue(ns)) => Ok((machine, Number(I64Value(Sequence::open(&ns)?.next_value()?)))),
+        (_, z) => throw(InvalidNamespace(z.to_code()))
+    }
+}
+
+/// Creates (or replaces) a trigger of a table
+/// (e.g. "create trigger audit after append on ns('a.b.stocks') do fn(row) => ...")
+fn do_trigger_create(
+    ms: &Machine,
+    table: &Expression,
+    name: &str,
+    timing: &TriggerTimings,
+    event: &TriggerEvents,
+    function: &Expression,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, function) = match ms.evaluate(function)? {
+        (_, ErrorValue(err)) => return throw(err),
+        (machine, function @ Function { .. }) => (machine, function),
+        (_, z) => return throw(Exact(format!("'{}' is not a function ({})", function.to_code(), z))),
+    };
+    let ns = expect_namespace(&machine, table)?;
+    let config = ObjectConfig::load(&ns)?;
+    if config.get_partitions().is_none() {
+        return throw(Exact(format!("{} is not a table", ns)));
+    }
+    let mut triggers = config.get_triggers();
+    triggers.retain(|trigger| trigger.get_name() != name);
+    triggers.push(TriggerConfig::new(name.to_string(), timing.to_owned(), event.to_owned(), Literal(function)));
+    config.with_triggers(triggers).save(&ns)?;
+    Ok((machine, Number(Ack)))
+}
+
+/// Drops a trigger of a table (e.g. "drop trigger audit on ns('a.b.stocks')")
+fn do_trigger_drop(ms: &Machine, table: &Expression, name: &str) -> std::io::Result<(Machine, TypedValue)> {
+    let ns = expect_namespace(ms, table)?;
+    let config = ObjectConfig::load(&ns)?;
+    let mut triggers = config.get_triggers();
+    let count = triggers.len();
+    triggers.retain(|trigger| trigger.get_name() != name);
+    if triggers.len() == count {
+        return Ok((ms.to_owned(), Boolean(false)));
+    }
+    config.with_triggers(triggers).save(&ns)?;
+    Ok((ms.to_owned(), Number(Ack)))
+}
+
 fn do_table_drop(ms: &Machine, table: &Expression) -> std::io::Result<(Machine, TypedValue)> {
     let (machine, table) = ms.evaluate(table)?;
     match table {
         ErrorValue(err) => Ok((machine, ErrorValue(err))),
         NamespaceValue(ns) => {
+            if let Some(true) = ObjectConfig::load(&ns).ok()
+                .and_then(|config| config.get_partitions().map(|partitions| !partitions.is_empty())) {
+                PartitionedRowCollection::open(&ns)?.drop_partitions()?;
+            }
             let result = fs::remove_file(ns.get_table_file_path());
+            WriteAheadLog::discard(ns.get_table_file_path().as_str())?;
+            RowHistory::discard(ns.get_table_file_path().as_str())?;
             Ok((machine, if result.is_ok() { Number(Ack) } else { Boolean(false) }))
         }
         _ => Ok((machine, Boolean(false)))
     }
 }
 
+/// Drops or truncates a partition of a partitioned table (e.g. "drop partition ns('a.b.stocks') ['NYSE']")
+fn do_table_partition_op(
+    ms: &Machine,
+    table: &Expression,
+    key: &Expression,
+    f: fn(&mut PartitionedRowCollection, &[TypedValue]) -> std::io::Result<TypedValue>,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, key) = match ms.evaluate(key)? {
+        (_, ErrorValue(err)) => return throw(err),
+        (machine, ArrayValue(items)) => (machine, items.values().to_owned()),
+        (machine, value) => (machine, vec![value]),
+    };
+    match machine.evaluate(table)? {
+        (_, ErrorValue(err)) => throw(err),
+        (machine, NamespaceValue(ns)) => match Dataframe::open(&ns)? {
+            Partitioned(mut prc) => Ok((machine, f(&mut prc, &key)?)),
+            _ => throw(Exact(format!("{} is not a partitioned table", ns)))
+        }
+        (_, z) => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
+fn do_table_refresh(ms: &Machine, view: &Expression) -> std::io::Result<(Machine, TypedValue)> {
+    match ms.evaluate(view)? {
+        (_, ErrorValue(err)) => throw(err),
+        (machine, NamespaceValue(ns)) => Ok((machine, MaterializedViews::refresh(&ns)?)),
+        (_, z) => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
+fn do_view_drop(ms: &Machine, view: &Expression) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, view) = ms.evaluate(view)?;
+    match view {
+        ErrorValue(err) => Ok((machine, ErrorValue(err))),
+        NamespaceValue(ns) => match ObjectConfig::load(&ns) {
+            Ok(ObjectConfig::MaterializedViewConfig { .. }) => {
+                fs::remove_file(ns.get_table_file_path())?;
+                ObjectConfig::delete(&ns)?;
+                Ok((machine, Number(Ack)))
+            }
+            Ok(ObjectConfig::ViewConfig { .. }) => {
+                ObjectConfig::delete(&ns)?;
+                Ok((machine, Number(Ack)))
+            }
+            _ => Ok((machine, Boolean(false)))
+        }
+        _ => Ok((machine, Boolean(false)))
+    }
+}
+
 pub fn do_select(
     ms: &Machine,
     fields: &Vec<Expression>,
@@ -383,14 +959,15 @@ pub fn do_select(
         Ok((ms, table_v)) =>
             match table_v {
                 ErrorValue(err) => throw(err),
+                // the query reads a snapshot of the table as of its start
                 NamespaceValue(ns) =>
-                    match FileRowCollection::open(&ns) {
-                        Ok(frc) =>
-                            Ok(do_select_go(ms, Disk(frc), fields, condition, group_by, having, order_by, limit)),
+                    match Dataframe::open(&ns).and_then(|df| df.snapshot()) {
+                        Ok(df) =>
+                            Ok(do_select_go(ms, df, fields, condition, group_by, having, order_by, limit)),
                         Err(err) => throw(Exact(err.to_string()))
                     }
                 TableValue(rc) =>
-                    Ok(do_select_go(ms, rc, fields, condition, group_by, having, order_by, limit)),
+                    Ok(do_select_go(ms, rc.snapshot()?, fields, condition, group_by, having, order_by, limit)),
                 z => throw(TypeMismatch(CollectionExpected(z.to_code())))
             }
         Err(err) => throw(Exact(err.to_string()))
@@ -410,13 +987,46 @@ fn do_select_go(
     // cache the initial state
     let ms0 = ms.clone();
 
+    // aggregate queries carry the source rows through to step 3,
+    // where the projection takes place per group.
+    let is_aggregate = group_by.is_some() || having.is_some()
+        || fields.iter().any(Aggregates::contains_any);
+    let row_fields = match is_aggregate {
+        true => df0.get_columns().iter()
+            .map(|c| Variable(c.get_name().to_string()))
+            .collect::<Vec<_>>(),
+        false => fields.to_owned()
+    };
+
+    // sort keys which are not selected (e.g. "select symbol ... order by last_sale")
+    // are carried through the projection, and dropped once the rows are sorted.
+    let (row_fields, order_by) = match order_by {
+        Some(order_fields) if !is_aggregate =>
+            match carry_sort_keys(df0.get_columns(), &row_fields, order_fields) {
+                Ok((row_fields, order_fields)) => (row_fields, Some(order_fields)),
+                Err(err) => return (ms0, ErrorValue(Exact(err.to_string())))
+            }
+        _ => (row_fields, order_by.to_owned())
+    };
+    let order_by = &order_by;
+
+    // an ordered index may provide the order of the rows, sparing the sort
+    let index_order = match (&df0, order_by) {
+        (Disk(frc), Some(order_fields)) if !is_aggregate =>
+            match find_index_order(frc, order_fields, &row_fields) {
+                Ok(row_ids) => row_ids,
+                Err(err) => return (ms0, ErrorValue(Exact(err.to_string())))
+            }
+        _ => None
+    };
+
     // step 1: determine output layout and limits
     let (_, rc1, new_columns, limit) =
-        step_1_determine_layout_and_limit(ms, df0, fields, limit);
+        step_1_determine_layout_and_limit(ms, df0, &row_fields, limit);
 
     // step 2: transform the eligible rows
     let (_, rc2) =
-        match step_2_transform_eligible_rows(&ms0, &rc1, fields, &new_columns, condition) {
+        match step_2_transform_eligible_rows(&ms0, &rc1, &row_fields, &new_columns, condition) {
             (ms, ErrorValue(err)) => return (ms, ErrorValue(err)),
             (ms, TableValue(rc)) => (ms, rc),
             (ms, other) => return (ms, ErrorValue(TypeMismatch(UnsupportedType(
@@ -428,15 +1038,25 @@ fn do_select_go(
         };
 
     // step 3: aggregate the dataset
-    let rc3 = match group_by {
-        Some(agg_fields) => step_3_aggregate_table(rc2, agg_fields, having),
-        None => rc2
+    let rc3 = match is_aggregate {
+        true => match step_3_aggregate_table(&ms0, rc2, fields, group_by, having) {
+            Ok(rc) => rc,
+            Err(err) => return (ms0, ErrorValue(Exact(err.to_string())))
+        }
+        false => rc2
     };
 
     // step 4: sort the dataset
-    let rc4 = match order_by {
-        Some(order_fields) => step_4_sort_table(rc3, order_fields),
-        None => rc3
+    let rc4 = match (order_by, index_order) {
+        (Some(_), Some(row_ids)) => match step_4_order_table_via_index(rc3, &row_ids) {
+            Ok(rc) => rc,
+            Err(err) => return (ms0, ErrorValue(Exact(err.to_string())))
+        }
+        (Some(order_fields), None) => match step_4_sort_table(&ms0, rc3, order_fields) {
+            Ok(rc) => rc,
+            Err(err) => return (ms0, ErrorValue(Exact(err.to_string())))
+        }
+        (None, _) => rc3
     };
 
     // step 5: limit the dataset
@@ -445,8 +1065,50 @@ fn do_select_go(
         _ => rc4
     };
 
+    // step 6: drop the carried sort keys
+    let rc6 = match row_fields.len() > fields.len() && !is_aggregate {
+        true => match step_6_drop_sort_keys(rc5, fields.len()) {
+            Ok(rc) => rc,
+            Err(err) => return (ms0, ErrorValue(Exact(err.to_string())))
+        }
+        false => rc5
+    };
+
     // return the table value
-    (ms0, TableValue(rc5))
+    (ms0, TableValue(rc6))
+}
+
+/// Appends the sort keys which are not among the selected fields to the fields as hidden
+/// (e.g. "__order_by_0: last_sale"), returning the fields and the sort fields referencing them.
+fn carry_sort_keys(
+    columns: &Vec<Column>,
+    fields: &Vec<Expression>,
+    order_fields: &[Expression],
+) -> std::io::Result<(Vec<Expression>, Vec<Expression>)> {
+    let selected = resolve_fields_as_columns(columns, fields)?.iter()
+        .map(|c| c.get_name().to_string())
+        .collect::<Vec<_>>();
+    let mut row_fields = fields.to_owned();
+    let mut sort_fields = Vec::with_capacity(order_fields.len());
+    for (n, order_field) in order_fields.iter().enumerate() {
+        let key = match order_field {
+            Ascending(key) | Descending(key) => key.deref(),
+            key => key
+        };
+        match key {
+            Variable(name) if selected.contains(name) => sort_fields.push(order_field.to_owned()),
+            key => {
+                let name = format!("__order_by_{n}");
+                row_fields.push(AsValue(name.to_string(), Box::new(key.to_owned())));
+                let hidden = Box::new(Variable(name));
+                sort_fields.push(match order_field {
+                    Descending(_) => Descending(hidden),
+                    _ => Ascending(hidden)
+                });
+            }
+        }
+    }
+    Ok((row_fields, sort_fields))
 }
 
 fn step_1_determine_layout_and_limit(
@@ -483,21 +1145,128 @@ fn step_2_transform_eligible_rows(
     (ms, result)
 }
 
+/// Groups the rows by the `group by` expressions (or as a single group when absent),
+/// projects the fields, including any aggregates, for each group and filters
+/// the groups via the `having` expression.
+/// ex: select exchange, total: sum(last_sale) from stocks group by exchange having total > 1.0
 fn step_3_aggregate_table(
+    ms: &Machine,
     src: Dataframe,
-    group_by: &Vec<Expression>,
+    fields: &Vec<Expression>,
+    group_by: &Option<Vec<Expression>>,
     having: &Option<Box<Expression>>,
-) -> Dataframe {
-    // todo add aggregation code to rc
-    src
+) -> std::io::Result<Dataframe> {
+    let src_columns = src.get_columns().to_owned();
+
+    // determine the output columns
+    let mut offset = Row::overhead();
+    let mut new_columns = Vec::new();
+    for field in fields {
+        let name = match field {
+            AsValue(label, _) => label.to_string(),
+            Variable(name) => name.to_string(),
+            other => other.to_code(),
+        };
+        let data_type = match Aggregates::infer(field, &src_columns) {
+            VaryingType(v) if v.is_empty() => return fail(format!("Variable type detected - {}", field.to_code())),
+            data_type => data_type
+        };
+        let column = Column::new(name, data_type, Null, offset);
+        offset += column.get_fixed_size();
+        new_columns.push(column);
+    }
+
+    // partition the rows into groups
+    let groups = match group_by {
+        Some(keys) => group_rows(ms, keys, &src_columns, src.get_rows())?,
+        None => vec![(vec![], src.get_rows())]
+    };
+
+    // compute the output row for each group
+    let mut dest = ModelRowCollection::new(new_columns.clone());
+    for (_, rows) in groups {
+        // make the group's first row visible to non-aggregate expressions
+        let ms = match rows.first() {
+            Some(row) => ms.with_row(&src_columns, row),
+            None => ms.to_owned()
+        };
+        let mut values = Vec::with_capacity(fields.len());
+        for field in fields {
+            let expr = Aggregates::resolve(&ms, field, &src_columns, &rows)?;
+            let (_, value) = ms.evaluate(&expr)?;
+            values.push(value);
+        }
+        let row = Row::new(0, values);
+
+        // does the group satisfy the `having` expression?
+        if let Some(having) = having {
+            let expr = Aggregates::resolve(&ms, having, &src_columns, &rows)?;
+            match ms.with_row(&new_columns, &row).evaluate(&expr)? {
+                (_, Boolean(true)) => {}
+                (_, ErrorValue(err)) => return throw(err),
+                _ => continue
+            }
+        }
+        dest.append_row(row);
+    }
+    Ok(Model(dest))
 }
 
+/// Partitions the rows by the values of the key expressions; preserving
+/// the order in which each group was first encountered.
+fn group_rows(
+    ms: &Machine,
+    keys: &Vec<Expression>,
+    columns: &Vec<Column>,
+    rows: Vec<Row>,
+) -> std::io::Result<Vec<(Vec<TypedValue>, Vec<Row>)>> {
+    let mut groups: Vec<(Vec<TypedValue>, Vec<Row>)> = Vec::new();
+    let mut lookup: BTreeMap<Vec<TypedValue>, usize> = BTreeMap::new();
+    for row in rows {
+        let ms = ms.with_row(columns, &row);
+        let mut key = Vec::with_capacity(keys.len());
+        for expr in keys {
+            let (_, value) = ms.evaluate(expr)?;
+            key.push(value);
+        }
+        match lookup.get(&key) {
+            Some(index) => groups[*index].1.push(row),
+            None => {
+                lookup.insert(key.clone(), groups.len());
+                groups.push((key, vec![row]));
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// Sorts the rows by the `order by` expressions (e.g. "order by exchange desc, symbol");
+/// large disk-based tables are sorted externally.
 fn step_4_sort_table(
+    ms: &Machine,
     src: Dataframe,
     sort_fields: &Vec<Expression>,
-) -> Dataframe {
-    // todo add sorting code to rc
-    src
+) -> std::io::Result<Dataframe> {
+    Sorting::sort_table(ms, src, sort_fields)
+}
+
+/// Orders the rows via the row IDs of an ordered index (see [find_index_order]);
+/// the transformed rows retain the IDs of their source rows.
+fn step_4_order_table_via_index(
+    src: Dataframe,
+    row_ids: &[usize],
+) -> std::io::Result<Dataframe> {
+    let columns = src.get_columns().to_owned();
+    let mut dest = match &src {
+        Disk(frc) => Disk(frc.create_scratch_table(columns)?),
+        _ => Model(ModelRowCollection::new(columns))
+    };
+    for row_id in row_ids {
+        if let Some(row) = src.read_one(*row_id)? {
+            if let ErrorValue(err) = dest.append_row(row) { return throw(err); }
+        }
+    }
+    Ok(dest)
 }
 
 fn step_5_limit_table(
@@ -514,6 +1283,48 @@ fn step_5_limit_table(
     Model(dest)
 }
 
+/// Drops the sort keys carried through the projection (see [carry_sort_keys]);
+/// i.e. the columns beyond the selected fields.
+fn step_6_drop_sort_keys(
+    src: Dataframe,
+    width: usize,
+) -> std::io::Result<Dataframe> {
+    let columns = src.get_columns()[..width].to_vec();
+    let mut dest = match &src {
+        Disk(frc) => Disk(frc.create_scratch_table(columns)?),
+        _ => Model(ModelRowCollection::new(columns))
+    };
+    for row in src.iter() {
+        let values = row.get_values()[..width].to_vec();
+        if let ErrorValue(err) = dest.append_row(row.with_values(values)) { return throw(err); }
+    }
+    Ok(dest)
+}
+
+/// Determines the order of the rows via an ordered (B-Tree) index; provided the rows are ordered
+/// by a single indexed column which is selected as-is (e.g. "select symbol, last_sale ... order by last_sale desc").
+fn find_index_order(
+    frc: &FileRowCollection,
+    order_fields: &[Expression],
+    fields: &[Expression],
+) -> std::io::Result<Option<Vec<usize>>> {
+    let (field, is_descending) = match order_fields {
+        [Ascending(field)] => (field.deref(), false),
+        [Descending(field)] => (field.deref(), true),
+        [field] => (field, false),
+        _ => return Ok(None)
+    };
+    match field {
+        Variable(name) if fields.contains(field) =>
+            match frc.get_ordered_column_ids().into_iter()
+                .find(|id| frc.get_columns()[*id].get_name() == name) {
+                Some(column_id) => frc.find_ordered_row_ids(column_id, is_descending).map(Some),
+                None => Ok(None)
+            }
+        _ => Ok(None)
+    }
+}
+
 fn do_rows_from_table_declaration(
     ms: &Machine,
     table: &Expression,
@@ -523,9 +1334,7 @@ fn do_rows_from_table_declaration(
     let machine = ms.to_owned();
     // create the config and an empty data file
     let ns = expect_namespace(&ms, table)?;
-    let cfg = ObjectConfig::build_table(columns.clone());
-    cfg.save(&ns)?;
-    FileRowCollection::table_file_create(&ns)?;
+    FileRowCollection::create_table(&ns, columns)?;
     // decipher the "from" expression
     let columns = Column::from_parameters(columns);
     let results = match from {
@@ -560,12 +1369,13 @@ fn expect_namespace(
     }
 }
 
+/// Evaluates the target of a mutation (e.g. "append stocks from ...") as a modifiable table
 fn expect_row_collection(
     ms: &Machine,
     table: &Expression,
 ) -> std::io::Result<Box<dyn RowCollection>> {
     let (_, v_table) = ms.evaluate(table)?;
-    v_table.to_table()
+    v_table.to_writable_table()
 }
 
 fn expect_rows(
@@ -585,7 +1395,7 @@ fn expect_rows(
             }
             Ok(rows)
         }
-        NamespaceValue(ns) => FileRowCollection::open(&ns)?.read_active_rows(),
+        NamespaceValue(ns) => Dataframe::open(&ns)?.read_active_rows(),
         Structured(s) => Ok(vec![Row::from_tuples(0, columns, &s.get_tuples())]),
         TableValue(rcv) => Ok(rcv.get_rows()),
         tv => throw(TypeMismatch(UnsupportedType(TableType(Parameter::from_columns(columns), 0), tv.get_type())))
@@ -633,8 +1443,26 @@ fn transform_table(
     condition: &Option<Conditions>,
 ) -> (Machine, TypedValue) {
     let columns = rc0.get_columns();
-    let mut rc1 = ModelRowCollection::new(field_columns.clone());
-    for row in rc0.iter() {
+    // large disk-based tables are transformed into a scratch file rather than in memory
+    let mut rc1 = match rc0 {
+        Disk(frc) if Sorting::exceeds_buffer(rc0, SORT_BUFFER_SIZE) =>
+            match frc.create_scratch_table(field_columns.clone()) {
+                Ok(frc) => Disk(frc),
+                Err(err) => return (ms0.clone(), ErrorValue(Exact(err.to_string())))
+            }
+        _ => Model(ModelRowCollection::new(field_columns.clone()))
+    };
+    // the condition may be satisfied via an index
+    let rows: Box<dyn Iterator<Item=Row> + '_> = match rc0.find_row_ids_where(ms0, condition) {
+        Ok(Some(row_ids)) =>
+            match row_ids.into_iter().map(|id| rc0.read_one(id)).collect::<std::io::Result<Vec<_>>>() {
+                Ok(rows) => Box::new(rows.into_iter().flatten()),
+                Err(err) => return (ms0.clone(), ErrorValue(Exact(err.to_string())))
+            }
+        Ok(None) => rc0.iter(),
+        Err(err) => return (ms0.clone(), ErrorValue(Exact(err.to_string())))
+    };
+    for row in rows {
         let ms = row.pollute(&ms0, columns);
         if row.matches(&ms, condition, columns) {
             match ms.evaluate_array(field_values) {
@@ -647,7 +1475,7 @@ fn transform_table(
             }
         }
     }
-    (ms0.clone(), TableValue(Model(rc1)))
+    (ms0.clone(), TableValue(rc1))
 }
 
 fn resolve_fields_as_columns(
@@ -714,7 +1542,970 @@ fn column_not_found(name: &str, columns: &Vec<Column>) -> String {
 #[cfg(test)]
 mod tests {
     use crate::columns::Column;
+    use crate::encryption::Encryption;
+    use crate::errors::Errors::{CheckViolation, ForeignKeyViolation, NotNullViolation, UniqueKeyViolation};
+    use crate::file_row_collection::FileRowCollection;
+    use crate::interpreter::Interpreter;
+    use crate::namespaces::Namespace;
+    use crate::numbers::Numbers::{Ack, F64Value, I64Value, RowsAffected};
+    use crate::object_config::{ObjectConfig, ViewSource};
+    use crate::row_collection::RowCollection;
+    use crate::row_history::RowHistory;
+    use crate::structures::Row;
     use crate::testdata::*;
+    use crate::typed_values::TypedValue::{Boolean, ErrorValue, Null, Number, StringValue, Undefined};
+
+    #[test]
+    fn test_select_group_by_having() {
+        verify_exact_table_with_ids(r#"
+            [+] stocks := ns("query-engine.aggregate.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.0 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 2.0 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 13.0 },
+                 { symbol: "GOTO", exchange: "OTC", last_sale: 24.0 },
+                 { symbol: "BOOM", exchange: "NYSE", last_sale: 4.0 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 17.0 }] ~> stocks
+            [+] select exchange, total: sum(last_sale), n: count(symbol),
+                       symbols: count(distinct symbol), high: max(last_sale),
+                       low: min(last_sale), mean: avg(last_sale)
+                from stocks
+                group by exchange
+                having count(symbol) > 1
+        "#, vec![
+            "|---------------------------------------------------------|",
+            "| id | exchange | total | n | symbols | high | low | mean |",
+            "|---------------------------------------------------------|",
+            "| 0  | NYSE     | 6     | 2 | 2       | 4    | 2   | 3    |",
+            "| 1  | OTC      | 54    | 3 | 2       | 24   | 13  | 18   |",
+            "|---------------------------------------------------------|"]);
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let code = r#"
+            [+] stocks := ns("query-engine.alter.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+            [+] create index stocks on [last_sale] using btree
+            [+] delete from stocks where symbol == "BIZ"
+            [+] alter table stocks add column rating: i64 = 5, drop column exchange,
+                    rename column last_sale to price, alter column price: i64
+            [+] select symbol, price, rating from stocks where price >= 1
+        "#;
+        verify_exact_table_with_ids(code, vec![
+            "|------------------------------|",
+            "| id | symbol | price | rating |",
+            "|------------------------------|",
+            "| 0  | ABC    | 11    | 5      |",
+            "|------------------------------|"]);
+        verify_exact_table_with_ids(code.replace("where price >= 1", "").as_str(), vec![
+            "|------------------------------|",
+            "| id | symbol | price | rating |",
+            "|------------------------------|",
+            "| 0  | ABC    | 11    | 5      |",
+            "| 2  | UNO    | 0     | 5      |",
+            "|------------------------------|"]);
+    }
+
+    #[test]
+    fn test_select_as_of() {
+        let code = r#"
+            [+] stocks := ns("query-engine.as_of.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> stocks
+            [+] update stocks via { last_sale: 12.05 } where symbol == "ABC"
+            [+] delete from stocks where symbol == "BIZ"
+            [+] QUERY
+        "#;
+        verify_exact_table_with_ids(code.replace("QUERY", "from stocks as of version 2").as_str(), vec![
+            "|------------------------------------|",
+            "| id | symbol | exchange | last_sale |",
+            "|------------------------------------|",
+            "| 0  | ABC    | AMEX     | 11.77     |",
+            "| 1  | BIZ    | NYSE     | 23.66     |",
+            "|------------------------------------|"]);
+        verify_exact_table_with_ids(code.replace("QUERY", r#"
+            select symbol, last_sale from stocks as of version 3 where last_sale > 20.0
+        "#).as_str(), vec![
+            "|-------------------------|",
+            "| id | symbol | last_sale |",
+            "|-------------------------|",
+            "| 1  | BIZ    | 23.66     |",
+            "|-------------------------|"]);
+        verify_exact_table_with_ids(code.replace("QUERY", "from stocks as of cal::now()").as_str(), vec![
+            "|------------------------------------|",
+            "| id | symbol | exchange | last_sale |",
+            "|------------------------------------|",
+            "| 0  | ABC    | AMEX     | 12.05     |",
+            "|------------------------------------|"]);
+    }
+
+    #[test]
+    fn test_select_via_hash_index() {
+        let code = r#"
+            [+] stocks := ns("query-engine.hash_index.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                 { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 },
+                 { symbol: "BOOM", exchange: "NYSE", last_sale: 56.87 }] ~> stocks
+            [+] create index stocks on [exchange]
+            [+] update stocks via { exchange: "NASDAQ" } where symbol == "UNO"
+            [+] delete from stocks where exchange == "AMEX"
+            [+] select symbol, exchange, last_sale from stocks where exchange == EXCHANGE
+        "#;
+        verify_exact_table_with_ids(code.replace("EXCHANGE", r#""NYSE""#).as_str(), vec![
+            "|------------------------------------|",
+            "| id | symbol | exchange | last_sale |",
+            "|------------------------------------|",
+            "| 1  | BIZ    | NYSE     | 23.66     |",
+            "| 4  | BOOM   | NYSE     | 56.87     |",
+            "|------------------------------------|"]);
+        verify_exact_table_with_ids(code.replace("EXCHANGE", r#""OTC""#).as_str(), vec![
+            "|------------------------------------|",
+            "| id | symbol | exchange | last_sale |",
+            "|------------------------------------|",
+            "| 3  | GOTO   | OTC      | 0.1428    |",
+            "|------------------------------------|"]);
+        verify_exact_table_with_ids(code.replace("EXCHANGE", r#""NASDAQ""#).as_str(), vec![
+            "|------------------------------------|",
+            "| id | symbol | exchange | last_sale |",
+            "|------------------------------------|",
+            "| 2  | UNO    | NASDAQ   | 0.2456    |",
+            "|------------------------------------|"]);
+    }
+
+    #[test]
+    fn test_select_via_btree_index() {
+        let code = r#"
+            [+] stocks := ns("query-engine.btree_index.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                 { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 },
+                 { symbol: "BOOM", exchange: "NYSE", last_sale: 56.87 },
+                 { symbol: "BAT", exchange: "AMEX", last_sale: 23.66 }] ~> stocks
+            [+] create index stocks on [last_sale] using btree
+            [+] update stocks via { last_sale: 0.5 } where symbol == "UNO"
+            [+] delete from stocks where last_sale > 50.0
+            [+] QUERY
+        "#;
+        verify_exact_table_with_ids(code.replace("QUERY", r#"
+            select symbol, exchange, last_sale from stocks
+            where last_sale >= 0.5
+            order by last_sale desc
+        "#).as_str(), vec![
+            "|------------------------------------|",
+            "| id | symbol | exchange | last_sale |",
+            "|------------------------------------|",
+            "| 0  | BIZ    | NYSE     | 23.66     |",
+            "| 1  | BAT    | AMEX     | 23.66     |",
+            "| 2  | ABC    | AMEX     | 11.77     |",
+            "| 3  | UNO    | OTC      | 0.5       |",
+            "|------------------------------------|"]);
+        verify_exact_table_with_ids(code.replace("QUERY", r#"
+            select symbol, last_sale from stocks
+            where last_sale betwixt 0.1 and 11.77
+            order by last_sale
+        "#).as_str(), vec![
+            "|-------------------------|",
+            "| id | symbol | last_sale |",
+            "|-------------------------|",
+            "| 0  | GOTO   | 0.1428    |",
+            "| 1  | UNO    | 0.5       |",
+            "|-------------------------|"]);
+    }
+
+    #[test]
+    fn test_select_via_memory_index() {
+        let code = r#"
+            [+] stocks := tools::to_table([
+                 { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                 { symbol: "BOOM", exchange: "NYSE", last_sale: 56.87 }])
+            [+] INDEX stocks on [exchange]
+            [+] select symbol, last_sale from stocks where exchange == "NYSE"
+        "#;
+        for keyword in ["create index", "declare index"] {
+            verify_exact_table_with_ids(code.replace("INDEX", keyword).as_str(), vec![
+                "|-------------------------|",
+                "| id | symbol | last_sale |",
+                "|-------------------------|",
+                "| 1  | BIZ    | 23.66     |",
+                "| 3  | BOOM   | 56.87     |",
+                "|-------------------------|"]);
+        }
+    }
+
+    #[test]
+    fn test_partitioned_table_lifecycle() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.partitioned.stocks")
+            [+] drop table stocks
+            [+] create table ns("query-engine.partitioned.stocks") (
+                    symbol: String(8), exchange: String(8), last_sale: f64
+                ) partition by [exchange]
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                 { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 }] ~> stocks
+        "#).unwrap();
+        let ns = Namespace::parse("query-engine.partitioned.stocks").unwrap();
+        assert!(std::path::Path::new(&ns.get_file_path("part2")).exists());
+
+        // queries are pruned to the matching partitions
+        let result = interpreter.evaluate(r#"
+            select symbol, last_sale from stocks where exchange == "OTC"
+        "#).unwrap();
+        assert_eq!(result.to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(1, vec![StringValue("UNO".into()), Number(F64Value(0.2456))]),
+            Row::new(3, vec![StringValue("GOTO".into()), Number(F64Value(0.1428))]),
+        ]);
+
+        // partitions are truncated and dropped for data retention
+        assert_eq!(
+            interpreter.evaluate(r#"truncate partition stocks ["AMEX"]"#).unwrap(),
+            Number(RowsAffected(1)));
+        assert_eq!(
+            interpreter.evaluate(r#"drop partition stocks ["OTC"]"#).unwrap(),
+            Number(RowsAffected(2)));
+        assert!(!std::path::Path::new(&ns.get_file_path("part1")).exists());
+        let result = interpreter.evaluate("stocks").unwrap();
+        assert_eq!(result.to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(2, vec![StringValue("BIZ".into()), StringValue("NYSE".into()), Number(F64Value(23.66))]),
+        ]);
+
+        // partitioned tables cannot be indexed
+        assert!(interpreter.evaluate("create index stocks on [symbol]").is_err());
+    }
+
+    #[test]
+    fn test_transaction_commit_and_rollback() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.transactions.stocks")
+            [+] drop table stocks
+            [+] create table ns("query-engine.transactions.stocks") (
+                    symbol: String(8), exchange: String(8), last_sale: f64
+                )
+            [+] { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 } ~> stocks
+        "#).unwrap();
+
+        // changes are undone upon rollback
+        interpreter.evaluate(r#"
+            [+] begin
+            [+] { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 } ~> stocks
+            [+] delete from stocks where symbol == "ABC"
+            [+] rollback
+        "#).unwrap();
+        let result = interpreter.evaluate("stocks").unwrap();
+        assert_eq!(result.to_table().unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+
+        // changes become visible upon commit
+        interpreter.evaluate(r#"
+            [+] begin
+            [+] { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 } ~> stocks
+            [+] commit
+        "#).unwrap();
+        let result = interpreter.evaluate("stocks").unwrap();
+        assert_eq!(result.to_table().unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "BIZ", "NYSE", 23.66),
+        ]);
+        assert!(interpreter.evaluate("commit").is_err());
+    }
+
+    #[test]
+    fn test_transaction_isolation() {
+        let mut session0 = Interpreter::new();
+        session0.evaluate(r#"
+            [+] stocks := ns("query-engine.transaction_isolation.stocks")
+            [+] drop table stocks
+            [+] create table ns("query-engine.transaction_isolation.stocks") (
+                    symbol: String(8), exchange: String(8), last_sale: f64
+                )
+            [+] { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 } ~> stocks
+            [+] begin
+            [+] { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 } ~> stocks
+        "#).unwrap();
+
+        // the uncommitted changes are visible to the transaction's session
+        let rows = session0.evaluate("from stocks").unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(rows, vec![make_quote(0, "ABC", "AMEX", 11.77), make_quote(1, "UNO", "OTC", 0.2456)]);
+
+        // whereas the other sessions may not read the table until the transaction ends
+        let mut session1 = Interpreter::new();
+        let select = r#"select symbol from ns("query-engine.transaction_isolation.stocks")"#;
+        assert_eq!(session1.evaluate(select).map_err(|err| err.to_string()), Err(
+            "Namespace 'query-engine.transaction_isolation.stocks' is in use by another transaction".into()));
+        session0.evaluate("commit").unwrap();
+        let rows = session1.evaluate(select).unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(rows, vec![
+            Row::new(0, vec![StringValue("ABC".into())]),
+            Row::new(1, vec![StringValue("UNO".into())]),
+        ]);
+    }
+
+    #[test]
+    fn test_transaction_block() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.transaction_block.stocks")
+            [+] quotes := ns("query-engine.transaction_block.quotes")
+            [+] drop table stocks
+            [+] drop table quotes
+            [+] create table ns("query-engine.transaction_block.stocks") (
+                    symbol: String(8), exchange: String(8), last_sale: f64
+                )
+            [+] create table ns("query-engine.transaction_block.quotes") (
+                    symbol: String(8), exchange: String(8), last_sale: f64
+                )
+            [+] { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 } ~> stocks
+        "#).unwrap();
+
+        // a failing block undoes its changes across all namespaces
+        let result = interpreter.evaluate(r#"
+            transaction {
+                { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 } ~> quotes
+                delete from stocks where symbol == "ABC"
+                [!] "Simulated failure"
+            }
+        "#).unwrap();
+        assert!(matches!(result, ErrorValue(..)));
+        let stocks = interpreter.evaluate("stocks").unwrap();
+        assert_eq!(stocks.to_table().unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+        let quotes = interpreter.evaluate("quotes").unwrap();
+        assert_eq!(quotes.to_table().unwrap().read_active_rows().unwrap(), vec![]);
+
+        // a successful block commits its changes
+        interpreter.evaluate(r#"
+            transaction {
+                { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 } ~> quotes
+                delete from stocks where symbol == "ABC"
+            }
+        "#).unwrap();
+        let stocks = interpreter.evaluate("stocks").unwrap();
+        assert_eq!(stocks.to_table().unwrap().read_active_rows().unwrap(), vec![]);
+        let quotes = interpreter.evaluate("quotes").unwrap();
+        assert_eq!(quotes.to_table().unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "UNO", "OTC", 0.2456),
+        ]);
+    }
+
+    #[test]
+    fn test_materialized_view_lifecycle() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.materialized_view.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> stocks
+        "#).unwrap();
+        assert_eq!(interpreter.evaluate(r#"
+            create materialized view ns("query-engine.materialized_view.high_flyers") as
+                select symbol, last_sale from ns("query-engine.materialized_view.stocks") where last_sale > 10.0
+        "#).unwrap(), Number(RowsAffected(2)));
+        let read = |interpreter: &mut Interpreter| interpreter.evaluate(r#"
+            ns("query-engine.materialized_view.high_flyers")
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap();
+
+        // the view is unaffected by changes to its source until refreshed
+        interpreter.evaluate(r#"
+            append stocks from { symbol: "BOOM", exchange: "NYSE", last_sale: 56.87 }
+        "#).unwrap();
+        assert_eq!(read(&mut interpreter).len(), 2);
+
+        // the source has only grown, so solely the new row is evaluated
+        assert_eq!(
+            interpreter.evaluate(r#"refresh ns("query-engine.materialized_view.high_flyers")"#).unwrap(),
+            Number(RowsAffected(1)));
+        assert_eq!(read(&mut interpreter), vec![
+            Row::new(0, vec![StringValue("ABC".into()), Number(F64Value(11.77))]),
+            Row::new(1, vec![StringValue("BIZ".into()), Number(F64Value(23.66))]),
+            Row::new(2, vec![StringValue("BOOM".into()), Number(F64Value(56.87))]),
+        ]);
+
+        // the refresh is recorded
+        let ns = Namespace::parse("query-engine.materialized_view.high_flyers").unwrap();
+        match ObjectConfig::load(&ns).unwrap() {
+            ObjectConfig::MaterializedViewConfig { last_refreshed, sources, .. } => {
+                assert!(last_refreshed > 0);
+                let stocks = FileRowCollection::open(&Namespace::parse("query-engine.materialized_view.stocks").unwrap()).unwrap();
+                assert_eq!(sources, vec![ViewSource::new(
+                    "query-engine.materialized_view.stocks".into(), 4, RowHistory::find_latest_version(&stocks).unwrap())]);
+            }
+            other => panic!("Expected a materialized view, got {:?}", other)
+        }
+        assert_eq!(
+            interpreter.evaluate(r#"drop materialized view ns("query-engine.materialized_view.high_flyers")"#).unwrap(),
+            Number(Ack));
+    }
+
+    #[test]
+    fn test_materialized_view_refresh_after_updates_and_deletes() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.materialized_view_rewrites.stocks")
+            [+] drop table stocks
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> stocks
+            [+] high_flyers := ns("query-engine.materialized_view_rewrites.high_flyers")
+            [+] create materialized view ns("query-engine.materialized_view_rewrites.high_flyers") as
+                select symbol, last_sale from ns("query-engine.materialized_view_rewrites.stocks") where last_sale > 10.0
+        "#).unwrap();
+        let read = |interpreter: &mut Interpreter| interpreter.evaluate("high_flyers").unwrap()
+            .to_table().unwrap().read_active_rows().unwrap();
+
+        // an updated row is recomputed, despite the source having grown
+        interpreter.evaluate(r#"
+            [+] update stocks via { last_sale: 99.0 } where symbol == "ABC"
+            [+] append stocks from { symbol: "BOOM", exchange: "NYSE", last_sale: 56.87 }
+            [+] refresh high_flyers
+        "#).unwrap();
+        assert_eq!(read(&mut interpreter), vec![
+            Row::new(0, vec![StringValue("ABC".into()), Number(F64Value(99.0))]),
+            Row::new(1, vec![StringValue("BIZ".into()), Number(F64Value(23.66))]),
+            Row::new(2, vec![StringValue("BOOM".into()), Number(F64Value(56.87))]),
+        ]);
+
+        // as is a deleted row
+        interpreter.evaluate(r#"
+            [+] delete from stocks where symbol == "ABC"
+            [+] refresh high_flyers
+        "#).unwrap();
+        assert_eq!(read(&mut interpreter), vec![
+            Row::new(0, vec![StringValue("BIZ".into()), Number(F64Value(23.66))]),
+            Row::new(1, vec![StringValue("BOOM".into()), Number(F64Value(56.87))]),
+        ]);
+
+        // and a row appended into the slot of the deleted row
+        assert_eq!(interpreter.evaluate(r#"
+            [+] append stocks from { symbol: "GOTO", exchange: "OTC", last_sale: 12.5 }
+            [+] refresh high_flyers
+        "#).unwrap(), Number(RowsAffected(3)));
+
+        // whereas once rows are solely appended, only they are evaluated
+        assert_eq!(interpreter.evaluate(r#"
+            [+] append stocks from { symbol: "JET", exchange: "NYSE", last_sale: 45.0 }
+            [+] refresh high_flyers
+        "#).unwrap(), Number(RowsAffected(1)));
+        assert_eq!(read(&mut interpreter).len(), 4);
+    }
+
+    #[test]
+    fn test_materialized_view_scheduled_refresh() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.materialized_view_scheduled.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+            [+] create materialized view ns("query-engine.materialized_view_scheduled.by_symbol") refresh every 0 as
+                select symbol, exchange from ns("query-engine.materialized_view_scheduled.stocks") order by symbol
+            [+] append stocks from { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }
+        "#).unwrap();
+
+        // the refresh is due upon each read
+        assert_eq!(interpreter.evaluate(r#"
+            select symbol from ns("query-engine.materialized_view_scheduled.by_symbol")
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(0, vec![StringValue("ABC".into())]),
+            Row::new(1, vec![StringValue("BIZ".into())]),
+            Row::new(2, vec![StringValue("UNO".into())]),
+        ]);
+    }
+
+    #[test]
+    fn test_view_lifecycle() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.view.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> stocks
+            [+] create view ns("query-engine.view.high_flyers") as
+                select symbol, last_sale from ns("query-engine.view.stocks") where last_sale > 10.0
+        "#).unwrap();
+        let query = r#"select symbol, last_sale from ns("query-engine.view.high_flyers") order by symbol"#;
+        let read = |interpreter: &mut Interpreter| interpreter.evaluate(query).unwrap()
+            .to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(read(&mut interpreter), vec![
+            Row::new(0, vec![StringValue("ABC".into()), Number(F64Value(11.77))]),
+            Row::new(1, vec![StringValue("BIZ".into()), Number(F64Value(23.66))]),
+        ]);
+
+        // the view reflects subsequent changes to the table
+        interpreter.evaluate(r#"
+            append stocks from { symbol: "BOOM", exchange: "NYSE", last_sale: 56.87 }
+        "#).unwrap();
+        assert_eq!(read(&mut interpreter).len(), 3);
+
+        // whereas the view itself cannot be modified
+        for mutation in [
+            r#"append ns("query-engine.view.high_flyers") from { symbol: "JET", last_sale: 32.12 }"#,
+            r#"update ns("query-engine.view.high_flyers") via { last_sale: 0.0 } where symbol == "ABC""#,
+            r#"delete from ns("query-engine.view.high_flyers") where symbol == "ABC""#,
+            r#"[{ symbol: "JET", last_sale: 32.12 }] ~> ns("query-engine.view.high_flyers")"#,
+        ] {
+            let outcome = interpreter.evaluate(mutation);
+            assert!(outcome.is_err() || matches!(outcome, Ok(ErrorValue(..))), "{mutation} => {outcome:?}");
+        }
+        assert_eq!(read(&mut interpreter).len(), 3);
+
+        // the view can be described
+        let described = interpreter.evaluate(r#"
+            tools::describe(ns("query-engine.view.high_flyers"))
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(described.iter().map(|row| row[0].to_owned()).collect::<Vec<_>>(), vec![
+            StringValue("symbol".into()), StringValue("last_sale".into()),
+        ]);
+
+        // once dropped, the view can no longer be queried
+        assert_eq!(interpreter.evaluate(r#"drop view ns("query-engine.view.high_flyers")"#).unwrap(), Number(Ack));
+        assert!(interpreter.evaluate(query).is_err());
+    }
+
+    #[test]
+    fn test_unique_index() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.unique_index.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+             { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+             { symbol: "ABC", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+        "#).unwrap();
+
+        // the existing rows violate uniqueness on symbol, but not on (symbol, exchange)
+        let err = interpreter.evaluate("create unique index stocks on [symbol]").unwrap_err();
+        assert_eq!(err.to_string(), r#"Unique key violation: (symbol) = ("ABC") already exists"#);
+        assert_eq!(interpreter.evaluate("create unique index stocks on [symbol, exchange]").unwrap(), Number(Ack));
+
+        // writes producing duplicate keys are rejected
+        assert_eq!(
+            interpreter.evaluate(r#"append stocks from { symbol: "BIZ", exchange: "NYSE", last_sale: 1.0 }"#).unwrap(),
+            ErrorValue(UniqueKeyViolation("symbol, exchange".into(), r#""BIZ", "NYSE""#.into())));
+        assert!(interpreter.evaluate(r#"
+            update stocks via { exchange: "AMEX" } where exchange == "OTC"
+        "#).is_err());
+        assert_eq!(
+            interpreter.evaluate(r#"append stocks from { symbol: "BIZ", exchange: "AMEX", last_sale: 1.0 }"#).unwrap(),
+            Number(RowsAffected(1)));
+
+        // verify the table
+        assert_eq!(interpreter.evaluate("stocks").unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "BIZ", "NYSE", 23.66),
+            make_quote(2, "ABC", "OTC", 0.2456),
+            make_quote(3, "BIZ", "AMEX", 1.0),
+        ]);
+    }
+
+    #[test]
+    fn test_column_constraints() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.constraints.stocks")
+            [+] drop table stocks
+            [+] create table ns("query-engine.constraints.stocks") (
+                    symbol: String(8) not null,
+                    exchange: String(8) = "NYSE",
+                    last_sale: f64 check (last_sale > 0.0))
+        "#).unwrap();
+
+        // missing fields are filled with their defaults
+        assert_eq!(
+            interpreter.evaluate(r#"append stocks from { symbol: "ABC", last_sale: 11.77 }"#).unwrap(),
+            Number(RowsAffected(1)));
+        assert_eq!(
+            interpreter.evaluate(r#"append stocks from { symbol: "BIZ", exchange: "AMEX" }"#).unwrap(),
+            Number(RowsAffected(1)));
+
+        // writes violating the constraints are rejected
+        assert_eq!(
+            interpreter.evaluate(r#"append stocks from { exchange: "OTC", last_sale: 0.2456 }"#).unwrap(),
+            ErrorValue(NotNullViolation("symbol".into())));
+        assert_eq!(
+            interpreter.evaluate(r#"append stocks from { symbol: "UNO", last_sale: -1.0 }"#).unwrap(),
+            ErrorValue(CheckViolation("last_sale".into(), "last_sale > 0.0".into())));
+        assert!(interpreter.evaluate(r#"
+            update stocks via { last_sale: 0.0 } where symbol == "ABC"
+        "#).is_err());
+
+        // fields overwritten without a value are filled with their defaults
+        let mut frc = FileRowCollection::open(&Namespace::parse("query-engine.constraints.stocks").unwrap()).unwrap();
+        assert!(!matches!(frc.overwrite_field(1, 1, Undefined), ErrorValue(..)));
+
+        // verify the table
+        assert_eq!(interpreter.evaluate("stocks").unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "NYSE", 11.77),
+            Row::new(1, vec![StringValue("BIZ".into()), StringValue("NYSE".into()), Null]),
+        ]);
+    }
+
+    #[test]
+    fn test_foreign_key_references() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.references.stocks")
+            [+] orders := ns("query-engine.references.orders")
+            [+] drop table orders
+            [+] drop table stocks
+            [+] create table ns("query-engine.references.stocks") (
+                    symbol: String(8), exchange: String(8), last_sale: f64)
+            [+] create index stocks on [symbol]
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+            [+] create table ns("query-engine.references.orders") (
+                    symbol: String(8) references ns("query-engine.references.stocks")(symbol) on delete cascade,
+                    quantity: i64)
+        "#).unwrap();
+
+        // only keys present within the referenced table are accepted
+        assert_eq!(
+            interpreter.evaluate(r#"append orders from { symbol: "ABC", quantity: 100 }"#).unwrap(),
+            Number(RowsAffected(1)));
+        assert_eq!(
+            interpreter.evaluate(r#"append orders from { symbol: "UNO", quantity: 50 }"#).unwrap(),
+            Number(RowsAffected(1)));
+        assert_eq!(
+            interpreter.evaluate(r#"append orders from { symbol: "XYZ", quantity: 25 }"#).unwrap(),
+            ErrorValue(ForeignKeyViolation("query-engine.references.stocks(symbol)".into(), "\"XYZ\"".into())));
+
+        // deleting a referenced row deletes the rows referencing it
+        assert_eq!(
+            interpreter.evaluate(r#"delete from stocks where symbol == "ABC""#).unwrap(),
+            Number(RowsAffected(1)));
+        assert_eq!(interpreter.evaluate("orders").unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(1, vec![StringValue("UNO".into()), Number(I64Value(50))]),
+        ]);
+    }
+
+    #[test]
+    fn test_foreign_key_cascade_rollback() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.references_tx.stocks")
+            [+] orders := ns("query-engine.references_tx.orders")
+            [+] trades := ns("query-engine.references_tx.trades")
+            [+] drop table orders
+            [+] drop table trades
+            [+] drop table stocks
+            [+] create table ns("query-engine.references_tx.stocks") (
+                    symbol: String(8), exchange: String(8), last_sale: f64)
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+            [+] create table ns("query-engine.references_tx.orders") (
+                    symbol: String(8) references ns("query-engine.references_tx.stocks")(symbol) on delete cascade,
+                    quantity: i64)
+            [+] create table ns("query-engine.references_tx.trades") (
+                    symbol: String(8) references ns("query-engine.references_tx.stocks")(symbol) on delete set null,
+                    quantity: i64)
+            [+] [{ symbol: "ABC", quantity: 100 }, { symbol: "UNO", quantity: 50 }] ~> orders
+            [+] { symbol: "ABC", quantity: 25 } ~> trades
+        "#).unwrap();
+
+        // the cascaded and nulled rows are restored along with the deleted row
+        interpreter.evaluate(r#"
+            [+] begin
+            [+] delete from stocks where symbol == "ABC"
+            [+] rollback
+        "#).unwrap();
+        let read = |interpreter: &mut Interpreter, name: &str|
+            interpreter.evaluate(name).unwrap().to_table().unwrap().read_active_rows().unwrap().len();
+        assert_eq!(read(&mut interpreter, "stocks"), 2);
+        assert_eq!(read(&mut interpreter, "orders"), 2);
+        assert_eq!(interpreter.evaluate(r#"select quantity from trades where symbol == "ABC""#).unwrap()
+                       .to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(0, vec![Number(I64Value(25))]),
+        ]);
+
+        // whereas upon commit, they remain deleted (or nulled)
+        interpreter.evaluate(r#"
+            [+] begin
+            [+] delete from stocks where symbol == "ABC"
+            [+] commit
+        "#).unwrap();
+        assert_eq!(read(&mut interpreter, "stocks"), 1);
+        assert_eq!(read(&mut interpreter, "orders"), 1);
+        assert_eq!(interpreter.evaluate("trades").unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(0, vec![Null, Number(I64Value(25))]),
+        ]);
+    }
+
+    #[test]
+    fn test_table_with_compressed_column() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] articles := ns("query-engine.compressed.articles")
+            [+] drop table articles
+            [+] create table ns("query-engine.compressed.articles") (title: String(16), body: String(128) compressed)
+            [+] append articles from { title: "lorem", body: "lorem ipsum dolor sit amet " * 20 }
+            [+] update articles via { body: "ipsum dolor sit amet lorem " * 10 } where title == "lorem"
+        "#).unwrap();
+
+        // the body is transparently decompressed
+        assert_eq!(interpreter.evaluate("select body from articles").unwrap()
+                       .to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(0, vec![StringValue("ipsum dolor sit amet lorem ".repeat(10))])
+        ]);
+
+        // the description reports whether each column is compressed
+        let described = interpreter.evaluate("tools::describe(articles)").unwrap()
+            .to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(described.iter().map(|row| row[4].to_owned()).collect::<Vec<_>>(), vec![
+            Boolean(false), Boolean(true)
+        ]);
+    }
+
+    #[test]
+    fn test_encrypted_table() {
+        Encryption::install_key(&[7; 32]);
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] clients := ns("query-engine.encrypted.clients")
+            [+] drop table clients
+            [+] create table ns("query-engine.encrypted.clients") (name: String(16), ssn: String(11)) encrypted
+            [+] append clients from [{ name: "Alice", ssn: "123-45-6789" }, { name: "Bob", ssn: "987-65-4321" }]
+            [+] update clients via { ssn: "555-55-5555" } where name == "Bob"
+        "#).unwrap();
+
+        // the rows are transparently decrypted
+        let expected = vec![
+            Row::new(0, vec![StringValue("Alice".into()), StringValue("123-45-6789".into())]),
+            Row::new(1, vec![StringValue("Bob".into()), StringValue("555-55-5555".into())]),
+        ];
+        let read_rows = |interpreter: &mut Interpreter| interpreter.evaluate("from clients").unwrap()
+            .to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(read_rows(&mut interpreter), expected);
+
+        // whereas the table file does not contain the values
+        let path = Namespace::parse("query-engine.encrypted.clients").unwrap().get_table_file_path();
+        let contains_ssn = || std::fs::read(&path).unwrap().windows(11).any(|window| window == b"123-45-6789");
+        assert!(!contains_ssn());
+
+        // decrypting (and re-encrypting) the table rewrites its rows
+        interpreter.evaluate("alter table clients decrypt").unwrap();
+        assert!(contains_ssn());
+        assert_eq!(read_rows(&mut interpreter), expected);
+        interpreter.evaluate("alter table clients encrypt").unwrap();
+        assert!(!contains_ssn());
+        assert_eq!(read_rows(&mut interpreter), expected);
+    }
+
+    #[test]
+    fn test_append_only_table() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] events := ns("query-engine.append_only.events")
+            [+] drop table events
+            [+] create table ns("query-engine.append_only.events") (kind: String(8)) append only
+            [+] append events from [{ kind: "open" }, { kind: "close" }]
+            [+] delete from events where kind == "open"
+            [+] append events from { kind: "reopen" }
+        "#).unwrap();
+        let read_ids = |interpreter: &mut Interpreter| interpreter.evaluate("from events").unwrap()
+            .to_table().unwrap().read_active_rows().unwrap().iter()
+            .map(|row| row.get_id()).collect::<Vec<_>>();
+        assert_eq!(read_ids(&mut interpreter), vec![1, 2]);
+
+        // once slots may be reused, appends fill the slots of deleted rows
+        interpreter.evaluate(r#"
+            [+] alter table events reuse slots
+            [+] delete from events where kind == "close"
+            [+] append events from { kind: "closed" }
+        "#).unwrap();
+        assert_eq!(read_ids(&mut interpreter), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_sequences_and_serial_columns() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] order_ids := ns("query-engine.serial.order_ids")
+            [+] create sequence order_ids start with 1000 increment by 10
+            [+] stocks := ns("query-engine.serial.stocks")
+            [+] drop table stocks
+            [+] create table ns("query-engine.serial.stocks") (id: i64 serial, symbol: String(8))
+            [+] append stocks from [{ symbol: "ABC" }, { symbol: "UNO" }, { symbol: "BIZ" }]
+            [+] delete from stocks where symbol == "ABC"
+            [+] import tools
+            [+] stocks:::compact()
+            [+] append stocks from { symbol: "GOTO" }
+        "#).unwrap();
+
+        // the serial values are unaffected by the compaction
+        let mut rows = interpreter.evaluate("stocks").unwrap().to_table().unwrap().read_active_rows().unwrap()
+            .into_iter().map(|row| (row.get(0), row.get(1))).collect::<Vec<_>>();
+        rows.sort();
+        assert_eq!(rows, vec![
+            (Number(I64Value(2)), StringValue("UNO".into())),
+            (Number(I64Value(3)), StringValue("BIZ".into())),
+            (Number(I64Value(4)), StringValue("GOTO".into())),
+        ]);
+
+        // sequences hand out successive values
+        assert_eq!(interpreter.evaluate("nextval(order_ids)").unwrap(), Number(I64Value(1000)));
+        assert_eq!(interpreter.evaluate("nextval(order_ids)").unwrap(), Number(I64Value(1010)));
+    }
+
+    #[test]
+    fn test_table_triggers() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            [+] stocks := ns("query-engine.triggers.stocks")
+            [+] audit := ns("query-engine.triggers.audit")
+            [+] drop table stocks
+            [+] drop table audit
+            [+] create table ns("query-engine.triggers.stocks") (symbol: String(8), exchange: String(8), last_sale: f64)
+            [+] create table ns("query-engine.triggers.audit") (symbol: String(8), action: String(8))
+            [+] create trigger positive_price before append on stocks do fn(row) => (row::last_sale) > 0.0
+            [+] create trigger otc_only before update on stocks do fn(row) => { exchange: "OTC" }
+            [+] create trigger audit_deletes after delete on stocks do fn(row) =>
+                    append ns("query-engine.triggers.audit") from { symbol: row::symbol, action: "delete" }
+            [+] append stocks from [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                                    { symbol: "UNO", exchange: "NYSE", last_sale: 0.2456 }]
+        "#).unwrap();
+
+        // before-triggers may reject the row
+        let err = interpreter.evaluate(r#"
+            append stocks from { symbol: "BAD", exchange: "AMEX", last_sale: -1.0 }
+        "#).unwrap_err();
+        assert_eq!(err.to_string(), "Trigger rejection: the row was rejected by trigger 'positive_price'");
+
+        // before-triggers may modify the row
+        assert_eq!(
+            interpreter.evaluate(r#"update stocks via { last_sale: 0.5 } where symbol == "UNO""#).unwrap(),
+            Number(RowsAffected(1)));
+
+        // after-triggers may write to other tables
+        assert_eq!(
+            interpreter.evaluate(r#"delete from stocks where symbol == "ABC""#).unwrap(),
+            Number(RowsAffected(1)));
+        assert_eq!(interpreter.evaluate("stocks").unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(1, vec![StringValue("UNO".into()), StringValue("OTC".into()), Number(F64Value(0.5))]),
+        ]);
+        assert_eq!(interpreter.evaluate("audit").unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(0, vec![StringValue("ABC".into()), StringValue("delete".into())]),
+        ]);
+
+        // dropped triggers are no longer fired
+        assert_eq!(interpreter.evaluate("drop trigger audit_deletes on stocks").unwrap(), Number(Ack));
+        assert_eq!(interpreter.evaluate("drop trigger audit_deletes on stocks").unwrap(), Boolean(false));
+        interpreter.evaluate(r#"delete from stocks where symbol == "UNO""#).unwrap();
+        assert_eq!(interpreter.evaluate("audit").unwrap().to_table().unwrap().read_active_rows().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_select_inner_join() {
+        verify_exact_table_with_ids(r#"
+            [+] stocks := ns("query-engine.join_inner.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                 { symbol: "BOOM", exchange: "NYSE", last_sale: 56.87 }] ~> stocks
+            [+] exchanges := tools::to_table([
+                 { code: "AMEX", name: "American Stock Exchange" },
+                 { code: "NYSE", name: "New York Stock Exchange" }])
+            [+] select s.symbol, e.name, price: s.last_sale
+                from s: stocks join e: exchanges on s.exchange == e.code
+                where s.last_sale > 20.0
+        "#, vec![
+            "|-------------------------------------------------|",
+            "| id | s.symbol | e.name                  | price |",
+            "|-------------------------------------------------|",
+            "| 1  | BIZ      | New York Stock Exchange | 23.66 |",
+            "| 2  | BOOM     | New York Stock Exchange | 56.87 |",
+            "|-------------------------------------------------|"]);
+    }
+
+    #[test]
+    fn test_select_outer_joins() {
+        let code = r#"
+            [+] stocks := ns("query-engine.join_outer.stocks")
+            [+] table(symbol: String(8), exchange: String(8)) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX" },
+                 { symbol: "UNO", exchange: "OTC" }] ~> stocks
+            [+] exchanges := tools::to_table([
+                 { code: "AMEX", city: "New York" },
+                 { code: "LSE", city: "London" }])
+            [+] select symbol: stocks.symbol, city: exchanges.city
+                from stocks JOIN exchanges on stocks.exchange == exchanges.code
+        "#;
+        verify_exact_table_with_ids(code.replace("JOIN", "left join").as_str(), vec![
+            "|------------------------|",
+            "| id | symbol | city     |",
+            "|------------------------|",
+            "| 0  | ABC    | New York |",
+            "| 1  | UNO    | null     |",
+            "|------------------------|"]);
+        verify_exact_table_with_ids(code.replace("JOIN", "right outer join").as_str(), vec![
+            "|------------------------|",
+            "| id | symbol | city     |",
+            "|------------------------|",
+            "| 0  | ABC    | New York |",
+            "| 1  | null   | London   |",
+            "|------------------------|"]);
+        verify_exact_table_with_ids(code.replace("JOIN", "full join").as_str(), vec![
+            "|------------------------|",
+            "| id | symbol | city     |",
+            "|------------------------|",
+            "| 0  | ABC    | New York |",
+            "| 1  | UNO    | null     |",
+            "| 2  | null   | London   |",
+            "|------------------------|"]);
+    }
+
+    #[test]
+    fn test_select_cross_join_with_non_equi_condition() {
+        verify_exact_table_with_ids(r#"
+            [+] a := tools::to_table([{ n: 1 }, { n: 2 }, { n: 3 }])
+            [+] b := tools::to_table([{ m: 1 }, { m: 2 }])
+            [+] select a.n, b.m from a cross join b where a.n > b.m
+        "#, vec![
+            "|----------------|",
+            "| id | a.n | b.m |",
+            "|----------------|",
+            "| 2  | 2   | 1   |",
+            "| 4  | 3   | 1   |",
+            "| 5  | 3   | 2   |",
+            "|----------------|"]);
+    }
+
+    #[test]
+    fn test_select_aggregates_without_group_by() {
+        verify_exact_table_with_ids(r#"
+            [+] stocks := ns("query-engine.aggregate_all.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.0 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 2.0 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 13.0 }] ~> stocks
+            [+] select n: count(), first_symbol: first(symbol), last_symbol: last(symbol)
+                from stocks
+                where last_sale > 1.0
+        "#, vec![
+            "|-------------------------------------|",
+            "| id | n | first_symbol | last_symbol |",
+            "|-------------------------------------|",
+            "| 0  | 3 | ABC          | UNO         |",
+            "|-------------------------------------|"]);
+    }
 
     #[test]
     fn test_select_from_where_order_by_limit() {
@@ -741,7 +2532,57 @@ mod tests {
             "| id | symbol | exchange | price   | msn                              |",
             "|---------------------------------------------------------------------|",
             "| 0  | ABC    | AMEX     | 11.77   | 902fbdd2b1df0c4f70b4a5d23525e932 |",
-            "| 2  | UNO    | OTC      | 13.2456 | 59f822bcaa8e119bde63eb00919b367a |",
+            "| 1  | GOTO   | OTC      | 24.1428 | 4b8bb3c94a9676b5f34ace4d7102e5b9 |",
             "|---------------------------------------------------------------------|"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_select_order_by_multiple_keys() {
+        verify_exact_table_with_ids(r#"
+            [+] stocks := ns("query-engine.select_order_by.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 0.66 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 13.2456 },
+                 { symbol: "GOTO", exchange: "OTC", last_sale: 24.1428 },
+                 { symbol: "BOOM", exchange: "NYSE", last_sale: 0.0872 }] ~> stocks
+            [+] select symbol, exchange, last_sale
+                from stocks
+                order by exchange desc, last_sale asc
+        "#, vec![
+            "|------------------------------------|",
+            "| id | symbol | exchange | last_sale |",
+            "|------------------------------------|",
+            "| 0  | UNO    | OTC      | 13.2456   |",
+            "| 1  | GOTO   | OTC      | 24.1428   |",
+            "| 2  | BOOM   | NYSE     | 0.0872    |",
+            "| 3  | BIZ    | NYSE     | 0.66      |",
+            "| 4  | ABC    | AMEX     | 11.77     |",
+            "|------------------------------------|"]);
+    }
+
+    #[test]
+    fn test_select_order_by_unselected_keys() {
+        verify_exact_table_with_ids(r#"
+            [+] stocks := ns("query-engine.select_order_by_unselected.stocks")
+            [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                 { symbol: "BIZ", exchange: "NYSE", last_sale: 0.66 },
+                 { symbol: "UNO", exchange: "OTC", last_sale: 13.2456 },
+                 { symbol: "GOTO", exchange: "OTC", last_sale: 24.1428 },
+                 { symbol: "BOOM", exchange: "NYSE", last_sale: 0.0872 }] ~> stocks
+            [+] select symbol
+                from stocks
+                where last_sale > 0.5
+                order by exchange desc, last_sale
+        "#, vec![
+            "|-------------|",
+            "| id | symbol |",
+            "|-------------|",
+            "| 0  | UNO    |",
+            "| 1  | GOTO   |",
+            "| 2  | BIZ    |",
+            "| 3  | ABC    |",
+            "|-------------|"]);
+    }
+}
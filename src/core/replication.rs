@@ -0,0 +1,605 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// replication module
+////////////////////////////////////////////////////////////////////
+
+use crate::encryption::Encryption;
+use crate::errors::throw;
+use crate::errors::Errors::{Exact, ReplicaReadOnly};
+use crate::file_row_collection::FileRowCollection;
+use crate::machine::Machine;
+use crate::namespaces::Namespace;
+use crate::numbers::Numbers::Ack;
+use crate::object_config::ObjectConfig;
+use crate::row_collection::RowCollection;
+use crate::row_history::RowHistory;
+use crate::sequences::Sequence;
+use crate::structures::Row;
+use crate::typed_values::TypedValue::{Binary, ErrorValue, Number, Undefined};
+use crate::websockets::OxideWebSocketClient;
+use crate::write_ahead_log::WriteAheadLog;
+use log::error;
+use serde::{Deserialize, Serialize};
+use shared_lib::fail;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// the interval (in milliseconds) at which a follower reconnects to its primary
+const RECONNECT_INTERVAL: u64 = 1000;
+
+/// the maximum number of rows published per event (e.g. while a table is synchronized)
+const ROWS_PER_EVENT: usize = 1000;
+
+/// the followers within this process => (oxide home|follower)
+static FOLLOWERS: OnceLock<Mutex<HashMap<String, Follower>>> = OnceLock::new();
+
+/// Represents a home following a primary
+struct Follower {
+    /// the database followed (if any); otherwise every database is followed
+    database: Option<String>,
+    /// the address of the primary (e.g. "localhost:8080")
+    primary: String,
+}
+
+/// Represents a change published by a primary to its followers
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplicationEvents {
+    /// the table was created (or re-created; e.g. "alter table"); its rows are published next
+    Created { ns: Namespace, config: Box<ObjectConfig> },
+    /// the view or sequence was created (or re-created)
+    Defined { ns: Namespace, config: Box<ObjectConfig> },
+    /// the object was dropped
+    Dropped { ns: Namespace },
+    /// the next value of a sequence changed => (the serial column, if the sequence backs one|next value)
+    Sequenced { ns: Namespace, column: Option<String>, next_value: i64 },
+    /// rows of the table were written => (row ID|the row; where None indicates a deleted row)
+    Written { ns: Namespace, rows: Vec<(usize, Option<Row>)> },
+}
+
+/// Replication; a server started as a follower of another (e.g. "oxide follower 8081 localhost 8080")
+/// subscribes to the primary's [ReplicationFeed] via websockets, and applies the tables (and their rows),
+/// views and sequences it receives to its own home (e.g. $OXIDE_HOME); marking the rows it writes replicated.
+/// Partitioned tables and materialized views are not replicated; thus a primary having any of them
+/// (within the followed database) refuses its followers. The tables of the followed database (or of
+/// every database, if none was given) are read-only. Failover is manual: restarting the follower
+/// as an ordinary server (or invoking `oxide::promote()`) makes its tables writable.
+pub struct Replication;
+
+impl Replication {
+    /// Applies a change published by the primary to the objects of the current home
+    pub fn apply(event: ReplicationEvents) -> std::io::Result<()> {
+        match event {
+            ReplicationEvents::Created { ns, config } => {
+                FileRowCollection::create_table(&ns, &config.get_columns())?;
+                if config.is_encrypted() { Encryption::ensure_key()?; }
+                config.save(&ns)?;
+                // the indices are reset, since the rows are republished
+                let frc = FileRowCollection::open(&ns)?;
+                for column_id in frc.get_indexed_column_ids() { frc.rebuild_hash_index(column_id)?; }
+                for column_id in frc.get_ordered_column_ids() { frc.rebuild_ordered_index(column_id)?; }
+                Ok(())
+            }
+            ReplicationEvents::Defined { ns, config } => config.save(&ns),
+            ReplicationEvents::Dropped { ns } => {
+                let path = ns.get_table_file_path();
+                for outcome in [fs::remove_file(&path), ObjectConfig::delete(&ns)] {
+                    match outcome {
+                        Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+                        _ => {}
+                    }
+                }
+                WriteAheadLog::discard(path.as_str())?;
+                RowHistory::discard(path.as_str())
+            }
+            ReplicationEvents::Sequenced { ns, column, next_value } => match column {
+                Some(column) => Sequence::for_column(&ns.get_table_file_path(), &column).set_next_value(next_value),
+                None => Sequence::open(&ns)?.set_next_value(next_value)
+            },
+            ReplicationEvents::Written { ns, rows } => {
+                let mut frc = FileRowCollection::open(&ns)?;
+                for (id, row) in rows {
+                    frc.overwrite_replicated_row(id, row).to_result(|_| ())?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Verifies the table may be written; the tables of the database followed
+    /// by the current home may only be written via its primary.
+    pub fn ensure_writable(ns: &Namespace) -> std::io::Result<()> {
+        match Self::lock_followers()?.get(&Machine::oxide_home()) {
+            Some(Follower { database, primary }) if database.as_ref().is_none_or(|name| *name == ns.database) =>
+                throw(ReplicaReadOnly(ns.get_full_name(), primary.to_owned())),
+            _ => Ok(())
+        }
+    }
+
+    /// Follows the primary; replicating its objects (or only those of the given database)
+    /// into the given home (e.g. $OXIDE_HOME), whose replicated tables become read-only.
+    /// Should the connection be lost, the follower reconnects and re-synchronizes its tables.
+    pub fn follow(
+        host: &str,
+        port: u16,
+        home: &str,
+        database: Option<String>,
+    ) -> std::io::Result<JoinHandle<()>> {
+        let runtime = Runtime::new()?;
+        let follower = Follower { database: database.to_owned(), primary: format!("{host}:{port}") };
+        Self::lock_followers()?.insert(home.to_string(), follower);
+        let (host, home) = (host.to_string(), home.to_string());
+        Ok(thread::spawn(move || Machine::with_oxide_home(&home, || {
+            while Self::is_follower() {
+                if let Err(err) = runtime.block_on(Self::replicate(&host, port, &database)) {
+                    error!("Replication from {host}:{port} was interrupted: {err}");
+                }
+                thread::sleep(Duration::from_millis(RECONNECT_INTERVAL));
+            }
+        })))
+    }
+
+    /// Indicates whether the current home (e.g. $OXIDE_HOME) is that of a follower
+    pub fn is_follower() -> bool {
+        Self::lock_followers()
+            .map(|followers| followers.contains_key(&Machine::oxide_home()))
+            .unwrap_or(false)
+    }
+
+    /// Stops following the primary (e.g. manual failover); the tables of the home
+    /// become writable.
+    pub fn promote(home: &str) -> std::io::Result<()> {
+        Self::lock_followers()?.remove(home);
+        Ok(())
+    }
+
+    fn lock_followers() -> std::io::Result<MutexGuard<'static, HashMap<String, Follower>>> {
+        FOLLOWERS.get_or_init(|| Mutex::new(HashMap::new())).lock()
+            .or_else(|err| fail(err.to_string()))
+    }
+
+    /// Subscribes to the primary's feed, and applies the changes it publishes
+    async fn replicate(host: &str, port: u16, database: &Option<String>) -> std::io::Result<()> {
+        let mut client = OxideWebSocketClient::connect(host, port).await?;
+        match client.replicate(database).await? {
+            Number(Ack) => {}
+            ErrorValue(err) => return throw(err),
+            other => return fail(format!("Unexpected response: {}", other.to_code()))
+        }
+        loop {
+            let events: Vec<ReplicationEvents> = match client.read_changes().await? {
+                Binary(bytes) => match bincode::deserialize(&bytes) {
+                    Ok(events) => events,
+                    Err(err) => return fail(err.to_string())
+                },
+                ErrorValue(err) => return throw(err),
+                Undefined => return throw(Exact("The connection to the primary was closed".to_string())),
+                other => return fail(format!("Unexpected change: {}", other.to_code()))
+            };
+            // a promoted follower no longer applies changes
+            if !Self::is_follower() { return Ok(()); }
+            for event in events {
+                Self::apply(event)?;
+            }
+        }
+    }
+}
+
+/// Replication Feed; the primary's side of replication. Each poll returns the objects
+/// created, written or dropped within the home (e.g. $OXIDE_HOME) since the previous poll;
+/// whereas the rows of each newly published table are synchronized in chunks over the
+/// subsequent polls. The home is only re-scanned for new objects once a configuration
+/// has since been saved (see [ObjectConfig::get_catalog_generation]).
+pub struct ReplicationFeed {
+    /// the database to which the feed is limited (if any)
+    database: Option<String>,
+    /// the catalog generation as of the latest scan of the home (if any)
+    generation: Option<u64>,
+    /// the objects found by the latest scan of the home
+    namespaces: Vec<Namespace>,
+    /// the published objects => (namespace|state as published)
+    published: BTreeMap<Namespace, Published>,
+}
+
+impl ReplicationFeed {
+    /// Creates a feed of the objects of the home (or only those of the given database);
+    /// failing if any of them cannot be replicated (e.g. partitioned tables).
+    pub fn new(database: Option<String>) -> std::io::Result<Self> {
+        let mut feed = Self { database, generation: None, namespaces: Vec::new(), published: BTreeMap::new() };
+        feed.find_objects()?;
+        Ok(feed)
+    }
+
+    /// Returns the changes made to the objects since the previous poll
+    pub fn poll(&mut self) -> std::io::Result<Vec<ReplicationEvents>> {
+        let mut events = Vec::new();
+        let objects = self.find_objects()?;
+        let dropped = self.published.keys()
+            .filter(|ns| !objects.iter().any(|(ns1, _)| ns1 == *ns))
+            .cloned()
+            .collect::<Vec<_>>();
+        for ns in dropped {
+            self.published.remove(&ns);
+            events.push(ReplicationEvents::Dropped { ns });
+        }
+        for (ns, config) in objects {
+            let published = match config {
+                ObjectConfig::TableConfig { .. } => self.poll_table(&ns, config, &mut events)?,
+                config => self.poll_definition(&ns, config, &mut events)?
+            };
+            self.published.insert(ns, published);
+        }
+        Ok(events)
+    }
+
+    /// Publishes the changes made to a view or sequence since the previous poll
+    fn poll_definition(
+        &mut self,
+        ns: &Namespace,
+        config: ObjectConfig,
+        events: &mut Vec<ReplicationEvents>,
+    ) -> std::io::Result<Published> {
+        let mut published = match self.published.remove(ns) {
+            Some(published) if published.config == config => published,
+            _ => {
+                events.push(ReplicationEvents::Defined { ns: ns.to_owned(), config: Box::new(config.to_owned()) });
+                Published::new(config.to_owned(), 0, 0..0)
+            }
+        };
+        if let ObjectConfig::SequenceConfig { .. } = config {
+            published.publish_sequences(ns, vec![(None, Sequence::open(ns)?.peek_value()?)], events);
+        }
+        Ok(published)
+    }
+
+    /// Publishes the changes made to a table since the previous poll; where a new (or re-created)
+    /// table is published, followed by its rows (a chunk per poll), and then by the rows written since.
+    fn poll_table(
+        &mut self,
+        ns: &Namespace,
+        config: ObjectConfig,
+        events: &mut Vec<ReplicationEvents>,
+    ) -> std::io::Result<Published> {
+        let frc = FileRowCollection::open(ns)?;
+        let latest = RowHistory::find_latest_version(&frc)?;
+        let mut published = match self.published.remove(ns) {
+            Some(published) if published.config == config && latest >= published.version => published,
+            _ => {
+                events.push(ReplicationEvents::Created { ns: ns.to_owned(), config: Box::new(config.to_owned()) });
+                Published::new(config.to_owned(), latest, 0..frc.len()?)
+            }
+        };
+        if !published.unsynchronized.is_empty() {
+            // publish the next chunk of the table's rows
+            let Range { start, end } = published.unsynchronized;
+            let end = end.min(start + ROWS_PER_EVENT);
+            let mut rows = Vec::with_capacity(end - start);
+            for id in start..end {
+                let (row, metadata) = frc.read_row(id)?;
+                rows.push((id, if metadata.is_allocated { Some(row) } else { None }));
+            }
+            published.unsynchronized.start = end;
+            events.push(ReplicationEvents::Written { ns: ns.to_owned(), rows });
+        } else if latest > published.version {
+            // publish the rows written since the latest version published
+            for rows in RowHistory::read_writes(&frc, published.version)?.chunks(ROWS_PER_EVENT) {
+                events.push(ReplicationEvents::Written { ns: ns.to_owned(), rows: rows.to_vec() });
+            }
+            published.version = latest;
+        }
+        let mut sequences = Vec::new();
+        for constraint in config.get_constraints().iter().filter(|c| c.is_serial()) {
+            let column = constraint.get_column_name();
+            let next_value = Sequence::for_column(&ns.get_table_file_path(), column).peek_value()?;
+            sequences.push((Some(column.to_owned()), next_value));
+        }
+        published.publish_sequences(ns, sequences, events);
+        Ok(published)
+    }
+
+    /// Returns the replicated objects of the home (and their configurations);
+    /// failing if any of them cannot be replicated. The home is re-scanned
+    /// only once a configuration has been saved (or deleted) since the latest scan.
+    fn find_objects(&mut self) -> std::io::Result<Vec<(Namespace, ObjectConfig)>> {
+        let generation = ObjectConfig::get_catalog_generation();
+        if self.generation != Some(generation) {
+            self.namespaces = self.scan_home()?;
+            self.generation = Some(generation);
+        }
+        let mut objects = Vec::new();
+        for ns in self.namespaces.iter() {
+            let config = match ObjectConfig::load(ns) {
+                Ok(config) => config,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err)
+            };
+            match &config {
+                ObjectConfig::MaterializedViewConfig { .. } =>
+                    return fail(format!("Materialized views (e.g. {ns}) cannot be replicated")),
+                ObjectConfig::TableConfig { partitions, .. } if !partitions.is_empty() =>
+                    return fail(format!("Partitioned tables (e.g. {ns}) cannot be replicated")),
+                ObjectConfig::TableConfig { .. } if !Path::new(&ns.get_table_file_path()).exists() => {}
+                _ => objects.push((ns.to_owned(), config))
+            }
+        }
+        Ok(objects)
+    }
+
+    /// Returns the namespaces of the home having a configuration => "$OXIDE_HOME/ns/database/schema/name/"
+    fn scan_home(&self) -> std::io::Result<Vec<Namespace>> {
+        let list_dirs = |path: &str| -> std::io::Result<Vec<String>> {
+            let mut names = Vec::new();
+            for entry in fs::read_dir(path)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    names.push(entry.file_name().to_string_lossy().to_string());
+                }
+            }
+            names.sort();
+            Ok(names)
+        };
+        let root = format!("{}/ns", Machine::oxide_home());
+        if !Path::new(&root).exists() { return Ok(Vec::new()); }
+        let mut namespaces = Vec::new();
+        for database in list_dirs(&root)? {
+            if self.database.as_ref().is_some_and(|name| *name != database) { continue; }
+            for schema in list_dirs(&format!("{root}/{database}"))? {
+                for name in list_dirs(&format!("{root}/{database}/{schema}"))? {
+                    let ns = Namespace::new(&database, &schema, &name);
+                    if Path::new(&ns.get_config_file_path()).exists() {
+                        namespaces.push(ns);
+                    }
+                }
+            }
+        }
+        Ok(namespaces)
+    }
+}
+
+/// Represents an object as last published by a [ReplicationFeed]
+struct Published {
+    /// the configuration of the object
+    config: ObjectConfig,
+    /// the next values of the object's sequences => (the serial column (if any)|next value)
+    sequences: Vec<(Option<String>, i64)>,
+    /// the rows of the table yet to be published by its initial synchronization
+    unsynchronized: Range<usize>,
+    /// the latest version of the table published
+    version: usize,
+}
+
+impl Published {
+    fn new(config: ObjectConfig, version: usize, unsynchronized: Range<usize>) -> Self {
+        Self { config, sequences: Vec::new(), unsynchronized, version }
+    }
+
+    /// Publishes the next values of the sequences which have changed since they were last published
+    fn publish_sequences(
+        &mut self,
+        ns: &Namespace,
+        sequences: Vec<(Option<String>, i64)>,
+        events: &mut Vec<ReplicationEvents>,
+    ) {
+        for (column, next_value) in sequences.iter().filter(|sequence| !self.sequences.contains(sequence)) {
+            events.push(ReplicationEvents::Sequenced { ns: ns.to_owned(), column: column.to_owned(), next_value: *next_value });
+        }
+        self.sequences = sequences;
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::numbers::Numbers::{F64Value, I64Value, RowsAffected};
+    use crate::oxide_server::start_http_server;
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use crate::typed_values::TypedValue::StringValue;
+
+    #[test]
+    fn test_feed_and_apply() {
+        let (primary, follower) = ("./oxide_db/replication/primary", "./oxide_db/replication/follower");
+        let _ = fs::remove_dir_all("./oxide_db/replication");
+        let mut interpreter = Interpreter::new();
+        let mut feed = Machine::with_oxide_home(primary, || ReplicationFeed::new(None)).unwrap();
+        let mut replicate = |code: &str| {
+            Machine::with_oxide_home(primary, || interpreter.evaluate(code)).unwrap();
+            let events = Machine::with_oxide_home(primary, || feed.poll()).unwrap();
+            Machine::with_oxide_home(follower, || {
+                for event in events { Replication::apply(event).unwrap(); }
+            })
+        };
+        let read_rows = || Machine::with_oxide_home(follower, || {
+            let frc = FileRowCollection::open(&Namespace::parse("replication.feed.stocks").unwrap()).unwrap();
+            let metadata = (0..frc.len().unwrap()).map(|id| frc.read_row_metadata(id).unwrap()).collect::<Vec<_>>();
+            (frc.read_active_rows().unwrap(), metadata)
+        });
+
+        // the table and its rows are replicated
+        replicate(r#"
+            stocks := ns("replication.feed.stocks")
+            table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            append stocks from [
+                { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }
+            ]
+        "#);
+        let (rows, metadata) = read_rows();
+        assert_eq!(rows, vec![make_quote(0, "ABC", "AMEX", 11.77), make_quote(1, "UNO", "OTC", 0.2456)]);
+        assert!(metadata.iter().all(|rmd| rmd.is_allocated && rmd.is_replicated));
+
+        // as are the subsequent writes
+        replicate(r#"
+            update stocks via { last_sale: 12.01 } where symbol == "ABC"
+            delete from stocks where symbol == "UNO"
+            append stocks from { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }
+        "#);
+        let (rows, metadata) = read_rows();
+        assert_eq!(rows, vec![make_quote(0, "ABC", "AMEX", 12.01), make_quote(1, "BIZ", "NYSE", 23.66)]);
+        assert_eq!(metadata.iter().map(|rmd| (rmd.is_allocated, rmd.is_replicated)).collect::<Vec<_>>(),
+                   vec![(true, true), (true, true)]);
+
+        // as is the dropping of the table
+        replicate("drop table stocks");
+        assert!(!Path::new(&Machine::with_oxide_home(follower, ||
+            Namespace::parse("replication.feed.stocks").unwrap().get_table_file_path())).exists());
+    }
+
+    #[test]
+    fn test_feed_views_and_sequences() {
+        let (primary, follower) = ("./oxide_db/replication-objects/primary", "./oxide_db/replication-objects/follower");
+        let _ = fs::remove_dir_all("./oxide_db/replication-objects");
+        let mut interpreter = Interpreter::new();
+        let mut feed = Machine::with_oxide_home(primary, || ReplicationFeed::new(None)).unwrap();
+        let mut replicate = |code: &str| {
+            Machine::with_oxide_home(primary, || interpreter.evaluate(code)).unwrap();
+            let events = Machine::with_oxide_home(primary, || feed.poll()).unwrap();
+            Machine::with_oxide_home(follower, || {
+                for event in events { Replication::apply(event).unwrap(); }
+            })
+        };
+        let evaluate = |code: &str| Machine::with_oxide_home(follower, || Interpreter::new().evaluate(code).unwrap());
+
+        // the view, the sequence and the table's serial column are replicated
+        replicate(r#"
+            order_ids := ns("replication-objects.feed.order_ids")
+            create sequence order_ids start with 1000 increment by 10
+            nextval(order_ids)
+            create table ns("replication-objects.feed.stocks") (id: i64 serial, symbol: String(8), last_sale: f64)
+            append ns("replication-objects.feed.stocks") from [{ symbol: "ABC", last_sale: 11.77 }, { symbol: "UNO", last_sale: 0.2456 }]
+            create view ns("replication-objects.feed.high_flyers") as
+                select symbol from ns("replication-objects.feed.stocks") where last_sale > 10.0
+        "#);
+        assert_eq!(evaluate(r#"nextval(ns("replication-objects.feed.order_ids"))"#), Number(I64Value(1010)));
+        let rows = evaluate(r#"select symbol from ns("replication-objects.feed.high_flyers")"#)
+            .to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(rows, vec![Row::new(0, vec![StringValue("ABC".into())])]);
+
+        // as are the values subsequently handed out
+        replicate(r#"append ns("replication-objects.feed.stocks") from { symbol: "BIZ", last_sale: 23.66 }"#);
+        Replication::promote(follower).unwrap();
+        evaluate(r#"append ns("replication-objects.feed.stocks") from { symbol: "GOTO", last_sale: 5.0 }"#);
+        let ids = evaluate(r#"select id from ns("replication-objects.feed.stocks")"#)
+            .to_table().unwrap().read_active_rows().unwrap()
+            .into_iter().map(|row| row.get(0)).collect::<Vec<_>>();
+        assert_eq!(ids, vec![Number(I64Value(1)), Number(I64Value(2)), Number(I64Value(3)), Number(I64Value(4))]);
+
+        // as is the dropping of the view
+        replicate(r#"drop view ns("replication-objects.feed.high_flyers")"#);
+        assert!(!Path::new(&Machine::with_oxide_home(follower, ||
+            Namespace::parse("replication-objects.feed.high_flyers").unwrap().get_config_file_path())).exists());
+    }
+
+    #[test]
+    fn test_feed_synchronizes_tables_in_chunks() {
+        let home = "./oxide_db/replication-chunks";
+        let _ = fs::remove_dir_all(home);
+        let ns = Namespace::parse("replication.chunks.stocks").unwrap();
+        let mut feed = Machine::with_oxide_home(home, || {
+            let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+            frc.append_rows((0..2500).map(|id| make_quote(id, "ABC", "AMEX", id as f64)).collect());
+            ReplicationFeed::new(None)
+        }).unwrap();
+
+        // the table is published, followed by its rows; a chunk per poll
+        let mut chunks = Vec::new();
+        for _ in 0..4 {
+            for event in Machine::with_oxide_home(home, || feed.poll()).unwrap() {
+                match event {
+                    ReplicationEvents::Created { .. } => chunks.push(None),
+                    ReplicationEvents::Written { rows, .. } => chunks.push(Some((rows[0].0, rows.len()))),
+                    other => panic!("Unexpected event: {:?}", other)
+                }
+            }
+        }
+        assert_eq!(chunks, vec![None, Some((0, 1000)), Some((1000, 1000)), Some((2000, 500))]);
+    }
+
+    #[test]
+    fn test_feed_rejects_partitioned_tables() {
+        let home = "./oxide_db/replication-partitioned";
+        let _ = fs::remove_dir_all(home);
+        Machine::with_oxide_home(home, || Interpreter::new().evaluate(r#"
+            create table ns("replication.partitioned.stocks") (
+                symbol: String(8), exchange: String(8), last_sale: f64
+            ) partition by [exchange]
+        "#)).unwrap();
+        let err = Machine::with_oxide_home(home, || ReplicationFeed::new(None)).err().unwrap();
+        assert_eq!(err.to_string(), "Partitioned tables (e.g. replication.partitioned.stocks) cannot be replicated");
+
+        // unless the feed is limited to another database
+        assert!(Machine::with_oxide_home(home, || ReplicationFeed::new(Some("other".into()))).is_ok());
+    }
+
+    #[actix::test]
+    async fn test_follow_the_primary() {
+        let port = 8013;
+        start_http_server(port);
+        let follower = "./oxide_db/replication/followers/8013";
+        let _ = fs::remove_dir_all(follower);
+
+        // create a table on the primary
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            stocks := ns("replication.follow.stocks")
+            drop table stocks
+            table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            append stocks from { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 }
+        "#).unwrap();
+
+        // follow the primary, and wait for the row to be replicated
+        Replication::follow("0.0.0.0", port, follower, Some("replication".into())).unwrap();
+        let evaluate = |code: &str| Machine::with_oxide_home(follower, || {
+            Interpreter::new().evaluate(code).unwrap_or_else(|err| ErrorValue(Exact(err.to_string())))
+        });
+        let select_last_sale = r#"
+            select last_sale from ns("replication.follow.stocks") where symbol == "ABC"
+        "#;
+        let await_last_sale = |expected: f64| {
+            for _ in 0..100 {
+                let found = evaluate(select_last_sale).to_table()
+                    .and_then(|df| df.read_active_rows())
+                    .map(|rows| rows.first().map(|row| row.get(0)));
+                if let Ok(Some(Number(F64Value(last_sale)))) = found {
+                    if last_sale == expected { return; }
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            panic!("The last sale ({expected}) was not replicated")
+        };
+        await_last_sale(11.77);
+
+        // subsequent writes are replicated
+        interpreter.evaluate(r#"
+            update stocks via { last_sale: 12.33 } where symbol == "ABC"
+        "#).unwrap();
+        await_last_sale(12.33);
+
+        // the follower is read-only
+        let append = r#"append ns("replication.follow.stocks") from { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }"#;
+        assert_eq!(evaluate(append), ErrorValue(Exact(
+            "Read-only replica: replication.follow.stocks may only be written via the primary (0.0.0.0:8013)".into()
+        )));
+
+        // whereas the other databases of the follower remain writable
+        assert_eq!(evaluate(r#"
+            stocks := ns("replication-local.follow.stocks")
+            drop table stocks
+            table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            append stocks from { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }
+        "#), Number(RowsAffected(1)));
+
+        // until it is promoted
+        assert_eq!(evaluate("oxide::promote()"), Number(Ack));
+        assert!(!Machine::with_oxide_home(follower, Replication::is_follower));
+        assert_eq!(evaluate(append), Number(RowsAffected(1)));
+    }
+}
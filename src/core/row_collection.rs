@@ -36,9 +36,9 @@ pub trait RowCollection: Debug {
     /// Appends the given row to the end of the table
     fn append_row(&mut self, row: Row) -> TypedValue {
         match self.len() {
-            Ok(id) => {
-                let _ = self.overwrite_row(id, row.with_row_id(id));
-                Number(RowId(id as u64))
+            Ok(id) => match self.overwrite_row(id, row.with_row_id(id)) {
+                ErrorValue(err) => ErrorValue(err),
+                _ => Number(RowId(id as u64))
             }
             Err(err) => ErrorValue(Errors::Exact(err.to_string()))
         }
@@ -0,0 +1,572 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// row history module
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::data_types::DataType::{BooleanType, NumberType, StringType, StructureType};
+use crate::file_row_collection::FileRowCollection;
+use crate::number_kind::NumberKind::{DateKind, U64Kind};
+use crate::numbers::Numbers::{DateValue, U64Value};
+use crate::parameter::Parameter;
+use crate::row_collection::RowCollection;
+use crate::row_metadata::RowMetadata;
+use crate::structures::Row;
+use crate::structures::Structures::Hard;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, Null, Number, StringValue, Structured};
+use chrono::Local;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::Range;
+
+/// the extension of the table file containing the history of the rows (e.g. "stocks.history")
+const HISTORY_EXTENSION: &str = "history";
+
+/// the extension of the table file containing the latest history entry of each row (e.g. "stocks.heads")
+const HEADS_EXTENSION: &str = "heads";
+
+/// the extension of the temporary file into which the history is rewritten (e.g. "stocks.history-rewrite")
+const REWRITE_EXTENSION: &str = "history-rewrite";
+
+/// the period (in milliseconds) for which every entry of the history is retained (i.e. 7 days)
+pub const HISTORY_RETENTION_MILLIS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Row History; an append-only log of every value written to the rows of a table.
+/// Each entry is the row (and its metadata) as of the write, stamped with the version of
+/// the table produced by the write and the time of the write; as well as the position of
+/// the row's previous entry. The position of each row's latest entry is kept alongside
+/// (e.g. "stocks.heads"); thus the entries of a row are found by walking back from it.
+/// Entries older than the retention period are reclaimed when the table is compacted;
+/// whereby only the latest of them for each row is retained.
+pub struct RowHistory;
+
+impl RowHistory {
+    /// Appends the current state of the rows to the history of the table
+    pub fn append(frc: &FileRowCollection, ids: Range<usize>) -> std::io::Result<()> {
+        let len = frc.len()?;
+        let timestamp = Number(DateValue(Local::now().timestamp_millis()));
+        let mut store = Self::open_store(frc)?;
+        let mut heads = Self::open_heads(frc)?;
+        let mut version = Self::read_latest_version(&store)?;
+        for id in ids {
+            let (row, metadata) = match id {
+                id if id < len => frc.read_row(id)?,
+                id => (Row::create(id, frc.get_columns()), RowMetadata::new(false))
+            };
+            version += 1;
+            let position = store.len()?;
+            let mut values = vec![
+                Number(U64Value(version as u64)),
+                timestamp.to_owned(),
+                Number(U64Value(Self::read_head(&heads, id)? as u64)),
+            ];
+            values.extend(row.get_values());
+            store.overwrite_row(position, Row::new(id, values)).to_result(|_| ())?;
+            store.overwrite_row_metadata(position, metadata).to_result(|_| ())?;
+            Self::write_head(&mut heads, id, position + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Records the deletion of a row whose slot is about to be filled by an append;
+    /// unless the latest entry of the row already records its deletion, or the row has no history.
+    pub fn append_deletion(frc: &FileRowCollection, id: usize) -> std::io::Result<()> {
+        let store = Self::open_store(frc)?;
+        match Self::read_head(&Self::open_heads(frc)?, id)? {
+            0 => Ok(()),
+            head if !store.read_row_metadata(head - 1)?.is_allocated => Ok(()),
+            _ => Self::append(frc, id..id + 1)
+        }
+    }
+
+    /// Removes the history of the table (e.g. when the table is re-created)
+    pub fn discard(table_path: &str) -> std::io::Result<()> {
+        for extension in [HISTORY_EXTENSION, HEADS_EXTENSION] {
+            let (_, full_path) = FileRowCollection::get_related_filename(table_path, extension);
+            match fs::remove_file(full_path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the version of the table as of the given time (in milliseconds)
+    pub fn find_version_at(frc: &FileRowCollection, timestamp: i64) -> std::io::Result<usize> {
+        let store = Self::open_store(frc)?;
+        let position = Self::find_position(&store, 1, |value| match value {
+            Number(DateValue(millis)) => *millis > timestamp,
+            _ => true
+        })?;
+        match position {
+            0 => Ok(0),
+            n => Ok(store.read_field(n - 1, 0).to_usize())
+        }
+    }
+
+    /// Returns the latest version of the table (e.g. the sequence number of its latest change)
+    pub fn find_latest_version(frc: &FileRowCollection) -> std::io::Result<usize> {
+        Self::read_latest_version(&Self::open_store(frc)?)
+    }
+
+    /// Returns the changes made to the table after the given version, in the order they were made
+    /// => (sequence|timestamp|event|row ID|before|after); where the sequence is the version of
+    /// the table produced by the change, and the event is one of "insert", "update" or "delete".
+    pub fn read_changes(frc: &FileRowCollection, since: usize) -> std::io::Result<(Vec<Column>, Vec<Row>)> {
+        Self::read_changes_at(frc, &mut HistoryCursor::new(since))
+    }
+
+    /// Returns the changes made to the table after the cursor (see [RowHistory::read_changes]);
+    /// advancing the cursor past them. Thus only the entries appended since the cursor's
+    /// previous read are visited; unless the history has since been reclaimed (or rewritten),
+    /// whereupon the cursor is repositioned via its version.
+    pub fn read_changes_at(frc: &FileRowCollection, cursor: &mut HistoryCursor) -> std::io::Result<(Vec<Column>, Vec<Row>)> {
+        let store = Self::open_store(frc)?;
+        let len = store.len()?;
+        let is_positioned = match cursor.position {
+            0 => cursor.version == 0,
+            position => position <= len && store.read_field(position - 1, 0).to_usize() == cursor.version
+        };
+        if !is_positioned {
+            cursor.position = Self::find_version_position(&store, cursor.version)?;
+        }
+        let columns = frc.get_columns();
+        let mut changes = Vec::new();
+        for position in cursor.position..len {
+            let entry = Self::read_entry(&store, position)?;
+            *cursor = HistoryCursor { position: position + 1, version: entry.version };
+            let before = match entry.previous {
+                0 => None,
+                previous => match Self::read_entry(&store, previous - 1)? {
+                    prior if prior.metadata.is_allocated => Some(prior.row),
+                    _ => None
+                }
+            };
+            let event = match (&before, entry.metadata.is_allocated) {
+                (None, true) => "insert",
+                (Some(..), true) => "update",
+                (Some(..), false) => "delete",
+                (None, false) => continue
+            };
+            let as_value = |row: Option<Row>| row
+                .map(|row| Structured(Hard(row.as_hard(columns))))
+                .unwrap_or(Null);
+            changes.push(Row::new(changes.len(), vec![
+                Number(U64Value(entry.version as u64)),
+                entry.timestamp,
+                StringValue(event.to_string()),
+                Number(U64Value(entry.row.get_id() as u64)),
+                as_value(before),
+                as_value(if entry.metadata.is_allocated { Some(entry.row) } else { None }),
+            ]));
+        }
+        let params = Parameter::from_columns(columns);
+        Ok((Column::from_parameters(&vec![
+            Parameter::new("_sequence", NumberType(U64Kind)),
+            Parameter::new("_timestamp", NumberType(DateKind)),
+            Parameter::new("_event", StringType(6)),
+            Parameter::new("_row_id", NumberType(U64Kind)),
+            Parameter::new("before", StructureType(params.to_owned())),
+            Parameter::new("after", StructureType(params)),
+        ]), changes))
+    }
+
+    /// Returns the rows written after the given version, in the order they were written
+    /// => (row ID|the row as written; where None indicates the row was deleted)
+    pub fn read_writes(frc: &FileRowCollection, since: usize) -> std::io::Result<Vec<(usize, Option<Row>)>> {
+        let store = Self::open_store(frc)?;
+        let mut writes = Vec::new();
+        for position in Self::find_version_position(&store, since)?..store.len()? {
+            let entry = Self::read_entry(&store, position)?;
+            writes.push((entry.row.get_id(), match entry.metadata.is_allocated {
+                true => Some(entry.row),
+                false => None
+            }));
+        }
+        Ok(writes)
+    }
+
+    /// Returns the active rows of the table as of the given version.
+    /// Rows having no history (e.g. written before the history began) are read as they are now.
+    pub fn read_as_of(frc: &FileRowCollection, version: usize) -> std::io::Result<Vec<Row>> {
+        let store = Self::open_store(frc)?;
+        let heads = Self::open_heads(frc)?;
+        let len = frc.len()?;
+        let mut rows = Vec::new();
+        for id in 0..len.max(heads.len()?) {
+            // walk back from the row's latest entry to the one in effect as of the version
+            let mut previous = Self::read_head(&heads, id)?;
+            if previous == 0 {
+                if id < len {
+                    let (row, metadata) = frc.read_row(id)?;
+                    if metadata.is_allocated { rows.push(row) }
+                }
+                continue;
+            }
+            while previous > 0 {
+                let position = previous - 1;
+                if store.read_field(position, 0).to_usize() <= version {
+                    let entry = Self::read_entry(&store, position)?;
+                    if entry.metadata.is_allocated { rows.push(entry.row) }
+                    break;
+                }
+                previous = store.read_field(position, 2).to_usize();
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Returns every past value of the row => (version|timestamp|active|...row)
+    pub fn read_row_history(frc: &FileRowCollection, id: usize) -> std::io::Result<(Vec<Column>, Vec<Row>)> {
+        let store = Self::open_store(frc)?;
+        let mut entries = Vec::new();
+        let mut previous = Self::read_head(&Self::open_heads(frc)?, id)?;
+        while previous > 0 {
+            let entry = Self::read_entry(&store, previous - 1)?;
+            previous = entry.previous;
+            entries.push(entry);
+        }
+        let rows = entries.into_iter().rev().enumerate()
+            .map(|(n, entry)| {
+                let mut values = vec![
+                    Number(U64Value(entry.version as u64)),
+                    entry.timestamp,
+                    Boolean(entry.metadata.is_allocated),
+                ];
+                values.extend(entry.row.get_values());
+                Row::new(n, values)
+            })
+            .collect();
+        let mut params = vec![
+            Parameter::new("_version", NumberType(U64Kind)),
+            Parameter::new("_timestamp", NumberType(DateKind)),
+            Parameter::new("_active", BooleanType),
+        ];
+        params.extend(Parameter::from_columns(frc.get_columns()));
+        Ok((Column::from_parameters(&params), rows))
+    }
+
+    /// Reclaims the entries of the history made before the given time (in milliseconds);
+    /// retaining only the latest of them for each row, so that the table's state as of any
+    /// later version remains readable. The remaining entries are moved toward the start of
+    /// the history within a single batch. Returns the number of entries reclaimed.
+    pub fn reclaim(frc: &FileRowCollection, before: i64) -> std::io::Result<usize> {
+        let mut store = Self::open_store(frc)?;
+        let mut heads = Self::open_heads(frc)?;
+        let cutoff = Self::find_position(&store, 1, |value| match value {
+            Number(DateValue(millis)) => *millis >= before,
+            _ => true
+        })?;
+
+        // determine the latest entry of each row prior to the cutoff
+        let mut seen = HashSet::new();
+        let mut retained = Vec::new();
+        for position in (0..cutoff).rev() {
+            let (entry, _) = store.read_row(position)?;
+            if seen.insert(entry.get_id()) { retained.push(position) }
+        }
+        retained.reverse();
+        let reclaimed = cutoff - retained.len();
+        if reclaimed == 0 { return Ok(0); }
+
+        // move the remaining entries; whose new positions precede their current ones
+        frc.batch(|| {
+            let mut moved = HashMap::new();
+            let positions = retained.into_iter().chain(cutoff..store.len()?);
+            for (new_position, position) in positions.enumerate() {
+                let (entry, metadata) = store.read_row(position)?;
+                let mut values = entry.get_values();
+                let previous = match values[2].to_usize() {
+                    0 => 0,
+                    previous => moved.get(&(previous - 1)).map(|p| p + 1).unwrap_or(0)
+                };
+                values[2] = Number(U64Value(previous as u64));
+                moved.insert(position, new_position);
+                store.overwrite_row(new_position, Row::new(entry.get_id(), values)).to_result(|_| ())?;
+                store.overwrite_row_metadata(new_position, metadata).to_result(|_| ())?;
+            }
+            store.resize(moved.len()).to_result(|_| ())?;
+            for id in 0..heads.len()? {
+                if let head @ 1.. = Self::read_head(&heads, id)? {
+                    let position = moved.get(&(head - 1)).map(|p| p + 1).unwrap_or(0);
+                    Self::write_head(&mut heads, id, position)?;
+                }
+            }
+            Ok(reclaimed)
+        })
+    }
+
+    /// Replaces the history of the table with its rewritten history (see [RowHistory::rewrite])
+    pub fn replace(table_path: &str) -> std::io::Result<()> {
+        let (_, temp_path) = FileRowCollection::get_related_filename(table_path, REWRITE_EXTENSION);
+        let (_, full_path) = FileRowCollection::get_related_filename(table_path, HISTORY_EXTENSION);
+        fs::rename(temp_path, full_path)
+    }
+
+    /// Rewrites the history of the table into the new layout of the table (e.g. "alter table");
+    /// where the rows of each entry are transformed into the new layout by the given function.
+    /// The entries are streamed into a temporary file (e.g. "stocks.history-rewrite"), which
+    /// replaces the history via [RowHistory::replace]; the positions of the entries are retained.
+    pub fn rewrite<F>(frc0: &FileRowCollection, frc1: &FileRowCollection, transform: F) -> std::io::Result<()>
+    where
+        F: Fn(Row) -> std::io::Result<Row>,
+    {
+        let store0 = Self::open_store(frc0)?;
+        let mut store1 = frc1.create_related_table(Self::get_store_columns(frc1), REWRITE_EXTENSION)?;
+        for position in 0..store0.len()? {
+            let (entry, metadata) = store0.read_row(position)?;
+            let id = entry.get_id();
+            let mut values = entry.get_values();
+            let row = transform(Row::new(id, values.split_off(3)))?;
+            values.extend(row.get_values());
+            store1.overwrite_row(position, Row::new(id, values)).to_result(|_| ())?;
+            store1.overwrite_row_metadata(position, metadata).to_result(|_| ())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the position of the first entry of the history after the given version
+    fn find_version_position(store: &FileRowCollection, version: usize) -> std::io::Result<usize> {
+        Self::find_position(store, 0, |value| value.to_usize() > version)
+    }
+
+    /// Returns the position of the first entry whose field satisfies the predicate;
+    /// where the entries are ordered by the field (e.g. the version or timestamp).
+    fn find_position<F>(store: &FileRowCollection, column_id: usize, predicate: F) -> std::io::Result<usize>
+    where
+        F: Fn(&TypedValue) -> bool,
+    {
+        let (mut low, mut high) = (0, store.len()?);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match predicate(&store.read_field(middle, column_id)) {
+                true => high = middle,
+                false => low = middle + 1
+            }
+        }
+        Ok(low)
+    }
+
+    /// Returns the columns of the per-row index => (position of the row's latest entry + 1)
+    fn get_heads_columns() -> Vec<Column> {
+        Column::from_parameters(&vec![Parameter::new("_position", NumberType(U64Kind))])
+    }
+
+    /// Returns the columns of the history => (version|timestamp|position of the row's previous entry + 1|...row)
+    fn get_store_columns(frc: &FileRowCollection) -> Vec<Column> {
+        let mut params = vec![
+            Parameter::new("_version", NumberType(U64Kind)),
+            Parameter::new("_timestamp", NumberType(DateKind)),
+            Parameter::new("_previous", NumberType(U64Kind)),
+        ];
+        params.extend(Parameter::from_columns(frc.get_columns()));
+        Column::from_parameters(&params)
+    }
+
+    /// Opens the per-row index of the history of the table (e.g. "stocks.heads")
+    fn open_heads(frc: &FileRowCollection) -> std::io::Result<FileRowCollection> {
+        frc.open_related_table(Self::get_heads_columns(), HEADS_EXTENSION)
+    }
+
+    /// Opens the history store of the table (e.g. "stocks.history")
+    pub fn open_store(frc: &FileRowCollection) -> std::io::Result<FileRowCollection> {
+        frc.open_related_table(Self::get_store_columns(frc), HISTORY_EXTENSION)
+    }
+
+    fn read_entry(store: &FileRowCollection, position: usize) -> std::io::Result<Entry> {
+        let (entry, metadata) = store.read_row(position)?;
+        let id = entry.get_id();
+        let mut values = entry.get_values();
+        let row = Row::new(id, values.split_off(3));
+        Ok(Entry {
+            version: values[0].to_usize(),
+            timestamp: values[1].to_owned(),
+            previous: values[2].to_usize(),
+            row,
+            metadata,
+        })
+    }
+
+    /// Returns the position (plus one) of the row's latest entry; or zero, if it has none
+    fn read_head(heads: &FileRowCollection, id: usize) -> std::io::Result<usize> {
+        if id >= heads.len()? { return Ok(0); }
+        match heads.read_row(id)? {
+            (row, metadata) if metadata.is_allocated => Ok(row.get(0).to_usize()),
+            _ => Ok(0)
+        }
+    }
+
+    fn read_latest_version(store: &FileRowCollection) -> std::io::Result<usize> {
+        match store.len()? {
+            0 => Ok(0),
+            len => Ok(store.read_field(len - 1, 0).to_usize())
+        }
+    }
+
+    fn write_head(heads: &mut FileRowCollection, id: usize, position: usize) -> std::io::Result<()> {
+        heads.overwrite_row(id, Row::new(id, vec![Number(U64Value(position as u64))])).to_result(|_| ())
+    }
+}
+
+/// Represents the position of a reader (e.g. a subscriber) within the history of a table
+/// => (position of the next entry to read|version of the last entry read)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HistoryCursor {
+    position: usize,
+    version: usize,
+}
+
+impl HistoryCursor {
+    /// Creates a cursor preceding the first change after the given version
+    pub fn new(version: usize) -> Self {
+        Self { position: 0, version }
+    }
+}
+
+/// Represents an entry of the history
+struct Entry {
+    version: usize,
+    timestamp: TypedValue,
+    previous: usize,
+    row: Row,
+    metadata: RowMetadata,
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespaces::Namespace;
+    use crate::numbers::Numbers::F64Value;
+    use crate::testdata::{make_quote, make_quote_parameters};
+
+    #[test]
+    fn test_read_as_of_version() {
+        let ns = Namespace::parse("row_history.as_of.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.05));
+        frc.delete_row(1);
+
+        assert_eq!(RowHistory::read_as_of(&frc, 0).unwrap(), vec![]);
+        assert_eq!(RowHistory::read_as_of(&frc, 2).unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+        assert_eq!(RowHistory::read_as_of(&frc, 3).unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 12.05),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+        assert_eq!(RowHistory::read_as_of(&frc, 4).unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 12.05),
+        ]);
+        assert_eq!(RowHistory::find_version_at(&frc, 0).unwrap(), 0);
+        assert_eq!(RowHistory::find_version_at(&frc, i64::MAX).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_read_changes() {
+        let ns = Namespace::parse("row_history.changes.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.05));
+        frc.delete_row(0);
+        frc.undelete_row(0);
+
+        let as_value = |last_sale: f64| Structured(Hard(make_quote(0, "ABC", "AMEX", last_sale).as_hard(frc.get_columns())));
+        let (columns, rows) = RowHistory::read_changes(&frc, 1).unwrap();
+        assert_eq!(columns.iter().map(|c| c.get_name()).collect::<Vec<_>>(),
+                   vec!["_sequence", "_timestamp", "_event", "_row_id", "before", "after"]);
+        assert_eq!(rows.iter().map(|r| (r.get(0), r.get(2), r.get(4), r.get(5))).collect::<Vec<_>>(), vec![
+            (Number(U64Value(2)), StringValue("update".into()), as_value(11.77), as_value(12.05)),
+            (Number(U64Value(3)), StringValue("delete".into()), as_value(12.05), Null),
+            (Number(U64Value(4)), StringValue("insert".into()), Null, as_value(12.05)),
+        ]);
+        assert_eq!(RowHistory::find_latest_version(&frc).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_read_changes_at_cursor() {
+        let ns = Namespace::parse("row_history.cursor.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let read_versions = |frc: &FileRowCollection, cursor: &mut HistoryCursor| {
+            let (_, rows) = RowHistory::read_changes_at(frc, cursor).unwrap();
+            rows.iter().map(|r| r.get(0).to_usize()).collect::<Vec<_>>()
+        };
+
+        // each read resumes after the previous one
+        let mut cursor = HistoryCursor::new(0);
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.05));
+        assert_eq!(read_versions(&frc, &mut cursor), vec![1, 2]);
+        assert_eq!(read_versions(&frc, &mut cursor), Vec::<usize>::new());
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        assert_eq!(read_versions(&frc, &mut cursor), vec![3]);
+
+        // the cursor is repositioned once the history has been reclaimed
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.33));
+        assert_eq!(RowHistory::reclaim(&frc, Local::now().timestamp_millis() + 1).unwrap(), 2);
+        frc.delete_row(1);
+        assert_eq!(read_versions(&frc, &mut cursor), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_reclaim() {
+        let ns = Namespace::parse("row_history.reclaim.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.05));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let cutoff = Local::now().timestamp_millis();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.33));
+        frc.delete_row(1);
+
+        // only the first entry of row 0 is superseded prior to the cutoff
+        assert_eq!(RowHistory::reclaim(&frc, cutoff).unwrap(), 1);
+        assert_eq!(RowHistory::reclaim(&frc, cutoff).unwrap(), 0);
+        let (_, rows) = RowHistory::read_row_history(&frc, 0).unwrap();
+        assert_eq!(rows.iter().map(|r| r.get(0)).collect::<Vec<_>>(), vec![
+            Number(U64Value(3)), Number(U64Value(4)),
+        ]);
+
+        // the versions since the cutoff remain readable
+        assert_eq!(RowHistory::read_as_of(&frc, 3).unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 12.05),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+        assert_eq!(RowHistory::read_as_of(&frc, 5).unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 12.33),
+        ]);
+        let (_, rows) = RowHistory::read_changes(&frc, 3).unwrap();
+        assert_eq!(rows.iter().map(|r| (r.get(0), r.get(2))).collect::<Vec<_>>(), vec![
+            (Number(U64Value(4)), StringValue("update".into())),
+            (Number(U64Value(5)), StringValue("delete".into())),
+        ]);
+        assert_eq!(RowHistory::find_latest_version(&frc).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_read_row_history() {
+        let ns = Namespace::parse("row_history.row.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.05));
+        frc.delete_row(0);
+
+        let (columns, rows) = RowHistory::read_row_history(&frc, 0).unwrap();
+        assert_eq!(columns.iter().map(|c| c.get_name()).collect::<Vec<_>>(),
+                   vec!["_version", "_timestamp", "_active", "symbol", "exchange", "last_sale"]);
+        assert_eq!(rows.iter().map(|r| (r.get(0), r.get(2), r.get(5))).collect::<Vec<_>>(), vec![
+            (Number(U64Value(1)), Boolean(true), Number(F64Value(11.77))),
+            (Number(U64Value(3)), Boolean(true), Number(F64Value(12.05))),
+            (Number(U64Value(4)), Boolean(false), Number(F64Value(12.05))),
+        ]);
+    }
+}
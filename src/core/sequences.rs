@@ -0,0 +1,143 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// sequences module
+////////////////////////////////////////////////////////////////////
+
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::file_row_collection::FileRowCollection;
+use crate::namespaces::Namespace;
+use crate::object_config::ObjectConfig;
+use shared_lib::fail;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::os::unix::fs::FileExt;
+use std::sync::Mutex;
+
+/// the extension of the file containing the next value of a sequence (e.g. "order_ids.sequence")
+const SEQUENCE_EXTENSION: &str = "sequence";
+
+/// serializes the allocation of sequence values within this process
+static SEQUENCE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Sequence; a persisted source of unique values, which is either an object within a namespace
+/// (e.g. "create sequence ns('a.b.order_ids')") or the backing of a serial column (e.g. "id: i64 serial").
+/// Unlike row IDs, the values are unaffected by "compact"; and, as the next value is synced to disk
+/// before a value is handed out, values are never reissued after a restart.
+pub struct Sequence {
+    increment: i64,
+    path: String,
+    start: i64,
+}
+
+impl Sequence {
+    /// Creates (or restarts) a sequence within the namespace (e.g. "create sequence ns('a.b.order_ids')")
+    pub fn create(ns: &Namespace, start: i64, increment: i64) -> std::io::Result<Self> {
+        ObjectConfig::build_sequence(start, increment).save(ns)?;
+        let sequence = Self::new(ns.get_file_path(SEQUENCE_EXTENSION), start, increment);
+        sequence.restart()?;
+        Ok(sequence)
+    }
+
+    /// Returns the sequence backing the serial column of the table (e.g. "stocks.id.sequence")
+    pub fn for_column(table_path: &str, column_name: &str) -> Self {
+        let extension = format!("{}.{}", column_name, SEQUENCE_EXTENSION);
+        let (_, full_path) = FileRowCollection::get_related_filename(table_path, extension.as_str());
+        Self::new(full_path, 1, 1)
+    }
+
+    fn new(path: String, start: i64, increment: i64) -> Self {
+        Self { increment, path, start }
+    }
+
+    /// Opens the sequence residing within the namespace
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        match ObjectConfig::load(ns)? {
+            ObjectConfig::SequenceConfig { increment, start } =>
+                Ok(Self::new(ns.get_file_path(SEQUENCE_EXTENSION), start, increment)),
+            _ => throw(Exact(format!("{} is not a sequence", ns)))
+        }
+    }
+
+    /// Returns the next value of the sequence (e.g. "nextval(ns('a.b.order_ids'))")
+    pub fn next_value(&self) -> std::io::Result<i64> {
+        let _guard = SEQUENCE_LOCK.lock().or_else(|err| fail(err.to_string()))?;
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&self.path)?;
+        let mut buffer = [0u8; 8];
+        let value = match file.read_exact_at(&mut buffer, 0) {
+            Ok(()) => i64::from_be_bytes(buffer),
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => self.start,
+            Err(err) => return Err(err)
+        };
+        file.write_all_at(&(value + self.increment).to_be_bytes(), 0)?;
+        file.sync_data()?;
+        Ok(value)
+    }
+
+    /// Returns the next value of the sequence, without consuming it
+    pub fn peek_value(&self) -> std::io::Result<i64> {
+        let _guard = SEQUENCE_LOCK.lock().or_else(|err| fail(err.to_string()))?;
+        let mut buffer = [0u8; 8];
+        match fs::File::open(&self.path).and_then(|file| file.read_exact_at(&mut buffer, 0)) {
+            Ok(()) => Ok(i64::from_be_bytes(buffer)),
+            Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::UnexpectedEof) => Ok(self.start),
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Moves the sequence of the serial column of the table to the column's new name
+    /// (e.g. "alter table stocks rename column id to stock_id")
+    pub fn rename_column(table_path: &str, old_name: &str, new_name: &str) -> std::io::Result<()> {
+        let (old, new) = (Self::for_column(table_path, old_name), Self::for_column(table_path, new_name));
+        match fs::rename(&old.path, &new.path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(())
+        }
+    }
+
+    /// Resets the sequence to its starting value
+    pub fn restart(&self) -> std::io::Result<()> {
+        self.set_next_value(self.start)
+    }
+
+    /// Sets the next value of the sequence (e.g. as replicated from a primary)
+    pub fn set_next_value(&self, value: i64) -> std::io::Result<()> {
+        let _guard = SEQUENCE_LOCK.lock().or_else(|err| fail(err.to_string()))?;
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(&self.path)?;
+        file.write_all_at(&value.to_be_bytes(), 0)?;
+        file.sync_data()
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_sequence_survives_reopen() {
+        let ns = Namespace::parse("sequences.reopen.order_ids").unwrap();
+        let sequence = Sequence::create(&ns, 1000, 10).unwrap();
+        assert_eq!(sequence.next_value().unwrap(), 1000);
+        assert_eq!(sequence.next_value().unwrap(), 1010);
+
+        let sequence = Sequence::open(&ns).unwrap();
+        assert_eq!(sequence.next_value().unwrap(), 1020);
+    }
+
+    #[test]
+    fn test_sequence_values_are_unique_across_threads() {
+        let ns = Namespace::parse("sequences.threads.order_ids").unwrap();
+        let sequence = Arc::new(Sequence::create(&ns, 1, 1).unwrap());
+        let handles = (0..4).map(|_| {
+            let sequence = sequence.clone();
+            thread::spawn(move || (0..25).map(|_| sequence.next_value().unwrap()).collect::<Vec<_>>())
+        }).collect::<Vec<_>>();
+        let mut values = handles.into_iter().flat_map(|h| h.join().unwrap()).collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, (1..=100).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,252 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// snapshots module
+////////////////////////////////////////////////////////////////////
+
+use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::errors::Errors;
+use crate::file_row_collection::FileRowCollection;
+use crate::row_collection::{RowCollection, RowEncoding};
+use crate::row_history::RowHistory;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::ErrorValue;
+use shared_lib::fail;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, Weak};
+
+/// the extension of the table file containing the preserved row versions (e.g. "stocks.versions")
+const VERSIONS_EXTENSION: &str = "versions";
+
+/// the row versions of the tables opened by this process => (table path|row versions)
+static TABLE_VERSIONS: OnceLock<Mutex<HashMap<String, Weak<RowVersions>>>> = OnceLock::new();
+
+/// Row Versions; while snapshots of a table are in progress, the prior version of each row
+/// is appended to the table's version store (e.g. "stocks.versions") before the row is overwritten.
+/// The version store is shared by all handles of the table within this process.
+pub struct RowVersions {
+    /// the positions of the version store at which the snapshots in progress began
+    snapshots: Mutex<Vec<usize>>,
+}
+
+impl RowVersions {
+    /// Returns the row versions of the table
+    pub fn open(table_path: &str) -> std::io::Result<Arc<Self>> {
+        let mut table_versions = TABLE_VERSIONS.get_or_init(|| Mutex::new(HashMap::new())).lock()
+            .or_else(|err| fail(err.to_string()))?;
+        if let Some(versions) = table_versions.get(table_path).and_then(|v| v.upgrade()) {
+            return Ok(versions);
+        }
+        let versions = Arc::new(Self { snapshots: Mutex::new(Vec::new()) });
+        table_versions.insert(table_path.to_string(), Arc::downgrade(&versions));
+        Ok(versions)
+    }
+
+    /// Opens the version store of the table (e.g. "stocks.versions")
+    pub fn open_store(frc: &FileRowCollection) -> std::io::Result<FileRowCollection> {
+        frc.open_related_table(frc.get_columns().to_owned(), VERSIONS_EXTENSION)
+    }
+
+    /// Performs the write operation upon the rows; preserving their prior versions
+    /// on behalf of any snapshots in progress, and recording their new values within
+    /// the table's row history.
+    pub fn preserve<F>(&self, frc: &mut FileRowCollection, ids: Range<usize>, write: F) -> TypedValue
+    where
+        F: FnOnce(&mut FileRowCollection) -> TypedValue,
+    {
+        let snapshots = match self.lock_snapshots() {
+            Ok(snapshots) => snapshots,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        let len0 = match frc.len() {
+            Ok(len) => len,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        if !snapshots.is_empty() {
+            if let Err(err) = Self::append_versions(frc, ids.start..ids.end.min(len0)) {
+                return ErrorValue(Errors::Exact(err.to_string()));
+            }
+        }
+        let outcome = write(frc);
+        if let ErrorValue(..) = outcome { return outcome; }
+
+        // the history includes the rows appended or truncated by the write
+        match frc.len().and_then(|len1| RowHistory::append(frc, ids.start..ids.end.min(len0.max(len1)))) {
+            Ok(()) => outcome,
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    /// Releases the preserved row versions, provided no snapshots are in progress;
+    /// returning true, if the versions were released.
+    pub fn reclaim(&self, frc: &FileRowCollection) -> std::io::Result<bool> {
+        let snapshots = self.lock_snapshots()?;
+        if !snapshots.is_empty() { return Ok(false); }
+        let mut store = Self::open_store(frc)?;
+        store.resize(0).to_result(|_| true)
+    }
+
+    fn append_versions(frc: &FileRowCollection, ids: Range<usize>) -> std::io::Result<()> {
+        let mut store = Self::open_store(frc)?;
+        for id in ids {
+            let (row, metadata) = frc.read_row(id)?;
+            let position = store.len()?;
+            store.overwrite_row(position, row.with_row_id(id)).to_result(|_| ())?;
+            store.overwrite_row_metadata(position, metadata).to_result(|_| ())?;
+        }
+        Ok(())
+    }
+
+    fn lock_snapshots(&self) -> std::io::Result<MutexGuard<'_, Vec<usize>>> {
+        self.snapshots.lock().or_else(|err| fail(err.to_string()))
+    }
+}
+
+/// Snapshot; represents a read-only view of a table as of the moment it began.
+/// Rows appended afterward are hidden, whereas rows overwritten afterward are read
+/// from the first version preserved for them after the snapshot began.
+pub struct Snapshot {
+    /// the position of the version store at which the snapshot began
+    base: usize,
+    /// the length of the table (in rows) at which the snapshot began
+    len: usize,
+    record_size: usize,
+    /// the scanned portion of the version store => (position|row ID => version position)
+    scanned: Mutex<(usize, HashMap<usize, usize>)>,
+    store: FileRowCollection,
+    versions: Arc<RowVersions>,
+}
+
+impl Snapshot {
+    /// Begins a snapshot of the table
+    pub fn begin(frc: &FileRowCollection, versions: Arc<RowVersions>) -> std::io::Result<Self> {
+        let store = RowVersions::open_store(frc)?;
+        let mut snapshots = versions.lock_snapshots()?;
+        let (base, len) = (store.len()?, frc.len()?);
+        snapshots.push(base);
+        drop(snapshots);
+        Ok(Self {
+            base,
+            len,
+            record_size: frc.get_record_size(),
+            scanned: Mutex::new((base, HashMap::new())),
+            store,
+            versions,
+        })
+    }
+
+    /// Returns the length of the table (in rows) as of the snapshot
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Reads the bytes at the offset as of the snapshot; where the current bytes
+    /// are produced by the given function, which must read them from the table.
+    pub fn read_at<F>(&self, offset: u64, count: usize, read_current: F) -> std::io::Result<Vec<u8>>
+    where
+        F: FnOnce() -> std::io::Result<Vec<u8>>,
+    {
+        let id = offset as usize / self.record_size;
+        if id >= self.len { return Ok(vec![0u8; count]); }
+
+        // the version is preserved prior to the row being overwritten, therefore, the
+        // row must be read before the version store is consulted.
+        let current = read_current()?;
+        match self.find_version(id)? {
+            Some(position) => {
+                let version_offset = (position * self.record_size) as u64 + offset % self.record_size as u64;
+                self.store.read_at(version_offset, count)
+            }
+            None => Ok(current)
+        }
+    }
+
+    /// Returns the position of the first version of the row preserved after the snapshot began
+    fn find_version(&self, id: usize) -> std::io::Result<Option<usize>> {
+        let mut scanned = self.scanned.lock().or_else(|err| fail(err.to_string()))?;
+        let (position, found) = &mut *scanned;
+        let store_len = self.store.len()?;
+        while *position < store_len {
+            let buffer = self.store.read_at((*position * self.record_size) as u64, 9)?;
+            found.entry(ByteCodeCompiler::decode_row_id(&buffer, 1)).or_insert(*position);
+            *position += 1;
+        }
+        Ok(found.get(&id).copied())
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        if let Ok(mut snapshots) = self.versions.lock_snapshots() {
+            if let Some(index) = snapshots.iter().position(|base| *base == self.base) {
+                snapshots.swap_remove(index);
+            }
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use crate::file_row_collection::FileRowCollection;
+    use crate::namespaces::Namespace;
+    use crate::row_collection::RowCollection;
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use std::path::Path;
+
+    #[test]
+    fn test_snapshot_isolation() {
+        let ns = Namespace::parse("snapshots.isolation.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        frc.append_row(make_quote(2, "BIZ", "NYSE", 23.66));
+
+        // begin a snapshot, then modify the table
+        let snapshot = frc.snapshot().unwrap();
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.05));
+        frc.overwrite_row(0, make_quote(0, "ABC", "AMEX", 12.33));
+        frc.delete_row(1);
+        frc.append_row(make_quote(3, "GOTO", "OTC", 0.1442));
+
+        // the snapshot is unaffected by the changes
+        assert_eq!(snapshot.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+        ]);
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 12.33),
+            make_quote(1, "GOTO", "OTC", 0.1442),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+        ]);
+    }
+
+    #[test]
+    fn test_versions_reclaimed_by_compact() {
+        let ns = Namespace::parse("snapshots.reclaim.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        let (_, versions_path) = FileRowCollection::get_related_filename(&ns.get_table_file_path(), "versions");
+
+        // the versions are retained while the snapshot is in progress
+        let snapshot = frc.snapshot().unwrap();
+        frc.delete_row(0);
+        frc.reclaim_versions();
+        assert!(Path::new(&versions_path).metadata().unwrap().len() > 0);
+        assert_eq!(snapshot.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+
+        // once the snapshot has ended, the versions are reclaimed
+        drop(snapshot);
+        frc.compact();
+        frc.reclaim_versions();
+        assert_eq!(Path::new(&versions_path).metadata().unwrap().len(), 0);
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "UNO", "OTC", 0.2456),
+        ]);
+    }
+}
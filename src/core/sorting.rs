@@ -0,0 +1,321 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// sorting module
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::dataframe::Dataframe;
+use crate::dataframe::Dataframe::{Disk, Model};
+use crate::errors::throw;
+use crate::expression::Expression;
+use crate::expression::Expression::{Ascending, Descending};
+use crate::file_row_collection::FileRowCollection;
+use crate::machine::Machine;
+use crate::model_row_collection::ModelRowCollection;
+use crate::row_collection::RowCollection;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::ErrorValue;
+use std::cmp::Ordering;
+
+/// the maximum number of bytes of row data sorted in memory;
+/// larger disk-based tables are sorted via an external merge sort.
+pub const SORT_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// Sorting facility for the `order by` clause (e.g. "order by exchange, last_sale desc")
+pub struct Sorting;
+
+impl Sorting {
+    /// Compares two sort keys; values are compared via [TypedValue]'s partial ordering,
+    /// falling back to its total ordering for values of differing types (e.g. null vs. 5).
+    pub fn compare(a: &[TypedValue], b: &[TypedValue], descending: &[bool]) -> Ordering {
+        for ((va, vb), is_descending) in a.iter().zip(b.iter()).zip(descending.iter()) {
+            let ordering = va.partial_cmp(vb).unwrap_or_else(|| va.cmp(vb));
+            let ordering = if *is_descending { ordering.reverse() } else { ordering };
+            if ordering != Ordering::Equal { return ordering; }
+        }
+        Ordering::Equal
+    }
+
+    /// Indicates whether the dataframe is a disk-based table too large to be sorted in memory
+    pub fn exceeds_buffer(df: &Dataframe, buffer_size: usize) -> bool {
+        match df {
+            Disk(frc) => frc.len().map(|n| n * frc.get_record_size() > buffer_size).unwrap_or(false),
+            _ => false
+        }
+    }
+
+    /// Sorts the dataframe by the given sort fields
+    pub fn sort_table(
+        ms: &Machine,
+        src: Dataframe,
+        sort_fields: &[Expression],
+    ) -> std::io::Result<Dataframe> {
+        Self::sort_table_with_buffer(ms, src, sort_fields, SORT_BUFFER_SIZE)
+    }
+
+    /// Sorts the dataframe by the given sort fields; disk-based tables larger than
+    /// the buffer are sorted in runs which are subsequently merged on disk.
+    pub fn sort_table_with_buffer(
+        ms: &Machine,
+        src: Dataframe,
+        sort_fields: &[Expression],
+        buffer_size: usize,
+    ) -> std::io::Result<Dataframe> {
+        let (keys, descending) = Self::decode(sort_fields);
+        let is_external = Self::exceeds_buffer(&src, buffer_size);
+        match src {
+            Disk(frc) if is_external => {
+                let rows_per_run = (buffer_size / frc.get_record_size()).max(1);
+                Ok(Disk(Self::sort_external(ms, &frc, &keys, &descending, rows_per_run)?))
+            }
+            df => {
+                let columns = df.get_columns().to_owned();
+                let rows = Self::sort_rows(ms, &columns, &keys, &descending, df.get_rows())?;
+                let mut dest = ModelRowCollection::new(columns);
+                Self::append_rows(&mut dest, rows)?;
+                Ok(Model(dest))
+            }
+        }
+    }
+
+    fn append_rows(dest: &mut dyn RowCollection, rows: Vec<Row>) -> std::io::Result<()> {
+        match dest.append_rows(rows) {
+            ErrorValue(err) => throw(err),
+            _ => Ok(())
+        }
+    }
+
+    /// Splits the sort fields into the key expressions and their directions
+    fn decode(sort_fields: &[Expression]) -> (Vec<Expression>, Vec<bool>) {
+        sort_fields.iter()
+            .map(|field| match field {
+                Ascending(expr) => (expr.as_ref().to_owned(), false),
+                Descending(expr) => (expr.as_ref().to_owned(), true),
+                expr => (expr.to_owned(), false)
+            })
+            .unzip()
+    }
+
+    /// Evaluates the key expressions against the given row
+    fn evaluate_key(
+        ms: &Machine,
+        columns: &Vec<Column>,
+        keys: &[Expression],
+        row: &Row,
+    ) -> std::io::Result<Vec<TypedValue>> {
+        let ms = ms.with_row(columns, row);
+        let mut key = Vec::with_capacity(keys.len());
+        for expr in keys {
+            match ms.evaluate(expr)? {
+                (_, ErrorValue(err)) => return throw(err),
+                (_, value) => key.push(value)
+            }
+        }
+        Ok(key)
+    }
+
+    /// Sorts the rows in memory; the sort is stable.
+    fn sort_rows(
+        ms: &Machine,
+        columns: &Vec<Column>,
+        keys: &[Expression],
+        descending: &[bool],
+        rows: Vec<Row>,
+    ) -> std::io::Result<Vec<Row>> {
+        let mut keyed_rows = Vec::with_capacity(rows.len());
+        for row in rows {
+            keyed_rows.push((Self::evaluate_key(ms, columns, keys, &row)?, row));
+        }
+        keyed_rows.sort_by(|(a, _), (b, _)| Self::compare(a, b, descending));
+        Ok(keyed_rows.into_iter().map(|(_, row)| row).collect())
+    }
+
+    /// Performs an external merge sort: the table is split into sorted runs
+    /// of `rows_per_run` rows, each of which is written to its own scratch table,
+    /// then the runs are merged into another scratch table (see [FileRowCollection::create_scratch_table]).
+    fn sort_external(
+        ms: &Machine,
+        frc: &FileRowCollection,
+        keys: &[Expression],
+        descending: &[bool],
+        rows_per_run: usize,
+    ) -> std::io::Result<FileRowCollection> {
+        let columns = frc.get_columns().to_owned();
+
+        // phase 1: produce the sorted runs
+        let mut cursors = Vec::new();
+        let row_count = frc.len()?;
+        for start in (0..row_count).step_by(rows_per_run) {
+            let rows = frc.read_range(start..(start + rows_per_run).min(row_count))?;
+            let rows = Self::sort_rows(ms, &columns, keys, descending, rows)?;
+            let mut run = frc.create_scratch_table(columns.clone())?;
+            Self::append_rows(&mut run, rows)?;
+            cursors.push(RunCursor::new(run));
+        }
+
+        // phase 2: merge the runs
+        let mut dest = frc.create_scratch_table(columns.clone())?;
+        for cursor in cursors.iter_mut() {
+            cursor.advance(ms, &columns, keys)?;
+        }
+        loop {
+            // select the run having the lowest key; ties go to the earlier run to keep the sort stable
+            let mut lowest: Option<usize> = None;
+            for (n, cursor) in cursors.iter().enumerate() {
+                if let Some((key, _)) = &cursor.head {
+                    let is_lower = match lowest.and_then(|m| cursors[m].head.as_ref()) {
+                        Some((lowest_key, _)) => Self::compare(key, lowest_key, descending) == Ordering::Less,
+                        None => true
+                    };
+                    if is_lower { lowest = Some(n) }
+                }
+            }
+            match lowest {
+                Some(n) => {
+                    if let Some((_, row)) = cursors[n].head.take() {
+                        if let ErrorValue(err) = dest.append_row(row) { return throw(err); }
+                    }
+                    cursors[n].advance(ms, &columns, keys)?;
+                }
+                None => break
+            }
+        }
+        Ok(dest)
+    }
+}
+
+/// Represents the read position within a sorted run
+struct RunCursor {
+    run: FileRowCollection,
+    position: usize,
+    head: Option<(Vec<TypedValue>, Row)>,
+}
+
+impl RunCursor {
+    fn new(run: FileRowCollection) -> Self {
+        Self { run, position: 0, head: None }
+    }
+
+    /// Reads the next active row (and its key) from the run into the head
+    fn advance(
+        &mut self,
+        ms: &Machine,
+        columns: &Vec<Column>,
+        keys: &[Expression],
+    ) -> std::io::Result<()> {
+        self.head = None;
+        let row_count = self.run.len()?;
+        while self.head.is_none() && self.position < row_count {
+            if let Some(row) = self.run.read_one(self.position)? {
+                self.head = Some((Sorting::evaluate_key(ms, columns, keys, &row)?, row));
+            }
+            self.position += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use crate::dataframe::Dataframe;
+    use crate::dataframe::Dataframe::{Disk, Model};
+    use crate::expression::Expression;
+    use crate::expression::Expression::{Ascending, Descending, Variable};
+    use crate::machine::Machine;
+    use crate::model_row_collection::ModelRowCollection;
+    use crate::namespaces::Namespace;
+    use crate::numbers::Numbers::{F64Value, I64Value};
+    use crate::row_collection::RowCollection;
+    use crate::sorting::Sorting;
+    use crate::testdata::{make_dataframe, make_dataframe_ns, make_quote, make_quote_columns, make_quote_parameters};
+    use crate::typed_values::TypedValue::{Null, Number, StringValue};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_compare() {
+        let (a, b) = (vec![Number(I64Value(1))], vec![Number(I64Value(2))]);
+        assert_eq!(Sorting::compare(&a, &b, &[false]), Ordering::Less);
+        assert_eq!(Sorting::compare(&a, &b, &[true]), Ordering::Greater);
+        assert_eq!(Sorting::compare(&[Null], &[Number(I64Value(2))], &[false]), Ordering::Less);
+        assert_eq!(Sorting::compare(&[StringValue("A".into())], &[Null], &[false]), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_sort_in_memory() {
+        let mrc = ModelRowCollection::from_columns_and_rows(&make_quote_columns(), &vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+            make_quote(4, "BOOM", "NYSE", 56.87),
+        ]);
+        let sort_fields = vec![
+            Descending(Box::new(Variable("exchange".into()))),
+            Ascending(Box::new(Variable("last_sale".into()))),
+        ];
+        let df = Sorting::sort_table(&Machine::new(), Model(mrc), &sort_fields).unwrap();
+        assert_eq!(df.get_rows(), vec![
+            make_quote(0, "GOTO", "OTC", 0.1428),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "BOOM", "NYSE", 56.87),
+            make_quote(4, "ABC", "AMEX", 11.77),
+        ]);
+    }
+
+    #[test]
+    fn test_sort_external() {
+        let mut df = make_dataframe("sorting", "external", "stocks", make_quote_parameters()).unwrap();
+        for (n, last_sale) in [3.5, 1.25, 9.0, 0.5, 7.75, 2.0, 8.25].iter().enumerate() {
+            df.append_row(make_quote(n, format!("S{}", n).as_str(), "NYSE", *last_sale));
+        }
+
+        // use a buffer which only holds two rows per run
+        let buffer_size = df.get_record_size() * 2;
+        let sort_fields = vec![Descending(Box::new(Variable("last_sale".into())))];
+        let df = Sorting::sort_table_with_buffer(&Machine::new(), df, &sort_fields, buffer_size).unwrap();
+        assert!(matches!(df, Disk(..)));
+        assert_eq!(df.get_rows().iter()
+                       .map(|row| row.get_values()[2].to_owned())
+                       .collect::<Vec<_>>(), vec![
+            Number(F64Value(9.0)), Number(F64Value(8.25)), Number(F64Value(7.75)),
+            Number(F64Value(3.5)), Number(F64Value(2.0)), Number(F64Value(1.25)),
+            Number(F64Value(0.5)),
+        ]);
+        assert_eq!(df.get_rows()[0].get_id(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_external_sorts() {
+        let ns = Namespace::new("sorting", "concurrent", "stocks");
+        let mut df = make_dataframe_ns(ns.clone(), make_quote_parameters()).unwrap();
+        for (n, last_sale) in [3.5, 1.25, 9.0, 0.5].iter().enumerate() {
+            df.append_row(make_quote(n, format!("S{}", n).as_str(), "NYSE", *last_sale));
+        }
+
+        // the results of one sort are unaffected by another sort of the same table
+        let buffer_size = df.get_record_size() * 2;
+        let sort = |field: Expression| Sorting::sort_table_with_buffer(
+            &Machine::new(), df.to_owned(), &[field], buffer_size).unwrap();
+        let ascending = sort(Ascending(Box::new(Variable("last_sale".into()))));
+        let descending = sort(Descending(Box::new(Variable("last_sale".into()))));
+        let last_sales = |df: &Dataframe| df.get_rows().iter()
+            .map(|row| row.get_values()[2].to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(last_sales(&ascending), vec![
+            Number(F64Value(0.5)), Number(F64Value(1.25)), Number(F64Value(3.5)), Number(F64Value(9.0)),
+        ]);
+        assert_eq!(last_sales(&descending), vec![
+            Number(F64Value(9.0)), Number(F64Value(3.5)), Number(F64Value(1.25)), Number(F64Value(0.5)),
+        ]);
+
+        // and no scratch tables are left behind
+        let files = std::fs::read_dir(ns.get_root_path()).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.contains("scratch"))
+            .collect::<Vec<_>>();
+        assert_eq!(files, Vec::<String>::new());
+    }
+}
@@ -0,0 +1,611 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// transactions module
+////////////////////////////////////////////////////////////////////
+
+use crate::machine::Machine;
+use crate::namespaces::Namespace;
+use crate::object_config::ObjectConfig;
+use crate::write_ahead_log::WriteAheadLog;
+use serde::{Deserialize, Serialize};
+use shared_lib::fail;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::{FileExt, MetadataExt};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use uuid::Uuid;
+
+/// the size of the chunks in which the bytes removed by a truncation are captured
+const CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// the extensions of the files which are rewritten in place without being journaled
+/// (e.g. "stocks.json"); thus they are copied, rather than linked, upon enlistment.
+const METADATA_EXTENSIONS: [&str; 2] = ["json", "partitions"];
+
+/// the namespaces enlisted by the transactions of this process => (log path|enlistment)
+static ENLISTMENTS: OnceLock<Mutex<HashMap<String, Enlistment>>> = OnceLock::new();
+
+thread_local! {
+    /// the transaction of the mutation being performed by the current thread (see [Transaction::within])
+    static ACTIVE_TRANSACTION: RefCell<Option<Transaction>> = const { RefCell::new(None) };
+}
+
+/// Represents a multi-statement transaction (e.g. "begin ... commit");
+/// prior to the first mutation of a namespace within the transaction, the namespace is
+/// enlisted into the transaction's workspace (e.g. "$OXIDE_HOME/tx/<id>/a.b.stocks/"):
+/// its files are preserved via hard links (its metadata via copies), and thereafter every
+/// journaled write to them (see [WriteAheadLog]) is preceded by an undo record of the bytes
+/// it replaces. Upon rollback, the preserved files are reinstated and the undo records are
+/// applied in reverse; whereas upon commit, the workspace is discarded. An enlisted namespace
+/// may neither be read nor modified by other sessions until the transaction ends (thus its
+/// uncommitted changes are hidden from them); whereas the workspaces of transactions
+/// in flight when the process ended are rolled back at startup.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transaction {
+    id: String,
+    is_aborted: bool,
+}
+
+impl Transaction {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Starts a new transaction
+    pub fn begin() -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            is_aborted: false,
+        }
+    }
+
+    /// Returns the transaction of the mutation being performed by the current thread (if any);
+    /// e.g. so that the writes it causes (e.g. "on delete cascade") join its transaction.
+    pub fn get_active() -> Option<Self> {
+        ACTIVE_TRANSACTION.with_borrow(|tx| tx.to_owned())
+    }
+
+    /// Records the bytes about to be truncated from a file of an enlisted namespace
+    pub fn capture_resize(
+        log_path: &str,
+        target: &str,
+        file: &File,
+        new_len: u64,
+    ) -> std::io::Result<()> {
+        let mut enlistments = Self::lock_enlistments()?;
+        let enlistment = match enlistments.get_mut(log_path) {
+            Some(enlistment) => enlistment,
+            None => return Ok(())
+        };
+        let metadata = file.metadata()?;
+        let mut offset = new_len;
+        while offset < metadata.len() {
+            let mut before = vec![0u8; (metadata.len() - offset).min(CHUNK_SIZE) as usize];
+            file.read_exact_at(&mut before, offset)?;
+            let length = before.len();
+            enlistment.append(&UndoRecord::Write {
+                target: target.to_string(),
+                inode: metadata.ino(),
+                offset,
+                old_len: metadata.len(),
+                length,
+                before,
+            })?;
+            offset += length as u64;
+        }
+        enlistment.append(&UndoRecord::Resize {
+            target: target.to_string(),
+            inode: metadata.ino(),
+            old_len: metadata.len(),
+        })
+    }
+
+    /// Records the bytes about to be overwritten within a file of an enlisted namespace
+    pub fn capture_write(
+        log_path: &str,
+        target: &str,
+        file: &File,
+        offset: u64,
+        length: usize,
+    ) -> std::io::Result<()> {
+        let mut enlistments = Self::lock_enlistments()?;
+        let enlistment = match enlistments.get_mut(log_path) {
+            Some(enlistment) => enlistment,
+            None => return Ok(())
+        };
+        let metadata = file.metadata()?;
+        let mut before = vec![0u8; (metadata.len().saturating_sub(offset) as usize).min(length)];
+        file.read_exact_at(&mut before, offset)?;
+        enlistment.append(&UndoRecord::Write {
+            target: target.to_string(),
+            inode: metadata.ino(),
+            offset,
+            old_len: metadata.len(),
+            length,
+            before,
+        })
+    }
+
+    /// Fails if the namespace is enlisted by a transaction; i.e. its changes are uncommitted
+    pub fn ensure_not_enlisted(ns: &Namespace) -> std::io::Result<()> {
+        match Self::lock_enlistments()?.contains_key(&Self::get_log_path(ns)) {
+            true => fail(format!("Namespace '{}' is in use by another transaction", ns)),
+            false => Ok(())
+        }
+    }
+
+    /// Fails if the namespace is enlisted by a transaction other than the active one
+    /// (see [Transaction::within]); i.e. its changes are uncommitted, and hidden from this session.
+    pub fn ensure_visible(ns: &Namespace) -> std::io::Result<()> {
+        let active = Self::get_active();
+        match Self::lock_enlistments()?.get(&Self::get_log_path(ns)) {
+            Some(enlistment) if active.is_none_or(|tx| tx.id != enlistment.tx_id) =>
+                fail(format!("Namespace '{}' is in use by another transaction", ns)),
+            _ => Ok(())
+        }
+    }
+
+    /// Rolls back the transactions left in flight by a prior process (e.g. the server was killed);
+    /// returning the number of transactions rolled back.
+    pub fn recover() -> std::io::Result<usize> {
+        let tx_path = format!("{}/tx", Machine::oxide_home());
+        let orphans = {
+            // since workspaces are created while holding the lock, those not enlisted are orphaned
+            let enlistments = Self::lock_enlistments()?;
+            let active = enlistments.values().map(|e| e.tx_id.as_str()).collect::<HashSet<_>>();
+            Self::read_dir(&tx_path)?.iter()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|id| !active.contains(id.as_str()))
+                .collect::<Vec<_>>()
+        };
+        let mut rolled_back = 0;
+        for id in orphans {
+            match id.strip_suffix(".committed") {
+                Some(..) => Self::remove_dir(&format!("{}/{}", tx_path, id))?,
+                None => {
+                    Self { id, is_aborted: true }.rollback()?;
+                    rolled_back += 1;
+                }
+            }
+        }
+        Ok(rolled_back)
+    }
+
+    /// Performs the operation on behalf of the transaction (if any) for the current thread
+    pub fn within<F, T>(transaction: Option<&Self>, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let previous = ACTIVE_TRANSACTION.replace(transaction.cloned());
+        let result = f();
+        ACTIVE_TRANSACTION.set(previous);
+        result
+    }
+
+    fn get_log_path(ns: &Namespace) -> String {
+        WriteAheadLog::get_log_path(&ns.get_table_file_path())
+    }
+
+    fn get_log_name(ns: &Namespace) -> String {
+        Path::new(&Self::get_log_path(ns)).file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    fn is_metadata(file_name: &str) -> bool {
+        Path::new(file_name).extension()
+            .map(|ext| METADATA_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()))
+            .unwrap_or(false)
+    }
+
+    fn lock_enlistments() -> std::io::Result<MutexGuard<'static, HashMap<String, Enlistment>>> {
+        ENLISTMENTS.get_or_init(|| Mutex::new(HashMap::new())).lock()
+            .or_else(|err| fail(err.to_string()))
+    }
+
+    /// Returns the files contained within a directory; or none if the directory does not exist
+    fn read_dir(path: &str) -> std::io::Result<Vec<fs::DirEntry>> {
+        match fs::read_dir(path) {
+            Ok(entries) => entries.collect(),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err)
+        }
+    }
+
+    fn remove_dir(path: &str) -> std::io::Result<()> {
+        match fs::remove_dir_all(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(())
+        }
+    }
+
+    fn remove_file(path: &Path) -> std::io::Result<()> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(())
+        }
+    }
+
+    /// Restores the namespace to its state upon enlistment from the workspace
+    fn restore(ns: &Namespace, workspace: &Path) -> std::io::Result<()> {
+        let manifest = match fs::read_to_string(workspace.join("manifest.json")) {
+            Ok(json) => serde_json::from_str::<Manifest>(&json)?,
+            // the namespace was not modified prior to the manifest being written
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err)
+        };
+        let root_path = ns.get_root_path();
+        let table_path = ns.get_table_file_path();
+        let files = match manifest.files {
+            Some(files) => files,
+            None => {
+                // the namespace did not exist prior to the transaction
+                WriteAheadLog::discard(&table_path)?;
+                return Self::remove_dir(&root_path);
+            }
+        };
+
+        // recover the log first, so that none of its entries are replayed hereafter
+        fs::create_dir_all(&root_path)?;
+        let wal = WriteAheadLog::open(&table_path)?;
+        let log_name = Self::get_log_name(ns);
+
+        // remove the files created during the transaction
+        for entry in Self::read_dir(&root_path)? {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_file() && name != log_name && !files.contains(&name) {
+                fs::remove_file(path)?;
+            }
+        }
+
+        // reinstate the preserved files which were removed or replaced
+        let preserved_path = workspace.join("files");
+        for name in files.iter().filter(|name| !Self::is_metadata(name)) {
+            let (preserved, path) = (preserved_path.join(name), Path::new(&root_path).join(name));
+            let inode = fs::metadata(&preserved)?.ino();
+            if fs::metadata(&path).map(|m| m.ino()).ok() != Some(inode) {
+                Self::remove_file(&path)?;
+                fs::hard_link(&preserved, &path)?;
+            }
+        }
+
+        // undo the writes made to the preserved files (in reverse)
+        UndoRecord::undo_all(&workspace.join("undo.log"), Path::new(&root_path))?;
+
+        // restore the metadata; whose (re)loading is prompted by saving the configuration
+        for name in files.iter().filter(|name| Self::is_metadata(name)) {
+            let (preserved, path) = (preserved_path.join(name), Path::new(&root_path).join(name));
+            if path.to_string_lossy() == ns.get_config_file_path() {
+                serde_json::from_str::<ObjectConfig>(&fs::read_to_string(preserved)?)?.save(ns)?;
+            } else {
+                fs::copy(preserved, path)?;
+            }
+        }
+
+        // flush the restored files, then discard the log's entries made within the transaction
+        wal.checkpoint()
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // instance methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Marks the transaction as rollback-only; a subsequent commit undoes its changes
+    pub fn abort(self) -> Self {
+        Self { is_aborted: true, ..self }
+    }
+
+    /// Makes the changes of the transaction permanent; since each change was logged
+    /// prior to being applied, the workspace is renamed to mark the transaction as committed.
+    pub fn commit(self) -> std::io::Result<()> {
+        let root_path = self.get_root_path();
+        let committed_path = format!("{}.committed", root_path);
+        match fs::rename(&root_path, &committed_path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        self.release()?;
+        Self::remove_dir(&committed_path)
+    }
+
+    /// Enlists the namespace within the transaction, unless already enlisted by it;
+    /// failing if the namespace is enlisted by another transaction.
+    pub fn enlist(&self, ns: &Namespace) -> std::io::Result<()> {
+        let log_path = Self::get_log_path(ns);
+        let mut enlistments = Self::lock_enlistments()?;
+        match enlistments.get(&log_path) {
+            Some(enlistment) if enlistment.tx_id == self.id => return Ok(()),
+            Some(..) => return fail(format!("Namespace '{}' is in use by another transaction", ns)),
+            None => {}
+        }
+
+        // preserve the files of the namespace
+        let workspace = self.get_workspace_path(ns);
+        let preserved_path = Path::new(&workspace).join("files");
+        let log_name = Self::get_log_name(ns);
+        Self::remove_dir(&workspace)?;
+        fs::create_dir_all(&preserved_path)?;
+        let files = match fs::read_dir(ns.get_root_path()) {
+            Ok(entries) => {
+                let mut files = Vec::new();
+                for entry in entries {
+                    let (entry, name) = entry.map(|e| (e.path(), e.file_name().to_string_lossy().to_string()))?;
+                    if !entry.is_file() || name == log_name { continue; }
+                    match Self::is_metadata(&name) {
+                        true => { fs::copy(&entry, preserved_path.join(&name))?; }
+                        false => fs::hard_link(&entry, preserved_path.join(&name))?
+                    }
+                    files.push(name);
+                }
+                Some(files)
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => return Err(err)
+        };
+
+        // write the manifest last; since without it, there is nothing to restore
+        let undo = OpenOptions::new().create(true).append(true).read(true)
+            .open(Path::new(&workspace).join("undo.log"))?;
+        let temp_path = Path::new(&workspace).join("manifest.tmp");
+        let mut manifest = File::create(&temp_path)?;
+        manifest.write_all(serde_json::to_string(&Manifest { files })?.as_bytes())?;
+        manifest.sync_all()?;
+        fs::rename(temp_path, Path::new(&workspace).join("manifest.json"))?;
+        enlistments.insert(log_path, Enlistment { tx_id: self.id.to_owned(), undo });
+        Ok(())
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.is_aborted
+    }
+
+    /// Undoes the changes of the transaction; restoring every enlisted namespace
+    pub fn rollback(self) -> std::io::Result<()> {
+        for entry in Self::read_dir(&self.get_root_path())? {
+            let ns = Namespace::parse(&entry.file_name().to_string_lossy())?;
+            Self::restore(&ns, &entry.path())?;
+        }
+        self.release()?;
+        Self::remove_dir(&self.get_root_path())
+    }
+
+    fn get_root_path(&self) -> String {
+        format!("{}/tx/{}", Machine::oxide_home(), self.id)
+    }
+
+    fn get_workspace_path(&self, ns: &Namespace) -> String {
+        format!("{}/{}", self.get_root_path(), ns.get_full_name())
+    }
+
+    /// Ends the enlistment of the transaction's namespaces
+    fn release(&self) -> std::io::Result<()> {
+        Self::lock_enlistments()?.retain(|_, enlistment| enlistment.tx_id != self.id);
+        Ok(())
+    }
+}
+
+/// Represents the enlistment of a namespace within a transaction
+struct Enlistment {
+    tx_id: String,
+    undo: File,
+}
+
+impl Enlistment {
+    /// Appends the record to the undo log; the record must be durable before the write it undoes
+    fn append(&mut self, record: &UndoRecord) -> std::io::Result<()> {
+        self.undo.write_all(&WriteAheadLog::encode_frame(record)?)?;
+        self.undo.sync_data()
+    }
+}
+
+/// Represents the files of a namespace upon enlistment; or none, if the namespace did not exist
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    files: Option<Vec<String>>,
+}
+
+/// Represents an undo record; which applies only to the file (i.e. inode) it was captured from,
+/// since a file may have since been replaced (e.g. by an alteration of its table).
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum UndoRecord {
+    Resize {
+        target: String,
+        inode: u64,
+        old_len: u64,
+    },
+    Write {
+        target: String,
+        inode: u64,
+        offset: u64,
+        old_len: u64,
+        length: usize,
+        before: Vec<u8>,
+    },
+}
+
+impl UndoRecord {
+    /// Applies the records of the undo log in reverse to the files of the directory
+    fn undo_all(undo_path: &Path, base_path: &Path) -> std::io::Result<()> {
+        let undo = match File::open(undo_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err)
+        };
+        let mut files = HashMap::new();
+        for (offset, length) in WriteAheadLog::locate_frames(&undo)?.into_iter().rev() {
+            let mut payload = vec![0u8; length];
+            undo.read_exact_at(&mut payload, offset)?;
+            match bincode::deserialize::<UndoRecord>(&payload) {
+                Ok(record) => record.undo(&mut files, base_path)?,
+                Err(err) => return fail(err.to_string())
+            }
+        }
+        for file in files.values().flatten() { file.sync_all()?; }
+        Ok(())
+    }
+
+    /// Opens the file targeted by the record; or none, if it is not the file the record was captured from
+    fn open_target<'a>(
+        files: &'a mut HashMap<String, Option<File>>,
+        base_path: &Path,
+        target: &str,
+        inode: u64,
+    ) -> std::io::Result<Option<&'a File>> {
+        if !files.contains_key(target) {
+            let file = match OpenOptions::new().read(true).write(true).open(base_path.join(target)) {
+                Ok(file) => Some(file),
+                Err(err) if err.kind() == ErrorKind::NotFound => None,
+                Err(err) => return Err(err)
+            };
+            files.insert(target.to_string(), file);
+        }
+        match files.get(target) {
+            Some(Some(file)) if file.metadata()?.ino() == inode => Ok(Some(file)),
+            _ => Ok(None)
+        }
+    }
+
+    /// Reverts the change; restoring the replaced bytes and the file's length
+    fn undo(&self, files: &mut HashMap<String, Option<File>>, base_path: &Path) -> std::io::Result<()> {
+        match self {
+            UndoRecord::Resize { target, inode, old_len } =>
+                match Self::open_target(files, base_path, target, *inode)? {
+                    Some(file) => file.set_len(*old_len),
+                    None => Ok(())
+                }
+            UndoRecord::Write { target, inode, offset, old_len, length, before } => {
+                if let Some(file) = Self::open_target(files, base_path, target, *inode)? {
+                    file.write_all_at(before, *offset)?;
+                    if offset + *length as u64 > *old_len { file.set_len(*old_len)?; }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_row_collection::FileRowCollection;
+    use crate::row_collection::RowCollection;
+    use crate::testdata::{make_quote, make_quote_parameters};
+
+    #[test]
+    fn test_commit() {
+        let ns = Namespace::parse("transactions.commit.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let tx = Transaction::begin();
+        tx.enlist(&ns).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        let root_path = tx.get_root_path();
+        tx.commit().unwrap();
+        assert!(!Path::new(&root_path).exists());
+        assert!(Transaction::ensure_not_enlisted(&ns).is_ok());
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+    }
+
+    #[test]
+    fn test_enlisted_namespaces_are_isolated() {
+        let ns = Namespace::parse("transactions.isolated.stocks").unwrap();
+        FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let (tx0, tx1) = (Transaction::begin(), Transaction::begin());
+        tx0.enlist(&ns).unwrap();
+        tx0.enlist(&ns).unwrap();
+        assert!(tx1.enlist(&ns).is_err());
+        assert!(Transaction::ensure_not_enlisted(&ns).is_err());
+        assert!(Transaction::ensure_visible(&ns).is_err());
+        assert!(Transaction::within(Some(&tx1), || Transaction::ensure_visible(&ns)).is_err());
+        assert!(Transaction::within(Some(&tx0), || Transaction::ensure_visible(&ns)).is_ok());
+        tx0.rollback().unwrap();
+        tx1.enlist(&ns).unwrap();
+        tx1.commit().unwrap();
+    }
+
+    #[test]
+    fn test_recover() {
+        let ns = Namespace::parse("transactions.recover.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+
+        // leave a transaction in flight (e.g. the server was killed)
+        let tx = Transaction::begin();
+        tx.enlist(&ns).unwrap();
+        frc.overwrite_row(0, make_quote(0, "UNO", "OTC", 0.2456));
+        frc.append_row(make_quote(1, "BIZ", "NYSE", 23.66));
+        tx.release().unwrap();
+
+        assert!(Transaction::recover().unwrap() >= 1);
+        assert!(!Path::new(&tx.get_root_path()).exists());
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let ns = Namespace::parse("transactions.rollback.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        let tx = Transaction::begin();
+        tx.enlist(&ns).unwrap();
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        frc.delete_row(0);
+        tx.enlist(&ns).unwrap();
+        tx.rollback().unwrap();
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+    }
+
+    #[test]
+    fn test_rollback_of_new_namespace() {
+        let ns = Namespace::parse("transactions.rollback_new.stocks").unwrap();
+        let _ = fs::remove_dir_all(ns.get_root_path());
+        let tx = Transaction::begin();
+        tx.enlist(&ns).unwrap();
+        FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        tx.rollback().unwrap();
+        assert!(!Path::new(&ns.get_root_path()).exists());
+    }
+
+    #[test]
+    fn test_rollback_of_truncation() {
+        let ns = Namespace::parse("transactions.rollback_truncation.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        let tx = Transaction::begin();
+        tx.enlist(&ns).unwrap();
+        frc.resize(0);
+        tx.rollback().unwrap();
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+    }
+
+    #[test]
+    fn test_rollback_of_dropped_table() {
+        let ns = Namespace::parse("transactions.rollback_drop.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        let tx = Transaction::begin();
+        tx.enlist(&ns).unwrap();
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        fs::remove_file(ns.get_table_file_path()).unwrap();
+        tx.rollback().unwrap();
+        let frc = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+    }
+}
@@ -0,0 +1,213 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// triggers module
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::errors::throw;
+use crate::errors::Errors::TriggerRejected;
+use crate::expression::Expression::{FunctionCall, Literal};
+use crate::machine::Machine;
+use crate::namespaces::Namespace;
+use crate::numbers::Numbers::{RowId, RowsAffected};
+use crate::object_config::{ObjectConfig, TriggerConfig, TriggerEvents, TriggerTimings};
+use crate::row_collection::RowCollection;
+use crate::structures::Structures::Hard;
+use crate::structures::{Row, Structure};
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, NamespaceValue, Number, Structured, Undefined};
+
+/// Triggers; the Oxide functions invoked with the rows written to a table
+/// (e.g. "create trigger audit after append on ns('a.b.stocks') do fn(row) => ...").
+/// A before-trigger may replace the row by returning a structure, or reject the write by
+/// returning false (or an error); whereas an after-trigger receives the row as it was written.
+#[derive(Clone, Debug, Default)]
+pub struct Triggers {
+    triggers: Vec<TriggerConfig>,
+}
+
+impl Triggers {
+    /// Returns the triggers of the table residing within the namespace
+    pub fn load(ns: &Namespace) -> std::io::Result<Self> {
+        Ok(Self::new(ObjectConfig::load(ns)?.get_triggers()))
+    }
+
+    /// Returns the triggers of the table value; only tables within a namespace have triggers.
+    pub fn for_table(table: &TypedValue) -> std::io::Result<Self> {
+        match table {
+            NamespaceValue(ns) => Self::load(ns),
+            _ => Ok(Self::default())
+        }
+    }
+
+    pub fn new(triggers: Vec<TriggerConfig>) -> Self {
+        Self { triggers }
+    }
+
+    /// Appends the row to the table; firing the append triggers
+    pub fn append_row(&self, rc: &mut dyn RowCollection, row: Row) -> std::io::Result<TypedValue> {
+        let row = self.fire(&TriggerTimings::Before, &TriggerEvents::Append, rc.get_columns(), row)?;
+        let result = rc.append_row(row);
+        if let Number(RowId(id)) = result {
+            self.fire_after_write(rc, &TriggerEvents::Append, id as usize)?;
+        }
+        Ok(result)
+    }
+
+    /// Appends the rows to the table; firing the append triggers for each row
+    pub fn append_rows(&self, rc: &mut dyn RowCollection, rows: Vec<Row>) -> std::io::Result<TypedValue> {
+        if !self.has_event(&TriggerEvents::Append) {
+            return Ok(rc.append_rows(rows));
+        }
+        let mut appended = 0;
+        for row in rows {
+            match self.append_row(rc, row)? {
+                ErrorValue(err) => return Ok(ErrorValue(err)),
+                _ => appended += 1
+            }
+        }
+        Ok(Number(RowsAffected(appended)))
+    }
+
+    /// Deletes the row from the table; firing the delete triggers.
+    /// The value returned by a before-delete trigger may only reject the deletion.
+    pub fn delete_row(&self, rc: &mut dyn RowCollection, id: usize) -> std::io::Result<TypedValue> {
+        let row = match self.has_event(&TriggerEvents::Delete) {
+            true => rc.read_one(id)?,
+            false => None
+        };
+        match row {
+            None => Ok(rc.delete_row(id)),
+            Some(row) => {
+                let columns = rc.get_columns().to_owned();
+                self.fire(&TriggerTimings::Before, &TriggerEvents::Delete, &columns, row.to_owned())?;
+                let result = rc.delete_row(id);
+                if !matches!(result, ErrorValue(..)) {
+                    self.fire(&TriggerTimings::After, &TriggerEvents::Delete, &columns, row)?;
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    /// Overwrites the row of the table; firing the update triggers
+    pub fn overwrite_row(&self, rc: &mut dyn RowCollection, id: usize, row: Row) -> std::io::Result<TypedValue> {
+        let row = self.fire(&TriggerTimings::Before, &TriggerEvents::Update, rc.get_columns(), row)?;
+        let result = rc.overwrite_row(id, row);
+        if !matches!(result, ErrorValue(..)) {
+            self.fire_after_write(rc, &TriggerEvents::Update, id)?;
+        }
+        Ok(result)
+    }
+
+    /// Updates the row of the table, where undefined fields retain their current values;
+    /// firing the update triggers with the resulting row.
+    pub fn update_row(&self, rc: &mut dyn RowCollection, id: usize, row: Row) -> std::io::Result<TypedValue> {
+        if !self.has_event(&TriggerEvents::Update) {
+            return Ok(rc.update_row(id, row));
+        }
+        let row = match rc.read_one(id)? {
+            Some(row0) => Row::new(id, row0.get_values().into_iter().zip(row.get_values())
+                .map(|(value0, value1)| if value1 == Undefined { value0 } else { value1 })
+                .collect()),
+            None => row.with_row_id(id)
+        };
+        self.overwrite_row(rc, id, row)
+    }
+
+    /// Invokes the triggers having the timing and event with the row; returning the
+    /// (possibly replaced) row.
+    fn fire(
+        &self,
+        timing: &TriggerTimings,
+        event: &TriggerEvents,
+        columns: &Vec<Column>,
+        row: Row,
+    ) -> std::io::Result<Row> {
+        let mut row = row;
+        for trigger in self.find_triggers(timing, event) {
+            let call = FunctionCall {
+                fx: Box::new(trigger.get_function().to_owned()),
+                args: vec![Literal(Structured(Hard(row.as_hard(columns))))],
+            };
+            match (timing, Machine::new_platform().evaluate(&call)?) {
+                (_, (_, ErrorValue(err))) => return throw(err),
+                (TriggerTimings::Before, (_, Boolean(false))) =>
+                    return throw(TriggerRejected(trigger.get_name().to_owned())),
+                (TriggerTimings::Before, (_, Structured(structure))) => {
+                    let tuples = structure.get_tuples();
+                    row = Row::new(row.get_id(), columns.iter().zip(row.get_values())
+                        .map(|(column, value)| tuples.iter()
+                            .find(|(name, _)| name == column.get_name())
+                            .map(|(_, value)| value.to_owned())
+                            .unwrap_or(value))
+                        .collect());
+                }
+                _ => {}
+            }
+        }
+        Ok(row)
+    }
+
+    /// Invokes the after-triggers of the event with the row as it was written
+    /// (e.g. including its defaults and serial values)
+    fn fire_after_write(&self, rc: &dyn RowCollection, event: &TriggerEvents, id: usize) -> std::io::Result<()> {
+        if self.find_triggers(&TriggerTimings::After, event).next().is_some() {
+            if let Some(row) = rc.read_one(id)? {
+                self.fire(&TriggerTimings::After, event, rc.get_columns(), row)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn find_triggers<'a>(
+        &'a self,
+        timing: &'a TriggerTimings,
+        event: &'a TriggerEvents,
+    ) -> impl Iterator<Item=&'a TriggerConfig> {
+        self.triggers.iter().filter(move |t| t.get_timing() == timing && t.get_event() == event)
+    }
+
+    fn has_event(&self, event: &TriggerEvents) -> bool {
+        self.triggers.iter().any(|t| t.get_event() == event)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::dataframe::Dataframe;
+    use crate::testdata::{make_quote, make_quote_parameters};
+
+    fn make_trigger(name: &str, timing: TriggerTimings, event: TriggerEvents, code: &str) -> TriggerConfig {
+        TriggerConfig::new(name.into(), timing, event, Compiler::build(code).unwrap())
+    }
+
+    #[test]
+    fn test_before_trigger_modifies_the_row() {
+        let ns = Namespace::parse("triggers.modify.stocks").unwrap();
+        let mut df = Dataframe::create_table(&ns, &make_quote_parameters()).unwrap();
+        let triggers = Triggers::new(vec![make_trigger(
+            "double_price", TriggerTimings::Before, TriggerEvents::Append, r#"
+                fn(row) => { last_sale: (row::last_sale) * 2.0 }
+            "#)]);
+        triggers.append_row(&mut df, make_quote(0, "ABC", "AMEX", 11.5)).unwrap();
+        assert_eq!(df.read_one(0).unwrap(), Some(make_quote(0, "ABC", "AMEX", 23.0)));
+    }
+
+    #[test]
+    fn test_before_trigger_rejects_the_row() {
+        let ns = Namespace::parse("triggers.reject.stocks").unwrap();
+        let mut df = Dataframe::create_table(&ns, &make_quote_parameters()).unwrap();
+        let triggers = Triggers::new(vec![make_trigger(
+            "positive_price", TriggerTimings::Before, TriggerEvents::Update, r#"
+                fn(row) => (row::last_sale) > 0.0
+            "#)]);
+        triggers.append_row(&mut df, make_quote(0, "ABC", "AMEX", 11.77)).unwrap();
+        let err = triggers.overwrite_row(&mut df, 0, make_quote(0, "ABC", "AMEX", -1.0)).unwrap_err();
+        assert_eq!(err.to_string(), "Trigger rejection: the row was rejected by trigger 'positive_price'");
+        assert_eq!(df.read_one(0).unwrap(), Some(make_quote(0, "ABC", "AMEX", 11.77)));
+    }
+}
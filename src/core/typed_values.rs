@@ -26,14 +26,13 @@ use crate::data_types::DataType::*;
 
 use crate::data_types::*;
 use crate::dataframe::Dataframe;
-use crate::dataframe::Dataframe::{Disk, Model};
+use crate::dataframe::Dataframe::Model;
 use crate::descriptor::Descriptor;
 use crate::errors::Errors::{CannotSubtract, Exact, Multiple, Syntax, TypeMismatch};
 use crate::errors::TypeMismatchErrors::UnsupportedType;
 use crate::errors::{throw, Errors};
 use crate::expression::Expression;
 use crate::field::FieldMetadata;
-use crate::file_row_collection::FileRowCollection;
 use crate::inferences::Inferences;
 use crate::machine::Machine;
 use crate::model_row_collection::ModelRowCollection;
@@ -42,7 +41,6 @@ use crate::number_kind::NumberKind;
 use crate::number_kind::NumberKind::*;
 use crate::numbers::Numbers;
 use crate::numbers::Numbers::*;
-use crate::object_config::ObjectConfig;
 use crate::parameter::Parameter;
 use crate::platform::PlatformOps;
 use crate::row_collection::RowCollection;
@@ -441,17 +439,34 @@ impl TypedValue {
         }
     }
 
+    /// Converts the value into a table which may be modified (see [Dataframe::open_writable])
+    pub fn to_writable_table(&self) -> std::io::Result<Box<dyn RowCollection>> {
+        match self.to_writable_table_value() {
+            ErrorValue(error) => throw(error),
+            TableValue(df) => Ok(Box::new(df)),
+            z => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), z.get_type())))
+        }
+    }
+
+    /// Converts the value into a table which may be modified (see [Dataframe::open_writable])
+    pub fn to_writable_table_value(&self) -> TypedValue {
+        match self {
+            NamespaceValue(ns) =>
+                match Dataframe::open_writable(ns) {
+                    Ok(df) => TableValue(df),
+                    Err(err) => ErrorValue(Exact(err.to_string()))
+                }
+            z => z.to_table_value()
+        }
+    }
+
     pub fn to_table_value(&self) -> TypedValue {
         match self {
             ArrayValue(items) => self.convert_array_to_table(items.values()),
             ErrorValue(err) => ErrorValue(err.to_owned()),
             NamespaceValue(ns) =>
-                match ObjectConfig::load(ns) {
-                    Ok(ObjectConfig::TableConfig { .. }) =>
-                        match FileRowCollection::open(ns) {
-                            Ok(frc) => TableValue(Disk(frc)),
-                            Err(err) => ErrorValue(Exact(err.to_string())),
-                        },
+                match Dataframe::open(ns) {
+                    Ok(df) => TableValue(df),
                     Err(err) => ErrorValue(Exact(err.to_string()))
                 }
             Structured(s) => TableValue(Model(s.to_table())),
@@ -4,22 +4,33 @@
 ////////////////////////////////////////////////////////////////////
 
 use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::dataframe::Dataframe::Model;
 use crate::errors::throw;
-use crate::errors::Errors::Exact;
+use crate::errors::Errors::{Exact, InvalidNamespace};
 use crate::expression::Expression;
+use crate::file_row_collection::FileRowCollection;
 use crate::interpreter::Interpreter;
+use crate::model_row_collection::ModelRowCollection;
+use crate::namespaces::Namespace;
+use crate::numbers::Numbers::Ack;
+use crate::replication::ReplicationFeed;
+use crate::row_history::{HistoryCursor, RowHistory};
 use crate::typed_values::TypedValue;
-use crate::typed_values::TypedValue::{ErrorValue, StringValue, Undefined};
-use actix::{Actor, StreamHandler};
+use crate::typed_values::TypedValue::{Binary, ErrorValue, NamespaceValue, Number, StringValue, TableValue, Undefined};
+use actix::{Actor, AsyncContext, StreamHandler};
 use actix_web_actors::ws;
 use actix_web_actors::ws::WebsocketContext;
 use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
 use shared_lib::cnv_error;
+use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 
+/// the interval (in milliseconds) at which the subscribed tables are checked for changes
+const CHANGES_POLL_INTERVAL: u64 = 250;
+
 /// Oxide WebSocket Client
 pub struct OxideWebSocketClient {
     read: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
@@ -46,6 +57,29 @@ impl OxideWebSocketClient {
         self.read_next().await
     }
 
+    /// Returns the next batch of changes published for the subscribed tables
+    pub async fn read_changes(&mut self) -> std::io::Result<TypedValue> {
+        self.read_next().await
+    }
+
+    /// Subscribes to the primary's replication feed (see [ReplicationFeed]); limited to
+    /// the given database (if any). The changes are subsequently published to this client.
+    pub async fn replicate(&mut self, database: &Option<String>) -> std::io::Result<TypedValue> {
+        let request = match database {
+            Some(database) => format!("replicate {database}"),
+            None => "replicate".to_string()
+        };
+        self.send_text_message(request.as_str()).await?;
+        self.read_next().await
+    }
+
+    /// Subscribes to the changes made to the table after the given sequence number;
+    /// which are subsequently published to this client (see [OxideWebSocketClient::read_changes]).
+    pub async fn subscribe(&mut self, ns: &Namespace, since: usize) -> std::io::Result<TypedValue> {
+        self.send_text_message(format!("subscribe ns(\"{}\") since {since}", ns.get_full_name()).as_str()).await?;
+        self.read_next().await
+    }
+
     pub async fn with_variable(&mut self, name: &str, value: TypedValue) -> std::io::Result<TypedValue> {
         self.send_text_message(format!("{name} := {}", value.to_code()).as_str()).await?;
         self.read_next().await
@@ -77,38 +111,124 @@ impl OxideWebSocketClient {
 
 /// Oxide WebSocket Server
 pub struct OxideWebSocketServer {
+    /// the replication feed to which the client (i.e. a follower) has subscribed (if any)
+    feed: Option<ReplicationFeed>,
     interpreter: Interpreter,
+    /// the subscribed tables => (namespace|the position of the last change published)
+    subscriptions: Vec<(Namespace, HistoryCursor)>,
 }
 
 impl OxideWebSocketServer {
     pub fn new() -> Self {
-        Self { interpreter: Interpreter::new() }
+        Self { feed: None, interpreter: Interpreter::new(), subscriptions: Vec::new() }
+    }
+
+    /// Publishes the changes of the replication feed since they were last published;
+    /// where each change is transmitted as a message of its own.
+    fn publish_replication(&mut self, ctx: &mut WebsocketContext<Self>) {
+        if let Some(feed) = self.feed.as_mut() {
+            match feed.poll() {
+                Ok(events) => for event in events {
+                    match bincode::serialize(&vec![event]) {
+                        Ok(bytes) => transmit(ctx, &Binary(bytes)),
+                        Err(err) => transmit(ctx, &ErrorValue(Exact(err.to_string())))
+                    }
+                },
+                Err(err) => transmit(ctx, &ErrorValue(Exact(err.to_string())))
+            }
+        }
+    }
+
+    /// Publishes the changes made to the subscribed tables since they were last published;
+    /// where each subscription's cursor limits the read to the changes made since.
+    fn publish_changes(&mut self, ctx: &mut WebsocketContext<Self>) {
+        let mut subscriptions = Vec::new();
+        for (ns, mut cursor) in self.subscriptions.drain(..) {
+            let outcome = FileRowCollection::open(&ns)
+                .and_then(|frc| RowHistory::read_changes_at(&frc, &mut cursor));
+            match outcome {
+                Ok((columns, rows)) => {
+                    if !rows.is_empty() {
+                        transmit(ctx, &TableValue(Model(ModelRowCollection::from_columns_and_rows(&columns, &rows))));
+                    }
+                    subscriptions.push((ns, cursor));
+                }
+                // the subscription ends (e.g. the table was dropped)
+                Err(err) => transmit(ctx, &ErrorValue(Exact(err.to_string())))
+            }
+        }
+        self.subscriptions = subscriptions;
+    }
+
+    /// Subscribes a follower to the replication feed (e.g. "replicate" or "replicate a");
+    /// where the feed may be limited to a database.
+    fn replicate(&mut self, request: &str) -> std::io::Result<TypedValue> {
+        let database = match request.trim() {
+            "" => None,
+            database => Some(database.to_string())
+        };
+        self.feed = Some(ReplicationFeed::new(database)?);
+        Ok(Number(Ack))
+    }
+
+    /// Subscribes to the changes of a table (e.g. "subscribe ns('a.b.stocks') since 0");
+    /// when the sequence number is omitted, only the subsequent changes are published.
+    fn subscribe(&mut self, request: &str) -> std::io::Result<TypedValue> {
+        let (table, since) = match request.rsplit_once(" since ") {
+            Some((table, since)) => (table, Some(since)),
+            None => (request, None)
+        };
+        let ns = match self.interpreter.evaluate(table)? {
+            NamespaceValue(ns) => ns,
+            other => return throw(InvalidNamespace(other.to_code()))
+        };
+        let since = match since {
+            Some(since) => self.interpreter.evaluate(since)?.to_usize(),
+            None => RowHistory::find_latest_version(&FileRowCollection::open(&ns)?)?
+        };
+        self.subscriptions.retain(|(ns0, _)| *ns0 != ns);
+        self.subscriptions.push((ns, HistoryCursor::new(since)));
+        Ok(Number(Ack))
     }
 }
 
 impl Actor for OxideWebSocketServer {
     type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(Duration::from_millis(CHANGES_POLL_INTERVAL), |server, ctx| {
+            if !server.subscriptions.is_empty() {
+                server.publish_changes(ctx)
+            }
+            server.publish_replication(ctx)
+        });
+    }
+}
+
+/// transmits the [TypedValue] to the client
+fn transmit(ctx: &mut WebsocketContext<OxideWebSocketServer>, value: &TypedValue) {
+    let bytes = ByteCodeCompiler::encode_value(value)
+        .unwrap_or_else(|err| {
+            eprintln!("ERROR: {}", err);
+            vec![]
+        });
+    ctx.binary(bytes);
 }
 
 impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for OxideWebSocketServer {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
-        /// transmits the [TypedValue] to the client
-        fn transmit(ctx: &mut WebsocketContext<OxideWebSocketServer>, value: &TypedValue) {
-            let bytes = ByteCodeCompiler::encode_value(&value)
-                .unwrap_or_else(|err| {
-                    eprintln!("ERROR: {}", err);
-                    vec![]
-                });
-            ctx.binary(bytes);
-        }
-
         match msg {
             Err(err) => transmit(ctx, &ErrorValue(Exact(err.to_string()))),
             Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
             Ok(ws::Message::Pong(msg)) => ctx.ping(&msg),
             Ok(ws::Message::Text(text)) => {
-                let value = self.interpreter.evaluate(text.trim_ascii())
-                    .unwrap_or_else(|err| ErrorValue(Exact(err.to_string())));
+                let text = text.trim_ascii();
+                let value = match (text.strip_prefix("subscribe "), text.strip_prefix("replicate")) {
+                    (Some(request), _) => self.subscribe(request),
+                    (None, Some(request)) if request.is_empty() || request.starts_with(' ') =>
+                        self.replicate(request),
+                    _ => self.interpreter.evaluate(text)
+                }.unwrap_or_else(|err| ErrorValue(Exact(err.to_string())));
                 transmit(ctx, &value)
             }
             Ok(ws::Message::Binary(bytes)) => {
@@ -129,13 +249,16 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for OxideWebSocketSer
 #[cfg(test)]
 mod tests {
     use crate::dataframe::Dataframe::Model;
+    use crate::interpreter::Interpreter;
     use crate::model_row_collection::ModelRowCollection;
-    use crate::numbers::Numbers::I64Value;
+    use crate::namespaces::Namespace;
+    use crate::numbers::Numbers::{Ack, I64Value, U64Value};
     use crate::oxide_server::start_http_server;
     use crate::repl;
+    use crate::structures::Row;
     use crate::testdata::{make_quote, make_quote_columns};
     use crate::typed_values::TypedValue;
-    use crate::typed_values::TypedValue::{Number, TableValue};
+    use crate::typed_values::TypedValue::{Number, StringValue, TableValue};
     use crate::websockets::OxideWebSocketClient;
 
     #[actix::test]
@@ -180,6 +303,40 @@ mod tests {
         ))))
     }
 
+    #[actix::test]
+    async fn test_websockets_subscribe_to_changes() {
+        let port = 8012;
+        start_http_server(port);
+
+        // create a table, and write some rows to it
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            stocks := ns("ws.changes.stocks")
+            table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+            append stocks from { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 }
+            append stocks from { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }
+            delete from stocks where symbol == "ABC"
+        "#).unwrap();
+
+        // subscribe to the changes
+        let ns = Namespace::parse("ws.changes.stocks").unwrap();
+        let mut wsc = OxideWebSocketClient::connect("0.0.0.0", port).await.unwrap();
+        assert_eq!(wsc.subscribe(&ns, 0).await.unwrap(), Number(Ack));
+
+        // the changes are published
+        let value = wsc.read_changes().await.unwrap();
+        show_value(value.clone());
+        let mut interpreter = Interpreter::new();
+        interpreter.with_variable("changes", value);
+        assert_eq!(interpreter.evaluate(r#"
+            select _sequence, _event, _row_id from changes
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            Row::new(0, vec![Number(U64Value(1)), StringValue("insert".into()), Number(U64Value(0))]),
+            Row::new(1, vec![Number(U64Value(2)), StringValue("insert".into()), Number(U64Value(1))]),
+            Row::new(2, vec![Number(U64Value(3)), StringValue("delete".into()), Number(U64Value(0))]),
+        ]);
+    }
+
     fn show_value(value: TypedValue) {
         for s in repl::build_output(1, value, 0.33).unwrap() {
             println!("{}", s)
@@ -0,0 +1,482 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// write-ahead log module
+////////////////////////////////////////////////////////////////////
+
+use crate::file_row_collection::FileRowCollection;
+use crate::transactions::Transaction;
+use serde::{Deserialize, Serialize};
+use shared_lib::fail;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, Weak};
+
+/// the size of a log entry's frame header => (payload length|MD5 checksum)
+const FRAME_HEADER_LEN: usize = 20;
+
+/// the size beyond which the log is checkpointed once no batch is in progress
+const CHECKPOINT_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+/// the logs opened by this process => (log path|shared log file); a log is recovered
+/// upon being opened for the first time, after which its (possibly dead) entry remains.
+static OPEN_LOGS: OnceLock<Mutex<HashMap<String, Weak<LogFile>>>> = OnceLock::new();
+
+/// Write-Ahead Log (e.g. "stocks.wal"); each write to a table file (or its BLOB store)
+/// is recorded alongside the bytes it replaces, and flushed to disk, before it is applied.
+/// Writes are grouped into batches (e.g. a row and its index entries), which are marked
+/// complete once applied. Upon recovery, complete batches are replayed, whereas incomplete
+/// ones are undone. Resizes are recorded by length only; thus undoing a truncation restores
+/// the file's length, but not the truncated bytes (e.g. truncated rows are restored as deleted).
+#[derive(Clone)]
+pub struct WriteAheadLog {
+    log: Arc<LogFile>,
+    target: String,
+}
+
+impl WriteAheadLog {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Deletes the log of the table (e.g. prior to (re)creating the table)
+    pub fn discard(table_path: &str) -> std::io::Result<()> {
+        let path = Self::get_log_path(table_path);
+        Self::lock_open_logs()?.insert(path.to_string(), Weak::new());
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(())
+        }
+    }
+
+    /// Returns the path of the log of the table (e.g. "stocks.table" => "stocks.wal")
+    pub fn get_log_path(table_path: &str) -> String {
+        let (_, log_path) = FileRowCollection::get_related_filename(table_path, "wal");
+        log_path
+    }
+
+    /// Opens the log of the table, whose writes are recorded on behalf of the table file;
+    /// the log is shared by all handles of the table (and its related files) within this process,
+    /// and is recovered upon being opened for the first time.
+    pub fn open(table_path: &str) -> std::io::Result<Self> {
+        let path = Self::get_log_path(table_path);
+        let target = Self::get_file_name(table_path);
+        let mut open_logs = Self::lock_open_logs()?;
+        let log = match open_logs.get(&path) {
+            Some(log) => match log.upgrade() {
+                Some(log) => return Ok(Self { log, target }),
+                None => LogFile::open(&path)?
+            }
+            None => {
+                Self::recover(&path)?;
+                LogFile::open(&path)?
+            }
+        };
+        open_logs.insert(path, Arc::downgrade(&log));
+        Ok(Self { log, target })
+    }
+
+    /// Replays the complete batches of the log, then undoes the incomplete ones;
+    /// returning the number of batches replayed and undone respectively.
+    /// An entry torn by a crash (i.e. failing its checksum) ends the log.
+    pub fn recover(path: &str) -> std::io::Result<(usize, usize)> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok((0, 0)),
+            Err(err) => return Err(err)
+        };
+        let entries = Self::decode_entries(&bytes);
+        let completed = entries.iter()
+            .filter_map(|entry| match entry {
+                LogEntry::Commit { batch } => Some(*batch),
+                _ => None
+            })
+            .collect::<HashSet<_>>();
+
+        // replay the complete batches (in order), then undo the incomplete ones (in reverse)
+        let base_path = Path::new(path).parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        let mut files = HashMap::new();
+        for entry in entries.iter().filter(|entry| completed.contains(&entry.get_batch())) {
+            entry.redo(&mut files, &base_path)?;
+        }
+        let mut undone = HashSet::new();
+        for entry in entries.iter().rev().filter(|entry| !completed.contains(&entry.get_batch())) {
+            entry.undo(&mut files, &base_path)?;
+            undone.insert(entry.get_batch());
+        }
+        for file in files.values() { file.sync_all()?; }
+        OpenOptions::new().write(true).open(path)?.set_len(0)?;
+        Ok((completed.len(), undone.len()))
+    }
+
+    /// Encodes the entry as a frame => (payload length|MD5 checksum|payload)
+    pub fn encode_frame<T: Serialize>(entry: &T) -> std::io::Result<Vec<u8>> {
+        let payload = match bincode::serialize(entry) {
+            Ok(payload) => payload,
+            Err(err) => return fail(err.to_string())
+        };
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+        frame.extend((payload.len() as u32).to_be_bytes());
+        frame.extend(md5::compute(&payload).0);
+        frame.extend(payload);
+        Ok(frame)
+    }
+
+    /// Returns the positions of the payloads of the frames of the file => (offset|length);
+    /// where a frame torn by a crash (i.e. failing its checksum) ends the file.
+    pub fn locate_frames(file: &File) -> std::io::Result<Vec<(u64, usize)>> {
+        let file_len = file.metadata()?.len();
+        let mut frames = Vec::new();
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        let mut offset = 0;
+        while offset + FRAME_HEADER_LEN as u64 <= file_len {
+            file.read_exact_at(&mut header, offset)?;
+            let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+            let payload_offset = offset + FRAME_HEADER_LEN as u64;
+            if payload_offset + length as u64 > file_len { break; }
+            let mut payload = vec![0u8; length];
+            file.read_exact_at(&mut payload, payload_offset)?;
+            if md5::compute(&payload).0 != header[4..] { break; }
+            frames.push((payload_offset, length));
+            offset = payload_offset + length as u64;
+        }
+        Ok(frames)
+    }
+
+    fn decode_entries(bytes: &[u8]) -> Vec<LogEntry> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset + FRAME_HEADER_LEN <= bytes.len() {
+            let (header, rest) = bytes[offset..].split_at(FRAME_HEADER_LEN);
+            let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+            if rest.len() < length { break; }
+            let payload = &rest[..length];
+            if md5::compute(payload).0 != header[4..] { break; }
+            match bincode::deserialize::<LogEntry>(payload) {
+                Ok(entry) => entries.push(entry),
+                Err(..) => break
+            }
+            offset += FRAME_HEADER_LEN + length;
+        }
+        entries
+    }
+
+    fn get_file_name(path: &str) -> String {
+        Path::new(path).file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    fn lock_open_logs() -> std::io::Result<MutexGuard<'static, HashMap<String, Weak<LogFile>>>> {
+        OPEN_LOGS.get_or_init(|| Mutex::new(HashMap::new())).lock()
+            .or_else(|err| fail(err.to_string()))
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // instance methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Performs the writes within a single batch; batches begun while another is
+    /// in progress join the latter. Once complete, the log may be checkpointed.
+    pub fn batch<A, F>(&self, f: F) -> std::io::Result<A>
+    where
+        F: FnOnce() -> A,
+    {
+        let batch = {
+            let mut batch = self.log.lock_batch()?;
+            match batch.as_mut() {
+                Some((_, depth)) => { *depth += 1; None }
+                None => {
+                    let id = rand::random::<u64>();
+                    *batch = Some((id, 1));
+                    Some(id)
+                }
+            }
+        };
+        let result = f();
+        let mut state = self.log.lock_batch()?;
+        match (batch, state.as_mut()) {
+            (Some(id), _) => {
+                *state = None;
+                drop(state);
+                self.append(&LogEntry::Commit { batch: id })?;
+                if self.log.file.metadata()?.len() > CHECKPOINT_THRESHOLD {
+                    self.checkpoint()?;
+                }
+            }
+            (None, Some((_, depth))) => *depth -= 1,
+            (None, None) => {}
+        }
+        Ok(result)
+    }
+
+    /// Flushes the files of the table to disk, then truncates the log
+    pub fn checkpoint(&self) -> std::io::Result<()> {
+        if self.log.lock_batch()?.is_some() { return Ok(()); }
+        let base_path = Path::new(&self.log.path).parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        for entry in fs::read_dir(base_path)? {
+            let path = entry?.path();
+            if path.is_file() && path.to_string_lossy() != self.log.path {
+                File::open(path)?.sync_all()?;
+            }
+        }
+        self.log.file.set_len(0)?;
+        self.log.file.sync_all()
+    }
+
+    /// Returns a log, sharing this log's batches, which records writes on behalf of another file
+    pub fn for_file(&self, path: &str) -> Self {
+        Self { log: self.log.clone(), target: Self::get_file_name(path) }
+    }
+
+    /// Records, then performs the resizing of the file
+    pub fn set_len(&self, file: &File, new_len: u64) -> std::io::Result<()> {
+        self.batch(|| {
+            Transaction::capture_resize(&self.log.path, &self.target, file, new_len)?;
+            self.append(&LogEntry::Resize {
+                batch: self.get_batch()?,
+                target: self.target.to_owned(),
+                old_len: file.metadata()?.len(),
+                new_len,
+            })?;
+            file.set_len(new_len)
+        })?
+    }
+
+    /// Records, then performs the write of the bytes at the offset of the file
+    pub fn write_at(&self, file: &File, bytes: &[u8], offset: u64) -> std::io::Result<()> {
+        self.batch(|| {
+            Transaction::capture_write(&self.log.path, &self.target, file, offset, bytes.len())?;
+            let old_len = file.metadata()?.len();
+            let mut before = vec![0u8; (old_len.saturating_sub(offset) as usize).min(bytes.len())];
+            file.read_exact_at(&mut before, offset)?;
+            self.append(&LogEntry::Write {
+                batch: self.get_batch()?,
+                target: self.target.to_owned(),
+                offset,
+                old_len,
+                before,
+                after: bytes.to_vec(),
+            })?;
+            file.write_all_at(bytes, offset)
+        })?
+    }
+
+    fn append(&self, entry: &LogEntry) -> std::io::Result<()> {
+        (&self.log.file).write_all(&Self::encode_frame(entry)?)?;
+        // the entry must be durable before the change it describes is applied
+        self.log.file.sync_data()
+    }
+
+    fn get_batch(&self) -> std::io::Result<u64> {
+        match self.log.lock_batch()?.as_ref() {
+            Some((id, _)) => Ok(*id),
+            None => fail("No batch is in progress")
+        }
+    }
+}
+
+/// Represents the log file shared by a table and its related files
+struct LogFile {
+    file: File,
+    path: String,
+    /// the batch in progress => (batch ID|nesting depth)
+    batch: Mutex<Option<(u64, usize)>>,
+}
+
+impl LogFile {
+    fn open(path: &str) -> std::io::Result<Arc<Self>> {
+        let file = OpenOptions::new().create(true).append(true).read(true).open(path)?;
+        Ok(Arc::new(Self { file, path: path.to_string(), batch: Mutex::new(None) }))
+    }
+
+    fn lock_batch(&self) -> std::io::Result<MutexGuard<'_, Option<(u64, usize)>>> {
+        self.batch.lock().or_else(|err| fail(err.to_string()))
+    }
+}
+
+/// Represents an entry of the log
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum LogEntry {
+    Commit {
+        batch: u64,
+    },
+    Resize {
+        batch: u64,
+        target: String,
+        old_len: u64,
+        new_len: u64,
+    },
+    Write {
+        batch: u64,
+        target: String,
+        offset: u64,
+        old_len: u64,
+        before: Vec<u8>,
+        after: Vec<u8>,
+    },
+}
+
+impl LogEntry {
+    fn get_batch(&self) -> u64 {
+        match self {
+            LogEntry::Commit { batch } | LogEntry::Resize { batch, .. } | LogEntry::Write { batch, .. } => *batch
+        }
+    }
+
+    /// Opens the file targeted by the entry; or none, if it has since been deleted
+    fn open_target<'a>(
+        files: &'a mut HashMap<String, File>,
+        base_path: &Path,
+        target: &str,
+    ) -> std::io::Result<Option<&'a File>> {
+        if !files.contains_key(target) {
+            match OpenOptions::new().read(true).write(true).open(base_path.join(target)) {
+                Ok(file) => { files.insert(target.to_string(), file); }
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err)
+            }
+        }
+        Ok(files.get(target))
+    }
+
+    /// Re-applies the change
+    fn redo(&self, files: &mut HashMap<String, File>, base_path: &Path) -> std::io::Result<()> {
+        match self {
+            LogEntry::Commit { .. } => Ok(()),
+            LogEntry::Resize { target, new_len, .. } =>
+                match Self::open_target(files, base_path, target)? {
+                    Some(file) => file.set_len(*new_len),
+                    None => Ok(())
+                }
+            LogEntry::Write { target, offset, after, .. } =>
+                match Self::open_target(files, base_path, target)? {
+                    Some(file) => file.write_all_at(after, *offset),
+                    None => Ok(())
+                }
+        }
+    }
+
+    /// Reverts the change; restoring the replaced bytes and the file's length
+    fn undo(&self, files: &mut HashMap<String, File>, base_path: &Path) -> std::io::Result<()> {
+        match self {
+            LogEntry::Commit { .. } => Ok(()),
+            LogEntry::Resize { target, old_len, .. } =>
+                match Self::open_target(files, base_path, target)? {
+                    Some(file) => file.set_len(*old_len),
+                    None => Ok(())
+                }
+            LogEntry::Write { target, offset, old_len, before, after, .. } => {
+                if let Some(file) = Self::open_target(files, base_path, target)? {
+                    file.write_all_at(before, *offset)?;
+                    if offset + after.len() as u64 > *old_len { file.set_len(*old_len)?; }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespaces::Namespace;
+    use crate::row_collection::RowCollection;
+    use crate::testdata::{make_quote, make_quote_parameters};
+
+    #[test]
+    fn test_recover_complete_batch() {
+        let ns = Namespace::parse("wal.recover_complete.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let table_path = ns.get_table_file_path();
+        let wal = WriteAheadLog::open(&table_path).unwrap();
+
+        // log a complete batch whose write never reached the table (e.g. the server was killed)
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        let bytes = fs::read(&table_path).unwrap();
+        frc.resize(0);
+        wal.checkpoint().unwrap();
+        wal.append(&LogEntry::Write {
+            batch: 1,
+            target: WriteAheadLog::get_file_name(&table_path),
+            offset: 0,
+            old_len: 0,
+            before: vec![],
+            after: bytes,
+        }).unwrap();
+        wal.append(&LogEntry::Commit { batch: 1 }).unwrap();
+
+        assert_eq!(WriteAheadLog::recover(&wal.log.path).unwrap(), (1, 0));
+        assert_eq!(wal.log.file.metadata().unwrap().len(), 0);
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+    }
+
+    #[test]
+    fn test_recover_incomplete_batch() {
+        let ns = Namespace::parse("wal.recover_incomplete.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        let table_path = ns.get_table_file_path();
+        let wal = WriteAheadLog::open(&table_path).unwrap();
+        wal.checkpoint().unwrap();
+
+        // tear a row, and append another, within a batch which never completes
+        let file = OpenOptions::new().read(true).write(true).open(&table_path).unwrap();
+        let record_size = frc.get_record_size() as u64;
+        *wal.log.batch.lock().unwrap() = Some((2, 1));
+        wal.write_at(&file, &[0xFF; 7], 9).unwrap();
+        wal.write_at(&file, &vec![0xFF; record_size as usize], record_size).unwrap();
+
+        // append a torn entry
+        (&wal.log.file).write_all(&[0, 0, 0, 99, 1, 2, 3]).unwrap();
+
+        assert_eq!(WriteAheadLog::recover(&wal.log.path).unwrap(), (0, 1));
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+    }
+
+    #[test]
+    fn test_truncation_is_logged_by_length() {
+        let ns = Namespace::parse("wal.truncation.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let wal = WriteAheadLog::open(&ns.get_table_file_path()).unwrap();
+        for n in 0..100 {
+            frc.append_row(make_quote(n, "ABC", "AMEX", 11.77));
+        }
+        wal.checkpoint().unwrap();
+
+        // the truncation is recorded by length, rather than by the truncated rows
+        let old_len = fs::metadata(ns.get_table_file_path()).unwrap().len();
+        frc.resize(0);
+        let entries = WriteAheadLog::decode_entries(&fs::read(&wal.log.path).unwrap());
+        assert_eq!(entries[0], LogEntry::Resize {
+            batch: entries[0].get_batch(),
+            target: "stocks.table".into(),
+            old_len,
+            new_len: 0,
+        });
+    }
+
+    #[test]
+    fn test_writes_are_logged_then_checkpointed() {
+        let ns = Namespace::parse("wal.checkpoint.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let wal = WriteAheadLog::open(&ns.get_table_file_path()).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        frc.append_row(make_quote(1, "UNO", "OTC", 0.2456));
+        assert!(wal.log.file.metadata().unwrap().len() > 0);
+        wal.checkpoint().unwrap();
+        assert_eq!(wal.log.file.metadata().unwrap().len(), 0);
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+    }
+}